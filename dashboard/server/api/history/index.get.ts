@@ -117,22 +117,9 @@ async function getProcessHistory(
       }
       
       try {
-        // Parse JSON output from Rust app
-        const lines = stdout.trim().split('\n')
-        const history = []
-        
-        for (const line of lines) {
-          if (line.trim()) {
-            try {
-              const historyEntry = JSON.parse(line)
-              if (historyEntry.pid && historyEntry.port) {
-                history.push(historyEntry)
-              }
-            } catch (e) {
-              // Skip invalid JSON lines
-            }
-          }
-        }
+        // The Rust app prints one JSON envelope; the history entries are its data
+        const envelope = JSON.parse(stdout)
+        const history = envelope.data ?? []
         
         // Limit results
         const limitedHistory = history.slice(0, limit)
@@ -88,24 +88,9 @@ async function getFrequentOffenders(binaryPath: string): Promise<any[]> {
       }
       
       try {
-        // Parse JSON output from Rust app
-        const lines = stdout.trim().split('\n')
-        const offenders = []
-        
-        for (const line of lines) {
-          if (line.trim()) {
-            try {
-              const offender = JSON.parse(line)
-              if (offender.process_name && offender.port) {
-                offenders.push(offender)
-              }
-            } catch (e) {
-              // Skip invalid JSON lines
-            }
-          }
-        }
-        
-        resolve(offenders)
+        // The Rust app prints one JSON envelope; the offenders are its data
+        const envelope = JSON.parse(stdout)
+        resolve(envelope.data ?? [])
       } catch (error) {
         reject(error)
       }
@@ -77,25 +77,9 @@ async function getRootCauseAnalysis(binaryPath: string): Promise<any | null> {
       }
       
       try {
-        // Filter out debug output and extract JSON
-        const lines = stdout.split('\n')
-        let jsonLine = ''
-        
-        for (const line of lines) {
-          const trimmed = line.trim()
-          // Look for lines that start with { (JSON)
-          if (trimmed.startsWith('{') && trimmed.endsWith('}')) {
-            jsonLine = trimmed
-            break
-          }
-        }
-        
-        if (!jsonLine) {
-          throw new Error('No valid JSON found in output')
-        }
-        
-        const analysis = JSON.parse(jsonLine)
-        resolve(analysis)
+        // The Rust app prints one JSON envelope; the analysis is its data
+        const envelope = JSON.parse(stdout)
+        resolve(envelope.data)
       } catch (error) {
         console.error('JSON parsing error:', error)
         console.error('Raw output:', stdout)
@@ -86,25 +86,9 @@ async function getHistoryStatistics(binaryPath: string): Promise<any> {
       }
       
       try {
-        // Parse JSON output from Rust app
-        const lines = stdout.trim().split('\n')
-        let stats = null
-        
-        for (const line of lines) {
-          if (line.trim()) {
-            try {
-              const parsed = JSON.parse(line)
-              if (parsed.total_kills !== undefined) {
-                stats = parsed
-                break
-              }
-            } catch (e) {
-              // Skip invalid JSON lines
-            }
-          }
-        }
-        
-        resolve(stats)
+        // The Rust app prints one JSON envelope; the statistics are its data
+        const envelope = JSON.parse(stdout)
+        resolve(envelope.data)
       } catch (error) {
         reject(error)
       }
@@ -86,25 +86,9 @@ async function getIgnoreSuggestions(binaryPath: string): Promise<any> {
       }
       
       try {
-        // Parse JSON output from Rust app
-        const lines = stdout.trim().split('\n')
-        let suggestions = null
-        
-        for (const line of lines) {
-          if (line.trim()) {
-            try {
-              const parsed = JSON.parse(line)
-              if (parsed.suggested_ports !== undefined) {
-                suggestions = parsed
-                break
-              }
-            } catch (e) {
-              // Skip invalid JSON lines
-            }
-          }
-        }
-        
-        resolve(suggestions)
+        // The Rust app prints one JSON envelope; the suggestions are its data
+        const envelope = JSON.parse(stdout)
+        resolve(envelope.data)
       } catch (error) {
         reject(error)
       }
@@ -167,24 +167,11 @@ async function getProcessesWithRustApp(
       }
       
       try {
-        // Parse JSON output from Rust app
-        const lines = stdout.trim().split('\n')
-        const processes = []
-        
-        for (const line of lines) {
-          if (line.trim()) {
-            try {
-              const processData = JSON.parse(line)
-              if (processData.pid && processData.port) {
-                processes.push(processData)
-              }
-            } catch (e) {
-              // Skip invalid JSON lines
-            }
-          }
-        }
-        
-        resolve(processes)
+        // The Rust app prints one JSON envelope. Its data is the process list, or
+        // { listeners, failures } when scanning a remote host.
+        const envelope = JSON.parse(stdout)
+        const data = envelope.data ?? []
+        resolve(Array.isArray(data) ? data : data.listeners)
       } catch (error) {
         reject(error)
       }
@@ -173,21 +173,8 @@ async function getProcessesWithRustApp(
       }
       
       try {
-        const lines = stdout.trim().split('\n')
-        const processes = []
-        for (const line of lines) {
-          if (line.trim()) {
-            try {
-              const process = JSON.parse(line)
-              if (process.pid && process.port) {
-                processes.push(process)
-              }
-            } catch (e) {
-              // Skip invalid JSON lines
-            }
-          }
-        }
-        resolve(processes)
+        const envelope = JSON.parse(stdout)
+        resolve(envelope.data ?? [])
       } catch (error) {
         reject(error)
       }
@@ -234,6 +221,7 @@ async function killAllProcessesWithRustApp(
 
     const rustApp = spawn(binaryPath, args, { 
       stdio: ['pipe', 'pipe', 'pipe'],
+      env: { ...process.env, PORT_KILL_ACTOR: 'dashboard' },
       timeout: 10000 // 10 second timeout
     })
     
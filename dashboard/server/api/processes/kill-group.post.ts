@@ -133,6 +133,7 @@ async function killGroupProcessesWithRustApp(
 
     const rustApp = spawn(binaryPath, args, { 
       stdio: ['pipe', 'pipe', 'pipe'],
+      env: { ...process.env, PORT_KILL_ACTOR: 'dashboard' },
       timeout: 10000
     })
     
@@ -122,6 +122,7 @@ async function restartProcessesWithRustApp(
 
     const rustApp = spawn(binaryPath, args, { 
       stdio: ['pipe', 'pipe', 'pipe'],
+      env: { ...process.env, PORT_KILL_ACTOR: 'dashboard' },
       timeout: 15000 // Longer timeout for restart
     })
     
@@ -159,13 +159,13 @@ const handler = async (name, args, ctx) => {
         }
         case "kill": {
             const remote = args?.remote ? `--remote ${args.remote}` : "";
-            const cmd = `${binPath()} --kill-all --ports ${args.ports} ${remote}`.trim();
+            const cmd = `${binPath()} --kill-all --ports ${args.ports} ${remote} --json`.trim();
             const out = await run(cmd, ctx);
             return { content: out };
         }
         case "reset": {
             const remote = args?.remote ? `--remote ${args.remote}` : "";
-            const cmd = `${binPath()} --reset ${remote}`.trim();
+            const cmd = `${binPath()} --reset ${remote} --json`.trim();
             const out = await run(cmd, ctx);
             return { content: out };
         }
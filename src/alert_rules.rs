@@ -0,0 +1,666 @@
+use crate::cli::Args;
+use crate::process_monitor::ProcessMonitor;
+use crate::scripting::ScriptEngine;
+use crate::security_audit::SecurityAuditor;
+use crate::smart_filter::SmartFilter;
+use crate::types::{parse_duration_secs, ProcessInfo, RiskLevel};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::interval;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortState {
+    Occupied,
+    Free,
+}
+
+/// Conditions that must all hold for a rule to match; unset fields are ignored
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleCondition {
+    #[serde(default)]
+    pub state: Option<PortState>,
+    /// Owner process name (case-insensitive)
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Match when the port is owned by anything but this process name
+    #[serde(default)]
+    pub owner_not: Option<String>,
+    #[serde(default)]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub project: Option<String>,
+    /// CPU usage in percent
+    #[serde(default)]
+    pub cpu_above: Option<f64>,
+    #[serde(default)]
+    pub memory_above_mb: Option<f64>,
+    /// Minimum audit risk level of the owner (low, medium, high, critical)
+    #[serde(default)]
+    pub risk_at_least: Option<RiskLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RuleAction {
+    Log,
+    /// Post the alert to `url`, or to `--monitor-endpoint` when unset
+    Endpoint {
+        #[serde(default)]
+        url: Option<String>,
+    },
+    /// Run a port-kill script; `{port}`, `{pid}` and `{name}` are bound into its command arguments
+    Script {
+        script: String,
+    },
+    Kill,
+    Notify,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRule {
+    pub id: String,
+    /// Port to watch; when unset the rule is checked against every occupied port
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub when: RuleCondition,
+    /// How long the condition must hold before firing, e.g. "30s", "5m", "1h"
+    #[serde(default, rename = "for")]
+    pub sustained: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    pub actions: Vec<RuleAction>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AlertRulesFile {
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+}
+
+fn risk_rank(risk: &RiskLevel) -> u8 {
+    match risk {
+        RiskLevel::Low => 0,
+        RiskLevel::Medium => 1,
+        RiskLevel::High => 2,
+        RiskLevel::Critical => 3,
+    }
+}
+
+fn same_name(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+impl AlertRule {
+    pub fn sustained_secs(&self) -> u64 {
+        self.sustained
+            .as_deref()
+            .and_then(parse_duration_secs)
+            .unwrap_or(0)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some(sustained) = &self.sustained {
+            if parse_duration_secs(sustained).is_none() {
+                return Err(anyhow::anyhow!(
+                    "Rule '{}': invalid duration '{}' (use e.g. 30s, 5m, 1h)",
+                    self.id,
+                    sustained
+                ));
+            }
+        }
+        if self.port.is_none() && self.when.state == Some(PortState::Free) {
+            return Err(anyhow::anyhow!(
+                "Rule '{}' checks for a free port but doesn't name one",
+                self.id
+            ));
+        }
+        if self.actions.is_empty() {
+            return Err(anyhow::anyhow!("Rule '{}' has no actions", self.id));
+        }
+        Ok(())
+    }
+
+    fn needs_audit(&self) -> bool {
+        self.when.risk_at_least.is_some()
+    }
+
+    fn needs_performance(&self) -> bool {
+        self.when.cpu_above.is_some() || self.when.memory_above_mb.is_some()
+    }
+
+    /// Whether the condition holds for `port` given its current owner
+    pub fn matches(&self, owner: Option<&ProcessInfo>, risk: Option<&RiskLevel>) -> bool {
+        let when = &self.when;
+        let state = if owner.is_some() {
+            PortState::Occupied
+        } else {
+            PortState::Free
+        };
+        if when.state.is_some_and(|expected| expected != state) {
+            return false;
+        }
+
+        let owner_conditions = when.owner.is_some()
+            || when.owner_not.is_some()
+            || when.group.is_some()
+            || when.project.is_some()
+            || when.cpu_above.is_some()
+            || when.memory_above_mb.is_some()
+            || when.risk_at_least.is_some();
+        let Some(process) = owner else {
+            // Conditions about the owner can't hold on a free port
+            return !owner_conditions;
+        };
+
+        if let Some(name) = &when.owner {
+            if !same_name(&process.name, name) {
+                return false;
+            }
+        }
+        if let Some(name) = &when.owner_not {
+            if same_name(&process.name, name) {
+                return false;
+            }
+        }
+        if let Some(group) = &when.group {
+            if !process
+                .process_group
+                .as_deref()
+                .is_some_and(|g| same_name(g, group))
+            {
+                return false;
+            }
+        }
+        if let Some(project) = &when.project {
+            if !process
+                .project_name
+                .as_deref()
+                .is_some_and(|p| same_name(p, project))
+            {
+                return false;
+            }
+        }
+        if let Some(limit) = when.cpu_above {
+            if !process.cpu_usage.is_some_and(|cpu| cpu > limit) {
+                return false;
+            }
+        }
+        if let Some(limit) = when.memory_above_mb {
+            if !process
+                .memory_usage
+                .is_some_and(|bytes| bytes as f64 / 1024.0 / 1024.0 > limit)
+            {
+                return false;
+            }
+        }
+        if let Some(min) = &when.risk_at_least {
+            if risk.is_none_or(|r| risk_rank(r) < risk_rank(min)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Per rule and port: when the condition started holding and whether it already fired.
+/// Persisted so a restarted daemon doesn't fire the same episode again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleState {
+    pub since: DateTime<Utc>,
+    pub fired: bool,
+    #[serde(default)]
+    pub last_fired: Option<DateTime<Utc>>,
+}
+
+/// A rule whose condition has held for its full duration
+#[derive(Debug, Clone)]
+pub struct Firing {
+    pub rule: AlertRule,
+    pub port: u16,
+    pub process: Option<ProcessInfo>,
+    pub since: DateTime<Utc>,
+}
+
+impl Firing {
+    pub fn message(&self) -> String {
+        let owner = self
+            .process
+            .as_ref()
+            .map(|p| format!("{} (PID {})", p.name, p.pid))
+            .unwrap_or_else(|| "nothing".to_string());
+        let base = self
+            .rule
+            .message
+            .clone()
+            .unwrap_or_else(|| format!("Rule '{}' matched", self.rule.id));
+        format!("{} - port {} owned by {}", base, self.port, owner)
+    }
+}
+
+/// Evaluates rules against scans and tracks how long each condition has held
+pub struct RuleEngine {
+    rules: Vec<AlertRule>,
+    state: HashMap<String, RuleState>,
+}
+
+impl RuleEngine {
+    pub fn new(rules: Vec<AlertRule>, state: HashMap<String, RuleState>) -> Self {
+        Self { rules, state }
+    }
+
+    /// Resume saved state after a restart. Nothing was observed while the daemon was down,
+    /// so durations count from `started_at`; rules that already fired stay fired.
+    pub fn resume(
+        rules: Vec<AlertRule>,
+        mut state: HashMap<String, RuleState>,
+        started_at: DateTime<Utc>,
+    ) -> Self {
+        for entry in state.values_mut() {
+            entry.since = entry.since.max(started_at);
+        }
+        Self::new(rules, state)
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    pub fn state(&self) -> &HashMap<String, RuleState> {
+        &self.state
+    }
+
+    /// Update rule state for one scan and return the rules that fire now.
+    /// Each rule fires once per episode and re-arms when its condition stops holding.
+    pub fn evaluate(
+        &mut self,
+        processes: &HashMap<u16, ProcessInfo>,
+        risks: &HashMap<u16, RiskLevel>,
+        now: DateTime<Utc>,
+    ) -> Vec<Firing> {
+        let mut firings = Vec::new();
+        let mut live_keys = Vec::new();
+
+        for rule in &self.rules {
+            let ports: Vec<u16> = match rule.port {
+                Some(port) => vec![port],
+                None => {
+                    let mut ports: Vec<u16> = processes.keys().copied().collect();
+                    ports.sort_unstable();
+                    ports
+                }
+            };
+
+            for port in ports {
+                let owner = processes.get(&port);
+                let key = format!("{}|{}", rule.id, port);
+                if !rule.matches(owner, risks.get(&port)) {
+                    self.state.remove(&key);
+                    continue;
+                }
+
+                let entry = self.state.entry(key.clone()).or_insert(RuleState {
+                    since: now,
+                    fired: false,
+                    last_fired: None,
+                });
+                live_keys.push(key);
+
+                let held = (now - entry.since).num_seconds().max(0) as u64;
+                if !entry.fired && held >= rule.sustained_secs() {
+                    entry.fired = true;
+                    entry.last_fired = Some(now);
+                    firings.push(Firing {
+                        rule: rule.clone(),
+                        port,
+                        process: owner.cloned(),
+                        since: entry.since,
+                    });
+                }
+            }
+        }
+
+        // Forget state for rules or ports that are no longer being evaluated
+        self.state.retain(|key, _| live_keys.contains(key));
+        firings
+    }
+}
+
+pub fn get_alert_state_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(home).join(".port-kill/alert-state.json")
+}
+
+/// Read and validate a rules file
+pub fn load_rules(path: &Path) -> Result<Vec<AlertRule>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read rules file {}", path.display()))?;
+    let file: AlertRulesFile = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid rules file {}", path.display()))?;
+    for rule in &file.rules {
+        rule.validate()?;
+    }
+    Ok(file.rules)
+}
+
+pub fn load_rule_state(path: &Path) -> HashMap<String, RuleState> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_rule_state(path: &Path, state: &HashMap<String, RuleState>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Write then rename so a crash mid-write keeps the previous state
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(state)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Rules daemon: scans, evaluates rules and runs their actions
+pub struct AlertMonitor {
+    engine: RuleEngine,
+    state_path: PathBuf,
+    process_monitor: Arc<Mutex<ProcessMonitor>>,
+    security_auditor: Option<SecurityAuditor>,
+    client: Client,
+    args: Args,
+}
+
+impl AlertMonitor {
+    pub fn new(args: &Args) -> Result<Self> {
+        let rules_path = args
+            .get_alert_rules_path()
+            .ok_or_else(|| anyhow::anyhow!("A rules file is required for alert rules"))?;
+        let rules = load_rules(Path::new(&rules_path))?;
+        let state_path = get_alert_state_path();
+        let engine = RuleEngine::resume(rules, load_rule_state(&state_path), Utc::now());
+
+        let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
+        let mut process_monitor = ProcessMonitor::new_with_performance(
+            update_sender,
+            args.get_ports_to_monitor(),
+            args.docker,
+            args.verbose,
+            SmartFilter::from_args(args)?,
+            args.performance || engine.rules().iter().any(AlertRule::needs_performance),
+        )?;
+        process_monitor.set_force_pid(args.force_pid);
+
+        let security_auditor = engine.rules().iter().any(AlertRule::needs_audit).then(|| {
+            SecurityAuditor::new(
+                args.get_suspicious_ports(),
+                args.baseline_file.clone(),
+                args.suspicious_only,
+            )
+        });
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(args.endpoint_timeout))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            engine,
+            state_path,
+            process_monitor: Arc::new(Mutex::new(process_monitor)),
+            security_auditor,
+            client,
+            args: args.clone(),
+        })
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.engine.rules().len()
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        let mut scan_timer = interval(Duration::from_secs(self.args.scan_interval.max(1)));
+
+        log::info!("Starting alert rules engine:");
+        log::info!("  - Rules: {}", self.engine.rules().len());
+        log::info!("  - State: {}", self.state_path.display());
+
+        loop {
+            scan_timer.tick().await;
+            if let Err(e) = self.tick().await {
+                log::warn!("Failed to evaluate alert rules: {}", e);
+            }
+        }
+    }
+
+    async fn tick(&mut self) -> Result<()> {
+        let processes = self.process_monitor.lock().await.scan_processes().await?;
+
+        let mut risks = HashMap::new();
+        if let Some(auditor) = &self.security_auditor {
+            match auditor.perform_audit(processes.clone()).await {
+                Ok(audit) => {
+                    for finding in audit.suspicious_processes {
+                        risks.insert(finding.port, finding.risk_level);
+                    }
+                }
+                Err(e) => log::warn!("Security audit failed: {}", e),
+            }
+        }
+
+        let previous_state = self.engine.state().clone();
+        let firings = self.engine.evaluate(&processes, &risks, Utc::now());
+        if *self.engine.state() != previous_state {
+            if let Err(e) = save_rule_state(&self.state_path, self.engine.state()) {
+                log::warn!("Failed to save alert rule state: {}", e);
+            }
+        }
+
+        for firing in &firings {
+            for action in &firing.rule.actions {
+                if let Err(e) = self.run_action(action, firing).await {
+                    log::warn!("Alert rule '{}' action failed: {}", firing.rule.id, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn run_action(&self, action: &RuleAction, firing: &Firing) -> Result<()> {
+        match action {
+            RuleAction::Log => {
+                log::warn!("🚨 {}", firing.message());
+                println!("🚨 {}", firing.message());
+            }
+            RuleAction::Endpoint { url } => {
+                let url = url
+                    .as_ref()
+                    .or(self.args.monitor_endpoint.as_ref())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("No url on the action and no --monitor-endpoint set")
+                    })?;
+                let payload = serde_json::json!({
+                    "type": "alert",
+                    "rule": firing.rule.id,
+                    "port": firing.port,
+                    "message": firing.message(),
+                    "since": firing.since,
+                    "timestamp": Utc::now(),
+                    "process": firing.process,
+                });
+                let mut request = self.client.post(url).json(&payload);
+                if let Some(auth) = &self.args.endpoint_auth {
+                    request = request.header("Authorization", auth);
+                }
+                let response = request.send().await?;
+                if !response.status().is_success() {
+                    return Err(anyhow::anyhow!("Endpoint returned {}", response.status()));
+                }
+            }
+            RuleAction::Script { script } => {
+                let process = firing.process.as_ref();
+                let variables = HashMap::from([
+                    ("port".to_string(), firing.port.to_string()),
+                    (
+                        "pid".to_string(),
+                        process.map(|p| p.pid.to_string()).unwrap_or_default(),
+                    ),
+                    (
+                        "name".to_string(),
+                        process.map(|p| p.name.clone()).unwrap_or_default(),
+                    ),
+                ]);
+                let mut engine = ScriptEngine::new(self.process_monitor.clone(), self.args.clone())
+                    .with_variables(variables);
+                engine.execute(script).await?;
+            }
+            RuleAction::Kill => {
+                let Some(process) = &firing.process else {
+                    return Err(anyhow::anyhow!(
+                        "Port {} is free, nothing to kill",
+                        firing.port
+                    ));
+                };
+                self.process_monitor
+                    .lock()
+                    .await
+                    .kill_process_with_context(process.pid, "alert", true)
+                    .await?;
+            }
+            RuleAction::Notify => send_desktop_notification("Port Kill alert", &firing.message())?,
+        }
+        Ok(())
+    }
+}
+
+/// Show a desktop notification using the platform's notifier
+fn send_desktop_notification(title: &str, message: &str) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    let status = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(format!(
+            "display notification {:?} with title {:?}",
+            message, title
+        ))
+        .status();
+
+    #[cfg(target_os = "linux")]
+    let status = std::process::Command::new("notify-send")
+        .arg(title)
+        .arg(message)
+        .status();
+
+    #[cfg(target_os = "windows")]
+    let status = std::process::Command::new("msg")
+        .arg("*")
+        .arg(format!("{}: {}", title, message))
+        .status();
+
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(anyhow::anyhow!("Notifier exited with {}", status)),
+        Err(e) => Err(anyhow::anyhow!("No desktop notifier available: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    fn process(name: &str, port: u16) -> ProcessInfo {
+        ProcessInfo {
+            cpu_usage: Some(80.0),
+            ..ProcessInfo::for_test(100, port, name)
+        }
+    }
+
+    fn rules(json: &str) -> Vec<AlertRule> {
+        serde_json::from_str::<AlertRulesFile>(json).unwrap().rules
+    }
+
+    #[test]
+    fn test_sustained_free_port_fires_once_and_survives_restart() {
+        let rules = rules(
+            r#"{"rules": [{"id": "pg-down", "port": 5432, "when": {"state": "free"},
+                "for": "30s", "actions": [{"type": "log"}]}]}"#,
+        );
+        let start = Utc::now();
+        let empty = HashMap::new();
+        let no_risks = HashMap::new();
+
+        let mut engine = RuleEngine::new(rules.clone(), HashMap::new());
+        assert!(engine.evaluate(&empty, &no_risks, start).is_empty());
+        assert!(engine
+            .evaluate(&empty, &no_risks, start + ChronoDuration::seconds(10))
+            .is_empty());
+
+        // Nothing was watched while the daemon was down, so the 30s restart with it
+        let restart = start + ChronoDuration::seconds(20);
+        let mut engine = RuleEngine::resume(rules.clone(), engine.state().clone(), restart);
+        assert!(engine
+            .evaluate(&empty, &no_risks, start + ChronoDuration::seconds(31))
+            .is_empty());
+        let fired = engine.evaluate(&empty, &no_risks, start + ChronoDuration::seconds(50));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].since, restart);
+
+        // A rule that already fired stays quiet after another restart
+        let mut engine = RuleEngine::resume(
+            rules,
+            engine.state().clone(),
+            start + ChronoDuration::seconds(55),
+        );
+        assert!(engine
+            .evaluate(&empty, &no_risks, start + ChronoDuration::seconds(90))
+            .is_empty());
+
+        // Postgres coming back re-arms the rule
+        let up = HashMap::from([(5432, process("postgres", 5432))]);
+        assert!(engine
+            .evaluate(&up, &no_risks, start + ChronoDuration::seconds(91))
+            .is_empty());
+        assert!(engine.state().is_empty());
+    }
+
+    #[test]
+    fn test_owner_and_threshold_conditions() {
+        let rules = rules(
+            r#"{"rules": [
+                {"id": "not-node", "port": 3000, "when": {"ownerNot": "node"}, "actions": [{"type": "kill"}]},
+                {"id": "hot", "when": {"cpuAbove": 50}, "actions": [{"type": "notify"}]}
+            ]}"#,
+        );
+        let mut engine = RuleEngine::new(rules, HashMap::new());
+        let scan = HashMap::from([
+            (3000, process("python", 3000)),
+            (8080, process("Node", 8080)),
+        ]);
+
+        let fired: Vec<(String, u16)> = engine
+            .evaluate(&scan, &HashMap::new(), Utc::now())
+            .into_iter()
+            .map(|f| (f.rule.id, f.port))
+            .collect();
+        assert_eq!(
+            fired,
+            vec![
+                ("not-node".to_string(), 3000),
+                ("hot".to_string(), 3000),
+                ("hot".to_string(), 8080)
+            ]
+        );
+    }
+}
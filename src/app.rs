@@ -1,4 +1,10 @@
-use crate::{cli::Args, tray_menu::TrayMenu, types::StatusBarInfo};
+use crate::{
+    cli::Args,
+    process_monitor::{check_kills, kill_and_record},
+    smart_filter::SmartFilter,
+    tray_menu::TrayMenu,
+    types::{ProcessHistoryEntry, StatusBarInfo},
+};
 use anyhow::Result;
 use crossbeam_channel::{bounded, Receiver};
 use log::{error, info, warn};
@@ -357,7 +363,7 @@ impl PortKillApp {
             ports.to_vec(),
             args.docker,
             args.verbose,
-            None,
+            SmartFilter::from_args(args).ok().flatten(),
             true,
         ) {
             // Use tokio runtime to run the async scan_processes method
@@ -380,14 +386,17 @@ impl PortKillApp {
         }
 
         #[cfg(target_os = "windows")]
-        {
-            return Self::get_processes_on_ports_windows(ports, args);
-        }
+        let (_, mut processes) = Self::get_processes_on_ports_windows(ports, args);
 
         #[cfg(not(target_os = "windows"))]
-        {
-            return Self::get_processes_on_ports_unix(ports, args);
+        let (_, mut processes) = Self::get_processes_on_ports_unix(ports, args);
+
+        // The quick scan only knows name, PID and port, but --where still applies
+        if let Ok(Some(filter)) = SmartFilter::from_args(args) {
+            filter.filter_processes(&mut processes);
         }
+
+        (processes.len(), processes)
     }
 
     #[cfg(not(target_os = "windows"))]
@@ -602,10 +611,12 @@ impl PortKillApp {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                start_time: crate::system_monitor::process_start_time(pid),
+                systemd_unit: crate::system_monitor::systemd_unit(pid),
+                framework: None,
             };
 
-            process_info.process_group = process_info.determine_process_group();
-            process_info.project_name = process_info.extract_project_name();
+            process_info.classify();
 
             processes.insert(port, process_info);
         }
@@ -704,10 +715,12 @@ impl PortKillApp {
                 cpu_usage: None,
                 memory_usage: None,
                 memory_percentage: None,
+                start_time: crate::system_monitor::process_start_time(pid),
+                systemd_unit: crate::system_monitor::systemd_unit(pid),
+                framework: None,
             };
 
-            process_info.process_group = process_info.determine_process_group();
-            process_info.project_name = process_info.extract_project_name();
+            process_info.classify();
 
             processes.insert(port, process_info);
         }
@@ -818,16 +831,20 @@ impl PortKillApp {
             pids_to_kill.len()
         );
 
-        for pid in pids_to_kill {
+        let mut kills = Vec::new();
+        for (pid, port, name) in pids_to_kill {
             info!("Attempting to kill process PID: {}", pid);
-            match Self::kill_process(pid) {
+            let entry = ProcessHistoryEntry::for_pid(pid, port, &name, "bulk");
+            let report = kill_and_record(entry.clone(), args.force_pid);
+            match report.clone().into_result() {
                 Ok(_) => info!("Successfully killed process PID: {}", pid),
                 Err(e) => error!("Failed to kill process {}: {}", pid, e),
             }
+            kills.push(entry.with_report(&report));
         }
 
         info!("Finished killing all processes");
-        Ok(())
+        check_kills(&kills)
     }
 
     #[cfg(target_os = "windows")]
@@ -871,16 +888,20 @@ impl PortKillApp {
             pids_to_kill.len()
         );
 
-        for pid in pids_to_kill {
+        let mut kills = Vec::new();
+        for (pid, port, name) in pids_to_kill {
             info!("Attempting to kill process PID: {}", pid);
-            match Self::kill_process(pid) {
+            let entry = ProcessHistoryEntry::for_pid(pid, port, &name, "bulk");
+            let report = kill_and_record(entry.clone(), args.force_pid);
+            match report.clone().into_result() {
                 Ok(_) => info!("Successfully killed process PID: {}", pid),
                 Err(e) => error!("Failed to kill process {}: {}", pid, e),
             }
+            kills.push(entry.with_report(&report));
         }
 
         info!("Finished killing all processes");
-        Ok(())
+        check_kills(&kills)
     }
 
     #[cfg(target_os = "windows")]
@@ -889,7 +910,7 @@ impl PortKillApp {
         ports_filter: &std::collections::HashSet<u16>,
         ignore_ports: &std::collections::HashSet<u16>,
         ignore_processes: &std::collections::HashSet<String>,
-        pids_to_kill: &mut Vec<i32>,
+        pids_to_kill: &mut Vec<(i32, u16, String)>,
     ) {
         for line in stdout.lines() {
             if !line.contains("LISTENING") {
@@ -940,8 +961,8 @@ impl PortKillApp {
                 continue;
             }
 
-            if !pids_to_kill.contains(&pid) {
-                pids_to_kill.push(pid);
+            if !pids_to_kill.iter().any(|(p, _, _)| *p == pid) {
+                pids_to_kill.push((pid, port, process_name));
             }
         }
     }
@@ -951,7 +972,7 @@ impl PortKillApp {
         ports_filter: &std::collections::HashSet<u16>,
         ignore_ports: &std::collections::HashSet<u16>,
         ignore_processes: &std::collections::HashSet<String>,
-        pids_to_kill: &mut Vec<i32>,
+        pids_to_kill: &mut Vec<(i32, u16, String)>,
     ) {
         for line in stdout.lines().skip(1) {
             let parts: Vec<&str> = line.split_whitespace().collect();
@@ -994,139 +1015,16 @@ impl PortKillApp {
                 continue;
             }
 
-            if !pids_to_kill.contains(&pid) {
-                pids_to_kill.push(pid);
-            }
-        }
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    fn kill_process(pid: i32) -> Result<()> {
-        use nix::sys::signal::{kill, Signal};
-        use nix::unistd::Pid;
-
-        info!("Killing process PID: {} with SIGTERM", pid);
-
-        // First try SIGTERM (graceful termination)
-        match kill(Pid::from_raw(pid), Signal::SIGTERM) {
-            Ok(_) => info!("SIGTERM sent to PID: {}", pid),
-            Err(e) => {
-                // Don't fail immediately, just log the error and continue
-                warn!(
-                    "Failed to send SIGTERM to PID {}: {} (process may already be terminated)",
-                    pid, e
-                );
-            }
-        }
-
-        // Wait a bit for graceful termination
-        std::thread::sleep(std::time::Duration::from_millis(500));
-
-        // Check if process is still running
-        let still_running = std::process::Command::new("ps")
-            .args(&["-p", &pid.to_string()])
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false);
-
-        if still_running {
-            // Process still running, send SIGKILL
-            info!("Process {} still running, sending SIGKILL", pid);
-            match kill(Pid::from_raw(pid), Signal::SIGKILL) {
-                Ok(_) => info!("SIGKILL sent to PID: {}", pid),
-                Err(e) => {
-                    // Log error but don't fail the entire operation
-                    warn!(
-                        "Failed to send SIGKILL to PID {}: {} (process may be protected)",
-                        pid, e
-                    );
-                }
-            }
-        } else {
-            info!("Process {} terminated gracefully", pid);
-        }
-
-        Ok(())
-    }
-
-    #[cfg(target_os = "windows")]
-    fn kill_process(pid: i32) -> Result<()> {
-        use std::process::Command;
-
-        info!("Killing process PID: {} on Windows", pid);
-
-        // Use taskkill to terminate the process
-        let output = Command::new("taskkill")
-            .args(&["/PID", &pid.to_string(), "/F"])
-            .output();
-
-        match output {
-            Ok(output) => {
-                if output.status.success() {
-                    info!("Successfully killed process PID: {}", pid);
-                } else {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    warn!("Failed to kill process PID {}: {}", pid, stderr);
-                }
-            }
-            Err(e) => {
-                warn!("Failed to execute taskkill for PID {}: {}", pid, e);
+            if !pids_to_kill.iter().any(|(p, _, _)| *p == pid) {
+                pids_to_kill.push((pid, port, name));
             }
         }
-
-        Ok(())
     }
 
     pub fn kill_single_process(pid: i32, args: &Args) -> Result<()> {
-        info!("Killing single process PID: {}", pid);
-
-        // Check if this process should be ignored
-        let ignore_ports = args.get_ignore_ports_set();
-        let ignore_processes = args.get_ignore_processes_set();
-
-        // Get process info to check if it should be ignored
-        let output = std::process::Command::new("ps")
-            .args(&["-p", &pid.to_string(), "-o", "comm="])
-            .output();
-
-        if let Ok(output) = output {
-            let process_name = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-            // Check if process name should be ignored
-            if ignore_processes.contains(&process_name) {
-                info!(
-                    "Ignoring process {} (PID {}) - process name is in ignore list",
-                    process_name, pid
-                );
-                return Ok(());
-            }
-        }
-
-        // Get port info to check if it should be ignored
-        let output = std::process::Command::new("lsof")
-            .args(&["-p", &pid.to_string(), "-i", "-P", "-n"])
-            .output();
-
-        if let Ok(output) = output {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 9 {
-                    if let Ok(port) = parts[8].split(':').last().unwrap_or("0").parse::<u16>() {
-                        if ignore_ports.contains(&port) {
-                            info!(
-                                "Ignoring process on port {} (PID {}) - port is in ignore list",
-                                port, pid
-                            );
-                            return Ok(());
-                        }
-                    }
-                }
-            }
-        }
-
-        // Process is not ignored, proceed with killing
-        Self::kill_process(pid)
+        // Same ignore checks and history recording as the console binary
+        crate::process_monitor::kill_single_process(pid, args)
+            .and_then(|kills| check_kills(&kills))
     }
 
     /// Check if a process is still running by its PID
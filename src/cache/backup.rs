@@ -1,14 +1,33 @@
-use super::types::CacheEntry;
+use super::types::{CacheEntry, CleanFailure};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupItem {
+    pub entry: CacheEntry,
+    /// Unique name of this entry inside the timestamped backup directory
+    pub backup_name: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BackupManifest {
     pub timestamp: DateTime<Utc>,
     pub entries: Vec<CacheEntry>,
     pub backup_dir: String,
+    /// Per-entry backup locations (missing in manifests written by older versions)
+    #[serde(default)]
+    pub items: Vec<BackupItem>,
+}
+
+/// Result of deleting (or backing up) a set of cache entries
+#[derive(Debug, Default)]
+pub struct DeleteOutcome {
+    pub deleted: Vec<CacheEntry>,
+    pub failed: Vec<CleanFailure>,
+    pub backup_path: Option<String>,
 }
 
 pub fn get_backup_dir() -> PathBuf {
@@ -31,61 +50,224 @@ pub fn get_timestamped_dir() -> PathBuf {
 pub async fn safe_delete_entries(
     entries: &[CacheEntry],
     safe_delete: bool,
-) -> Result<(Vec<CacheEntry>, Option<String>), std::io::Error> {
-    let mut deleted = Vec::new();
-    let mut backup_path = None;
-
+) -> Result<DeleteOutcome, std::io::Error> {
     if safe_delete {
         let timestamped_dir = get_timestamped_dir();
-        fs::create_dir_all(&timestamped_dir)?;
-        backup_path = Some(timestamped_dir.to_string_lossy().to_string());
-
-        // Create manifest
-        let manifest = BackupManifest {
-            timestamp: Utc::now(),
-            entries: entries.to_vec(),
-            backup_dir: timestamped_dir.to_string_lossy().to_string(),
-        };
+        backup_entries(entries, &timestamped_dir)
+    } else {
+        Ok(delete_entries(entries))
+    }
+}
+
+/// Move entries into `backup_dir` under unique names, keeping a manifest of them.
+///
+/// The manifest is written before anything moves and rewritten after every move, so
+/// whatever made it into the backup can always be restored. An error is only returned
+/// before the first move.
+pub fn backup_entries(
+    entries: &[CacheEntry],
+    backup_dir: &Path,
+) -> Result<DeleteOutcome, std::io::Error> {
+    fs::create_dir_all(backup_dir)?;
+
+    let mut manifest = BackupManifest {
+        timestamp: Utc::now(),
+        entries: Vec::new(),
+        backup_dir: backup_dir.to_string_lossy().to_string(),
+        items: Vec::new(),
+    };
+    write_manifest(backup_dir, &manifest)?;
+
+    let mut outcome = DeleteOutcome {
+        backup_path: Some(backup_dir.to_string_lossy().to_string()),
+        ..Default::default()
+    };
+    // Once the manifest can't be updated, nothing else may be moved
+    let mut manifest_error: Option<String> = None;
+
+    for (index, entry) in entries.iter().enumerate() {
+        let src = Path::new(&entry.path);
+        if fs::symlink_metadata(src).is_err() {
+            continue;
+        }
+        if let Some(error) = &manifest_error {
+            outcome.failed.push(CleanFailure {
+                entry: entry.clone(),
+                error: error.clone(),
+            });
+            continue;
+        }
+
+        let backup_name = unique_backup_name(index, src);
+        let dst = backup_dir.join(&backup_name);
+        if let Err(e) = move_path(src, &dst) {
+            outcome.failed.push(CleanFailure {
+                entry: entry.clone(),
+                error: format!("backup failed: {}", e),
+            });
+            continue;
+        }
 
-        let manifest_path = timestamped_dir.join("manifest.json");
-        let manifest_json = serde_json::to_string_pretty(&manifest)?;
-        fs::write(&manifest_path, manifest_json)?;
-
-        // Move entries to backup
-        for entry in entries {
-            let src = Path::new(&entry.path);
-            if src.exists() {
-                let dst = timestamped_dir.join(src.file_name().unwrap_or_default());
-                if let Err(e) = fs::rename(src, &dst) {
-                    eprintln!("Warning: Failed to backup {}: {}", entry.path, e);
-                } else {
-                    deleted.push(entry.clone());
+        // Only entries that actually made it into the backup are recorded
+        manifest.entries.push(entry.clone());
+        manifest.items.push(BackupItem {
+            entry: entry.clone(),
+            backup_name,
+        });
+        match write_manifest(backup_dir, &manifest) {
+            Ok(()) => outcome.deleted.push(entry.clone()),
+            Err(e) => {
+                manifest.entries.pop();
+                manifest.items.pop();
+                let error = format!("backup manifest could not be written: {}", e);
+                match move_path(&dst, src) {
+                    Ok(()) => outcome.failed.push(CleanFailure {
+                        entry: entry.clone(),
+                        error: error.clone(),
+                    }),
+                    // Stuck in the backup without a manifest entry; say where it is
+                    Err(e) => outcome.failed.push(CleanFailure {
+                        entry: entry.clone(),
+                        error: format!(
+                            "{}; moving it back also failed ({}), it is at {}",
+                            error,
+                            e,
+                            dst.display()
+                        ),
+                    }),
                 }
+                manifest_error = Some(error);
             }
         }
-    } else {
-        // Direct deletion without backup
-        for entry in entries {
-            let path = Path::new(&entry.path);
-            if path.exists() {
-                if path.is_dir() {
-                    if let Err(e) = fs::remove_dir_all(path) {
-                        eprintln!("Warning: Failed to delete {}: {}", entry.path, e);
-                    } else {
-                        deleted.push(entry.clone());
-                    }
-                } else if path.is_file() {
-                    if let Err(e) = fs::remove_file(path) {
-                        eprintln!("Warning: Failed to delete {}: {}", entry.path, e);
-                    } else {
-                        deleted.push(entry.clone());
-                    }
-                }
+    }
+
+    Ok(outcome)
+}
+
+fn write_manifest(backup_dir: &Path, manifest: &BackupManifest) -> io::Result<()> {
+    let manifest_json = serde_json::to_string_pretty(manifest)?;
+    // Write beside the manifest and rename, so a crash never leaves a truncated one
+    let tmp = backup_dir.join("manifest.json.tmp");
+    fs::write(&tmp, manifest_json)?;
+    fs::rename(&tmp, backup_dir.join("manifest.json"))
+}
+
+/// Delete entries in place without keeping a backup
+fn delete_entries(entries: &[CacheEntry]) -> DeleteOutcome {
+    let mut outcome = DeleteOutcome::default();
+
+    for entry in entries {
+        let path = Path::new(&entry.path);
+        if fs::symlink_metadata(path).is_err() {
+            continue;
+        }
+
+        match remove_path(path) {
+            Ok(()) => outcome.deleted.push(entry.clone()),
+            Err(e) => outcome.failed.push(CleanFailure {
+                entry: entry.clone(),
+                error: format!("delete failed: {}", e),
+            }),
+        }
+    }
+
+    outcome
+}
+
+/// Build a backup name that cannot collide with other entries sharing the same file name
+fn unique_backup_name(index: usize, src: &Path) -> String {
+    let base = src
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| "entry".to_string());
+    format!("{:04}-{}", index, base)
+}
+
+/// Move a file or directory, falling back to copy + verify + delete across filesystems
+pub fn move_path(src: &Path, dst: &Path) -> io::Result<()> {
+    match fs::rename(src, dst) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            if let Err(e) = copy_tree(src, dst).and_then(|_| verify_copy(src, dst)) {
+                // Never leave a half-copied tree behind
+                let _ = remove_path(dst);
+                return Err(e);
             }
+            remove_path(src)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn copy_tree(src: &Path, dst: &Path) -> io::Result<()> {
+    for entry in walkdir::WalkDir::new(src) {
+        let entry = entry.map_err(io::Error::other)?;
+        let relative = entry.path().strip_prefix(src).map_err(io::Error::other)?;
+        let target = if relative.as_os_str().is_empty() {
+            dst.to_path_buf()
+        } else {
+            dst.join(relative)
+        };
+
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(src)?, dst)
+}
+
+#[cfg(target_os = "windows")]
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::copy(src, dst).map(|_| ())
+}
+
+/// Compare file counts and byte totals of two trees
+fn verify_copy(src: &Path, dst: &Path) -> io::Result<()> {
+    let expected = tree_stats(src)?;
+    let actual = tree_stats(dst)?;
+    if expected != actual {
+        return Err(io::Error::other(format!(
+            "copy verification failed for {} ({} files/{} bytes copied, expected {} files/{} bytes)",
+            src.display(),
+            actual.0,
+            actual.1,
+            expected.0,
+            expected.1
+        )));
+    }
+    Ok(())
+}
+
+fn tree_stats(path: &Path) -> io::Result<(usize, u64)> {
+    let mut files = 0usize;
+    let mut bytes = 0u64;
+    for entry in walkdir::WalkDir::new(path) {
+        let entry = entry.map_err(io::Error::other)?;
+        if entry.file_type().is_file() {
+            files += 1;
+            bytes = bytes.saturating_add(entry.metadata().map_err(io::Error::other)?.len());
         }
     }
+    Ok((files, bytes))
+}
 
-    Ok((deleted, backup_path))
+fn remove_path(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
 }
 
 pub fn find_latest_backup() -> Result<Option<PathBuf>, std::io::Error> {
@@ -114,30 +296,144 @@ pub async fn restore_from_backup(backup_path: &Path) -> Result<usize, std::io::E
     let manifest_content = fs::read_to_string(&manifest_path)?;
     let manifest: BackupManifest = serde_json::from_str(&manifest_content)?;
 
+    // Older manifests stored entries under their bare file name
+    let items: Vec<(CacheEntry, String)> = if manifest.items.is_empty() {
+        manifest
+            .entries
+            .iter()
+            .map(|entry| {
+                let name = Path::new(&entry.path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                (entry.clone(), name)
+            })
+            .collect()
+    } else {
+        manifest
+            .items
+            .iter()
+            .map(|item| (item.entry.clone(), item.backup_name.clone()))
+            .collect()
+    };
+
     let mut restored_count = 0;
+    let mut failed_count = 0;
 
-    for entry in &manifest.entries {
-        let backup_file = backup_path.join(Path::new(&entry.path).file_name().unwrap_or_default());
+    for (entry, backup_name) in &items {
+        let backup_file = backup_path.join(backup_name);
         let original_path = Path::new(&entry.path);
 
-        if backup_file.exists() {
+        if fs::symlink_metadata(&backup_file).is_ok() {
             // Ensure parent directory exists
             if let Some(parent) = original_path.parent() {
                 fs::create_dir_all(parent)?;
             }
 
-            if let Err(e) = fs::rename(&backup_file, original_path) {
+            if let Err(e) = move_path(&backup_file, original_path) {
                 eprintln!("Warning: Failed to restore {}: {}", entry.path, e);
+                failed_count += 1;
             } else {
                 restored_count += 1;
             }
         }
     }
 
-    // Clean up backup directory after successful restore
-    if restored_count > 0 {
+    // Only drop the backup once everything in it is back in place
+    if restored_count > 0 && failed_count == 0 {
         let _ = fs::remove_dir_all(backup_path);
     }
 
     Ok(restored_count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+    use serde_json::json;
+
+    fn entry_for(path: &Path) -> CacheEntry {
+        CacheEntry {
+            id: format!("test:{}", path.display()),
+            kind: "test".to_string(),
+            name: "test".to_string(),
+            path: path.to_string_lossy().to_string(),
+            size_bytes: 0,
+            last_used_at: None,
+            stale: false,
+            details: json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_same_named_entries_do_not_collide() {
+        let root = scratch_dir("backup", "collide");
+        let first = root.join("a/target");
+        let second = root.join("b/target");
+        fs::create_dir_all(&first).unwrap();
+        fs::create_dir_all(&second).unwrap();
+        fs::write(first.join("marker"), "first").unwrap();
+        fs::write(second.join("marker"), "second").unwrap();
+
+        let entries = vec![entry_for(&first), entry_for(&second)];
+        let backup_dir = root.join("backup");
+        let outcome = backup_entries(&entries, &backup_dir).unwrap();
+        assert_eq!(outcome.deleted.len(), 2);
+        assert!(outcome.failed.is_empty());
+        assert!(!first.exists() && !second.exists());
+
+        let restored = restore_from_backup(&backup_dir).await.unwrap();
+        assert_eq!(restored, 2);
+        assert_eq!(fs::read_to_string(first.join("marker")).unwrap(), "first");
+        assert_eq!(fs::read_to_string(second.join("marker")).unwrap(), "second");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_failed_move_keeps_earlier_entries_restorable() {
+        let root = scratch_dir("backup", "partial");
+        let paths: Vec<PathBuf> = ["a", "b", "c"].iter().map(|n| root.join(n)).collect();
+        for path in &paths {
+            fs::write(path, path.to_string_lossy().as_bytes()).unwrap();
+        }
+
+        // A non-empty directory where the second entry would go makes its move fail
+        let backup_dir = root.join("backup");
+        fs::create_dir_all(backup_dir.join("0001-b/occupied")).unwrap();
+
+        let entries: Vec<CacheEntry> = paths.iter().map(|p| entry_for(p)).collect();
+        let outcome = backup_entries(&entries, &backup_dir).unwrap();
+        assert_eq!(outcome.deleted.len(), 2);
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(outcome.failed[0].entry.path, entries[1].path);
+        assert!(!paths[0].exists() && paths[1].exists() && !paths[2].exists());
+
+        fs::remove_dir_all(backup_dir.join("0001-b")).unwrap();
+        let restored = restore_from_backup(&backup_dir).await.unwrap();
+        assert_eq!(restored, 2);
+        assert!(paths.iter().all(|p| p.exists()));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_copy_tree_round_trip_verifies() {
+        let root = scratch_dir("backup", "copy");
+        let src = root.join("src");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("a.txt"), "hello").unwrap();
+        fs::write(src.join("nested/b.txt"), "world!").unwrap();
+
+        let dst = root.join("dst");
+        copy_tree(&src, &dst).unwrap();
+        verify_copy(&src, &dst).unwrap();
+        assert_eq!(tree_stats(&dst).unwrap(), (2, 11));
+
+        fs::write(dst.join("extra"), "x").unwrap();
+        assert!(verify_copy(&src, &dst).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
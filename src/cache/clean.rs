@@ -1,108 +1,298 @@
 use super::backup::safe_delete_entries;
-use super::detect::{
-    detect_cloudflare_caches, detect_hf_caches, detect_java_caches, detect_js_caches,
-    detect_js_pm_caches, detect_npx_caches, detect_python_caches, detect_rust_caches,
-    detect_torch_caches, detect_vercel_caches,
+use super::provider::{CacheProvider, CacheRegistry, CacheSelection, DetectContext};
+use super::size_index;
+use super::types::{
+    BlockedEntry, CacheEntry, CacheHolder, CleanFailure, CleanResponse, CleanSummary,
 };
-use super::types::{CleanResponse, CleanSummary};
-use std::path::Path;
+use crate::file_monitor::FileMonitor;
+use crate::process_monitor::ProcessMonitor;
+use std::collections::HashMap;
 
-pub async fn clean_caches(
-    lang: &str,
-    include_npx: bool,
-    include_js_pm: bool,
-    safe_delete: bool,
-    _force: bool,
-    include_hf: bool,
-    include_torch: bool,
-    include_vercel: bool,
-    include_cloudflare: bool,
-    stale_days: Option<u32>,
-) -> CleanResponse {
-    let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+/// Processes (other than ourselves) holding files open under an entry's path
+fn find_holders(monitor: &FileMonitor, entry: &CacheEntry) -> anyhow::Result<Vec<CacheHolder>> {
+    let own_pid = std::process::id() as i32;
+    Ok(monitor
+        .find_processes_in_directory(&entry.path)?
+        .into_iter()
+        .filter(|p| p.pid != own_pid)
+        .map(|p| CacheHolder {
+            pid: p.pid,
+            name: p.name,
+        })
+        .collect())
+}
 
-    let mut entries = Vec::new();
+/// Split entries into those free to remove and those in use, optionally stopping the holders first.
+/// An entry whose holders can't be determined counts as in use unless `force` is set.
+async fn check_holders(
+    entries: Vec<CacheEntry>,
+    kill_holders: bool,
+    force: bool,
+) -> (Vec<CacheEntry>, Vec<BlockedEntry>) {
+    let monitor = FileMonitor::new();
+    let mut free = Vec::new();
+    let mut blocked = Vec::new();
+    let mut warned = false;
+    let mut killer: Option<ProcessMonitor> = None;
 
-    // If specific flags are provided, only use those
-    if include_npx
-        || include_js_pm
-        || include_hf
-        || include_torch
-        || include_vercel
-        || include_cloudflare
-    {
-        // NPX caches
-        if include_npx {
-            entries.extend(detect_npx_caches(stale_days));
+    for entry in entries {
+        let holders = match find_holders(&monitor, &entry) {
+            Ok(holders) => holders,
+            Err(e) => {
+                if !warned {
+                    eprintln!(
+                        "Warning: Could not check for processes using caches: {}{}",
+                        e,
+                        if force {
+                            ""
+                        } else {
+                            "; those entries are kept (use --force to remove them anyway)"
+                        }
+                    );
+                    warned = true;
+                }
+                if !force {
+                    blocked.push(BlockedEntry {
+                        entry,
+                        holders: Vec::new(),
+                        error: Some(format!("could not check for processes using it: {}", e)),
+                    });
+                    continue;
+                }
+                Vec::new()
+            }
+        };
+        if holders.is_empty() {
+            free.push(entry);
+            continue;
         }
-
-        // JS Package Manager caches
-        if include_js_pm {
-            entries.extend(detect_js_pm_caches());
+        if !kill_holders {
+            blocked.push(BlockedEntry {
+                entry,
+                holders,
+                error: None,
+            });
+            continue;
         }
 
-        // Specialized integrations
-        if include_hf {
-            entries.extend(detect_hf_caches());
+        if killer.is_none() {
+            let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
+            match ProcessMonitor::new(update_sender, vec![], false, false) {
+                Ok(m) => killer = Some(m),
+                Err(e) => eprintln!("Warning: Could not set up process killing: {}", e),
+            }
         }
-
-        if include_torch {
-            entries.extend(detect_torch_caches());
+        if let Some(killer) = killer.as_mut() {
+            for holder in &holders {
+                if let Err(e) = killer
+                    .kill_process_with_context(holder.pid, "cache-clean", true)
+                    .await
+                {
+                    eprintln!(
+                        "Warning: Failed to stop {} (PID {}): {}",
+                        holder.name, holder.pid, e
+                    );
+                }
+            }
         }
 
-        if include_vercel {
-            entries.extend(detect_vercel_caches());
+        // Only proceed once nothing is holding the entry any more
+        match find_holders(&monitor, &entry) {
+            Ok(remaining) if remaining.is_empty() => free.push(entry),
+            Ok(remaining) => blocked.push(BlockedEntry {
+                entry,
+                holders: remaining,
+                error: None,
+            }),
+            Err(e) => blocked.push(BlockedEntry {
+                entry,
+                holders,
+                error: Some(format!("could not check for processes using it: {}", e)),
+            }),
         }
+    }
 
-        if include_cloudflare {
-            entries.extend(detect_cloudflare_caches());
-        }
-    } else {
-        // Use language-based detection
-        // Rust caches
-        if lang == "auto" || lang == "rust" {
-            entries.extend(detect_rust_caches(Path::new(&cwd)));
-        }
+    (free, blocked)
+}
 
-        // JavaScript/TypeScript caches
-        if lang == "auto" || lang == "js" {
-            entries.extend(detect_js_caches(Path::new(&cwd)));
-        }
+pub async fn clean_caches(
+    selection: &CacheSelection,
+    safe_delete: bool,
+    force: bool,
+    kill_holders: bool,
+    stale_days: Option<u32>,
+) -> CleanResponse {
+    let registry = CacheRegistry::load();
+    let ctx = DetectContext::from_cwd(stale_days);
+    clean_with(&registry, selection, &ctx, safe_delete, force, kill_holders).await
+}
 
-        // Python caches
-        if lang == "auto" || lang == "py" {
-            entries.extend(detect_python_caches());
+async fn clean_with(
+    registry: &CacheRegistry,
+    selection: &CacheSelection,
+    ctx: &DetectContext,
+    safe_delete: bool,
+    force: bool,
+    kill_holders: bool,
+) -> CleanResponse {
+    let mut candidates = Vec::new();
+    let mut refused = Vec::new();
+    let mut owners = HashMap::new();
+    let detected = registry.detect(selection, ctx);
+    size_index::flush();
+    for (provider, entry) in detected {
+        owners.insert(entry.id.clone(), provider);
+        if force || provider.is_safe_to_delete(&entry) {
+            candidates.push(entry);
+        } else {
+            refused.push(CleanFailure {
+                error: format!(
+                    "provider '{}' does not allow deleting this entry (use --force to override)",
+                    provider.id()
+                ),
+                entry,
+            });
         }
+    }
+
+    // Moving a cache away underneath a dev server or language server corrupts it
+    let (unheld, blocked) = check_holders(candidates, kill_holders, force).await;
 
-        // Java caches
-        if lang == "auto" || lang == "java" {
-            entries.extend(detect_java_caches());
+    let mut entries = Vec::new();
+    for entry in unheld {
+        let Some(provider) = owners.get(&entry.id) else {
+            continue;
+        };
+        match provider.pre_clean(&entry) {
+            Ok(()) => entries.push(entry),
+            Err(e) => refused.push(CleanFailure {
+                error: format!("could not prepare entry for removal: {}", e),
+                entry,
+            }),
         }
     }
 
-    match safe_delete_entries(&entries, safe_delete).await {
-        Ok((deleted, backup_path)) => {
-            let freed_bytes: u64 = deleted.iter().map(|e| e.size_bytes).sum();
-            let deleted_count = deleted.len();
+    let mut response = match safe_delete_entries(&entries, safe_delete).await {
+        Ok(outcome) => {
+            let freed_bytes: u64 = outcome.deleted.iter().map(|e| e.size_bytes).sum();
+            let deleted_count = outcome.deleted.len();
+            let failed_count = outcome.failed.len();
             CleanResponse {
-                deleted,
-                backed_up_to: backup_path,
+                deleted: outcome.deleted,
+                failed: outcome.failed,
+                blocked: vec![],
+                backed_up_to: outcome.backup_path,
                 summary: CleanSummary {
-                    freed_bytes: freed_bytes,
-                    deleted_count: deleted_count,
+                    freed_bytes,
+                    deleted_count,
+                    failed_count,
+                    blocked_count: 0,
                 },
             }
         }
         Err(e) => {
             eprintln!("Error during cleanup: {}", e);
+            // The backup fails before moving anything, so every entry is still in place
+            let failed: Vec<CleanFailure> = entries
+                .into_iter()
+                .map(|entry| CleanFailure {
+                    entry,
+                    error: e.to_string(),
+                })
+                .collect();
             CleanResponse {
                 deleted: vec![],
+                blocked: vec![],
                 backed_up_to: None,
                 summary: CleanSummary {
                     freed_bytes: 0,
                     deleted_count: 0,
+                    failed_count: failed.len(),
+                    blocked_count: 0,
                 },
+                failed,
             }
         }
+    };
+
+    // Let each provider react to what was removed on its behalf
+    let mut deleted_by_provider: HashMap<&str, (&dyn CacheProvider, Vec<CacheEntry>)> =
+        HashMap::new();
+    for entry in &response.deleted {
+        if let Some(provider) = owners.get(&entry.id) {
+            deleted_by_provider
+                .entry(provider.id())
+                .or_insert_with(|| (*provider, Vec::new()))
+                .1
+                .push(entry.clone());
+        }
+    }
+    for (provider, deleted) in deleted_by_provider.values() {
+        provider.post_clean(deleted);
+    }
+
+    response.summary.failed_count += refused.len();
+    response.failed.extend(refused);
+    response.summary.blocked_count = blocked.len();
+    response.blocked = blocked;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::detect::detect_ruby_caches_in;
+    use std::path::PathBuf;
+
+    /// The Ruby provider pointed at a scratch gem directory
+    struct ScratchRuby(PathBuf);
+
+    impl CacheProvider for ScratchRuby {
+        fn id(&self) -> &str {
+            "ruby"
+        }
+
+        fn lang(&self) -> Option<&str> {
+            Some("ruby")
+        }
+
+        fn detect(&self, _ctx: &DetectContext) -> Vec<CacheEntry> {
+            detect_ruby_caches_in(None, std::slice::from_ref(&self.0), None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_clean_keeps_installed_gems() {
+        let gem_dir =
+            std::env::temp_dir().join(format!("port-kill-clean-test-gems-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&gem_dir);
+        std::fs::create_dir_all(gem_dir.join("cache")).unwrap();
+        std::fs::write(gem_dir.join("cache/rake-13.0.0.gem"), "gem").unwrap();
+        std::fs::create_dir_all(gem_dir.join("gems/rake-13.0.0/lib")).unwrap();
+        std::fs::write(gem_dir.join("gems/rake-13.0.0/lib/rake.rb"), "# rake").unwrap();
+
+        let mut registry = CacheRegistry::new();
+        registry.register(Box::new(ScratchRuby(gem_dir.clone())));
+        let selection = CacheSelection {
+            lang: "auto".to_string(),
+            providers: vec![],
+        };
+        let ctx = DetectContext {
+            root: gem_dir.clone(),
+            stale_days: None,
+        };
+        let response = clean_with(&registry, &selection, &ctx, false, false, false).await;
+
+        assert!(gem_dir.join("gems/rake-13.0.0/lib/rake.rb").exists());
+        assert!(response
+            .failed
+            .iter()
+            .any(|f| f.entry.path.ends_with("rake-13.0.0") && f.error.contains("--force")));
+        assert!(!response
+            .failed
+            .iter()
+            .any(|f| f.entry.path.ends_with("cache")));
+
+        let _ = std::fs::remove_dir_all(&gem_dir);
     }
 }
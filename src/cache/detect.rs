@@ -4,26 +4,8 @@ use serde_json::json;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-fn dir_size_and_mtime(path: &Path) -> (u64, Option<DateTime<Utc>>) {
-    let mut total: u64 = 0;
-    let mut newest: Option<DateTime<Utc>> = None;
-    let _ = walkdir::WalkDir::new(path).into_iter().for_each(|e| {
-        if let Ok(entry) = e {
-            if let Ok(md) = entry.metadata() {
-                if md.is_file() {
-                    total = total.saturating_add(md.len());
-                }
-                if let Ok(modified) = md.modified() {
-                    let dt: DateTime<Utc> = modified.into();
-                    newest = Some(match newest {
-                        Some(n) => n.max(dt),
-                        None => dt,
-                    });
-                }
-            }
-        }
-    });
-    (total, newest)
+pub(crate) fn dir_size_and_mtime(path: &Path) -> (u64, Option<DateTime<Utc>>) {
+    super::size_index::global().measure(path)
 }
 
 pub fn detect_rust_caches(root: &Path) -> Vec<CacheEntry> {
@@ -598,3 +580,749 @@ pub fn detect_cloudflare_caches() -> Vec<CacheEntry> {
 
     entries
 }
+
+/// Directories never worth descending into when looking for project files
+const SKIP_SCAN_DIRS: [&str; 7] = [
+    "node_modules",
+    ".git",
+    "target",
+    ".gradle",
+    "bin",
+    "obj",
+    "vendor",
+];
+
+fn scan_project_files(root: &Path, matches: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !e.file_type().is_dir()
+                || !SKIP_SCAN_DIRS.contains(&e.file_name().to_string_lossy().as_ref())
+        })
+        .flatten()
+        .filter(|e| e.file_type().is_file() && matches(&e.file_name().to_string_lossy()))
+        .map(|e| e.into_path())
+        .collect()
+}
+
+fn is_older_than(mtime: Option<DateTime<Utc>>, stale_days: Option<u32>) -> bool {
+    match (stale_days, mtime) {
+        (Some(days), Some(last_used)) => (Utc::now() - last_used).num_days() > days as i64,
+        _ => false,
+    }
+}
+
+/// Compare two version strings numerically, treating pre-release versions as older
+pub(crate) fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    fn split(v: &str) -> (Vec<u64>, Option<&str>) {
+        let v = v.trim_start_matches('v');
+        let (core, pre) = match v.find(['-', '+']) {
+            Some(i) => (&v[..i], Some(&v[i + 1..])),
+            None => (v, None),
+        };
+        let parts = core
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().unwrap_or(0))
+            .collect();
+        (parts, pre)
+    }
+
+    let (a_core, a_pre) = split(a);
+    let (b_core, b_pre) = split(b);
+    let len = a_core.len().max(b_core.len());
+    for i in 0..len {
+        let ord = a_core.get(i).unwrap_or(&0).cmp(b_core.get(i).unwrap_or(&0));
+        if ord != std::cmp::Ordering::Equal {
+            return ord;
+        }
+    }
+    match (a_pre, b_pre) {
+        (None, None) => std::cmp::Ordering::Equal,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (Some(a), Some(b)) => a.cmp(b),
+    }
+}
+
+/// Mark entries stale when a newer version of the same package is cached alongside them.
+/// Relies on `details.package` and `details.version`.
+fn mark_superseded(entries: &mut [CacheEntry]) {
+    let mut latest: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+    for entry in entries.iter() {
+        if let (Some(package), Some(version)) = (
+            entry.details.get("package").and_then(|v| v.as_str()),
+            entry.details.get("version").and_then(|v| v.as_str()),
+        ) {
+            let newer = match latest.get(package) {
+                Some(current) => compare_versions(version, current).is_gt(),
+                None => true,
+            };
+            if newer {
+                latest.insert(package.to_string(), version.to_string());
+            }
+        }
+    }
+
+    for entry in entries.iter_mut() {
+        let package = entry.details.get("package").and_then(|v| v.as_str());
+        if let Some(newest) = package.and_then(|p| latest.get(p)).cloned() {
+            let version = entry.details["version"].as_str().unwrap_or_default();
+            if compare_versions(version, &newest).is_lt() {
+                entry.stale = true;
+            }
+            entry.details["latest"] = json!(newest);
+        }
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var("HOME").ok().map(PathBuf::from)
+}
+
+/// GOCACHE and GOMODCACHE as the go tool resolves them (go.env, `go env -w`, ...)
+fn go_env_dirs() -> Option<(Option<PathBuf>, Option<PathBuf>)> {
+    let output = std::process::Command::new("go")
+        .args(["env", "GOCACHE", "GOMODCACHE"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines().map(|l| {
+        let l = l.trim();
+        (!l.is_empty() && l != "off").then(|| PathBuf::from(l))
+    });
+    Some((lines.next().flatten(), lines.next().flatten()))
+}
+
+fn go_cache_dirs() -> (Option<PathBuf>, Option<PathBuf>) {
+    if let Some(dirs) = go_env_dirs() {
+        return dirs;
+    }
+    let gocache = std::env::var("GOCACHE")
+        .ok()
+        .filter(|v| !v.is_empty() && v != "off")
+        .map(PathBuf::from)
+        .or_else(|| {
+            if cfg!(target_os = "macos") {
+                home_dir().map(|h| h.join("Library/Caches/go-build"))
+            } else if cfg!(target_os = "windows") {
+                std::env::var("LocalAppData")
+                    .ok()
+                    .map(|d| PathBuf::from(d).join("go-build"))
+            } else {
+                std::env::var("XDG_CACHE_HOME")
+                    .ok()
+                    .map(PathBuf::from)
+                    .or_else(|| home_dir().map(|h| h.join(".cache")))
+                    .map(|d| d.join("go-build"))
+            }
+        });
+
+    let gomodcache = std::env::var("GOMODCACHE")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            let gopath = std::env::var("GOPATH")
+                .ok()
+                .and_then(|p| std::env::split_paths(&p).next())
+                .or_else(|| home_dir().map(|h| h.join("go")));
+            gopath.map(|p| p.join("pkg/mod"))
+        });
+
+    (gocache, gomodcache)
+}
+
+pub fn detect_go_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
+    let (gocache, gomodcache) = go_cache_dirs();
+    detect_go_caches_in(gocache.as_deref(), gomodcache.as_deref(), stale_days)
+}
+
+fn detect_go_caches_in(
+    gocache: Option<&Path>,
+    gomodcache: Option<&Path>,
+    stale_days: Option<u32>,
+) -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+
+    if let Some(gocache) = gocache.filter(|p| p.exists()) {
+        let (size, mtime) = dir_size_and_mtime(gocache);
+        entries.push(CacheEntry {
+            id: "go:build-cache".to_string(),
+            kind: "go".to_string(),
+            name: "Go build cache".to_string(),
+            path: gocache.to_string_lossy().to_string(),
+            size_bytes: size,
+            last_used_at: mtime,
+            stale: is_older_than(mtime, stale_days),
+            details: json!({"type": "go_build_cache"}),
+        });
+    }
+
+    let Some(modcache) = gomodcache.filter(|p| p.exists()) else {
+        return entries;
+    };
+
+    let download = modcache.join("cache");
+    if download.exists() {
+        let (size, mtime) = dir_size_and_mtime(&download);
+        entries.push(CacheEntry {
+            id: "go:mod-download".to_string(),
+            kind: "go".to_string(),
+            name: "Go module download cache".to_string(),
+            path: download.to_string_lossy().to_string(),
+            size_bytes: size,
+            last_used_at: mtime,
+            stale: is_older_than(mtime, stale_days),
+            details: json!({"type": "go_mod", "readOnly": true}),
+        });
+    }
+
+    // Extracted modules live at <modcache>/<module path>@<version>. Builds use them in
+    // place, so they are installed packages rather than a download cache.
+    let mut modules = Vec::new();
+    let walker = walkdir::WalkDir::new(modcache)
+        .into_iter()
+        .filter_entry(|e| {
+            e.file_type().is_dir()
+                && !(e.depth() == 1 && e.file_name() == "cache")
+                && (e.depth() == 0
+                    || !e
+                        .path()
+                        .parent()
+                        .and_then(|p| p.file_name())
+                        .map(|n| n.to_string_lossy().contains('@'))
+                        .unwrap_or(false))
+        });
+    for entry in walker.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some((module_name, version)) = name.rsplit_once('@') else {
+            continue;
+        };
+        let relative = entry.path().strip_prefix(modcache).unwrap_or(entry.path());
+        let parent = relative
+            .parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let module = if parent.is_empty() {
+            module_name.to_string()
+        } else {
+            format!("{}/{}", parent, module_name)
+        };
+        let (size, mtime) = dir_size_and_mtime(entry.path());
+        modules.push(CacheEntry {
+            id: format!("go:mod:{}@{}", module, version),
+            kind: "go".to_string(),
+            name: module.clone(),
+            path: entry.path().to_string_lossy().to_string(),
+            size_bytes: size,
+            last_used_at: mtime,
+            stale: false,
+            details: json!({
+                "type": "go_mod",
+                "package": module,
+                "version": version,
+                "readOnly": true,
+                "installed": true
+            }),
+        });
+    }
+    mark_superseded(&mut modules);
+    entries.extend(modules);
+
+    entries
+}
+
+/// Go marks module cache contents read-only; restore write permission so the tree can be removed
+pub fn make_tree_writable(path: &Path) -> std::io::Result<()> {
+    for entry in walkdir::WalkDir::new(path) {
+        let entry = entry.map_err(std::io::Error::other)?;
+        if entry.file_type().is_symlink() {
+            continue;
+        }
+        let mut perms = entry
+            .metadata()
+            .map_err(std::io::Error::other)?
+            .permissions();
+        #[cfg(not(target_os = "windows"))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            perms.set_mode(perms.mode() | 0o200);
+        }
+        #[cfg(target_os = "windows")]
+        {
+            #[allow(clippy::permissions_set_readonly_false)]
+            perms.set_readonly(false);
+        }
+        fs::set_permissions(entry.path(), perms)?;
+    }
+    Ok(())
+}
+
+fn gradle_version_from_url(url: &str) -> Option<String> {
+    let file = url.rsplit('/').next()?;
+    let rest = file.strip_prefix("gradle-")?;
+    let rest = rest.strip_suffix(".zip")?;
+    let version = rest
+        .strip_suffix("-bin")
+        .or_else(|| rest.strip_suffix("-all"))?;
+    Some(version.to_string())
+}
+
+/// Gradle versions referenced by wrapper properties under `root`
+fn referenced_gradle_versions(root: &Path) -> Vec<String> {
+    let mut versions = Vec::new();
+    for props in scan_project_files(root, |n| n == "gradle-wrapper.properties") {
+        if let Ok(content) = fs::read_to_string(&props) {
+            for line in content.lines() {
+                if let Some(url) = line.trim().strip_prefix("distributionUrl=") {
+                    if let Some(version) = gradle_version_from_url(&url.replace("\\:", ":")) {
+                        if !versions.contains(&version) {
+                            versions.push(version);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    versions
+}
+
+pub fn detect_gradle_caches(root: &Path, stale_days: Option<u32>) -> Vec<CacheEntry> {
+    let gradle_home = std::env::var("GRADLE_USER_HOME")
+        .ok()
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|h| h.join(".gradle")));
+    match gradle_home {
+        Some(gradle_home) => detect_gradle_caches_in(&gradle_home, root, stale_days),
+        None => Vec::new(),
+    }
+}
+
+/// Gradle's dists and caches are shared by every project on the machine, so whether the
+/// project under `root` references a version is only reported, never taken as staleness
+fn detect_gradle_caches_in(
+    gradle_home: &Path,
+    root: &Path,
+    stale_days: Option<u32>,
+) -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+    if !gradle_home.exists() {
+        return entries;
+    }
+    let referenced = referenced_gradle_versions(root);
+
+    // Wrapper distributions: wrapper/dists/gradle-<version>-<bin|all>
+    if let Ok(dists) = fs::read_dir(gradle_home.join("wrapper/dists")) {
+        for dist in dists.flatten().filter(|d| d.path().is_dir()) {
+            let dist_name = dist.file_name().to_string_lossy().to_string();
+            let Some(version) = gradle_version_from_url(&format!("{}.zip", dist_name)) else {
+                continue;
+            };
+            let (size, mtime) = dir_size_and_mtime(&dist.path());
+            entries.push(CacheEntry {
+                id: format!("gradle:wrapper:{}", dist_name),
+                kind: "gradle".to_string(),
+                name: format!("Gradle {} wrapper", version),
+                path: dist.path().to_string_lossy().to_string(),
+                size_bytes: size,
+                last_used_at: mtime,
+                stale: is_older_than(mtime, stale_days),
+                details: json!({
+                    "type": "gradle_wrapper",
+                    "version": version,
+                    "referencedHere": referenced.contains(&version)
+                }),
+            });
+        }
+    }
+
+    // Per-version caches (caches/<version>) plus the shared dependency cache
+    if let Ok(caches) = fs::read_dir(gradle_home.join("caches")) {
+        for cache in caches.flatten().filter(|c| c.path().is_dir()) {
+            let name = cache.file_name().to_string_lossy().to_string();
+            let versioned = name.chars().next().is_some_and(|c| c.is_ascii_digit());
+            if !versioned && name != "modules-2" {
+                continue;
+            }
+            let (size, mtime) = dir_size_and_mtime(&cache.path());
+            entries.push(CacheEntry {
+                id: format!("gradle:caches:{}", name),
+                kind: "gradle".to_string(),
+                name: if versioned {
+                    format!("Gradle {} caches", name)
+                } else {
+                    "Gradle dependency cache".to_string()
+                },
+                path: cache.path().to_string_lossy().to_string(),
+                size_bytes: size,
+                last_used_at: mtime,
+                stale: is_older_than(mtime, stale_days),
+                details: json!({
+                    "type": if versioned { "gradle_version_cache" } else { "gradle_dependency_cache" },
+                    "version": if versioned { Some(name.clone()) } else { None },
+                    "referencedHere": versioned && referenced.contains(&name),
+                }),
+            });
+        }
+    }
+
+    entries
+}
+
+pub fn detect_dotnet_caches(root: &Path, stale_days: Option<u32>) -> Vec<CacheEntry> {
+    let nuget = std::env::var("NUGET_PACKAGES")
+        .ok()
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|h| h.join(".nuget/packages")));
+    detect_dotnet_caches_in(
+        nuget.as_deref(),
+        nuget_http_cache().as_deref(),
+        root,
+        stale_days,
+    )
+}
+
+/// NuGet's HTTP cache of downloaded packages and feed responses
+fn nuget_http_cache() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("NUGET_HTTP_CACHE_PATH") {
+        return Some(PathBuf::from(dir));
+    }
+    let base = if cfg!(target_os = "windows") {
+        std::env::var("LocalAppData")
+            .ok()
+            .map(|d| PathBuf::from(d).join("NuGet"))
+    } else {
+        home_dir().map(|h| h.join(".local/share/NuGet"))
+    }?;
+    // Renamed from v3-cache in newer SDKs
+    ["http-cache", "v3-cache"]
+        .iter()
+        .map(|name| base.join(name))
+        .find(|dir| dir.exists())
+}
+
+fn project_target_frameworks(project: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(project) else {
+        return Vec::new();
+    };
+    let re = regex::Regex::new(r"<TargetFrameworks?>([^<]+)</TargetFrameworks?>").unwrap();
+    re.captures_iter(&content)
+        .flat_map(|c| {
+            c[1].split(';')
+                .map(|f| f.trim().to_lowercase())
+                .filter(|f| !f.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn detect_dotnet_caches_in(
+    nuget: Option<&Path>,
+    http_cache: Option<&Path>,
+    root: &Path,
+    stale_days: Option<u32>,
+) -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+
+    if let Some(http_cache) = http_cache.filter(|p| p.exists()) {
+        let (size, mtime) = dir_size_and_mtime(http_cache);
+        entries.push(CacheEntry {
+            id: "dotnet:http-cache".to_string(),
+            kind: "dotnet".to_string(),
+            name: "NuGet HTTP cache".to_string(),
+            path: http_cache.to_string_lossy().to_string(),
+            size_bytes: size,
+            last_used_at: mtime,
+            stale: is_older_than(mtime, stale_days),
+            details: json!({"type": "nuget_http_cache"}),
+        });
+    }
+
+    // NuGet global packages folder: <packages>/<id>/<version>. Restored projects build
+    // against it directly, so it is an install location rather than a cache.
+    if let Some(nuget) = nuget.filter(|p| p.exists()) {
+        let mut packages = Vec::new();
+        for package in fs::read_dir(nuget).into_iter().flatten().flatten() {
+            let package_id = package.file_name().to_string_lossy().to_string();
+            for version in fs::read_dir(package.path()).into_iter().flatten().flatten() {
+                if !version.path().is_dir() {
+                    continue;
+                }
+                let version_name = version.file_name().to_string_lossy().to_string();
+                let (size, mtime) = dir_size_and_mtime(&version.path());
+                packages.push(CacheEntry {
+                    id: format!("dotnet:nuget:{}@{}", package_id, version_name),
+                    kind: "dotnet".to_string(),
+                    name: package_id.clone(),
+                    path: version.path().to_string_lossy().to_string(),
+                    size_bytes: size,
+                    last_used_at: mtime,
+                    stale: false,
+                    details: json!({
+                        "type": "nuget_package",
+                        "package": package_id,
+                        "version": version_name,
+                        "installed": true
+                    }),
+                });
+            }
+        }
+        mark_superseded(&mut packages);
+        entries.extend(packages);
+    }
+
+    // Project bin/ and obj/ output, laid out as <dir>/<configuration>/<tfm>
+    let projects = scan_project_files(root, |n| {
+        n.ends_with(".csproj") || n.ends_with(".fsproj") || n.ends_with(".vbproj")
+    });
+    for project in projects {
+        let Some(project_dir) = project.parent() else {
+            continue;
+        };
+        let targets = project_target_frameworks(&project);
+        for output in ["bin", "obj"] {
+            let dir = project_dir.join(output);
+            if !dir.exists() {
+                continue;
+            }
+            let built: Vec<String> = fs::read_dir(&dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter(|c| c.path().is_dir())
+                .flat_map(|config| fs::read_dir(config.path()).into_iter().flatten().flatten())
+                .filter(|f| f.path().is_dir())
+                .map(|f| f.file_name().to_string_lossy().to_lowercase())
+                .collect();
+            // Stale once nothing in it was built for a framework the project still targets
+            let stale = !built.is_empty() && !built.iter().any(|f| targets.contains(f));
+            let (size, mtime) = dir_size_and_mtime(&dir);
+            entries.push(CacheEntry {
+                id: format!("dotnet:{}:{}", output, dir.to_string_lossy()),
+                kind: "dotnet".to_string(),
+                name: format!(".NET {} output", output),
+                path: dir.to_string_lossy().to_string(),
+                size_bytes: size,
+                last_used_at: mtime,
+                stale,
+                details: json!({
+                    "type": format!("dotnet_{}", output),
+                    "project": project.to_string_lossy(),
+                    "targetFrameworks": targets,
+                    "builtFrameworks": built
+                }),
+            });
+        }
+    }
+
+    entries
+}
+
+pub fn detect_ruby_caches(stale_days: Option<u32>) -> Vec<CacheEntry> {
+    let mut gem_dirs = Vec::new();
+    if let Ok(gem_home) = std::env::var("GEM_HOME") {
+        gem_dirs.push(PathBuf::from(gem_home));
+    }
+    if let Some(home) = home_dir() {
+        for base in [home.join(".gem/ruby"), home.join(".local/share/gem/ruby")] {
+            for abi in fs::read_dir(base).into_iter().flatten().flatten() {
+                if abi.path().is_dir() && !gem_dirs.contains(&abi.path()) {
+                    gem_dirs.push(abi.path());
+                }
+            }
+        }
+    }
+    let bundle_cache = home_dir().map(|h| h.join(".bundle/cache"));
+    detect_ruby_caches_in(bundle_cache.as_deref(), &gem_dirs, stale_days)
+}
+
+/// Split a gem directory name like `aws-sdk-s3-1.2.0` into name and version
+fn split_gem_name(dir_name: &str) -> Option<(&str, &str)> {
+    let bytes = dir_name.as_bytes();
+    (1..bytes.len())
+        .find(|&i| bytes[i - 1] == b'-' && bytes[i].is_ascii_digit())
+        .map(|i| (&dir_name[..i - 1], &dir_name[i..]))
+}
+
+pub(crate) fn detect_ruby_caches_in(
+    bundle_cache: Option<&Path>,
+    gem_dirs: &[PathBuf],
+    stale_days: Option<u32>,
+) -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+
+    if let Some(bundle_cache) = bundle_cache.filter(|p| p.exists()) {
+        let (size, mtime) = dir_size_and_mtime(bundle_cache);
+        entries.push(CacheEntry {
+            id: "ruby:bundler".to_string(),
+            kind: "ruby".to_string(),
+            name: "Bundler cache".to_string(),
+            path: bundle_cache.to_string_lossy().to_string(),
+            size_bytes: size,
+            last_used_at: mtime,
+            stale: is_older_than(mtime, stale_days),
+            details: json!({"type": "bundler_cache"}),
+        });
+    }
+
+    for gem_dir in gem_dirs {
+        let download = gem_dir.join("cache");
+        if download.exists() {
+            let (size, mtime) = dir_size_and_mtime(&download);
+            entries.push(CacheEntry {
+                id: format!("ruby:gem-cache:{}", download.to_string_lossy()),
+                kind: "ruby".to_string(),
+                name: "RubyGems download cache".to_string(),
+                path: download.to_string_lossy().to_string(),
+                size_bytes: size,
+                last_used_at: mtime,
+                stale: is_older_than(mtime, stale_days),
+                details: json!({"type": "gem_cache"}),
+            });
+        }
+
+        // Installed gems, which bundles and scripts load from in place
+        let mut gems = Vec::new();
+        for gem in fs::read_dir(gem_dir.join("gems"))
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            let dir_name = gem.file_name().to_string_lossy().to_string();
+            let Some((name, version)) = split_gem_name(&dir_name) else {
+                continue;
+            };
+            let (size, mtime) = dir_size_and_mtime(&gem.path());
+            gems.push(CacheEntry {
+                id: format!("ruby:gem:{}", gem.path().to_string_lossy()),
+                kind: "ruby".to_string(),
+                name: name.to_string(),
+                path: gem.path().to_string_lossy().to_string(),
+                size_bytes: size,
+                last_used_at: mtime,
+                stale: false,
+                details: json!({
+                    "type": "gem",
+                    // Scope by gem dir so different Ruby ABIs don't supersede each other
+                    "package": format!("{}:{}", gem_dir.to_string_lossy(), name),
+                    "version": version,
+                    "installed": true
+                }),
+            });
+        }
+        mark_superseded(&mut gems);
+        entries.extend(gems);
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    #[test]
+    fn test_compare_versions() {
+        use std::cmp::Ordering;
+        assert_eq!(compare_versions("v1.10.0", "v1.9.3"), Ordering::Greater);
+        assert_eq!(compare_versions("8.5", "8.5.0"), Ordering::Equal);
+        assert_eq!(compare_versions("2.0.0-rc1", "2.0.0"), Ordering::Less);
+        assert_eq!(
+            compare_versions("v0.0.0-20230101-abc", "v0.0.0-20240101-def"),
+            Ordering::Less
+        );
+        assert_eq!(
+            split_gem_name("aws-sdk-s3-1.2.0"),
+            Some(("aws-sdk-s3", "1.2.0"))
+        );
+    }
+
+    #[test]
+    fn test_gradle_wrapper_staleness() {
+        let root = scratch_dir("detect", "gradle");
+        let gradle_home = root.join("gradle-home");
+        fs::create_dir_all(gradle_home.join("wrapper/dists/gradle-8.5-bin/abc")).unwrap();
+        fs::create_dir_all(gradle_home.join("wrapper/dists/gradle-7.6-all/def")).unwrap();
+        let project = root.join("project/gradle/wrapper");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(
+            project.join("gradle-wrapper.properties"),
+            "distributionUrl=https\\://services.gradle.org/distributions/gradle-8.5-bin.zip\n",
+        )
+        .unwrap();
+
+        // Other projects may still use 7.6, so only age makes a dist stale
+        let entries = detect_gradle_caches_in(&gradle_home, &root.join("project"), None);
+        let referenced: Vec<_> = entries
+            .iter()
+            .map(|e| {
+                assert!(!e.stale);
+                (
+                    e.details["version"].as_str().unwrap(),
+                    e.details["referencedHere"].as_bool().unwrap(),
+                )
+            })
+            .collect();
+        assert!(referenced.contains(&("8.5", true)));
+        assert!(referenced.contains(&("7.6", false)));
+
+        let entries = detect_gradle_caches_in(&gradle_home, &root.join("project"), Some(0));
+        assert!(entries.iter().all(|e| !e.stale));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_go_modules_superseded_and_removable() {
+        let root = scratch_dir("detect", "go");
+        let modcache = root.join("mod");
+        let old = modcache.join("github.com/foo/bar@v1.2.0");
+        let new = modcache.join("github.com/foo/bar@v1.10.0");
+        fs::create_dir_all(&old).unwrap();
+        fs::create_dir_all(&new).unwrap();
+        fs::write(old.join("go.mod"), "module github.com/foo/bar").unwrap();
+
+        let entries = detect_go_caches_in(None, Some(&modcache), None);
+        assert_eq!(entries.len(), 2);
+        for entry in &entries {
+            assert_eq!(entry.name, "github.com/foo/bar");
+            assert_eq!(entry.stale, entry.details["version"] == "v1.2.0");
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(old.join("go.mod"), fs::Permissions::from_mode(0o444)).unwrap();
+            fs::set_permissions(&old, fs::Permissions::from_mode(0o555)).unwrap();
+        }
+        make_tree_writable(&old).unwrap();
+        fs::remove_dir_all(&old).unwrap();
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_dotnet_output_staleness() {
+        let root = scratch_dir("detect", "dotnet");
+        let project = root.join("App");
+        fs::create_dir_all(project.join("bin/Debug/net6.0")).unwrap();
+        fs::create_dir_all(project.join("obj/Debug/net8.0")).unwrap();
+        fs::write(
+            project.join("App.csproj"),
+            "<Project><PropertyGroup><TargetFramework>net8.0</TargetFramework></PropertyGroup></Project>",
+        )
+        .unwrap();
+
+        let entries = detect_dotnet_caches_in(None, None, &root, None);
+        let bin = entries.iter().find(|e| e.path.ends_with("bin")).unwrap();
+        let obj = entries.iter().find(|e| e.path.ends_with("obj")).unwrap();
+        assert!(bin.stale);
+        assert!(!obj.stale);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
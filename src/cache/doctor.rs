@@ -1,3 +1,4 @@
+use super::size_index;
 use serde::Serialize;
 use std::fs;
 use std::path::PathBuf;
@@ -77,14 +78,13 @@ pub async fn doctor() -> DoctorReport {
         for (dir, threshold) in &large_caches {
             let path = PathBuf::from(&home).join(dir);
             if path.exists() {
-                if let Ok(size) = get_dir_size(&path) {
-                    if size > *threshold {
-                        warnings.push(format!(
-                            "Large cache detected: {} ({:.1} MB)",
-                            dir,
-                            size as f64 / 1_000_000.0
-                        ));
-                    }
+                let (size, _) = size_index::global().measure(&path);
+                if size > *threshold {
+                    warnings.push(format!(
+                        "Large cache detected: {} ({:.1} MB)",
+                        dir,
+                        size as f64 / 1_000_000.0
+                    ));
                 }
             }
         }
@@ -115,6 +115,8 @@ pub async fn doctor() -> DoctorReport {
         }
     }
 
+    size_index::flush();
+
     let ok = errors.is_empty();
     DoctorReport {
         ok,
@@ -123,17 +125,3 @@ pub async fn doctor() -> DoctorReport {
         errors,
     }
 }
-
-fn get_dir_size(path: &std::path::Path) -> Result<u64, std::io::Error> {
-    let mut total = 0u64;
-    for entry in walkdir::WalkDir::new(path) {
-        if let Ok(entry) = entry {
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_file() {
-                    total += metadata.len();
-                }
-            }
-        }
-    }
-    Ok(total)
-}
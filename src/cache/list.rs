@@ -1,82 +1,52 @@
-use super::detect::{
-    detect_cloudflare_caches, detect_hf_caches, detect_java_caches, detect_js_caches,
-    detect_js_pm_caches, detect_npx_caches, detect_python_caches, detect_rust_caches,
-    detect_torch_caches, detect_vercel_caches,
-};
 use super::output::{human_since, human_size, print_cache_summary, print_table};
-use super::types::{ListResponse, ListSummary};
-use std::path::Path;
+use super::provider::{CacheRegistry, CacheSelection, DetectContext};
+use super::size_index;
+use super::types::{CacheEntry, ListResponse, ListSummary};
 
-pub async fn list_caches(
-    lang: &str,
-    include_npx: bool,
-    include_js_pm: bool,
-    include_hf: bool,
-    include_torch: bool,
-    include_vercel: bool,
-    include_cloudflare: bool,
+pub async fn list_caches(selection: &CacheSelection, stale_days: Option<u32>) -> ListResponse {
+    collect_entries(selection, stale_days, &|_| {})
+}
+
+/// List caches while reporting entries as they are measured: one JSON event per line on stdout
+/// with `json`, otherwise progress lines on stderr. The JSON stream ends with a summary event.
+pub async fn list_caches_streaming(
+    selection: &CacheSelection,
     stale_days: Option<u32>,
+    json: bool,
 ) -> ListResponse {
-    let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
-
-    let mut entries = Vec::new();
-
-    // If specific flags are provided, only use those
-    if include_npx
-        || include_js_pm
-        || include_hf
-        || include_torch
-        || include_vercel
-        || include_cloudflare
-    {
-        // NPX caches
-        if include_npx {
-            entries.extend(detect_npx_caches(stale_days));
-        }
-
-        // JS Package Manager caches
-        if include_js_pm {
-            entries.extend(detect_js_pm_caches());
-        }
-
-        // Specialized integrations
-        if include_hf {
-            entries.extend(detect_hf_caches());
-        }
-
-        if include_torch {
-            entries.extend(detect_torch_caches());
-        }
-
-        if include_vercel {
-            entries.extend(detect_vercel_caches());
-        }
-
-        if include_cloudflare {
-            entries.extend(detect_cloudflare_caches());
-        }
-    } else {
-        // Use language-based detection
-        // Rust caches
-        if lang == "auto" || lang == "rust" {
-            entries.extend(detect_rust_caches(Path::new(&cwd)));
-        }
-
-        // JavaScript/TypeScript caches
-        if lang == "auto" || lang == "js" {
-            entries.extend(detect_js_caches(Path::new(&cwd)));
-        }
-
-        // Python caches
-        if lang == "auto" || lang == "py" {
-            entries.extend(detect_python_caches());
-        }
-
-        // Java caches
-        if lang == "auto" || lang == "java" {
-            entries.extend(detect_java_caches());
+    let resp = collect_entries(selection, stale_days, &|entry: &CacheEntry| {
+        if json {
+            println!("{}", serde_json::json!({"event": "entry", "entry": entry}));
+        } else {
+            eprintln!(
+                "⏳ Measured {} ({})",
+                entry.path,
+                human_size(entry.size_bytes)
+            );
         }
+    });
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({"event": "summary", "summary": resp.summary})
+        );
     }
+    resp
+}
+
+fn collect_entries(
+    selection: &CacheSelection,
+    stale_days: Option<u32>,
+    on_entry: &(dyn Fn(&CacheEntry) + Sync),
+) -> ListResponse {
+    let registry = CacheRegistry::load();
+    let ctx = DetectContext::from_cwd(stale_days);
+    let entries: Vec<_> = registry
+        .detect_with_progress(selection, &ctx, on_entry)
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .collect();
+    size_index::flush();
 
     // summary
     let mut total = 0u64;
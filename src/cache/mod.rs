@@ -6,5 +6,7 @@ pub mod js_pm;
 pub mod list;
 pub mod npx;
 pub mod output;
+pub mod provider;
 pub mod restore;
+pub mod size_index;
 pub mod types;
@@ -1,11 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 
-pub fn print_or_json<T: Serialize + std::fmt::Debug>(value: &T, json: bool) {
+pub fn print_or_json<T: Serialize + std::fmt::Debug>(kind: &str, value: &T, json: bool) {
     if json {
-        match serde_json::to_string_pretty(value) {
-            Ok(s) => println!("{}", s),
-            Err(e) => eprintln!("Failed to serialize JSON: {}", e),
+        if let Err(e) = crate::output::print(kind, value) {
+            eprintln!("Failed to serialize JSON: {}", e);
         }
     } else {
         println!("{:?}", value);
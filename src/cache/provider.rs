@@ -0,0 +1,522 @@
+use super::detect::{
+    detect_cloudflare_caches, detect_dotnet_caches, detect_go_caches, detect_gradle_caches,
+    detect_hf_caches, detect_java_caches, detect_js_caches, detect_js_pm_caches, detect_npx_caches,
+    detect_python_caches, detect_ruby_caches, detect_rust_caches, detect_torch_caches,
+    detect_vercel_caches, dir_size_and_mtime, make_tree_writable,
+};
+use super::types::CacheEntry;
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Inputs shared by every provider during detection
+#[derive(Debug, Clone)]
+pub struct DetectContext {
+    pub root: PathBuf,
+    pub stale_days: Option<u32>,
+}
+
+impl DetectContext {
+    pub fn from_cwd(stale_days: Option<u32>) -> Self {
+        Self {
+            root: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+            stale_days,
+        }
+    }
+}
+
+/// Which providers a list/clean run should use
+#[derive(Debug, Clone, Default)]
+pub struct CacheSelection {
+    /// Language filter used when no provider is named explicitly ("auto" matches all)
+    pub lang: String,
+    /// Explicitly requested provider ids
+    pub providers: Vec<String>,
+}
+
+/// A source of cache entries that can be listed and cleaned
+pub trait CacheProvider: Send + Sync {
+    fn id(&self) -> &str;
+
+    /// Language this provider belongs to; providers without one only run when named explicitly
+    fn lang(&self) -> Option<&str> {
+        None
+    }
+
+    fn detect(&self, ctx: &DetectContext) -> Vec<CacheEntry>;
+
+    /// Whether a clean may remove the entry without `--force`. Installed packages (entries
+    /// marked `details.installed`) are not a cache and need `--force`.
+    fn is_safe_to_delete(&self, entry: &CacheEntry) -> bool {
+        entry.details.get("installed").and_then(|v| v.as_bool()) != Some(true)
+    }
+
+    /// Prepare an entry for removal; an error leaves the entry in place and reports it as failed
+    fn pre_clean(&self, _entry: &CacheEntry) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    /// Called with the entries of this provider that were removed by a clean
+    fn post_clean(&self, _deleted: &[CacheEntry]) {}
+}
+
+/// Provider backed by one of the built-in `detect_*_caches` functions
+pub struct BuiltinProvider {
+    id: &'static str,
+    lang: Option<&'static str>,
+    detect: fn(&DetectContext) -> Vec<CacheEntry>,
+}
+
+impl CacheProvider for BuiltinProvider {
+    fn id(&self) -> &str {
+        self.id
+    }
+
+    fn lang(&self) -> Option<&str> {
+        self.lang
+    }
+
+    fn detect(&self, ctx: &DetectContext) -> Vec<CacheEntry> {
+        (self.detect)(ctx)
+    }
+}
+
+/// Go build and module caches; the module cache is read-only on disk
+pub struct GoProvider;
+
+impl CacheProvider for GoProvider {
+    fn id(&self) -> &str {
+        "go"
+    }
+
+    fn lang(&self) -> Option<&str> {
+        Some("go")
+    }
+
+    fn detect(&self, ctx: &DetectContext) -> Vec<CacheEntry> {
+        detect_go_caches(ctx.stale_days)
+    }
+
+    fn pre_clean(&self, entry: &CacheEntry) -> std::io::Result<()> {
+        if entry.details.get("readOnly").and_then(|v| v.as_bool()) == Some(true) {
+            make_tree_writable(Path::new(&entry.path))?;
+        }
+        Ok(())
+    }
+}
+
+/// Staleness rule for a user-defined provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum StaleRule {
+    /// Never report entries as stale
+    Never,
+    /// Stale when nothing inside was modified for this many days
+    UnusedFor { days: u32 },
+}
+
+/// A provider declared in the user's cache provider config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserProviderConfig {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub kind: String,
+    /// Glob patterns; `~` expands to HOME and relative patterns resolve against the scan root
+    pub paths: Vec<String>,
+    #[serde(default)]
+    pub stale: Option<StaleRule>,
+    /// Set to false to list entries without ever deleting them
+    #[serde(default = "default_true")]
+    pub deletable: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UserProvidersFile {
+    #[serde(default)]
+    pub providers: Vec<UserProviderConfig>,
+}
+
+pub struct UserProvider {
+    config: UserProviderConfig,
+}
+
+impl UserProvider {
+    pub fn new(config: UserProviderConfig) -> Self {
+        Self { config }
+    }
+
+    fn expand_pattern(&self, pattern: &str, root: &Path) -> String {
+        let expanded = match pattern.strip_prefix("~/") {
+            Some(rest) => {
+                let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+                format!("{}/{}", home, rest)
+            }
+            None => pattern.to_string(),
+        };
+        if Path::new(&expanded).is_absolute() {
+            expanded
+        } else {
+            root.join(expanded).to_string_lossy().to_string()
+        }
+    }
+
+    fn is_stale(&self, mtime: Option<chrono::DateTime<chrono::Utc>>, ctx: &DetectContext) -> bool {
+        let days = match &self.config.stale {
+            Some(StaleRule::Never) => return false,
+            Some(StaleRule::UnusedFor { days }) => *days,
+            None => match ctx.stale_days {
+                Some(days) => days,
+                None => return false,
+            },
+        };
+        match mtime {
+            Some(last_used) => (chrono::Utc::now() - last_used).num_days() > days as i64,
+            None => true,
+        }
+    }
+}
+
+impl CacheProvider for UserProvider {
+    fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn lang(&self) -> Option<&str> {
+        Some(&self.config.kind)
+    }
+
+    fn detect(&self, ctx: &DetectContext) -> Vec<CacheEntry> {
+        let mut entries = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+
+        for pattern in &self.config.paths {
+            let pattern = self.expand_pattern(pattern, &ctx.root);
+            let paths = match glob::glob(&pattern) {
+                Ok(paths) => paths,
+                Err(e) => {
+                    eprintln!(
+                        "Warning: Invalid path pattern '{}' in cache provider '{}': {}",
+                        pattern, self.config.id, e
+                    );
+                    continue;
+                }
+            };
+
+            for path in paths.flatten() {
+                if !seen.insert(path.clone()) {
+                    continue;
+                }
+                let (size, mtime) = dir_size_and_mtime(&path);
+                entries.push(CacheEntry {
+                    id: format!("{}:{}", self.config.id, path.to_string_lossy()),
+                    kind: self.config.kind.clone(),
+                    name: self
+                        .config
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| self.config.id.clone()),
+                    path: path.to_string_lossy().to_string(),
+                    size_bytes: size,
+                    last_used_at: mtime,
+                    stale: self.is_stale(mtime, ctx),
+                    details: json!({
+                        "type": "user_provider",
+                        "provider": self.config.id
+                    }),
+                });
+            }
+        }
+
+        entries
+    }
+
+    fn is_safe_to_delete(&self, _entry: &CacheEntry) -> bool {
+        self.config.deletable
+    }
+}
+
+/// Ordered collection of cache providers
+#[derive(Default)]
+pub struct CacheRegistry {
+    providers: Vec<Box<dyn CacheProvider>>,
+}
+
+impl CacheRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with the built-in providers only
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::new();
+        let builtin: [BuiltinProvider; 13] = [
+            BuiltinProvider {
+                id: "rust",
+                lang: Some("rust"),
+                detect: |ctx| detect_rust_caches(&ctx.root),
+            },
+            BuiltinProvider {
+                id: "js",
+                lang: Some("js"),
+                detect: |ctx| detect_js_caches(&ctx.root),
+            },
+            BuiltinProvider {
+                id: "python",
+                lang: Some("py"),
+                detect: |_| detect_python_caches(),
+            },
+            BuiltinProvider {
+                id: "java",
+                lang: Some("java"),
+                detect: |_| detect_java_caches(),
+            },
+            BuiltinProvider {
+                id: "gradle",
+                lang: Some("java"),
+                detect: |ctx| detect_gradle_caches(&ctx.root, ctx.stale_days),
+            },
+            BuiltinProvider {
+                id: "dotnet",
+                lang: Some("dotnet"),
+                detect: |ctx| detect_dotnet_caches(&ctx.root, ctx.stale_days),
+            },
+            BuiltinProvider {
+                id: "ruby",
+                lang: Some("ruby"),
+                detect: |ctx| detect_ruby_caches(ctx.stale_days),
+            },
+            BuiltinProvider {
+                id: "npx",
+                lang: None,
+                detect: |ctx| detect_npx_caches(ctx.stale_days),
+            },
+            BuiltinProvider {
+                id: "js-pm",
+                lang: None,
+                detect: |_| detect_js_pm_caches(),
+            },
+            BuiltinProvider {
+                id: "hf",
+                lang: None,
+                detect: |_| detect_hf_caches(),
+            },
+            BuiltinProvider {
+                id: "torch",
+                lang: None,
+                detect: |_| detect_torch_caches(),
+            },
+            BuiltinProvider {
+                id: "vercel",
+                lang: None,
+                detect: |_| detect_vercel_caches(),
+            },
+            BuiltinProvider {
+                id: "cloudflare",
+                lang: None,
+                detect: |_| detect_cloudflare_caches(),
+            },
+        ];
+        for provider in builtin {
+            registry.register(Box::new(provider));
+        }
+        registry.register(Box::new(GoProvider));
+        registry
+    }
+
+    /// Built-in providers plus any declared in the user config file
+    pub fn load() -> Self {
+        let mut registry = Self::with_builtin();
+        let path = get_user_providers_path();
+        if path.exists() {
+            if let Err(e) = registry.load_user_providers(&path) {
+                eprintln!("Warning: Failed to load cache providers: {}", e);
+            }
+        }
+        registry
+    }
+
+    pub fn load_user_providers(&mut self, path: &Path) -> Result<usize> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let file: UserProvidersFile = serde_json::from_str(&content)
+            .with_context(|| format!("parsing {}", path.display()))?;
+
+        let mut loaded = 0;
+        for config in file.providers {
+            if self.get(&config.id).is_some() {
+                eprintln!(
+                    "Warning: Cache provider '{}' is already defined, skipping",
+                    config.id
+                );
+                continue;
+            }
+            self.register(Box::new(UserProvider::new(config)));
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    pub fn register(&mut self, provider: Box<dyn CacheProvider>) {
+        self.providers.push(provider);
+    }
+
+    pub fn providers(&self) -> &[Box<dyn CacheProvider>] {
+        &self.providers
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn CacheProvider> {
+        self.providers
+            .iter()
+            .find(|p| p.id() == id)
+            .map(|p| p.as_ref())
+    }
+
+    /// Providers matching a selection, in registration order
+    pub fn select(&self, selection: &CacheSelection) -> Vec<&dyn CacheProvider> {
+        if !selection.providers.is_empty() {
+            for id in &selection.providers {
+                if self.get(id).is_none() {
+                    eprintln!("Warning: Unknown cache provider '{}'", id);
+                }
+            }
+            return self
+                .providers
+                .iter()
+                .filter(|p| selection.providers.iter().any(|id| id == p.id()))
+                .map(|p| p.as_ref())
+                .collect();
+        }
+
+        self.providers
+            .iter()
+            .filter(|p| match p.lang() {
+                Some(lang) => selection.lang == "auto" || selection.lang == lang,
+                None => false,
+            })
+            .map(|p| p.as_ref())
+            .collect()
+    }
+
+    /// Run detection for the selected providers, keeping track of which provider found each entry
+    pub fn detect(
+        &self,
+        selection: &CacheSelection,
+        ctx: &DetectContext,
+    ) -> Vec<(&dyn CacheProvider, CacheEntry)> {
+        self.detect_with_progress(selection, ctx, &|_| {})
+    }
+
+    /// Like `detect`, running providers in parallel and reporting each entry as soon as its
+    /// provider has measured it. Results keep registration order.
+    pub fn detect_with_progress(
+        &self,
+        selection: &CacheSelection,
+        ctx: &DetectContext,
+        on_entry: &(dyn Fn(&CacheEntry) + Sync),
+    ) -> Vec<(&dyn CacheProvider, CacheEntry)> {
+        self.select(selection)
+            .into_par_iter()
+            .map(|provider| {
+                let entries = provider.detect(ctx);
+                entries.iter().for_each(on_entry);
+                entries
+                    .into_iter()
+                    .map(|entry| (provider, entry))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+}
+
+pub fn get_user_providers_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(home).join(".port-kill/cache-providers.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    #[test]
+    fn test_selection_by_lang_and_id() {
+        let registry = CacheRegistry::with_builtin();
+
+        let auto = CacheSelection {
+            lang: "auto".to_string(),
+            providers: vec![],
+        };
+        let ids: Vec<&str> = registry.select(&auto).iter().map(|p| p.id()).collect();
+        assert_eq!(
+            ids,
+            vec!["rust", "js", "python", "java", "gradle", "dotnet", "ruby", "go"]
+        );
+
+        let py = CacheSelection {
+            lang: "py".to_string(),
+            providers: vec![],
+        };
+        let ids: Vec<&str> = registry.select(&py).iter().map(|p| p.id()).collect();
+        assert_eq!(ids, vec!["python"]);
+
+        let explicit = CacheSelection {
+            lang: "auto".to_string(),
+            providers: vec!["torch".to_string(), "npx".to_string()],
+        };
+        let ids: Vec<&str> = registry.select(&explicit).iter().map(|p| p.id()).collect();
+        assert_eq!(ids, vec!["npx", "torch"]);
+    }
+
+    #[test]
+    fn test_user_provider_from_config() {
+        let root = scratch_dir("provider", "user");
+        fs::create_dir_all(root.join("a/artifacts")).unwrap();
+        fs::create_dir_all(root.join("b/artifacts")).unwrap();
+        fs::write(root.join("a/artifacts/out.bin"), "1234").unwrap();
+
+        let config_path = root.join("providers.json");
+        fs::write(
+            &config_path,
+            r#"{"providers": [{
+                "id": "team-artifacts",
+                "kind": "team",
+                "paths": ["*/artifacts"],
+                "stale": {"type": "never"},
+                "deletable": false
+            }]}"#,
+        )
+        .unwrap();
+
+        let mut registry = CacheRegistry::with_builtin();
+        assert_eq!(registry.load_user_providers(&config_path).unwrap(), 1);
+
+        let selection = CacheSelection {
+            lang: "team".to_string(),
+            providers: vec![],
+        };
+        let ctx = DetectContext {
+            root: root.clone(),
+            stale_days: Some(0),
+        };
+        let found = registry.detect(&selection, &ctx);
+        assert_eq!(found.len(), 2);
+        assert!(found
+            .iter()
+            .all(|(p, e)| e.kind == "team" && !e.stale && !p.is_safe_to_delete(e)));
+        assert_eq!(found.iter().map(|(_, e)| e.size_bytes).sum::<u64>(), 4);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
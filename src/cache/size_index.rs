@@ -0,0 +1,243 @@
+use chrono::{DateTime, Utc};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A regular file directly inside an indexed directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct FileRecord {
+    name: String,
+    len: u64,
+    mtime_ns: Option<u128>,
+}
+
+/// What we know about one directory, valid while its own mtime and the size and mtime of
+/// each of its files are unchanged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DirRecord {
+    mtime_ns: u128,
+    files: Vec<FileRecord>,
+    subdirs: Vec<String>,
+}
+
+impl DirRecord {
+    /// Total size of regular files directly inside the directory
+    fn file_bytes(&self) -> u64 {
+        self.files
+            .iter()
+            .fold(0u64, |total, f| total.saturating_add(f.len))
+    }
+
+    /// Newest mtime among the directory and its direct files
+    fn newest_ns(&self) -> Option<u128> {
+        self.files
+            .iter()
+            .map(|f| f.mtime_ns)
+            .fold(Some(self.mtime_ns), Option::max)
+    }
+
+    /// Whether the recorded files still have the same size and mtime. A directory's mtime
+    /// only changes when entries are added, removed or renamed, so a file rewritten in
+    /// place has to be checked on its own.
+    fn files_unchanged(&self, dir: &Path) -> bool {
+        self.files.par_iter().all(|file| {
+            fs::symlink_metadata(dir.join(&file.name)).is_ok_and(|md| {
+                !md.is_dir()
+                    && file_len(&md) == file.len
+                    && md.modified().ok().map(to_ns) == file.mtime_ns
+            })
+        })
+    }
+}
+
+/// Persistent directory size index, so the directories of an unchanged tree don't need to
+/// be listed again: only their files are checked against the recorded size and mtime.
+pub struct SizeIndex {
+    path: PathBuf,
+    previous: HashMap<String, DirRecord>,
+    current: Mutex<HashMap<String, DirRecord>>,
+    roots: Mutex<Vec<PathBuf>>,
+}
+
+fn to_ns(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0)
+}
+
+/// Bytes a file counts for; symlinks and special files count as empty
+fn file_len(md: &fs::Metadata) -> u64 {
+    if md.is_file() {
+        md.len()
+    } else {
+        0
+    }
+}
+
+fn from_ns(ns: u128) -> DateTime<Utc> {
+    let secs = (ns / 1_000_000_000) as i64;
+    let nanos = (ns % 1_000_000_000) as u32;
+    DateTime::from_timestamp(secs, nanos).unwrap_or_default()
+}
+
+impl SizeIndex {
+    pub fn load(path: PathBuf) -> Self {
+        let previous = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            previous,
+            current: Mutex::new(HashMap::new()),
+            roots: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Total size of regular files under `path` and the newest mtime seen
+    pub fn measure(&self, path: &Path) -> (u64, Option<DateTime<Utc>>) {
+        self.roots.lock().unwrap().push(path.to_path_buf());
+        let (size, newest) = self.measure_path(path);
+        (size, newest.map(from_ns))
+    }
+
+    fn measure_path(&self, path: &Path) -> (u64, Option<u128>) {
+        let Ok(md) = fs::symlink_metadata(path) else {
+            return (0, None);
+        };
+        let mtime = md.modified().ok().map(to_ns);
+        if !md.is_dir() {
+            return (file_len(&md), mtime);
+        }
+
+        let key = path.to_string_lossy().to_string();
+        let record = match self.previous.get(&key) {
+            Some(record) if mtime == Some(record.mtime_ns) && record.files_unchanged(path) => {
+                record.clone()
+            }
+            _ => match Self::read_dir_record(path, mtime) {
+                Some(record) => record,
+                None => return (0, mtime),
+            },
+        };
+
+        let (sub_bytes, sub_newest) = record
+            .subdirs
+            .par_iter()
+            .map(|name| self.measure_path(&path.join(name)))
+            .reduce(|| (0, None), |a, b| (a.0.saturating_add(b.0), a.1.max(b.1)));
+
+        let total = record.file_bytes().saturating_add(sub_bytes);
+        let newest = record.newest_ns().max(sub_newest);
+        self.current.lock().unwrap().insert(key, record);
+        (total, newest)
+    }
+
+    fn read_dir_record(path: &Path, mtime: Option<u128>) -> Option<DirRecord> {
+        let mut record = DirRecord {
+            mtime_ns: mtime?,
+            files: Vec::new(),
+            subdirs: Vec::new(),
+        };
+        for child in fs::read_dir(path).ok()?.flatten() {
+            let Ok(md) = fs::symlink_metadata(child.path()) else {
+                continue;
+            };
+            if md.is_dir() {
+                record
+                    .subdirs
+                    .push(child.file_name().to_string_lossy().to_string());
+                continue;
+            }
+            record.files.push(FileRecord {
+                name: child.file_name().to_string_lossy().to_string(),
+                len: file_len(&md),
+                mtime_ns: md.modified().ok().map(to_ns),
+            });
+        }
+        Some(record)
+    }
+
+    /// Write the index back, dropping records for directories that vanished under measured roots
+    pub fn save(&self) -> std::io::Result<()> {
+        let current = self.current.lock().unwrap();
+        if current.is_empty() {
+            return Ok(());
+        }
+        let roots = self.roots.lock().unwrap();
+
+        let mut merged: HashMap<&String, &DirRecord> = self
+            .previous
+            .iter()
+            .filter(|(key, _)| !roots.iter().any(|root| Path::new(key).starts_with(root)))
+            .collect();
+        merged.extend(current.iter());
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string(&merged)?)
+    }
+}
+
+pub fn get_size_index_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(home).join(".port-kill/cache-size-index.json")
+}
+
+static GLOBAL_INDEX: OnceLock<SizeIndex> = OnceLock::new();
+
+/// Index shared by every detector in this process
+pub fn global() -> &'static SizeIndex {
+    GLOBAL_INDEX.get_or_init(|| SizeIndex::load(get_size_index_path()))
+}
+
+/// Persist the shared index if anything was measured
+pub fn flush() {
+    if let Some(index) = GLOBAL_INDEX.get() {
+        if let Err(e) = index.save() {
+            eprintln!("Warning: Failed to save cache size index: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_reuses_unchanged_directories() {
+        let root =
+            std::env::temp_dir().join(format!("port-kill-size-index-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let store = root.join("store");
+        fs::create_dir_all(store.join("a/b")).unwrap();
+        fs::write(store.join("a/one"), "12345").unwrap();
+        fs::write(store.join("a/b/two"), "123").unwrap();
+        let index_path = root.join("index.json");
+
+        let index = SizeIndex::load(index_path.clone());
+        let (size, newest) = index.measure(&store);
+        assert_eq!(size, 8);
+        assert!(newest.is_some());
+        index.save().unwrap();
+
+        // Rewriting a file in place keeps the directory mtime, but not the file's own size
+        fs::write(store.join("a/one"), "1234567890").unwrap();
+        let index = SizeIndex::load(index_path.clone());
+        assert_eq!(index.measure(&store).0, 13);
+        index.save().unwrap();
+
+        // Adding a file changes the directory mtime and forces a re-read of that directory
+        fs::write(store.join("a/b/three"), "12").unwrap();
+        let index = SizeIndex::load(index_path);
+        assert_eq!(index.measure(&store).0, 15);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -33,12 +33,43 @@ pub struct ListResponse {
 pub struct CleanSummary {
     pub freed_bytes: u64,
     pub deleted_count: usize,
+    pub failed_count: usize,
+    pub blocked_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanFailure {
+    pub entry: CacheEntry,
+    pub error: String,
+}
+
+/// A process holding files open inside a cache entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheHolder {
+    pub pid: i32,
+    pub name: String,
+}
+
+/// An entry left in place because running processes are using it, or because that
+/// could not be checked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedEntry {
+    pub entry: CacheEntry,
+    pub holders: Vec<CacheHolder>,
+    /// Why the holders are unknown, when the check itself failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CleanResponse {
     pub deleted: Vec<CacheEntry>,
+    pub failed: Vec<CleanFailure>,
+    pub blocked: Vec<BlockedEntry>,
     pub backed_up_to: Option<String>,
     pub summary: CleanSummary,
 }
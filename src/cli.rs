@@ -1,7 +1,32 @@
-use crate::preset_manager::{PortPreset, PresetManager};
+use crate::cache::provider::CacheSelection;
+use crate::filter_expr::{FilterExpr, ParseError};
+use crate::history_store::HistoryConfig;
+use crate::preset_manager::{
+    EndpointSettings, GuardSettings, KillPolicy, PortPreset, PresetManager,
+};
+use crate::update_check::{ReleaseSource, UpdateConfig};
 use clap::Parser;
 use clap::{Args as ClapArgs, Subcommand};
 use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Reject a malformed --where expression while parsing, so it can't be saved into a preset
+fn parse_where_expr(value: &str) -> Result<String, String> {
+    FilterExpr::parse(value)
+        .map(|_| value.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Resolve a leading `~/` against $HOME, falling back to /tmp when it isn't set
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => {
+            PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string())).join(rest)
+        }
+        None => PathBuf::from(path),
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum LogLevel {
@@ -51,7 +76,8 @@ pub struct Args {
     #[arg(long, value_delimiter = ',')]
     pub ignore_groups: Option<Vec<String>>,
 
-    /// Enable smart filtering (auto-detect and ignore system processes)
+    /// Enable smart filtering (auto-detect and ignore system processes: OS binaries, service
+    /// accounts, systemd system services and known desktop daemons; see --show-filters)
     #[arg(long)]
     pub smart_filter: bool,
 
@@ -59,6 +85,11 @@ pub struct Args {
     #[arg(long, value_delimiter = ',')]
     pub only_groups: Option<Vec<String>>,
 
+    /// Only show processes matching an expression, e.g.
+    /// "port in 3000..3999 and name ~ node and memory > 500MB" (see README for fields)
+    #[arg(long = "where", value_name = "EXPR", value_parser = parse_where_expr)]
+    pub where_expr: Option<String>,
+
     /// Run in console mode instead of status bar mode
     #[arg(short, long)]
     pub console: bool,
@@ -119,7 +150,7 @@ pub struct Args {
     #[arg(long)]
     pub show_tree: bool,
 
-    /// Output processes in JSON format (for API integration)
+    /// Print the result as JSON in a versioned envelope (see `port-kill-console schema`)
     #[arg(long)]
     pub json: bool,
 
@@ -179,6 +210,10 @@ pub struct Args {
     #[arg(long)]
     pub process_name: Option<String>,
 
+    /// Show Port Guard status: watched ports, reservations and conflicts resolved
+    #[arg(long)]
+    pub guard_status: bool,
+
     /// Enable Security Audit Mode - comprehensive security analysis
     #[arg(long)]
     pub audit: bool,
@@ -199,10 +234,23 @@ pub struct Args {
     #[arg(long)]
     pub suspicious_only: bool,
 
-    /// Remote mode: connect to remote host via SSH
-    #[arg(long)]
+    /// Remote mode: list or kill on other machines via SSH (comma-separated hosts,
+    /// e.g. web1,deploy@10.0.0.5)
+    #[arg(long, value_name = "HOSTS")]
     pub remote: Option<String>,
 
+    /// Host inventory for remote mode: one SSH destination per line
+    #[arg(long, value_name = "FILE")]
+    pub hosts: Option<String>,
+
+    /// port-kill-console on the remote hosts (looked up on the remote PATH)
+    #[arg(long, default_value = "port-kill-console")]
+    pub remote_bin: String,
+
+    /// Seconds to wait for each remote host
+    #[arg(long, default_value = "30")]
+    pub remote_timeout: u64,
+
     /// Endpoint monitoring: send data to external endpoint for monitoring/alerting
     #[arg(long)]
     pub monitor_endpoint: Option<String>,
@@ -235,6 +283,46 @@ pub struct Args {
     #[arg(long, default_value = "10")]
     pub endpoint_timeout: u64,
 
+    /// Directory for endpoint payloads that could not be delivered yet
+    #[arg(long, default_value = "~/.port-kill/endpoint-spool")]
+    pub endpoint_spool_dir: String,
+
+    /// Maximum size of the endpoint spool in MB; the oldest payloads are dropped beyond it
+    #[arg(long, default_value = "50")]
+    pub endpoint_spool_max_mb: u64,
+
+    /// Event mode: post port/process change events to this webhook URL (repeatable)
+    #[arg(long, value_name = "URL")]
+    pub webhook: Vec<String>,
+
+    /// Secret used to sign events sent to --webhook URLs (HMAC-SHA256)
+    #[arg(long)]
+    pub webhook_secret: Option<String>,
+
+    /// JSON file with webhook sinks and per-sink event/port filters (enables event mode)
+    #[arg(long, value_name = "PATH")]
+    pub webhook_config: Option<String>,
+
+    /// Kill history log location (default: ~/.port-kill/history.jsonl, or $PORT_KILL_HISTORY_FILE)
+    #[arg(long, value_name = "PATH")]
+    pub history_file: Option<String>,
+
+    /// Rotate the history log once it reaches this size in MB
+    #[arg(long, default_value = "5")]
+    pub history_max_mb: u64,
+
+    /// Rotate the history log once its oldest entry is this many days old (0 disables)
+    #[arg(long, default_value = "30")]
+    pub history_max_age_days: u64,
+
+    /// Evaluate alert rules from this JSON file on every scan
+    #[arg(long, value_name = "PATH")]
+    pub alert_rules: Option<String>,
+
+    /// Serve Prometheus metrics on this address (e.g., "127.0.0.1:9464"), refreshed every scan interval
+    #[arg(long, value_name = "ADDR")]
+    pub metrics_listen: Option<String>,
+
     /// Execute inline script
     #[arg(long)]
     pub script: Option<String>,
@@ -280,10 +368,27 @@ pub struct Args {
     #[arg(long)]
     pub list: bool,
 
+    /// Full-screen interactive UI: live sortable table, search, multi-select kill/restart,
+    /// details and kill history
+    #[arg(long)]
+    pub tui: bool,
+
     /// Ask for confirmation before killing
     #[arg(long)]
     pub safe: bool,
 
+    /// Watch freed ports this many seconds for a respawn after a kill (0 disables)
+    #[arg(long, value_name = "SECS", default_value = "5")]
+    pub respawn_window: u64,
+
+    /// Stop the supervisor behind a respawned process without asking
+    #[arg(long)]
+    pub stop_supervisor: bool,
+
+    /// Kill systemd-managed processes by PID instead of running `systemctl stop` on their unit
+    #[arg(long)]
+    pub force_pid: bool,
+
     /// Positional ports imply clearPort on each (e.g., `port-kill 3000 5000`)
     #[arg(value_parser, value_name = "PORTS")]
     pub positional_ports: Vec<u16>,
@@ -316,9 +421,135 @@ pub struct Args {
     #[arg(long)]
     pub self_update: bool,
 
-    /// Cache management subcommand
+    /// Restore the binary replaced by the last --self-update
+    #[arg(long)]
+    pub rollback: bool,
+
+    /// Where --self-update gets releases: `github`, a URL or a local directory
+    /// serving VERSION, SHA256SUMS and the assets (env: PORT_KILL_RELEASE_SOURCE)
+    #[arg(long, value_name = "URL|DIR")]
+    pub release_source: Option<String>,
+
+    /// Require a minisign signature on SHA256SUMS from this public key, given as
+    /// base64 or a .pub file (env: PORT_KILL_RELEASE_PUBKEY)
+    #[arg(long, value_name = "KEY")]
+    pub release_pubkey: Option<String>,
+
+    /// Subcommand: cache, history, config, check, preset or schema
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Cache operations: list, clean, dry-run, restore, doctor, NPX, JS PM
+    #[command(name = "cache", visible_alias = "c")]
+    Cache(CacheArgs),
+    /// Query, analyse and export the kill history
+    #[command(name = "history")]
+    History(HistoryArgs),
+    /// Inspect the layered configuration (config files, environment, flags)
+    #[command(name = "config")]
+    Config(ConfigArgs),
+    /// Check whether the ports this project uses are free, and who holds them
+    #[command(name = "check")]
+    Check(CheckArgs),
+    /// Share presets as files: export, import and validate
+    #[command(name = "preset")]
+    Preset(PresetArgs),
+    /// Print the JSON Schema that describes --json output
+    #[command(name = "schema")]
+    Schema,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct ConfigArgs {
     #[command(subcommand)]
-    pub cache: Option<CacheSubcommand>,
+    pub action: ConfigAction,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigAction {
+    /// Show the settings that config files and the environment provide
+    Show(ConfigShowArgs),
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct ConfigShowArgs {
+    /// List every setting with its resolved value and the layer it came from
+    #[arg(long)]
+    pub effective: bool,
+
+    /// JSON output
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct CheckArgs {
+    /// Project directory to inspect (default: the current directory)
+    #[arg(long, value_name = "DIR")]
+    pub path: Option<std::path::PathBuf>,
+
+    /// JSON output
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct PresetArgs {
+    #[command(subcommand)]
+    pub action: PresetAction,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum PresetAction {
+    /// Write a preset, with everything it inherits, as a standalone JSON file
+    Export(PresetExportArgs),
+    /// Add a preset from a JSON file (a path or a file:// URL)
+    Import(PresetImportArgs),
+    /// Check the saved presets, or a preset file, against the schema
+    Validate(PresetValidateArgs),
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct PresetExportArgs {
+    /// Preset to export
+    pub name: String,
+
+    /// Write to this file instead of stdout
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<std::path::PathBuf>,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct PresetImportArgs {
+    /// Preset file: a path or a file:// URL
+    pub source: String,
+
+    /// Save under this name instead of the one in the file
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Replace an existing preset of the same name
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct PresetValidateArgs {
+    /// Preset file to check (default: the saved presets)
+    pub file: Option<String>,
+}
+
+impl Command {
+    /// The cache operation's arguments, or `None` for the other subcommands
+    pub fn cache_args(&self) -> Option<&CacheArgs> {
+        match self {
+            Command::Cache(args) => Some(args),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -339,6 +570,72 @@ impl CacheSubcommand {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HistoryFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HistoryReport {
+    /// The matching kill records
+    Entries,
+    /// Totals and most-killed processes, ports and projects
+    Stats,
+    /// Processes killed repeatedly on the same port
+    Offenders,
+    /// Kills by hour of day and day of week
+    Patterns,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct HistoryArgs {
+    /// Only kills at or after TIME: RFC 3339, local "YYYY-MM-DD[ HH:MM]", or an age like 2h or 7d
+    #[arg(long, value_name = "TIME")]
+    pub since: Option<String>,
+
+    /// Only kills before TIME (same formats as --since)
+    #[arg(long, value_name = "TIME")]
+    pub until: Option<String>,
+
+    /// Only kills on these ports (comma-separated)
+    #[arg(long, value_delimiter = ',', value_name = "PORT")]
+    pub port: Vec<u16>,
+
+    /// Only processes whose name contains this text (case-insensitive)
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Only processes in this group
+    #[arg(long)]
+    pub group: Option<String>,
+
+    /// Only processes from this project
+    #[arg(long)]
+    pub project: Option<String>,
+
+    /// Only kills by this actor (cli, tray, script, guard, dashboard, alert) or context (user, bulk)
+    #[arg(long, value_name = "ACTOR")]
+    pub by: Option<String>,
+
+    /// What to report on the matching entries
+    #[arg(long, value_enum, default_value = "entries")]
+    pub report: HistoryReport,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "table")]
+    pub format: HistoryFormat,
+
+    /// Show only the most recent N matching entries
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Minimum kills for a process to count as an offender
+    #[arg(long, default_value = "2")]
+    pub min_kills: usize,
+}
+
 #[derive(ClapArgs, Debug, Clone)]
 pub struct CacheArgs {
     /// Operation mode: list, clean, dry-run, restore-last, doctor
@@ -357,7 +654,11 @@ pub struct CacheArgs {
     #[arg(long)]
     pub json: bool,
 
-    /// Language filter
+    /// Report entries while they are measured (newline-delimited JSON events with --json)
+    #[arg(long)]
+    pub stream: bool,
+
+    /// Language filter: auto, rust, js, py, java, go, dotnet, ruby, or a custom provider kind
     #[arg(long, default_value = "auto")]
     pub lang: String,
 
@@ -379,17 +680,59 @@ pub struct CacheArgs {
     #[arg(long)]
     pub cloudflare: bool,
 
+    /// Cache provider ids to use, including ones from ~/.port-kill/cache-providers.json (repeatable)
+    #[arg(long = "provider", value_name = "ID")]
+    pub providers: Vec<String>,
+
     /// Safety and force flags for clean
     #[arg(long, default_value = "true")]
     pub safe_delete: bool,
     #[arg(long)]
     pub force: bool,
 
+    /// Stop processes holding files inside a cache before cleaning it (otherwise it is skipped)
+    #[arg(long)]
+    pub kill_holders: bool,
+
     /// NPX stale days
     #[arg(long)]
     pub stale_days: Option<u32>,
 }
 
+impl CacheArgs {
+    /// Translate the provider flags into a registry selection
+    pub fn selection(&self) -> CacheSelection {
+        let mut providers = self.providers.clone();
+        for (enabled, id) in [
+            (self.npx, "npx"),
+            (self.js_pm, "js-pm"),
+            (self.hf, "hf"),
+            (self.torch, "torch"),
+            (self.vercel, "vercel"),
+            (self.cloudflare, "cloudflare"),
+        ] {
+            if enabled && !providers.iter().any(|p| p == id) {
+                providers.push(id.to_string());
+            }
+        }
+
+        CacheSelection {
+            lang: self.lang.clone(),
+            providers,
+        }
+    }
+}
+
+impl Default for Args {
+    /// Every flag at its default, as if run with no arguments. Parsed once, cloned after
+    fn default() -> Self {
+        static DEFAULT: OnceLock<Args> = OnceLock::new();
+        DEFAULT
+            .get_or_init(|| Args::parse_from(["port-kill"]))
+            .clone()
+    }
+}
+
 impl Args {
     /// Get the list of ports to monitor
     pub fn get_ports_to_monitor(&self) -> Vec<u16> {
@@ -468,83 +811,6 @@ impl Args {
             .map(|groups| groups.iter().cloned().collect())
     }
 
-    /// Get smart filter defaults
-    pub fn get_smart_filter_defaults(&self) -> (HashSet<u16>, HashSet<String>, HashSet<String>) {
-        if !self.smart_filter {
-            return (HashSet::new(), HashSet::new(), HashSet::new());
-        }
-
-        // Smart port ignores (common system ports)
-        let smart_ignore_ports: HashSet<u16> = [
-            22,    // SSH
-            25,    // SMTP
-            53,    // DNS
-            80,    // HTTP (system)
-            443,   // HTTPS (system)
-            993,   // IMAPS
-            995,   // POP3S
-            1433,  // SQL Server
-            3306,  // MySQL
-            5432,  // PostgreSQL
-            6379,  // Redis
-            27017, // MongoDB
-            5353,  // mDNS/Bonjour
-            5000,  // AirDrop
-            7000,  // AirDrop
-            8080,  // Common proxy
-            8443,  // HTTPS Alt
-        ]
-        .iter()
-        .cloned()
-        .collect();
-
-        // Smart process ignores (common system processes)
-        let smart_ignore_processes: HashSet<String> = [
-            "Chrome",
-            "Safari",
-            "Firefox",
-            "Edge", // Browsers
-            "ControlCe",
-            "rapportd",
-            "AirPlayXP", // macOS system
-            "systemd",
-            "init",
-            "kthreadd", // Linux system
-            "svchost",
-            "explorer",
-            "winlogon", // Windows system
-            "docker",
-            "dockerd",
-            "containerd", // Docker system
-            "nginx",
-            "apache2",
-            "httpd", // Web servers (system)
-            "mysqld",
-            "postgres",
-            "redis-server", // Database servers
-            "ssh",
-            "sshd", // SSH servers
-        ]
-        .iter()
-        .map(|s| s.to_string())
-        .collect();
-
-        // Smart group ignores (system service groups)
-        let smart_ignore_groups: HashSet<String> = [
-            "Web Server", // System web servers
-            "Database",   // System databases
-        ]
-        .iter()
-        .map(|s| s.to_string())
-        .collect();
-
-        (
-            smart_ignore_ports,
-            smart_ignore_processes,
-            smart_ignore_groups,
-        )
-    }
-
     /// Get a description of the port configuration
     pub fn get_port_description(&self) -> String {
         let mut description = if let Some(ref specific_ports) = self.ports {
@@ -636,9 +902,20 @@ impl Args {
             }
         }
 
+        // Validate the --where expression
+        self.get_where_filter().map_err(|e| e.to_string())?;
+
         Ok(())
     }
 
+    /// Parse the --where expression, if one is given
+    pub fn get_where_filter(&self) -> Result<Option<FilterExpr>, ParseError> {
+        self.where_expr
+            .as_deref()
+            .map(FilterExpr::parse)
+            .transpose()
+    }
+
     /// Get common development ports for reset functionality
     pub fn get_reset_ports(&self) -> Vec<u16> {
         vec![
@@ -664,14 +941,64 @@ impl Args {
 
     /// Get the expanded reservation file path
     pub fn get_reservation_file_path(&self) -> String {
-        if self.reservation_file.starts_with("~/") {
-            let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
-            self.reservation_file.replace("~/", &format!("{}/", home))
-        } else {
-            self.reservation_file.clone()
+        expand_home(&self.reservation_file).display().to_string()
+    }
+
+    /// Get the expanded endpoint spool directory
+    pub fn get_endpoint_spool_dir(&self) -> String {
+        expand_home(&self.endpoint_spool_dir).display().to_string()
+    }
+
+    /// Get the expanded webhook config path, if one was given
+    pub fn get_webhook_config_path(&self) -> Option<String> {
+        self.webhook_config
+            .as_deref()
+            .map(|path| expand_home(path).display().to_string())
+    }
+
+    /// History log location and rotation settings
+    pub fn history_config(&self) -> HistoryConfig {
+        let mut config = HistoryConfig {
+            max_bytes: self.history_max_mb.saturating_mul(1024 * 1024),
+            max_age_days: (self.history_max_age_days > 0).then_some(self.history_max_age_days),
+            ..HistoryConfig::default()
+        };
+        if let Some(path) = &self.history_file {
+            config.path = expand_home(path);
+        }
+        config
+    }
+
+    /// Release source and signing key for --self-update
+    pub fn update_config(&self) -> UpdateConfig {
+        let source = self
+            .release_source
+            .clone()
+            .or_else(|| std::env::var("PORT_KILL_RELEASE_SOURCE").ok());
+        UpdateConfig {
+            source: source
+                .map(|s| ReleaseSource::parse(&s))
+                .unwrap_or(ReleaseSource::GitHub),
+            public_key: self
+                .release_pubkey
+                .clone()
+                .or_else(|| std::env::var("PORT_KILL_RELEASE_PUBKEY").ok())
+                .filter(|key| !key.trim().is_empty()),
         }
     }
 
+    /// Get the expanded alert rules path, if one was given
+    pub fn get_alert_rules_path(&self) -> Option<String> {
+        self.alert_rules
+            .as_deref()
+            .map(|path| expand_home(path).display().to_string())
+    }
+
+    /// Whether event mode (webhook delivery) was requested
+    pub fn event_mode_enabled(&self) -> bool {
+        !self.webhook.is_empty() || self.webhook_config.is_some()
+    }
+
     /// Get the list of suspicious ports
     pub fn get_suspicious_ports(&self) -> Vec<u16> {
         self.suspicious_ports
@@ -692,9 +1019,159 @@ impl Args {
         })
     }
 
-    /// Get the remote host for SSH connection
-    pub fn get_remote_host(&self) -> Option<String> {
-        self.remote.clone()
+    /// Whether the result goes to stdout as a JSON envelope, either from `--json` or
+    /// from the subcommand's own JSON flag
+    pub fn wants_json(&self) -> bool {
+        self.json
+            || match &self.command {
+                Some(Command::Cache(c)) => c.json && !c.stream,
+                Some(Command::Check(c)) => c.json,
+                Some(Command::Config(c)) => {
+                    let ConfigAction::Show(show) = &c.action;
+                    show.json
+                }
+                Some(Command::History(h)) => h.format == HistoryFormat::Json,
+                _ => false,
+            }
+    }
+
+    /// The envelope `kind` this invocation produces, so a failure can be reported
+    /// under the same kind a success would have been
+    pub fn output_kind(&self) -> &'static str {
+        match &self.command {
+            Some(Command::Cache(c)) if c.list || c.dry_run => return "cache.list",
+            Some(Command::Cache(c)) if c.clean => return "cache.clean",
+            Some(Command::Cache(c)) if c.restore_last => return "cache.restore",
+            Some(Command::Cache(c)) if c.doctor => return "cache.doctor",
+            Some(Command::History(h)) => {
+                return match h.report {
+                    HistoryReport::Entries => "history",
+                    HistoryReport::Stats => "stats",
+                    HistoryReport::Offenders => "offenders",
+                    HistoryReport::Patterns => "patterns",
+                }
+            }
+            Some(Command::Check(_)) => return "check",
+            Some(Command::Config(_)) => return "config",
+            _ => {}
+        }
+        if self.list_presets {
+            "presets"
+        } else if self.save_preset.is_some() || self.delete_preset.is_some() {
+            "preset"
+        } else if self.show_history {
+            "history"
+        } else if self.clear_history {
+            "history_cleared"
+        } else if self.show_filters {
+            "filters"
+        } else if self.restart {
+            "restart"
+        } else if self.show_offenders {
+            "offenders"
+        } else if self.show_patterns {
+            "patterns"
+        } else if self.show_suggestions {
+            "suggestions"
+        } else if self.show_stats {
+            "stats"
+        } else if self.show_root_cause {
+            "root_cause"
+        } else if self.audit {
+            "audit"
+        } else if self.guard_status {
+            "guard_status"
+        } else if self.is_remote()
+            && self.positional_ports.is_empty()
+            && self.clear.is_none()
+            && self.kill.is_none()
+        {
+            "remote"
+        } else if self.show_tree {
+            "tree"
+        } else if self.kill_all
+            || self.reset
+            || self.kill_group.is_some()
+            || self.kill_project.is_some()
+            || !self.positional_ports.is_empty()
+            || self.clear.is_some()
+            || self.kill.is_some()
+        {
+            "kill"
+        } else {
+            "processes"
+        }
+    }
+
+    /// Whether to work on other machines over SSH (`--remote` or `--hosts`)
+    pub fn is_remote(&self) -> bool {
+        self.remote.is_some() || self.hosts.is_some()
+    }
+
+    /// Hosts for remote mode: those given to `--remote`, then the `--hosts` inventory
+    pub fn get_remote_hosts(&self) -> anyhow::Result<Vec<String>> {
+        let mut hosts: Vec<String> = Vec::new();
+        for host in self.remote.iter().flat_map(|r| r.split(',')) {
+            let host = host.trim();
+            crate::remote::validate_host(host)?;
+            hosts.push(host.to_string());
+        }
+        if let Some(ref inventory) = self.hosts {
+            hosts.extend(crate::remote::load_inventory(std::path::Path::new(
+                inventory,
+            ))?);
+        }
+        let mut seen = std::collections::HashSet::new();
+        hosts.retain(|host| seen.insert(host.clone()));
+        if hosts.is_empty() {
+            return Err(anyhow::anyhow!("No remote hosts given"));
+        }
+        Ok(hosts)
+    }
+
+    /// The scan flags to repeat on the remote side, so filtering happens where the
+    /// processes are
+    pub fn remote_scan_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        match self.ports {
+            Some(ref ports) => args.extend(["--ports".to_string(), ports.join(",")]),
+            None => args.extend([
+                "--start-port".to_string(),
+                self.start_port.to_string(),
+                "--end-port".to_string(),
+                self.end_port.to_string(),
+            ]),
+        }
+        let lists = [
+            (
+                "--ignore-ports",
+                self.ignore_ports
+                    .as_ref()
+                    .map(|p| p.iter().map(|p| p.to_string()).collect::<Vec<_>>()),
+            ),
+            ("--ignore-processes", self.ignore_processes.clone()),
+            ("--ignore-patterns", self.ignore_patterns.clone()),
+            ("--ignore-groups", self.ignore_groups.clone()),
+            ("--only-groups", self.only_groups.clone()),
+        ];
+        for (flag, values) in lists {
+            if let Some(values) = values.filter(|v| !v.is_empty()) {
+                args.extend([flag.to_string(), values.join(",")]);
+            }
+        }
+        if let Some(ref expr) = self.where_expr {
+            args.extend(["--where".to_string(), expr.clone()]);
+        }
+        for (flag, enabled) in [
+            ("--smart-filter", self.smart_filter),
+            ("--docker", self.docker),
+            ("--performance", self.performance),
+        ] {
+            if enabled {
+                args.push(flag.to_string());
+            }
+        }
+        args
     }
 
     /// Apply preset configuration to these args
@@ -723,12 +1200,84 @@ impl Args {
             self.only_groups = Some(Vec::from(only_groups.as_slice()));
         }
 
+        // A preset's expression narrows one given on the command line
+        if let Some(ref expr) = preset.where_expr {
+            self.where_expr = Some(match self.where_expr.take() {
+                Some(own) => format!("({}) and ({})", own, expr),
+                None => expr.clone(),
+            });
+        }
+
         // Apply other preset settings
         self.smart_filter = preset.smart_filter;
         self.docker = preset.docker;
         self.show_pid = preset.show_pid;
         self.performance = preset.performance;
         self.show_context = preset.show_context;
+
+        if let Some(ref guard) = preset.guard {
+            if let Some(enabled) = guard.enabled {
+                self.guard_mode = enabled;
+            }
+            if let Some(ref ports) = guard.ports {
+                self.guard_ports = ports
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",");
+            }
+            if let Some(auto_resolve) = guard.auto_resolve {
+                self.auto_resolve = auto_resolve;
+            }
+            if let Some(ref reservation_file) = guard.reservation_file {
+                self.reservation_file = reservation_file.clone();
+            }
+            if let Some(intercept_commands) = guard.intercept_commands {
+                self.intercept_commands = intercept_commands;
+            }
+        }
+
+        if let Some(ref kill) = preset.kill {
+            if let Some(safe) = kill.safe {
+                self.safe = safe;
+            }
+            if let Some(respawn_window) = kill.respawn_window {
+                self.respawn_window = respawn_window;
+            }
+            if let Some(stop_supervisor) = kill.stop_supervisor {
+                self.stop_supervisor = stop_supervisor;
+            }
+            if let Some(force_pid) = kill.force_pid {
+                self.force_pid = force_pid;
+            }
+        }
+
+        if let Some(ref endpoint) = preset.endpoint {
+            if let Some(ref url) = endpoint.url {
+                self.monitor_endpoint = Some(url.clone());
+            }
+            if let Some(ref auth) = endpoint.auth {
+                self.endpoint_auth = Some(auth.clone());
+            }
+            if let Some(ref fields) = endpoint.fields {
+                self.endpoint_fields = Some(fields.clone());
+            }
+            if let Some(include_audit) = endpoint.include_audit {
+                self.endpoint_include_audit = include_audit;
+            }
+            if let Some(send_interval) = endpoint.send_interval {
+                self.send_interval = send_interval;
+            }
+            if let Some(scan_interval) = endpoint.scan_interval {
+                self.scan_interval = scan_interval;
+            }
+            if let Some(retries) = endpoint.retries {
+                self.endpoint_retries = retries;
+            }
+            if let Some(timeout) = endpoint.timeout {
+                self.endpoint_timeout = timeout;
+            }
+        }
     }
 
     /// Load and apply preset by name
@@ -738,15 +1287,11 @@ impl Args {
             .load_presets()
             .map_err(|e| format!("Failed to load presets: {}", e))?;
 
-        if let Some(preset) = manager.get_preset(preset_name) {
-            self.apply_preset(preset);
-            Ok(())
-        } else {
-            Err(format!(
-                "Preset '{}' not found. Use --list-presets to see available presets.",
-                preset_name
-            ))
-        }
+        let preset = manager
+            .require_preset(preset_name)
+            .map_err(|e| e.to_string())?;
+        self.apply_preset(preset);
+        Ok(())
     }
 
     /// List available presets
@@ -758,22 +1303,56 @@ impl Args {
         Ok(manager.list_presets())
     }
 
-    /// Build a PortPreset from current arguments
+    /// Build a PortPreset from current arguments. Guard, kill and endpoint
+    /// settings are only recorded where they differ from the defaults.
     pub fn build_preset_from_args(&self, name: String, description: String) -> PortPreset {
+        let defaults = Args::default();
+
+        let guard = self.guard_mode.then(|| GuardSettings {
+            enabled: Some(true),
+            ports: Some(self.get_guard_ports()),
+            auto_resolve: Some(self.auto_resolve),
+            reservation_file: (self.reservation_file != defaults.reservation_file)
+                .then(|| self.reservation_file.clone()),
+            intercept_commands: Some(self.intercept_commands),
+        });
+        let kill = KillPolicy {
+            safe: self.safe.then_some(true),
+            respawn_window: (self.respawn_window != defaults.respawn_window)
+                .then_some(self.respawn_window),
+            stop_supervisor: self.stop_supervisor.then_some(true),
+            force_pid: self.force_pid.then_some(true),
+        };
+        let endpoint = self.monitor_endpoint.as_ref().map(|url| EndpointSettings {
+            url: Some(url.clone()),
+            auth: self.endpoint_auth.clone(),
+            fields: self.endpoint_fields.clone(),
+            include_audit: Some(self.endpoint_include_audit),
+            send_interval: Some(self.send_interval),
+            scan_interval: Some(self.scan_interval),
+            retries: Some(self.endpoint_retries),
+            timeout: Some(self.endpoint_timeout),
+        });
+
         PortPreset {
             name,
             description,
+            extends: Vec::new(),
             ports: self.get_ports_to_monitor(),
             ignore_ports: self.ignore_ports.clone(),
             ignore_processes: self.ignore_processes.clone(),
             ignore_patterns: self.ignore_patterns.clone(),
             ignore_groups: self.ignore_groups.clone(),
             only_groups: self.only_groups.clone(),
+            where_expr: self.where_expr.clone(),
             smart_filter: self.smart_filter,
             docker: self.docker,
             show_pid: self.show_pid,
             performance: self.performance,
             show_context: self.show_context,
+            guard,
+            kill: (kill != KillPolicy::default()).then_some(kill),
+            endpoint,
         }
     }
 }
@@ -821,6 +1400,8 @@ mod tests {
             ignore_groups: None,
             smart_filter: false,
             only_groups: None,
+            where_expr: None,
+            tui: false,
             console: false,
             verbose: false,
             docker: false,
@@ -857,6 +1438,10 @@ mod tests {
             baseline_file: None,
             suspicious_only: false,
             remote: None,
+            guard_status: false,
+            hosts: None,
+            remote_bin: "port-kill-console".to_string(),
+            remote_timeout: 30,
             monitor_endpoint: None,
             send_interval: 30,
             scan_interval: 2,
@@ -865,6 +1450,16 @@ mod tests {
             endpoint_include_audit: false,
             endpoint_retries: 3,
             endpoint_timeout: 10,
+            webhook: Vec::new(),
+            webhook_secret: None,
+            webhook_config: None,
+            history_file: None,
+            history_max_mb: 5,
+            history_max_age_days: 30,
+            alert_rules: None,
+            metrics_listen: None,
+            endpoint_spool_dir: "~/.port-kill/endpoint-spool".to_string(),
+            endpoint_spool_max_mb: 50,
             script: None,
             script_file: None,
             script_lang: "js".to_string(),
@@ -877,6 +1472,9 @@ mod tests {
             list_file: None,
             list: false,
             safe: false,
+            respawn_window: 5,
+            stop_supervisor: false,
+            force_pid: false,
             positional_ports: vec![],
             preset: None,
             list_presets: false,
@@ -885,7 +1483,10 @@ mod tests {
             delete_preset: None,
             check_updates: false,
             self_update: false,
-            cache: None,
+            rollback: false,
+            release_source: None,
+            release_pubkey: None,
+            command: None,
         }
     }
 
@@ -1020,4 +1621,39 @@ mod tests {
 
         assert!(args.validate().is_err());
     }
+
+    #[test]
+    fn test_output_kind_and_json_mode() {
+        let args = Args::parse_from(["port-kill", "--show-stats", "--json"]);
+        assert!(args.wants_json());
+        assert_eq!(args.output_kind(), "stats");
+
+        let args = Args::parse_from(["port-kill", "3000", "--json"]);
+        assert_eq!(args.output_kind(), "kill");
+
+        let args = Args::parse_from([
+            "port-kill",
+            "history",
+            "--report",
+            "offenders",
+            "--format",
+            "json",
+        ]);
+        assert!(args.wants_json());
+        assert_eq!(args.output_kind(), "offenders");
+
+        // Streaming stays newline-delimited events rather than one envelope
+        let args = Args::parse_from(["port-kill", "cache", "--list", "--stream", "--json"]);
+        assert!(!args.wants_json());
+        assert_eq!(Args::parse_from(["port-kill"]).output_kind(), "processes");
+    }
+
+    #[test]
+    fn test_expand_home_only_touches_a_leading_tilde() {
+        let expanded = expand_home("~/.port-kill/history.jsonl");
+        assert!(expanded.is_absolute(), "{}", expanded.display());
+        assert!(expanded.ends_with(".port-kill/history.jsonl"));
+        assert_eq!(expand_home("/srv/a~/b"), PathBuf::from("/srv/a~/b"));
+        assert_eq!(expand_home("rules.toml"), PathBuf::from("rules.toml"));
+    }
 }
@@ -0,0 +1,166 @@
+use crate::cli::{Args, Command};
+use crate::output::{self, CommandError};
+use crate::{config, history_query, history_store, preset_manager, project_ports, update_check};
+use anyhow::{anyhow, Context, Result};
+use serde_json::json;
+
+/// Runs the one-shot commands every binary shares: self-update and rollback, update checks,
+/// presets and the config, history, check and schema subcommands. `Some` when a command ran
+/// and the binary should exit with its result; `None` when it should go on to monitor, in
+/// which case `--preset` has been applied, the arguments validated and the history store
+/// configured.
+pub fn dispatch_subcommand(args: &mut Args) -> Option<Result<()>> {
+    match run(args) {
+        Ok(true) => Some(Ok(())),
+        Ok(false) => None,
+        Err(e) => Some(Err(e)),
+    }
+}
+
+fn run(args: &mut Args) -> Result<bool> {
+    // Handle rollback of the last self-update
+    if args.rollback {
+        update_check::rollback().context("Rollback failed")?;
+        return Ok(true);
+    }
+
+    // Handle self-update
+    if args.self_update {
+        update_check::self_update(&args.update_config()).context("Self-update failed")?;
+        return Ok(true);
+    }
+
+    // Handle update check
+    if args.check_updates {
+        let current_version = env!("CARGO_PKG_VERSION");
+        match update_check::check_for_updates(current_version) {
+            Ok(Some(update_info)) => update_check::print_update_check_result(&update_info),
+            Ok(None) => println!("✅ You're running the latest version ({})", current_version),
+            Err(e) => eprintln!("⚠️  Could not check for updates: {}", e),
+        }
+        return Ok(true);
+    }
+
+    // Check for updates in background (non-blocking); JSON output stays a single envelope
+    if !args.wants_json() {
+        let current_version = env!("CARGO_PKG_VERSION");
+        if let Ok(Some(update_info)) = update_check::check_for_updates(current_version) {
+            update_check::print_update_notification(&update_info);
+        }
+    }
+
+    // Print the schema for --json output
+    if let Some(Command::Schema) = &args.command {
+        print!("{}", output::SCHEMA);
+        return Ok(true);
+    }
+
+    // Handle preset functionality
+    if args.list_presets {
+        if args.json {
+            let mut mgr = preset_manager::PresetManager::new();
+            mgr.load_presets()?;
+            output::print("presets", &mgr.sorted_presets())?;
+            return Ok(true);
+        }
+        let presets_list = Args::list_available_presets().map_err(|e| anyhow!(e))?;
+        println!("{}", presets_list);
+        return Ok(true);
+    }
+
+    // Save preset
+    if let Some(name) = args.save_preset.clone() {
+        let desc = args
+            .preset_desc
+            .clone()
+            .unwrap_or_else(|| "User-defined preset".to_string());
+        let preset = args.build_preset_from_args(name.clone(), desc);
+        let mut mgr = preset_manager::PresetManager::new();
+        mgr.load_presets()?;
+        mgr.add_preset(preset);
+        mgr.save_presets()?;
+        if args.json {
+            output::print("preset", &json!({ "name": name, "action": "saved" }))?;
+        } else {
+            println!("✅ Saved preset '{}'.", name);
+        }
+        return Ok(true);
+    }
+
+    // Delete preset
+    if let Some(name) = args.delete_preset.clone() {
+        let mut mgr = preset_manager::PresetManager::new();
+        mgr.load_presets()?;
+        if mgr.remove_preset(&name).is_none() {
+            return Err(CommandError::usage(format!("Preset '{}' not found.", name)));
+        }
+        mgr.save_presets()?;
+        if args.json {
+            output::print("preset", &json!({ "name": name, "action": "deleted" }))?;
+        } else {
+            println!("🗑️  Deleted preset '{}'.", name);
+        }
+        return Ok(true);
+    }
+
+    // Handle preset export/import/validate subcommand
+    if let Some(Command::Preset(preset)) = &args.command {
+        preset_manager::run(preset)?;
+        return Ok(true);
+    }
+
+    // Apply preset if specified
+    if let Some(preset_name) = args.preset.clone() {
+        args.load_preset(&preset_name)
+            .map_err(CommandError::usage)?;
+    }
+
+    // Handle config subcommand
+    if let Some(Command::Config(config)) = &args.command {
+        config::run(config)?;
+        return Ok(true);
+    }
+
+    // Validate arguments
+    args.validate().map_err(CommandError::usage)?;
+
+    // Point every history reader and writer at the configured log
+    history_store::configure(args.history_config());
+
+    // Handle history query subcommand
+    if let Some(Command::History(query)) = &args.command {
+        history_query::run(query)?;
+        return Ok(true);
+    }
+
+    // Handle project port check subcommand
+    if let Some(Command::Check(check)) = &args.command {
+        if !project_ports::run(check, args)? {
+            return Err(CommandError::conflict("project ports are in use"));
+        }
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{exit_code_of, ExitCode};
+    use clap::Parser;
+
+    #[test]
+    fn test_invalid_arguments_are_usage_errors() {
+        let mut args = Args::parse_from([
+            "port-kill",
+            "--start-port",
+            "4000",
+            "--end-port",
+            "3000",
+            "--json",
+        ]);
+        let error = dispatch_subcommand(&mut args).unwrap().unwrap_err();
+        assert_eq!(exit_code_of(&error), ExitCode::Usage);
+    }
+}
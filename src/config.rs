@@ -0,0 +1,761 @@
+use crate::cli::{Args, CacheArgs, Command, ConfigAction, ConfigArgs};
+use crate::output::{self, CommandError};
+use anyhow::{anyhow, Context, Result};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use toml::Value;
+
+/// Per-project overrides, found by walking up from the working directory
+pub const PROJECT_CONFIG_FILE: &str = ".port-kill.toml";
+
+/// Settings a `.port-kill.toml` can't change, since it is picked up from whatever directory
+/// port-kill runs in: they send data elsewhere, write files, run remote binaries or make
+/// kills and cache cleaning more destructive. Only the global file, the environment and
+/// flags may set them.
+const GLOBAL_ONLY_SETTINGS: &[&str] = &[
+    "endpoint.url",
+    "endpoint.auth",
+    "endpoint.spool_dir",
+    "history.file",
+    "history.max_mb",
+    "guard.reservation_file",
+    "remote.bin",
+    "kill.force_pid",
+    "kill.stop_supervisor",
+    "cache.kill_holders",
+    "cache.safe_delete",
+];
+
+/// Where a setting's value came from, lowest precedence first
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigLayer {
+    Default,
+    Global,
+    Project,
+    Env,
+    Cli,
+}
+
+impl ConfigLayer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfigLayer::Default => "default",
+            ConfigLayer::Global => "global",
+            ConfigLayer::Project => "project",
+            ConfigLayer::Env => "env",
+            ConfigLayer::Cli => "cli",
+        }
+    }
+}
+
+/// A configurable value: its key in the TOML files and the flag it stands in for
+struct Setting {
+    key: &'static str,
+    /// clap id of the flag, i.e. the `Args` (or `CacheArgs`) field name
+    arg: &'static str,
+    /// Applies to the cache subcommand rather than the top-level flags
+    cache: bool,
+    apply: fn(&mut Args, &Value) -> Result<()>,
+}
+
+impl Setting {
+    /// `kill.respawn_window` is read from `PORT_KILL_KILL_RESPAWN_WINDOW`
+    fn env_var(&self) -> String {
+        format!("PORT_KILL_{}", self.key.replace('.', "_").to_uppercase())
+    }
+
+    fn flag(&self) -> String {
+        format!("--{}", self.arg.replace('_', "-"))
+    }
+}
+
+macro_rules! setting {
+    ($key:literal, $field:ident, $convert:expr) => {
+        Setting {
+            key: $key,
+            arg: stringify!($field),
+            cache: false,
+            apply: |args, value| {
+                args.$field = $convert(value)?;
+                Ok(())
+            },
+        }
+    };
+    (cache $key:literal, $field:ident, $convert:expr) => {
+        Setting {
+            key: $key,
+            arg: stringify!($field),
+            cache: true,
+            apply: |args, value| {
+                if let Some(cache) = cache_args_mut(args) {
+                    cache.$field = $convert(value)?;
+                }
+                Ok(())
+            },
+        }
+    };
+}
+
+fn settings() -> Vec<Setting> {
+    vec![
+        // Ports to monitor
+        setting!("ports.start", start_port, as_u16),
+        setting!("ports.end", end_port, as_u16),
+        setting!("ports.list", ports, as_optional_list),
+        // What to leave alone
+        setting!("ignore.ports", ignore_ports, as_optional_port_list),
+        setting!("ignore.processes", ignore_processes, as_optional_list),
+        setting!("ignore.patterns", ignore_patterns, as_optional_list),
+        setting!("ignore.groups", ignore_groups, as_optional_list),
+        setting!("ignore.only_groups", only_groups, as_optional_list),
+        setting!("ignore.smart_filter", smart_filter, as_bool),
+        setting!("ignore.where", where_expr, as_optional_string),
+        // How kills are carried out
+        setting!("kill.safe", safe, as_bool),
+        setting!("kill.docker", docker, as_bool),
+        setting!("kill.respawn_window", respawn_window, as_u64),
+        setting!("kill.stop_supervisor", stop_supervisor, as_bool),
+        setting!("kill.force_pid", force_pid, as_bool),
+        // Port guard
+        setting!("guard.ports", guard_ports, as_joined_list),
+        setting!("guard.auto_resolve", auto_resolve, as_bool),
+        setting!("guard.reservation_file", reservation_file, as_string),
+        setting!("guard.intercept_commands", intercept_commands, as_bool),
+        // Endpoint monitoring
+        setting!("endpoint.url", monitor_endpoint, as_optional_string),
+        setting!("endpoint.auth", endpoint_auth, as_optional_string),
+        setting!("endpoint.fields", endpoint_fields, as_optional_list),
+        setting!("endpoint.include_audit", endpoint_include_audit, as_bool),
+        setting!("endpoint.send_interval", send_interval, as_u64),
+        setting!("endpoint.scan_interval", scan_interval, as_u64),
+        setting!("endpoint.retries", endpoint_retries, as_u32),
+        setting!("endpoint.timeout", endpoint_timeout, as_u64),
+        setting!("endpoint.spool_dir", endpoint_spool_dir, as_string),
+        setting!("endpoint.spool_max_mb", endpoint_spool_max_mb, as_u64),
+        // Remote mode
+        setting!("remote.bin", remote_bin, as_string),
+        setting!("remote.timeout", remote_timeout, as_u64),
+        // Kill history
+        setting!("history.file", history_file, as_optional_string),
+        setting!("history.max_mb", history_max_mb, as_u64),
+        setting!("history.max_age_days", history_max_age_days, as_u64),
+        // Cache cleaning rules
+        setting!(cache "cache.lang", lang, as_string),
+        setting!(cache "cache.providers", providers, as_list),
+        setting!(cache "cache.safe_delete", safe_delete, as_bool),
+        setting!(cache "cache.kill_holders", kill_holders, as_bool),
+        setting!(cache "cache.stale_days", stale_days, |v| as_u32(v).map(Some)),
+    ]
+}
+
+fn cache_args_mut(args: &mut Args) -> Option<&mut CacheArgs> {
+    match &mut args.command {
+        Some(Command::Cache(cache)) => Some(cache),
+        _ => None,
+    }
+}
+
+fn as_bool(value: &Value) -> Result<bool> {
+    match value {
+        Value::Boolean(b) => Ok(*b),
+        Value::String(s) => match s.trim().to_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(true),
+            "false" | "0" | "no" | "off" => Ok(false),
+            _ => Err(anyhow!("expected true or false, got `{}`", s)),
+        },
+        other => Err(anyhow!("expected true or false, got {}", other)),
+    }
+}
+
+fn as_u64(value: &Value) -> Result<u64> {
+    match value {
+        Value::Integer(n) if *n >= 0 => Ok(*n as u64),
+        Value::String(s) => s
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("expected a non-negative number, got `{}`", s)),
+        other => Err(anyhow!("expected a non-negative number, got {}", other)),
+    }
+}
+
+fn as_u32(value: &Value) -> Result<u32> {
+    let n = as_u64(value)?;
+    u32::try_from(n).map_err(|_| anyhow!("{} is too large", n))
+}
+
+fn as_u16(value: &Value) -> Result<u16> {
+    let n = as_u64(value)?;
+    u16::try_from(n).map_err(|_| anyhow!("{} is not a valid port", n))
+}
+
+fn as_string(value: &Value) -> Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Integer(n) => Ok(n.to_string()),
+        other => Err(anyhow!("expected a string, got {}", other)),
+    }
+}
+
+/// An array, or a comma-separated string as on the command line
+fn as_list(value: &Value) -> Result<Vec<String>> {
+    match value {
+        Value::Array(items) => items.iter().map(as_string).collect(),
+        Value::String(s) => Ok(s
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()),
+        Value::Integer(n) => Ok(vec![n.to_string()]),
+        other => Err(anyhow!("expected a list, got {}", other)),
+    }
+}
+
+fn as_optional_string(value: &Value) -> Result<Option<String>> {
+    as_string(value).map(Some)
+}
+
+fn as_optional_list(value: &Value) -> Result<Option<Vec<String>>> {
+    as_list(value).map(Some)
+}
+
+/// A list stored the way its flag takes it, as one comma-separated string
+fn as_joined_list(value: &Value) -> Result<String> {
+    as_list(value).map(|items| items.join(","))
+}
+
+fn as_optional_port_list(value: &Value) -> Result<Option<Vec<u16>>> {
+    as_port_list(value).map(Some)
+}
+
+fn as_port_list(value: &Value) -> Result<Vec<u16>> {
+    as_list(value)?
+        .iter()
+        .map(|item| {
+            item.parse()
+                .map_err(|_| anyhow!("`{}` is not a valid port", item))
+        })
+        .collect()
+}
+
+/// Render a value the way it would be written on the command line
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(display_value)
+            .collect::<Vec<_>>()
+            .join(","),
+        other => other.to_string(),
+    }
+}
+
+/// One parsed config file and the settings it provides
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigFile {
+    pub layer: ConfigLayer,
+    pub path: PathBuf,
+    #[serde(skip)]
+    values: Vec<(String, Value)>,
+}
+
+impl ConfigFile {
+    /// Parse a config file, flattening `[section] key = ...` into `section.key`.
+    /// Unknown keys, and global-only keys in a project file, are reported but don't stop
+    /// the rest of the file from applying.
+    pub fn load(path: &Path, layer: ConfigLayer) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let table: toml::Table = content
+            .parse()
+            .with_context(|| format!("Invalid TOML in {}", path.display()))?;
+        let known: Vec<&str> = settings().iter().map(|s| s.key).collect();
+
+        let mut values = Vec::new();
+        for (section, entries) in table {
+            let Value::Table(entries) = entries else {
+                eprintln!(
+                    "⚠️  Ignoring `{}` in {}: settings belong in a [section]",
+                    section,
+                    path.display()
+                );
+                continue;
+            };
+            for (name, value) in entries {
+                let key = format!("{}.{}", section, name);
+                if layer == ConfigLayer::Project && GLOBAL_ONLY_SETTINGS.contains(&key.as_str()) {
+                    eprintln!(
+                        "⚠️  Ignoring `{}` in {}: set it in the global config, the environment or a flag",
+                        key,
+                        path.display()
+                    );
+                } else if known.contains(&key.as_str()) {
+                    values.push((key, value));
+                } else {
+                    eprintln!("⚠️  Unknown setting `{}` in {}", key, path.display());
+                }
+            }
+        }
+        Ok(Self {
+            layer,
+            path: path.to_path_buf(),
+            values,
+        })
+    }
+
+    fn get(&self, key: &str) -> Option<&Value> {
+        self.values.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+}
+
+/// Where to look for config files
+#[derive(Debug, Clone)]
+pub struct ConfigPaths {
+    pub global: PathBuf,
+    pub project: Option<PathBuf>,
+}
+
+impl ConfigPaths {
+    /// `~/.port-kill/config.toml` (or `PORT_KILL_CONFIG`) and the nearest project file
+    pub fn discover() -> Self {
+        let global = std::env::var("PORT_KILL_CONFIG")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                let home = std::env::var("HOME")
+                    .or_else(|_| std::env::var("USERPROFILE"))
+                    .unwrap_or_else(|_| "/tmp".to_string());
+                PathBuf::from(home).join(".port-kill").join("config.toml")
+            });
+        let project = std::env::current_dir()
+            .ok()
+            .and_then(|cwd| find_project_config(&cwd));
+        Self { global, project }
+    }
+}
+
+/// The closest `.port-kill.toml` in `start` or any of its parents
+pub fn find_project_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// A setting's resolved value and the layer that supplied it
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveSetting {
+    pub key: String,
+    pub value: String,
+    pub layer: ConfigLayer,
+    /// The file, environment variable or flag the value came from
+    pub origin: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigReport {
+    pub files: Vec<ConfigFile>,
+    pub settings: Vec<EffectiveSetting>,
+}
+
+static REPORT: OnceLock<ConfigReport> = OnceLock::new();
+
+/// Parse the command line and layer the config files and environment beneath it.
+/// Exits with an error message when a config value is invalid.
+pub fn parse_args() -> Args {
+    // Nothing is parsed yet when this fails, so `--json` anywhere asks for an error envelope
+    let json = std::env::args().any(|arg| arg == "--json");
+    let usage_error = |message: String| -> ! {
+        let error = CommandError::usage(message);
+        std::process::exit(output::report_error(json, "error", &error))
+    };
+
+    let matches = match Args::command().try_get_matches() {
+        Ok(matches) => matches,
+        // --help and --version, and usage errors in text mode, print as clap intends
+        Err(e) if !json || !e.use_stderr() => e.exit(),
+        Err(e) => {
+            let rendered = e.render().to_string();
+            let first = rendered.lines().next().unwrap_or_default();
+            usage_error(first.trim_start_matches("error: ").to_string())
+        }
+    };
+    let resolved = resolve(&matches, &ConfigPaths::discover(), &|name| {
+        std::env::var(name).ok()
+    });
+    match resolved {
+        Ok((args, report)) => {
+            let _ = REPORT.set(report);
+            args
+        }
+        Err(e) => usage_error(format!("{:#}", e)),
+    }
+}
+
+/// Apply defaults < global file < project file < environment < explicit flags
+pub fn resolve(
+    matches: &ArgMatches,
+    paths: &ConfigPaths,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<(Args, ConfigReport)> {
+    let mut args = Args::from_arg_matches(matches)?;
+
+    let mut files = Vec::new();
+    if paths.global.is_file() {
+        files.push(ConfigFile::load(&paths.global, ConfigLayer::Global)?);
+    }
+    if let Some(project) = &paths.project {
+        files.push(ConfigFile::load(project, ConfigLayer::Project)?);
+    }
+
+    let cache_matches = matches.subcommand_matches("cache");
+    let mut command = Args::command();
+    command.build();
+    let mut effective = Vec::new();
+    for setting in settings() {
+        let matches = if setting.cache {
+            cache_matches
+        } else {
+            Some(matches)
+        };
+        let from_cli =
+            matches.and_then(|m| m.value_source(setting.arg)) == Some(ValueSource::CommandLine);
+
+        let mut chosen: Option<(Value, ConfigLayer, String)> = None;
+        for file in &files {
+            if let Some(value) = file.get(setting.key) {
+                let origin = file.path.display().to_string();
+                chosen = Some((value.clone(), file.layer, origin));
+            }
+        }
+        let env_var = setting.env_var();
+        if let Some(value) = env(&env_var) {
+            chosen = Some((Value::String(value), ConfigLayer::Env, env_var));
+        }
+
+        let raw = matches.and_then(|m| m.get_raw(setting.arg)).map(|values| {
+            values
+                .map(|v| v.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        });
+        let (value, layer, origin) = if from_cli {
+            (raw.unwrap_or_default(), ConfigLayer::Cli, setting.flag())
+        } else if let Some((value, layer, origin)) = chosen {
+            (setting.apply)(&mut args, &value).map_err(|e| {
+                anyhow!("Invalid value for `{}` from {}: {}", setting.key, origin, e)
+            })?;
+            (display_value(&value), layer, origin)
+        } else {
+            let default = raw.unwrap_or_else(|| default_value(&command, &setting));
+            (
+                default,
+                ConfigLayer::Default,
+                "built-in default".to_string(),
+            )
+        };
+
+        effective.push(EffectiveSetting {
+            key: setting.key.to_string(),
+            value,
+            layer,
+            origin,
+        });
+    }
+
+    Ok((
+        args,
+        ConfigReport {
+            files,
+            settings: effective,
+        },
+    ))
+}
+
+fn default_value(command: &clap::Command, setting: &Setting) -> String {
+    let command = if setting.cache {
+        command.find_subcommand("cache")
+    } else {
+        Some(command)
+    };
+    command
+        .and_then(|c| c.get_arguments().find(|a| a.get_id() == setting.arg))
+        .map(|arg| {
+            arg.get_default_values()
+                .iter()
+                .map(|v| v.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "(unset)".to_string())
+}
+
+/// Handle `port-kill config ...`
+pub fn run(args: &ConfigArgs) -> Result<()> {
+    let ConfigAction::Show(show) = &args.action;
+    let report = REPORT
+        .get()
+        .ok_or_else(|| anyhow!("Configuration was not loaded"))?;
+
+    let settings: Vec<&EffectiveSetting> = report
+        .settings
+        .iter()
+        .filter(|s| show.effective || s.layer != ConfigLayer::Default)
+        .collect();
+
+    if show.json {
+        return output::print(
+            "config",
+            &serde_json::json!({
+                "files": report.files,
+                "settings": settings,
+            }),
+        );
+    }
+
+    let paths = ConfigPaths::discover();
+    println!("⚙️  Configuration layers (later ones win):");
+    println!("   1. built-in defaults");
+    println!(
+        "   2. global   {}{}",
+        paths.global.display(),
+        if paths.global.is_file() {
+            ""
+        } else {
+            " (not found)"
+        }
+    );
+    match &paths.project {
+        Some(path) => println!("   3. project  {}", path.display()),
+        None => println!("   3. project  {} (none found)", PROJECT_CONFIG_FILE),
+    }
+    println!("   4. environment  PORT_KILL_<SECTION>_<KEY>");
+    println!("   5. command-line flags");
+    println!();
+
+    if settings.is_empty() {
+        println!("ℹ️  No settings come from config files or the environment");
+        println!("   Use `port-kill config show --effective` to list every value");
+        return Ok(());
+    }
+
+    let key_width = settings.iter().map(|s| s.key.len()).max().unwrap_or(0);
+    let value_width = settings
+        .iter()
+        .map(|s| s.value.chars().count().min(40))
+        .max()
+        .unwrap_or(0)
+        .max(5);
+    println!(
+        "{:<kw$}  {:<vw$}  {:<8} ORIGIN",
+        "KEY",
+        "VALUE",
+        "LAYER",
+        kw = key_width,
+        vw = value_width
+    );
+    for setting in settings {
+        let value: String = if setting.value.chars().count() > 40 {
+            format!("{}…", setting.value.chars().take(39).collect::<String>())
+        } else {
+            setting.value.clone()
+        };
+        println!(
+            "{:<kw$}  {:<vw$}  {:<8} {}",
+            setting.key,
+            value,
+            setting.layer.as_str(),
+            setting.origin,
+            kw = key_width,
+            vw = value_width
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    fn setting<'a>(report: &'a ConfigReport, key: &str) -> &'a EffectiveSetting {
+        report.settings.iter().find(|s| s.key == key).unwrap()
+    }
+
+    #[test]
+    fn test_layers_apply_in_order_with_provenance() {
+        let dir = scratch_dir("config", "layers");
+        let global = dir.join("config.toml");
+        std::fs::write(
+            &global,
+            "[ignore]\nports = [5353, 7000]\nprocesses = \"Chrome,rapportd\"\n\n\
+             [kill]\nsafe = true\nrespawn_window = 10\n\n[guard]\nports = [3000, 3001]\n",
+        )
+        .unwrap();
+        let project_root = dir.join("web");
+        let nested = project_root.join("src").join("pages");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(
+            project_root.join(PROJECT_CONFIG_FILE),
+            "[kill]\nrespawn_window = 2\n\n[ports]\nlist = [\"3000\", \"5173\"]\n",
+        )
+        .unwrap();
+
+        let paths = ConfigPaths {
+            global,
+            project: find_project_config(&nested),
+        };
+        assert_eq!(paths.project, Some(project_root.join(PROJECT_CONFIG_FILE)));
+
+        let env = |name: &str| (name == "PORT_KILL_KILL_SAFE").then(|| "false".to_string());
+        let matches = Args::command()
+            .try_get_matches_from(["port-kill", "--ignore-ports", "9000"])
+            .unwrap();
+        let (args, report) = resolve(&matches, &paths, &env).unwrap();
+
+        // Flags beat everything
+        assert_eq!(args.ignore_ports, Some(vec![9000]));
+        assert_eq!(setting(&report, "ignore.ports").layer, ConfigLayer::Cli);
+        // The environment beats both files
+        assert!(!args.safe);
+        assert_eq!(setting(&report, "kill.safe").origin, "PORT_KILL_KILL_SAFE");
+        // The project file beats the global one
+        assert_eq!(args.respawn_window, 2);
+        assert_eq!(
+            setting(&report, "kill.respawn_window").layer,
+            ConfigLayer::Project
+        );
+        assert_eq!(
+            args.ports,
+            Some(vec!["3000".to_string(), "5173".to_string()])
+        );
+        // The global file beats the defaults
+        assert_eq!(
+            args.ignore_processes,
+            Some(vec!["Chrome".to_string(), "rapportd".to_string()])
+        );
+        assert_eq!(args.guard_ports, "3000,3001");
+        assert_eq!(setting(&report, "guard.ports").layer, ConfigLayer::Global);
+        let end_port = setting(&report, "ports.end");
+        assert_eq!(
+            (end_port.layer, end_port.value.as_str()),
+            (ConfigLayer::Default, "6000")
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_project_file_cannot_set_global_only_settings() {
+        let dir = scratch_dir("config", "global-only");
+        let project = dir.join(PROJECT_CONFIG_FILE);
+        std::fs::write(
+            &project,
+            "[endpoint]\nurl = \"https://collector.example\"\nauth = \"Bearer stolen\"\n\n\
+             [kill]\nforce_pid = true\nrespawn_window = 9\n\n[cache]\nsafe_delete = false\n",
+        )
+        .unwrap();
+        let paths = ConfigPaths {
+            global: dir.join("config.toml"),
+            project: Some(project.clone()),
+        };
+        let matches = Args::command().try_get_matches_from(["port-kill"]).unwrap();
+        let (args, report) = resolve(&matches, &paths, &|_| None).unwrap();
+
+        assert_eq!(args.monitor_endpoint, None);
+        assert_eq!(args.endpoint_auth, None);
+        assert!(!args.force_pid);
+        assert_eq!(setting(&report, "endpoint.url").layer, ConfigLayer::Default);
+        // The rest of the file still applies
+        assert_eq!(args.respawn_window, 9);
+
+        // The same keys are fine in the global file
+        std::fs::rename(&project, &paths.global).unwrap();
+        let paths = ConfigPaths {
+            project: None,
+            ..paths
+        };
+        let (args, _) = resolve(&matches, &paths, &|_| None).unwrap();
+        assert_eq!(
+            args.monitor_endpoint.as_deref(),
+            Some("https://collector.example")
+        );
+        assert!(args.force_pid);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_project_file_cannot_stop_supervisors() {
+        let dir = scratch_dir("config", "stop-supervisor");
+        let project = dir.join(PROJECT_CONFIG_FILE);
+        std::fs::write(&project, "[kill]\nstop_supervisor = true\n").unwrap();
+        let paths = ConfigPaths {
+            global: dir.join("config.toml"),
+            project: Some(project),
+        };
+        let matches = Args::command().try_get_matches_from(["port-kill"]).unwrap();
+        let (args, report) = resolve(&matches, &paths, &|_| None).unwrap();
+
+        assert!(!args.stop_supervisor);
+        assert_eq!(
+            setting(&report, "kill.stop_supervisor").layer,
+            ConfigLayer::Default
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_project_file_cannot_resize_history() {
+        let dir = scratch_dir("config", "history-max-mb");
+        let project = dir.join(PROJECT_CONFIG_FILE);
+        std::fs::write(&project, "[history]\nmax_mb = 0\n").unwrap();
+        let paths = ConfigPaths {
+            global: dir.join("config.toml"),
+            project: Some(project),
+        };
+        let matches = Args::command().try_get_matches_from(["port-kill"]).unwrap();
+        let (args, report) = resolve(&matches, &paths, &|_| None).unwrap();
+
+        assert_eq!(args.history_max_mb, 5);
+        assert_eq!(
+            setting(&report, "history.max_mb").layer,
+            ConfigLayer::Default
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_invalid_values_name_key_and_origin() {
+        let dir = scratch_dir("config", "invalid");
+        let global = dir.join("config.toml");
+        std::fs::write(&global, "[ports]\nstart = 70000\n").unwrap();
+        let paths = ConfigPaths {
+            global,
+            project: None,
+        };
+        let matches = Args::command().try_get_matches_from(["port-kill"]).unwrap();
+        let err = resolve(&matches, &paths, &|_| None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("ports.start"), "{}", err);
+        assert!(err.contains("config.toml"), "{}", err);
+
+        // Cache rules only apply to the cache subcommand, and its own flags still win
+        std::fs::write(&paths.global, "[cache]\nlang = \"rust\"\nstale_days = 14\n").unwrap();
+        let matches = Args::command()
+            .try_get_matches_from(["port-kill", "cache", "--list", "--stale-days", "3"])
+            .unwrap();
+        let (args, _) = resolve(&matches, &paths, &|_| None).unwrap();
+        let cache = args.command.as_ref().and_then(Command::cache_args).unwrap();
+        assert_eq!((cache.lang.as_str(), cache.stale_days), ("rust", Some(3)));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
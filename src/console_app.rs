@@ -1,15 +1,25 @@
 use crate::{
+    alert_rules::AlertMonitor,
     cli::Args,
     endpoint_monitor::EndpointMonitor,
+    event_monitor::EventMonitor,
+    metrics_exporter::MetricsExporter,
+    output::{self, CommandError, ExitCode},
     port_guard::PortGuardDaemon,
-    process_monitor::ProcessMonitor,
+    process_monitor::{kill_and_record, ProcessMonitor},
+    remote::{RemoteProcess, RemoteRunner},
     security_audit::SecurityAuditor,
     smart_filter::SmartFilter,
-    types::{GuardStatus, ProcessUpdate, SecurityAuditResult, StatusBarInfo},
+    supervisor::{watch_for_respawns, Respawn, DEFAULT_RESPAWN_WINDOW},
+    types::{
+        format_duration_short, FrequentOffender, GuardStatus, HistoryStatistics, KillOutcome,
+        ProcessHistoryEntry, ProcessInfo, ProcessUpdate, SecurityAuditResult, StatusBarInfo,
+        TimePatterns,
+    },
 };
 use anyhow::Result;
 use crossbeam_channel::{bounded, Receiver};
-use log::{error, info};
+use log::{debug, error, info};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -32,37 +42,45 @@ impl ConsolePortKillApp {
         &self.args
     }
 
-    pub fn new(args: Args) -> Result<Self> {
+    pub fn new(mut args: Args) -> Result<Self> {
+        // A --where on cpu or memory needs the metrics collected, and the TUI shows them
+        if let Ok(Some(expr)) = args.get_where_filter() {
+            args.performance |= expr.uses_metrics();
+        }
+        args.performance |= args.tui;
+
         // Create channels for communication
         let (update_sender, update_receiver) = bounded(100);
 
         // Create smart filter if needed
-        let smart_filter = Self::create_smart_filter(&args)?;
+        let smart_filter = SmartFilter::from_args(&args)?;
 
         // Create process monitor with configurable ports
-        println!(
-            "DEBUG: Creating ProcessMonitor with verbose={}, performance={}",
+        debug!(
+            "Creating ProcessMonitor with verbose={}, performance={}",
             args.verbose, args.performance
         );
-        let process_monitor = if let Some(filter) = smart_filter {
-            Arc::new(Mutex::new(ProcessMonitor::new_with_performance(
+        let mut monitor = if let Some(filter) = smart_filter {
+            ProcessMonitor::new_with_performance(
                 update_sender,
                 args.get_ports_to_monitor(),
                 args.docker,
                 args.verbose,
                 Some(filter),
                 args.performance,
-            )?))
+            )?
         } else {
-            Arc::new(Mutex::new(ProcessMonitor::new_with_performance(
+            ProcessMonitor::new_with_performance(
                 update_sender,
                 args.get_ports_to_monitor(),
                 args.docker,
                 args.verbose,
                 None,
                 args.performance,
-            )?))
+            )?
         };
+        monitor.set_force_pid(args.force_pid);
+        let process_monitor = Arc::new(Mutex::new(monitor));
 
         // Initialize Port Guard if enabled
         let port_guard = if args.guard_mode {
@@ -102,9 +120,9 @@ impl ConsolePortKillApp {
     /// Create a temporary process monitor with smart port selection
     async fn create_temp_monitor(&self, ports_to_scan: Vec<u16>) -> Result<ProcessMonitor> {
         let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
-        let smart_filter = Self::create_smart_filter(&self.args)?;
+        let smart_filter = SmartFilter::from_args(&self.args)?;
 
-        if let Some(filter) = smart_filter {
+        let mut monitor = if let Some(filter) = smart_filter {
             ProcessMonitor::new_with_performance(
                 update_sender,
                 ports_to_scan,
@@ -112,7 +130,7 @@ impl ConsolePortKillApp {
                 self.args.verbose,
                 Some(filter),
                 self.args.performance,
-            )
+            )?
         } else {
             ProcessMonitor::new_with_performance(
                 update_sender,
@@ -121,44 +139,28 @@ impl ConsolePortKillApp {
                 self.args.verbose,
                 None,
                 self.args.performance,
-            )
-        }
+            )?
+        };
+        monitor.set_force_pid(self.args.force_pid);
+        Ok(monitor)
     }
 
-    fn create_smart_filter(args: &Args) -> Result<Option<SmartFilter>> {
-        // Get smart filter defaults
-        let (smart_ignore_ports, smart_ignore_processes, smart_ignore_groups) =
-            args.get_smart_filter_defaults();
-
-        // Combine user ignores with smart ignores
-        let mut ignore_ports = args.get_ignore_ports_set();
-        ignore_ports.extend(smart_ignore_ports);
-
-        let mut ignore_processes = args.get_ignore_processes_set();
-        ignore_processes.extend(smart_ignore_processes);
-
-        let mut ignore_groups = args.get_ignore_groups_set();
-        ignore_groups.extend(smart_ignore_groups);
-
-        // Check if any filtering is needed
-        if ignore_ports.is_empty()
-            && ignore_processes.is_empty()
-            && args.ignore_patterns.is_none()
-            && ignore_groups.is_empty()
-            && args.only_groups.is_none()
-        {
-            return Ok(None);
+    /// List what the filter hid on the last scan and why
+    fn print_filtered_processes(monitor: &ProcessMonitor) {
+        let hidden = monitor.get_filtered_processes();
+        if hidden.is_empty() {
+            return;
+        }
+        println!("🙈 Hidden by filters ({}):", hidden.len());
+        for filtered in hidden {
+            println!(
+                "  • Port {}: {} (PID {}) — {}",
+                filtered.process.port,
+                filtered.process.get_display_name(),
+                filtered.process.pid,
+                filtered.reason
+            );
         }
-
-        let filter = SmartFilter::new(
-            ignore_ports,
-            ignore_processes,
-            args.ignore_patterns.clone(),
-            ignore_groups,
-            args.get_only_groups_set(),
-        )?;
-
-        Ok(Some(filter))
     }
 
     pub async fn run(mut self) -> Result<()> {
@@ -169,6 +171,9 @@ impl ConsolePortKillApp {
             let ports_to_scan = Self::get_ports_to_scan(&self.args);
             let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
             let processes = temp_monitor.scan_processes().await?;
+            if self.args.json {
+                return output::print("processes", &sorted_by_port(&processes));
+            }
             if processes.is_empty() {
                 println!("ℹ️  No processes detected");
             } else {
@@ -182,6 +187,7 @@ impl ConsolePortKillApp {
                     );
                 }
             }
+            Self::print_filtered_processes(&temp_monitor);
             return Ok(());
         }
 
@@ -204,8 +210,11 @@ impl ConsolePortKillApp {
                     return Ok(());
                 }
             }
-            kill_on_ports(&self.args.positional_ports, &self.args)?;
-            return Ok(());
+            let ports = self.args.positional_ports.clone();
+            let killed = self.snapshot_ports(&ports);
+            let kills = kill_on_ports(&ports, &self.args)?;
+            let respawns = self.handle_respawns(&killed).await?;
+            return self.finish_kill("kill", &kills, &respawns);
         }
 
         // One-shot: --clear
@@ -220,8 +229,10 @@ impl ConsolePortKillApp {
                 }
             }
             use crate::process_monitor::kill_all_processes as kill_on_ports;
-            kill_on_ports(&[port], &self.args)?;
-            return Ok(());
+            let killed = self.snapshot_ports(&[port]);
+            let kills = kill_on_ports(&[port], &self.args)?;
+            let respawns = self.handle_respawns(&killed).await?;
+            return self.finish_kill("kill", &kills, &respawns);
         }
 
         // One-shot: --kill (by PID)
@@ -236,8 +247,8 @@ impl ConsolePortKillApp {
                 }
             }
             use crate::process_monitor::kill_single_process;
-            kill_single_process(pid, &self.args)?;
-            return Ok(());
+            let kills = kill_single_process(pid, &self.args)?;
+            return self.finish_kill("kill", &kills, &[]);
         }
 
         // One-shot: file-based operations
@@ -261,20 +272,7 @@ impl ConsolePortKillApp {
                     return Ok(());
                 }
             }
-            // Kill each PID
-            let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
-            let mut temp_monitor = ProcessMonitor::new_with_performance(
-                update_sender,
-                vec![],
-                self.args.docker,
-                self.args.verbose,
-                None,
-                self.args.performance,
-            )?;
-            for p in procs {
-                let _ = temp_monitor.kill_process(p.pid).await;
-            }
-            return Ok(());
+            return self.kill_file_holders(&procs);
         }
 
         if let Some(ext) = &self.args.kill_ext {
@@ -297,19 +295,7 @@ impl ConsolePortKillApp {
                     return Ok(());
                 }
             }
-            let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
-            let mut temp_monitor = ProcessMonitor::new_with_performance(
-                update_sender,
-                vec![],
-                self.args.docker,
-                self.args.verbose,
-                None,
-                self.args.performance,
-            )?;
-            for p in procs {
-                let _ = temp_monitor.kill_process(p.pid).await;
-            }
-            return Ok(());
+            return self.kill_file_holders(&procs);
         }
 
         if let Some(pattern) = &self.args.list_file {
@@ -359,6 +345,25 @@ impl ConsolePortKillApp {
             return self.run_endpoint_monitoring().await;
         }
 
+        // Check if alert rules are enabled
+        if self.args.alert_rules.is_some() {
+            return self.run_alert_rules().await;
+        }
+
+        // Check if event mode (webhooks) is enabled
+        if self.args.event_mode_enabled() {
+            return self.run_event_monitoring().await;
+        }
+
+        // Check if the Prometheus exporter is enabled
+        if self.args.metrics_listen.is_some() {
+            return self.run_metrics_exporter().await;
+        }
+
+        if self.args.tui {
+            return crate::tui::run(&self.args, self.process_monitor.clone()).await;
+        }
+
         println!("🚀 Port Kill Console Monitor Started!");
         println!(
             "📡 Monitoring {} every 2 seconds...",
@@ -419,6 +424,64 @@ impl ConsolePortKillApp {
         Ok(())
     }
 
+    /// Run the alert rules engine
+    async fn run_alert_rules(&mut self) -> Result<()> {
+        let mut alert_monitor = AlertMonitor::new(&self.args)?;
+
+        println!("🚀 Port Kill Alert Rules Started!");
+        println!(
+            "📡 Watching {} every {}s with {} rule(s)",
+            self.args.get_port_description(),
+            self.args.scan_interval,
+            alert_monitor.rule_count()
+        );
+        println!("💡 Press Ctrl+C to quit");
+        println!();
+
+        alert_monitor.run().await?;
+
+        Ok(())
+    }
+
+    /// Run event mode, posting changes to webhook sinks
+    async fn run_event_monitoring(&mut self) -> Result<()> {
+        let mut event_monitor = EventMonitor::new(&self.args, self.port_guard.clone())?;
+
+        println!("🚀 Port Kill Event Monitor Started!");
+        println!(
+            "📡 Watching {} every {}s, posting events to {} webhook sink(s)",
+            self.args.get_port_description(),
+            self.args.scan_interval,
+            event_monitor.sink_count()
+        );
+        println!("💡 Press Ctrl+C to quit");
+        println!();
+
+        event_monitor.run(&self.args).await?;
+
+        Ok(())
+    }
+
+    /// Run the Prometheus metrics exporter
+    async fn run_metrics_exporter(&mut self) -> Result<()> {
+        println!("🚀 Port Kill Metrics Exporter Started!");
+        if let Some(ref addr) = self.args.metrics_listen {
+            println!(
+                "📈 Serving metrics for {} at http://{}/metrics (refreshed every {}s)",
+                self.args.get_port_description(),
+                addr,
+                self.args.scan_interval
+            );
+        }
+        println!("💡 Press Ctrl+C to quit");
+        println!();
+
+        let mut exporter = MetricsExporter::new(&self.args, self.port_guard.clone())?;
+        exporter.run(&self.args).await?;
+
+        Ok(())
+    }
+
     async fn output_processes_json(&mut self) -> Result<()> {
         // Use smart port selection to avoid hanging on large port ranges
         let ports_to_scan = Self::get_ports_to_scan(&self.args);
@@ -428,13 +491,7 @@ impl ConsolePortKillApp {
         // Filter out ignored processes
         let filtered_processes = self.filter_ignored_processes(&processes);
 
-        // Output each process as JSON
-        for (_port, process_info) in &filtered_processes {
-            let json = serde_json::to_string(process_info)?;
-            println!("{}", json);
-        }
-
-        Ok(())
+        output::print("processes", &sorted_by_port(&filtered_processes))
     }
 
     async fn handle_console_updates(&mut self) {
@@ -538,6 +595,10 @@ impl ConsolePortKillApp {
                                 parts.push(format!("(PID {})", process_info.pid));
                             }
 
+                            if let Some(age) = process_info.format_age() {
+                                parts.push(format!("up {}", age));
+                            }
+
                             println!("{}", parts.join(" "));
                         } else {
                             // Normal mode: show enhanced display name
@@ -587,6 +648,10 @@ impl ConsolePortKillApp {
                                 parts.push(format!("(PID {})", process_info.pid));
                             }
 
+                            if let Some(age) = process_info.format_age() {
+                                parts.push(format!("up {}", age));
+                            }
+
                             println!("{}", parts.join(" "));
                         }
                     }
@@ -639,54 +704,70 @@ impl ConsolePortKillApp {
         let monitor = self.process_monitor.lock().await;
         let history = monitor.get_history();
 
+        if self.args.json {
+            // Last 50 entries for API
+            return output::print("history", &history.get_recent_entries(50));
+        }
+
         if history.is_empty() {
-            if self.args.json {
-                println!("[]");
-            } else {
-                println!("📋 No process kill history found");
-            }
+            println!("📋 No process kill history found");
             return Ok(());
         }
 
-        if self.args.json {
-            // Output history as JSON
-            let recent_entries = history.get_recent_entries(50); // Show last 50 entries for API
+        // Output history in human-readable format
+        println!("📋 Process Kill History ({} entries):", history.len());
+        println!("{}", "─".repeat(80));
 
-            for entry in recent_entries.iter() {
-                let json = serde_json::to_string(entry)?;
-                println!("{}", json);
-            }
-        } else {
-            // Output history in human-readable format
-            println!("📋 Process Kill History ({} entries):", history.len());
-            println!("{}", "─".repeat(80));
+        let recent_entries = history.get_recent_entries(20); // Show last 20 entries
 
-            let recent_entries = history.get_recent_entries(20); // Show last 20 entries
+        for (i, entry) in recent_entries.iter().enumerate() {
+            let display_name = entry.get_display_name();
+            let time_str = format_time_ago(entry.killed_at);
 
-            for (i, entry) in recent_entries.iter().enumerate() {
-                let display_name = entry.get_display_name();
-                let time_str = format_time_ago(entry.killed_at);
+            println!(
+                "{:2}. {} (PID {}) on port {} - {} ago",
+                i + 1,
+                display_name,
+                entry.pid,
+                entry.port,
+                time_str
+            );
 
-                println!(
-                    "{:2}. {} (PID {}) on port {} - {} ago",
-                    i + 1,
-                    display_name,
-                    entry.pid,
-                    entry.port,
-                    time_str
-                );
+            if let Some(ref cmd_line) = entry.command_line {
+                println!("    Command: {}", cmd_line);
+            }
 
-                if let Some(ref cmd_line) = entry.command_line {
-                    println!("    Command: {}", cmd_line);
-                }
+            if let Some(ref work_dir) = entry.working_directory {
+                println!("    Directory: {}", work_dir);
+            }
 
-                if let Some(ref work_dir) = entry.working_directory {
-                    println!("    Directory: {}", work_dir);
+            let mut killed_by = format!("{} ({})", entry.killed_by, entry.actor.as_str());
+            if let Some(ref user) = entry.user {
+                killed_by.push_str(&format!(" as {}", user));
+            }
+            if let Some(ref tty) = entry.tty {
+                killed_by.push_str(&format!(" on {}", tty));
+            }
+            println!("    Killed by: {}", killed_by);
+
+            if entry.outcome != KillOutcome::Unknown {
+                let mut outcome = entry.outcome.as_str().replace('_', " ");
+                if let Some(ref signal) = entry.signal {
+                    outcome.push_str(&format!(" by {}", signal));
+                }
+                if entry.attempts > 1 {
+                    outcome.push_str(&format!(" after {} attempts", entry.attempts));
                 }
+                if let Some(ref error) = entry.error {
+                    outcome.push_str(&format!(" ({})", error));
+                }
+                println!("    Outcome: {}", outcome);
+            }
 
-                println!("    Killed by: {}", entry.killed_by);
-                println!();
+            if let Some(lifetime) = entry.lifetime_secs {
+                println!("    Lifetime: {}", format_duration_short(lifetime));
             }
+            println!();
         }
 
         Ok(())
@@ -694,12 +775,22 @@ impl ConsolePortKillApp {
 
     pub async fn clear_history(&self) -> Result<()> {
         let mut monitor = self.process_monitor.lock().await;
+        let cleared = monitor.get_history().len();
         monitor.clear_history();
+        if self.args.json {
+            return output::print(
+                "history_cleared",
+                &serde_json::json!({ "cleared": cleared }),
+            );
+        }
         println!("🗑️  Process kill history cleared");
         Ok(())
     }
 
     pub async fn display_filter_info(&self) -> Result<()> {
+        if self.args.json {
+            return self.output_filter_info_json().await;
+        }
         let monitor = self.process_monitor.lock().await;
 
         if let Some(filter_stats) = monitor.get_filter_stats() {
@@ -708,14 +799,11 @@ impl ConsolePortKillApp {
             println!("{}", filter_stats.get_description());
 
             if filter_stats.ignore_ports_count > 0 {
-                println!(
-                    "  • Ignoring {} system ports",
-                    filter_stats.ignore_ports_count
-                );
+                println!("  • Ignoring {} ports", filter_stats.ignore_ports_count);
             }
             if filter_stats.ignore_processes_count > 0 {
                 println!(
-                    "  • Ignoring {} system processes",
+                    "  • Ignoring {} process names",
                     filter_stats.ignore_processes_count
                 );
             }
@@ -737,13 +825,50 @@ impl ConsolePortKillApp {
                     filter_stats.only_groups_count
                 );
             }
+            if filter_stats.system_detection {
+                println!(
+                    "  • Hiding system processes (OS binaries, service accounts, systemd system services, desktop daemons)"
+                );
+            }
         } else {
             println!("🔍 No filtering enabled - showing all processes");
+            return Ok(());
+        }
+        drop(monitor);
+
+        println!();
+        let ports_to_scan = Self::get_ports_to_scan(&self.args);
+        let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
+        temp_monitor.scan_processes().await?;
+        if temp_monitor.get_filtered_processes().is_empty() {
+            println!("✅ Nothing is hidden on the scanned ports");
+        } else {
+            Self::print_filtered_processes(&temp_monitor);
         }
 
         Ok(())
     }
 
+    async fn output_filter_info_json(&self) -> Result<()> {
+        let stats = self.process_monitor.lock().await.get_filter_stats();
+        let active = stats.as_ref().is_some_and(|stats| stats.is_active());
+        let mut filtered = Vec::new();
+        if stats.is_some() {
+            let ports_to_scan = Self::get_ports_to_scan(&self.args);
+            let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
+            temp_monitor.scan_processes().await?;
+            filtered = temp_monitor.get_filtered_processes().to_vec();
+        }
+        output::print(
+            "filters",
+            &serde_json::json!({
+                "active": active,
+                "stats": stats,
+                "filtered": filtered,
+            }),
+        )
+    }
+
     pub async fn kill_by_group(&self, groups: &[String]) -> Result<()> {
         // Use smart port selection to avoid hanging on large port ranges
         let ports_to_scan = Self::get_ports_to_scan(&self.args);
@@ -752,34 +877,43 @@ impl ConsolePortKillApp {
 
         let mut killed_count = 0;
         let mut total_count = 0;
+        let mut killed = Vec::new();
+        let mut kills = Vec::new();
 
         for (port, process_info) in &processes {
             if let Some(ref group) = process_info.process_group {
                 if groups.contains(group) {
                     total_count += 1;
-                    println!(
-                        "🔪 Killing {} (PID {}) on port {} - Group: {}",
-                        process_info.get_short_name(),
-                        process_info.pid,
-                        port,
-                        group
-                    );
+                    if !self.args.json {
+                        println!(
+                            "🔪 Killing {} (PID {}) on port {} - Group: {}",
+                            process_info.get_short_name(),
+                            process_info.pid,
+                            port,
+                            group
+                        );
+                    }
 
-                    if let Err(e) = temp_monitor.kill_process(process_info.pid).await {
+                    let entry = Self::kill_recorded(&mut temp_monitor, process_info).await;
+                    if entry.outcome.succeeded() {
+                        killed_count += 1;
+                        killed.push((*port, process_info.pid));
+                    } else if !self.args.json {
                         println!(
                             "❌ Failed to kill {} (PID {}): {}",
                             process_info.get_short_name(),
                             process_info.pid,
-                            e
+                            entry.error.as_deref().unwrap_or("process is still running")
                         );
-                    } else {
-                        killed_count += 1;
                     }
+                    kills.push(entry);
                 }
             }
         }
 
-        if total_count == 0 {
+        if self.args.json {
+            // The kill report says it all
+        } else if total_count == 0 {
             println!("ℹ️  No processes found in groups: {}", groups.join(", "));
         } else {
             println!(
@@ -790,7 +924,8 @@ impl ConsolePortKillApp {
             );
         }
 
-        Ok(())
+        let respawns = self.handle_respawns(&killed).await?;
+        self.finish_kill("kill", &kills, &respawns)
     }
 
     pub async fn kill_by_project(&self, projects: &[String]) -> Result<()> {
@@ -801,11 +936,13 @@ impl ConsolePortKillApp {
 
         let mut killed_count = 0;
         let mut total_count = 0;
+        let mut killed = Vec::new();
+        let mut kills = Vec::new();
 
         for (port, process_info) in &processes {
-            if let Some(ref project) = process_info.project_name {
-                if projects.contains(project) {
-                    total_count += 1;
+            if let Some(project) = projects.iter().find(|p| process_info.matches_project(p)) {
+                total_count += 1;
+                if !self.args.json {
                     println!(
                         "🔪 Killing {} (PID {}) on port {} - Project: {}",
                         process_info.get_short_name(),
@@ -813,22 +950,27 @@ impl ConsolePortKillApp {
                         port,
                         project
                     );
+                }
 
-                    if let Err(e) = temp_monitor.kill_process(process_info.pid).await {
-                        println!(
-                            "❌ Failed to kill {} (PID {}): {}",
-                            process_info.get_short_name(),
-                            process_info.pid,
-                            e
-                        );
-                    } else {
-                        killed_count += 1;
-                    }
+                let entry = Self::kill_recorded(&mut temp_monitor, process_info).await;
+                if entry.outcome.succeeded() {
+                    killed_count += 1;
+                    killed.push((*port, process_info.pid));
+                } else if !self.args.json {
+                    println!(
+                        "❌ Failed to kill {} (PID {}): {}",
+                        process_info.get_short_name(),
+                        process_info.pid,
+                        entry.error.as_deref().unwrap_or("process is still running")
+                    );
                 }
+                kills.push(entry);
             }
         }
 
-        if total_count == 0 {
+        if self.args.json {
+            // The kill report says it all
+        } else if total_count == 0 {
             println!(
                 "ℹ️  No processes found in projects: {}",
                 projects.join(", ")
@@ -842,7 +984,8 @@ impl ConsolePortKillApp {
             );
         }
 
-        Ok(())
+        let respawns = self.handle_respawns(&killed).await?;
+        self.finish_kill("kill", &kills, &respawns)
     }
 
     pub async fn kill_all_processes(&self) -> Result<()> {
@@ -852,19 +995,111 @@ impl ConsolePortKillApp {
         let processes = temp_monitor.scan_processes().await?;
 
         if processes.is_empty() {
+            if self.args.json {
+                return self.finish_kill("kill", &[], &[]);
+            }
             println!("ℹ️  No processes found to kill");
             return Ok(());
         }
 
         let total_count = processes.len();
-        println!("🔪 Killing all {} processes...", total_count);
+        if !self.args.json {
+            println!("🔪 Killing all {} processes...", total_count);
+        }
 
         // Use the ProcessMonitor's kill_all_processes method which handles history properly
-        temp_monitor.kill_all_processes().await?;
+        let kills = temp_monitor.kill_all_processes_recorded().await?;
 
-        println!("✅ Killed all {} processes", total_count);
+        let killed: Vec<(u16, i32)> = kills
+            .iter()
+            .filter(|entry| entry.outcome.succeeded())
+            .map(|entry| (entry.port, entry.pid))
+            .collect();
+        if !self.args.json {
+            println!("✅ Killed {}/{} processes", killed.len(), total_count);
+        }
 
-        Ok(())
+        let respawns = self.handle_respawns(&killed).await?;
+        self.finish_kill("kill", &kills, &respawns)
+    }
+
+    /// Kill the processes `--kill-file` or `--kill-ext` matched, recording each attempt
+    /// in the history; they were found by open file, not by port scan, so no monitor knows them
+    fn kill_file_holders(&self, procs: &[ProcessInfo]) -> Result<()> {
+        let mut kills = Vec::new();
+        for process_info in procs {
+            let entry = ProcessHistoryEntry::new(process_info, "user".to_string());
+            let report = kill_and_record(entry.clone(), self.args.force_pid);
+            let entry = entry.with_report(&report);
+            if !self.args.json {
+                if entry.outcome.succeeded() {
+                    println!(
+                        "✅ Killed {} (PID {})",
+                        process_info.get_short_name(),
+                        process_info.pid
+                    );
+                } else {
+                    println!(
+                        "❌ Failed to kill {} (PID {}): {}",
+                        process_info.get_short_name(),
+                        process_info.pid,
+                        entry.error.as_deref().unwrap_or("process is still running")
+                    );
+                }
+            }
+            kills.push(entry);
+        }
+        self.finish_kill("kill", &kills, &[])
+    }
+
+    /// Kill a scanned process and return the attempt as recorded in the history
+    async fn kill_recorded(
+        monitor: &mut ProcessMonitor,
+        process_info: &ProcessInfo,
+    ) -> ProcessHistoryEntry {
+        let (report, recorded) = monitor
+            .kill_process_recorded(process_info.pid, "user", true)
+            .await;
+        recorded.unwrap_or_else(|| {
+            ProcessHistoryEntry::new(process_info, "user".to_string()).with_report(&report)
+        })
+    }
+
+    /// Report how a kill command went: the envelope in JSON mode, and an error carrying
+    /// the exit code when some of the processes could not be killed
+    fn finish_kill(
+        &self,
+        kind: &str,
+        kills: &[ProcessHistoryEntry],
+        respawns: &[Respawn],
+    ) -> Result<()> {
+        let failed = kills
+            .iter()
+            .filter(|entry| !entry.outcome.succeeded())
+            .count();
+        let data = serde_json::json!({ "kills": kills, "respawns": respawns });
+        if failed == 0 {
+            if self.args.json {
+                output::print(kind, &data)?;
+            }
+            return Ok(());
+        }
+
+        let exit_code = if failed == kills.len() {
+            ExitCode::Failure
+        } else {
+            ExitCode::Partial
+        };
+        let message = format!(
+            "{} of {} process(es) could not be killed",
+            failed,
+            kills.len()
+        );
+        if self.args.json {
+            Err(output::print_failed(kind, &data, exit_code, message))
+        } else {
+            Err(CommandError::with_code(exit_code, message))
+        }
     }
 
     pub async fn restart_processes(&self) -> Result<()> {
@@ -872,48 +1107,161 @@ impl ConsolePortKillApp {
         let ports_to_scan = Self::get_ports_to_scan(&self.args);
         let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
         let processes = temp_monitor.scan_processes().await?;
+        let json = self.args.json;
 
         if processes.is_empty() {
+            if json {
+                return self.finish_kill("restart", &[], &[]);
+            }
             println!("ℹ️  No processes to restart");
             return Ok(());
         }
 
-        println!("🔄 Restarting {} processes...", processes.len());
+        if !json {
+            println!("🔄 Restarting {} processes...", processes.len());
+        }
 
         // Kill all processes
+        let mut killed = Vec::new();
+        let mut kills = Vec::new();
         for (port, process_info) in &processes {
-            println!(
-                "🔪 Killing {} (PID {}) on port {}",
-                process_info.get_short_name(),
-                process_info.pid,
-                port
-            );
+            if !json {
+                println!(
+                    "🔪 Killing {} (PID {}) on port {}",
+                    process_info.get_short_name(),
+                    process_info.pid,
+                    port
+                );
+            }
 
-            if let Err(e) = temp_monitor.kill_process(process_info.pid).await {
+            let entry = Self::kill_recorded(&mut temp_monitor, process_info).await;
+            if entry.outcome.succeeded() {
+                killed.push((*port, process_info.pid));
+            } else if !json {
                 println!(
                     "❌ Failed to kill {} (PID {}): {}",
                     process_info.get_short_name(),
                     process_info.pid,
-                    e
+                    entry.error.as_deref().unwrap_or("process is still running")
                 );
             }
+            kills.push(entry);
         }
 
-        println!("⏳ Waiting 3 seconds for processes to restart...");
-        tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+        // Restarting relies on a supervisor bringing the process back, so always watch
+        let window = match self.args.respawn_window {
+            0 => DEFAULT_RESPAWN_WINDOW,
+            secs => std::time::Duration::from_secs(secs),
+        };
+        if !json {
+            println!(
+                "⏳ Waiting up to {}s for {} port(s) to come back...",
+                window.as_secs(),
+                killed.len()
+            );
+        }
+        let respawns = watch_for_respawns(&killed, window, &self.args).await;
+        if json {
+            return self.finish_kill("restart", &kills, &respawns);
+        }
+        for respawn in &respawns {
+            Self::print_respawn(respawn);
+        }
+        let restarted: Vec<u16> = respawns.iter().map(|r| r.port).collect();
+        let missing: Vec<String> = killed
+            .iter()
+            .filter(|(port, _)| !restarted.contains(port))
+            .map(|(port, _)| port.to_string())
+            .collect();
 
-        // Check if processes have restarted
-        let new_processes = temp_monitor.scan_processes().await?;
-        if new_processes.is_empty() {
+        if respawns.is_empty() {
             println!("ℹ️  No processes detected after restart");
         } else {
             println!(
-                "✅ Detected {} processes after restart",
-                new_processes.len()
+                "✅ {}/{} ports came back after restart",
+                respawns.len(),
+                killed.len()
             );
         }
+        if !missing.is_empty() && !respawns.is_empty() {
+            println!("⚠️  Still free: {}", missing.join(", "));
+        }
 
-        Ok(())
+        self.finish_kill("restart", &kills, &respawns)
+    }
+
+    /// Remember which pid held each port so a later re-bind can be told apart
+    fn snapshot_ports(&self, ports: &[u16]) -> Vec<(u16, i32)> {
+        let (_, processes) = crate::process_monitor::get_processes_on_ports(ports, &self.args);
+        processes.iter().map(|(port, p)| (*port, p.pid)).collect()
+    }
+
+    fn print_respawn(respawn: &Respawn) {
+        println!(
+            "🔁 Port {} was re-bound {:.1}s after the kill by {} (PID {})",
+            respawn.port,
+            respawn.after_ms as f64 / 1000.0,
+            respawn.process.get_display_name(),
+            respawn.process.pid
+        );
+        match &respawn.supervisor {
+            Some(supervisor) => println!("   Restarted by {}", supervisor.describe()),
+            None => println!("   No supervisor identified"),
+        }
+    }
+
+    /// Watch freed ports for `--respawn-window` seconds. When something re-binds one,
+    /// name the supervisor that restarted it and offer to stop that instead.
+    async fn handle_respawns(&self, killed: &[(u16, i32)]) -> Result<Vec<Respawn>> {
+        if killed.is_empty() || self.args.respawn_window == 0 {
+            return Ok(Vec::new());
+        }
+        let window = std::time::Duration::from_secs(self.args.respawn_window);
+        let respawns = watch_for_respawns(killed, window, &self.args).await;
+
+        use std::io::IsTerminal;
+        let interactive = !self.args.json && std::io::stdin().is_terminal();
+        for respawn in &respawns {
+            if !self.args.json {
+                Self::print_respawn(respawn);
+            }
+            let Some(supervisor) = &respawn.supervisor else {
+                continue;
+            };
+            let stop = if self.args.stop_supervisor {
+                true
+            } else if interactive {
+                println!(
+                    "Stop {} with `{}`? y/N",
+                    supervisor.describe(),
+                    supervisor.stop_command_line()
+                );
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input)?;
+                matches!(input.trim(), "y" | "Y" | "yes" | "YES")
+            } else {
+                if !self.args.json {
+                    println!(
+                        "   Run `{}` or pass --stop-supervisor to stop it",
+                        supervisor.stop_command_line()
+                    );
+                }
+                false
+            };
+            if !stop {
+                continue;
+            }
+            match supervisor.stop() {
+                Ok(()) => {
+                    if !self.args.json {
+                        println!("🛑 Stopped {}", supervisor.describe());
+                    }
+                }
+                Err(e) => eprintln!("❌ Failed to stop {}: {}", supervisor.describe(), e),
+            }
+        }
+
+        Ok(respawns)
     }
 
     pub async fn show_process_tree(&self) -> Result<()> {
@@ -922,6 +1270,25 @@ impl ConsolePortKillApp {
         let mut temp_monitor = self.create_temp_monitor(ports_to_scan).await?;
         let processes = temp_monitor.scan_processes().await?;
 
+        if self.args.json {
+            let mut projects: std::collections::BTreeMap<String, Vec<ProcessInfo>> =
+                std::collections::BTreeMap::new();
+            for process_info in sorted_by_port(&processes) {
+                let project = process_info
+                    .project_name
+                    .clone()
+                    .unwrap_or_else(|| "Unknown".to_string());
+                projects.entry(project).or_default().push(process_info);
+            }
+            let tree: Vec<_> = projects
+                .into_iter()
+                .map(|(project, processes)| {
+                    serde_json::json!({ "project": project, "processes": processes })
+                })
+                .collect();
+            return output::print("tree", &tree);
+        }
+
         if processes.is_empty() {
             println!("ℹ️  No processes detected");
             return Ok(());
@@ -987,18 +1354,23 @@ impl ConsolePortKillApp {
             .collect::<Vec<_>>()
             .join(", ");
 
-        println!("🔄 Resetting common development ports: {}", port_list);
-        println!("This will kill all processes on these ports:");
-        for port in &reset_ports {
-            println!("  • Port {} (common dev services)", port);
+        if !self.args.json {
+            println!("🔄 Resetting common development ports: {}", port_list);
+            println!("This will kill all processes on these ports:");
+            for port in &reset_ports {
+                println!("  • Port {} (common dev services)", port);
+            }
+            println!();
         }
-        println!();
 
         // Use the existing kill_all_processes function directly with reset ports
         use crate::process_monitor::kill_all_processes;
-        kill_all_processes(&reset_ports, &self.args)?;
+        let kills = kill_all_processes(&reset_ports, &self.args)?;
+        self.finish_kill("kill", &kills, &[])?;
 
-        println!("✅ Reset complete! Development ports are now free and ready for use!");
+        if !self.args.json {
+            println!("✅ Reset complete! Development ports are now free and ready for use!");
+        }
 
         Ok(())
     }
@@ -1007,72 +1379,21 @@ impl ConsolePortKillApp {
         let monitor = self.process_monitor.lock().await;
         let history = monitor.get_history();
 
-        if history.is_empty() {
-            if self.args.json {
-                println!("[]");
-            } else {
-                println!("ℹ️  No history available. Start killing some processes to see frequent offenders!");
-            }
-            return Ok(());
-        }
-
         let offenders = history.get_frequent_offenders(2); // Show processes killed 2+ times
 
         if self.args.json {
             // Output JSON for API consumption
-            for offender in &offenders {
-                println!("{}", serde_json::to_string(offender)?);
-            }
-            return Ok(());
-        }
-
-        if offenders.is_empty() {
-            println!("✅ No frequent offenders found! All processes have been killed only once.");
-            return Ok(());
+            return output::print("offenders", &offenders);
         }
 
-        println!("🚨 Frequent Offenders (killed 2+ times):");
-        println!("{}", "─".repeat(80));
-
-        for (i, offender) in offenders.iter().enumerate() {
-            let time_span = offender
-                .last_killed
-                .signed_duration_since(offender.first_killed);
-            let time_span_str = if time_span.num_days() > 0 {
-                format!("{}d", time_span.num_days())
-            } else if time_span.num_hours() > 0 {
-                format!("{}h", time_span.num_hours())
-            } else {
-                format!("{}m", time_span.num_minutes())
-            };
-
-            println!(
-                "{}. {} on port {} (killed {} times over {})",
-                i + 1,
-                offender.process_name,
-                offender.port,
-                offender.kill_count,
-                time_span_str
-            );
-
-            if let Some(ref group) = offender.process_group {
-                println!("   Group: {}", group);
-            }
-            if let Some(ref project) = offender.project_name {
-                println!("   Project: {}", project);
-            }
-            println!(
-                "   First killed: {}",
-                offender.first_killed.format("%Y-%m-%d %H:%M")
-            );
+        if history.is_empty() {
             println!(
-                "   Last killed: {}",
-                offender.last_killed.format("%Y-%m-%d %H:%M")
+                "ℹ️  No history available. Start killing some processes to see frequent offenders!"
             );
-            println!();
+            return Ok(());
         }
 
-        println!("💡 Consider adding these to your ignore lists to avoid repeated kills!");
+        print_frequent_offenders(&offenders, 2);
 
         Ok(())
     }
@@ -1081,6 +1402,12 @@ impl ConsolePortKillApp {
         let monitor = self.process_monitor.lock().await;
         let history = monitor.get_history();
 
+        let patterns = history.get_time_patterns();
+
+        if self.args.json {
+            return output::print("patterns", &patterns);
+        }
+
         if history.is_empty() {
             println!(
                 "ℹ️  No history available. Start killing some processes to see time patterns!"
@@ -1088,40 +1415,7 @@ impl ConsolePortKillApp {
             return Ok(());
         }
 
-        let patterns = history.get_time_patterns();
-
-        println!("📊 Time Patterns Analysis:");
-        println!("{}", "─".repeat(50));
-        println!("Total kills: {}", patterns.total_kills);
-
-        if let Some(peak_hour) = patterns.peak_hour {
-            println!("Peak hour: {}:00", peak_hour);
-        }
-
-        if let Some(peak_day) = patterns.peak_day {
-            println!("Peak day: {}", peak_day);
-        }
-
-        println!();
-        println!("📈 Hour Distribution:");
-        let mut hour_entries: Vec<_> = patterns.hour_distribution.iter().collect();
-        hour_entries.sort_by_key(|(hour, _)| *hour);
-
-        for (hour, count) in hour_entries {
-            let bar_length = (*count as f32 / patterns.total_kills as f32 * 20.0) as usize;
-            let bar = "█".repeat(bar_length);
-            println!("{:2}:00 │{} {} kills", hour, bar, count);
-        }
-
-        println!();
-        println!("📅 Day Distribution:");
-        let day_names = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
-        for (day, count) in &patterns.day_distribution {
-            let day_name = day_names[*day as usize];
-            let bar_length = (*count as f32 / patterns.total_kills as f32 * 20.0) as usize;
-            let bar = "█".repeat(bar_length);
-            println!("{} │{} {} kills", day_name, bar, count);
-        }
+        print_time_patterns(&patterns);
 
         Ok(())
     }
@@ -1130,22 +1424,15 @@ impl ConsolePortKillApp {
         let monitor = self.process_monitor.lock().await;
         let history = monitor.get_history();
 
-        if history.is_empty() {
-            if self.args.json {
-                println!("null");
-            } else {
-                println!(
-                    "ℹ️  No history available. Start killing some processes to get suggestions!"
-                );
-            }
-            return Ok(());
-        }
-
         let suggestions = history.get_ignore_suggestions(2); // Suggest for processes killed 2+ times
 
         if self.args.json {
             // Output JSON for API consumption
-            println!("{}", serde_json::to_string(&suggestions)?);
+            return output::print("suggestions", &suggestions);
+        }
+
+        if history.is_empty() {
+            println!("ℹ️  No history available. Start killing some processes to get suggestions!");
             return Ok(());
         }
 
@@ -1217,52 +1504,19 @@ impl ConsolePortKillApp {
         let monitor = self.process_monitor.lock().await;
         let history = monitor.get_history();
 
-        if history.is_empty() {
-            if self.args.json {
-                println!("null");
-            } else {
-                println!(
-                    "ℹ️  No history available. Start killing some processes to see statistics!"
-                );
-            }
-            return Ok(());
-        }
-
         let stats = history.get_statistics();
 
         if self.args.json {
             // Output JSON for API consumption
-            println!("{}", serde_json::to_string(&stats)?);
-            return Ok(());
+            return output::print("stats", &stats);
         }
 
-        println!("📊 History Statistics:");
-        println!("{}", "─".repeat(50));
-        println!("Total kills: {}", stats.total_kills);
-        println!("Unique processes: {}", stats.unique_processes);
-        println!("Unique ports: {}", stats.unique_ports);
-        println!("Unique projects: {}", stats.unique_projects);
-        println!("Average kills per day: {:.1}", stats.average_kills_per_day);
-
-        if let Some((name, count)) = &stats.most_killed_process {
-            println!("Most killed process: {} ({} times)", name, count);
-        }
-
-        if let Some((port, count)) = &stats.most_killed_port {
-            println!("Most killed port: {} ({} times)", port, count);
-        }
-
-        if let Some((project, count)) = &stats.most_killed_project {
-            println!("Most killed project: {} ({} times)", project, count);
-        }
-
-        if let Some(oldest) = &stats.oldest_kill {
-            println!("Oldest kill: {}", oldest.format("%Y-%m-%d %H:%M"));
+        if history.is_empty() {
+            println!("ℹ️  No history available. Start killing some processes to see statistics!");
+            return Ok(());
         }
 
-        if let Some(newest) = &stats.newest_kill {
-            println!("Newest kill: {}", newest.format("%Y-%m-%d %H:%M"));
-        }
+        print_history_statistics(&stats);
 
         Ok(())
     }
@@ -1271,20 +1525,15 @@ impl ConsolePortKillApp {
         let monitor = self.process_monitor.lock().await;
         let history = monitor.get_history();
 
-        if history.is_empty() {
-            if self.args.json {
-                println!("null");
-            } else {
-                println!("ℹ️  No history available. Start killing some processes to get root cause analysis!");
-            }
-            return Ok(());
-        }
-
         let analysis = history.get_root_cause_analysis();
 
         if self.args.json {
             // Output JSON for API consumption
-            println!("{}", serde_json::to_string(&analysis)?);
+            return output::print("root_cause", &analysis);
+        }
+
+        if history.is_empty() {
+            println!("ℹ️  No history available. Start killing some processes to get root cause analysis!");
             return Ok(());
         }
 
@@ -1384,6 +1633,68 @@ impl ConsolePortKillApp {
         }
     }
 
+    /// Show Port Guard status from outside the daemon: its settings, the reservations on
+    /// disk and the conflicts it resolved according to the kill history
+    pub async fn show_guard_status(&self) -> Result<()> {
+        let status = {
+            let monitor = self.process_monitor.lock().await;
+            crate::port_guard::status_from_disk(
+                self.args.get_guard_ports(),
+                &self.args.get_reservation_file_path(),
+                self.args.auto_resolve,
+                monitor.get_history(),
+            )?
+        };
+
+        if self.args.json {
+            return output::print("guard_status", &status);
+        }
+
+        println!("🛡️  Port Guard Status:");
+        println!("{}", "─".repeat(50));
+        println!(
+            "Daemon: {}",
+            if status.is_active {
+                "running"
+            } else {
+                "not running"
+            }
+        );
+        println!(
+            "Watched ports: {}",
+            status
+                .watched_ports
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        println!(
+            "Auto-resolve: {}",
+            if status.auto_resolve_enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        );
+        println!("Conflicts resolved: {}", status.conflicts_resolved);
+        if let Some(last) = status.last_activity {
+            println!("Last resolved: {} ago", format_time_ago(last));
+        }
+        if status.active_reservations.is_empty() {
+            println!("Reservations: none");
+        } else {
+            println!("Reservations:");
+            for reservation in &status.active_reservations {
+                println!(
+                    "  • Port {}: {} ({})",
+                    reservation.port, reservation.project_name, reservation.process_name
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Reserve a port
     pub async fn reserve_port(
         &self,
@@ -1439,7 +1750,7 @@ impl ConsolePortKillApp {
 
         // Limit audit to only processes that are actually running
         // This prevents hanging when scanning large port ranges
-        if processes.is_empty() {
+        if processes.is_empty() && !self.args.json {
             println!("ℹ️  No processes found to audit");
             return Ok(());
         }
@@ -1459,8 +1770,7 @@ impl ConsolePortKillApp {
 
         if self.args.json {
             // Output JSON for API consumption
-            println!("{}", serde_json::to_string_pretty(&audit_result)?);
-            return Ok(());
+            return output::print("audit", &audit_result);
         }
 
         // Display audit results
@@ -1468,74 +1778,214 @@ impl ConsolePortKillApp {
         Ok(())
     }
 
-    /// Execute command on remote host via SSH
-    pub async fn execute_remote_command(&self, command: &str) -> Result<String> {
-        if let Some(remote_host) = &self.args.get_remote_host() {
-            let ssh_command = format!("ssh {} '{}'", remote_host, command);
-
-            let output = std::process::Command::new("sh")
-                .arg("-c")
-                .arg(&ssh_command)
-                .output()?;
+    /// Run in remote mode: list the listeners on every host in parallel, or kill on one
+    /// host. Each host runs its own port-kill-console over SSH, in JSON mode for scans.
+    pub async fn run_remote_mode(&self) -> Result<()> {
+        let hosts = self.args.get_remote_hosts()?;
+        let runner = RemoteRunner::new(
+            &self.args.remote_bin,
+            std::time::Duration::from_secs(self.args.remote_timeout),
+        );
 
-            if output.status.success() {
-                Ok(String::from_utf8_lossy(&output.stdout).to_string())
-            } else {
-                Err(anyhow::anyhow!(
-                    "Remote command failed: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                ))
+        if let Some(kill_args) = self.remote_kill_args() {
+            let [host] = hosts.as_slice() else {
+                return Err(anyhow::anyhow!(
+                    "Killing over SSH works on one host at a time; choose it with --remote <HOST> ({} given)",
+                    hosts.len()
+                ));
+            };
+            if self.args.safe {
+                println!("Confirm `{}` on {}? y/N", kill_args.join(" "), host);
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input)?;
+                if !matches!(input.trim(), "y" | "Y" | "yes" | "YES") {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
             }
-        } else {
-            Err(anyhow::anyhow!("No remote host specified"))
+            if self.args.json {
+                // The remote side reports its own kills; pass them on as they are
+                let mut envelope = runner.run_json(host, &kill_args).await?;
+                let data = envelope["data"].take();
+                let exit_code = ExitCode::from_code(envelope["exit_code"].as_i64().unwrap_or(1));
+                if exit_code == ExitCode::Success {
+                    return output::print("kill", &data);
+                }
+                let message = envelope["error"]["message"]
+                    .as_str()
+                    .unwrap_or("remote kill failed");
+                return Err(output::print_failed(
+                    "kill",
+                    &data,
+                    exit_code,
+                    format!("{}: {}", host, message),
+                ));
+            }
+            // The remote kill only logs, so look at the ports before and after to
+            // say what happened
+            let ports: Vec<String> = match self.args.clear {
+                Some(port) if self.args.positional_ports.is_empty() => vec![port.to_string()],
+                _ => self
+                    .args
+                    .positional_ports
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect(),
+            };
+            let port_args = vec!["--ports".to_string(), ports.join(",")];
+            if !ports.is_empty() {
+                for p in runner.scan(host, &port_args).await? {
+                    println!(
+                        "🔪 {}: killing {} (PID {})",
+                        host,
+                        p.get_display_name(),
+                        p.pid
+                    );
+                }
+            }
+            runner.run(host, &kill_args).await?;
+            if ports.is_empty() {
+                println!("✅ {}: kill sent", host);
+                return Ok(());
+            }
+            let remaining = runner.scan(host, &port_args).await?;
+            for p in &remaining {
+                println!(
+                    "⚠️  {}: port {} is still held by {} (PID {})",
+                    host, p.port, p.name, p.pid
+                );
+            }
+            if !remaining.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "{} port(s) still in use on {}",
+                    remaining.len(),
+                    host
+                ));
+            }
+            println!("✅ {}: port(s) {} free", host, ports.join(", "));
+            return Ok(());
         }
-    }
 
-    /// Run in remote mode - execute commands on remote host
-    pub async fn run_remote_mode(&self, remote_host: &str) -> Result<()> {
-        println!("🌐 Remote Mode: Connecting to {}", remote_host);
+        if !self.args.json {
+            println!("🌐 Scanning {} host(s): {}", hosts.len(), hosts.join(", "));
+        }
+        let scans = runner.scan_all(&hosts, &self.args.remote_scan_args()).await;
 
-        // Build the remote command
-        let mut remote_command = String::from("./port-kill-console");
+        let mut listeners = Vec::new();
+        let mut failures = Vec::new();
+        for scan in scans {
+            match scan.result {
+                Ok(mut processes) => {
+                    processes.sort_by_key(|p| p.port);
+                    listeners.extend(processes.into_iter().map(|process| RemoteProcess {
+                        host: scan.host.clone(),
+                        process,
+                    }));
+                }
+                Err(e) => failures.push((scan.host, e)),
+            }
+        }
 
-        // Add console mode
-        remote_command.push_str(" --console");
+        let failed = if failures.is_empty() {
+            None
+        } else {
+            let exit_code = if failures.len() == hosts.len() {
+                ExitCode::Failure
+            } else {
+                ExitCode::Partial
+            };
+            let message = format!(
+                "{} of {} host(s) could not be scanned",
+                failures.len(),
+                hosts.len()
+            );
+            Some((exit_code, message))
+        };
 
-        // Add ports if specified
-        if !self.args.get_ports_to_monitor().is_empty() {
-            let ports_str = self
-                .args
-                .get_ports_to_monitor()
+        if self.args.json {
+            let failures: Vec<_> = failures
                 .iter()
-                .map(|p| p.to_string())
-                .collect::<Vec<_>>()
-                .join(",");
-            remote_command.push_str(&format!(" --ports {}", ports_str));
+                .map(|(host, error)| serde_json::json!({ "host": host, "error": format!("{:#}", error) }))
+                .collect();
+            let data = serde_json::json!({ "listeners": listeners, "failures": failures });
+            return match failed {
+                None => output::print("remote", &data),
+                Some((exit_code, message)) => {
+                    Err(output::print_failed("remote", &data, exit_code, message))
+                }
+            };
         }
 
-        // Add other flags
-        if self.args.verbose {
-            remote_command.push_str(" --verbose");
-        }
-        if self.args.docker {
-            remote_command.push_str(" --docker");
-        }
-        if self.args.show_pid {
-            remote_command.push_str(" --show-pid");
+        if listeners.is_empty() {
+            println!("ℹ️  No processes detected");
+        } else {
+            let host_width = listeners
+                .iter()
+                .map(|l| l.host.len())
+                .max()
+                .unwrap_or(0)
+                .max(4);
+            println!(
+                "{:<host_width$}  {:>5}  {:>7}  PROCESS",
+                "HOST", "PORT", "PID"
+            );
+            for listener in &listeners {
+                let p = &listener.process;
+                println!(
+                    "{:<host_width$}  {:>5}  {:>7}  {}",
+                    listener.host,
+                    p.port,
+                    p.pid,
+                    p.get_display_name()
+                );
+            }
         }
-        if self.args.json {
-            remote_command.push_str(" --json");
+        for (host, error) in &failures {
+            eprintln!("❌ {}: {:#}", host, error);
         }
+        println!(
+            "📊 {} process(es) on {} of {} host(s)",
+            listeners.len(),
+            hosts.len() - failures.len(),
+            hosts.len()
+        );
 
-        println!("📡 Executing: {}", remote_command);
-
-        // Execute the command on remote host
-        let output = self.execute_remote_command(&remote_command).await?;
-
-        // Display the output
-        print!("{}", output);
+        match failed {
+            None => Ok(()),
+            Some((exit_code, message)) => Err(CommandError::with_code(exit_code, message)),
+        }
+    }
 
-        Ok(())
+    /// The remote invocation for a one-shot kill (positional ports, `--clear` or
+    /// `--kill`), if one was asked for
+    fn remote_kill_args(&self) -> Option<Vec<String>> {
+        let mut args: Vec<String> = if !self.args.positional_ports.is_empty() {
+            self.args
+                .positional_ports
+                .iter()
+                .map(|p| p.to_string())
+                .collect()
+        } else if let Some(port) = self.args.clear {
+            vec!["--clear".to_string(), port.to_string()]
+        } else if let Some(pid) = self.args.kill {
+            vec!["--kill".to_string(), pid.to_string()]
+        } else {
+            return None;
+        };
+        for (flag, enabled) in [
+            ("--docker", self.args.docker),
+            ("--stop-supervisor", self.args.stop_supervisor),
+            ("--force-pid", self.args.force_pid),
+        ] {
+            if enabled {
+                args.push(flag.to_string());
+            }
+        }
+        args.extend([
+            "--respawn-window".to_string(),
+            self.args.respawn_window.to_string(),
+        ]);
+        Some(args)
     }
 
     /// Display security audit results
@@ -1610,6 +2060,13 @@ impl ConsolePortKillApp {
     }
 }
 
+/// Scanned processes in port order, so JSON output is stable between runs
+fn sorted_by_port(processes: &HashMap<u16, ProcessInfo>) -> Vec<ProcessInfo> {
+    let mut sorted: Vec<ProcessInfo> = processes.values().cloned().collect();
+    sorted.sort_by_key(|p| p.port);
+    sorted
+}
+
 fn format_time_ago(time: chrono::DateTime<chrono::Utc>) -> String {
     let now = chrono::Utc::now();
     let duration = now.signed_duration_since(time);
@@ -1625,3 +2082,118 @@ fn format_time_ago(time: chrono::DateTime<chrono::Utc>) -> String {
         format!("{}d {}h", seconds / 86400, (seconds % 86400) / 3600)
     }
 }
+
+pub fn print_frequent_offenders(offenders: &[FrequentOffender], min_kills: usize) {
+    if offenders.is_empty() {
+        println!("✅ No frequent offenders found! All processes have been killed only once.");
+        return;
+    }
+
+    println!("🚨 Frequent Offenders (killed {}+ times):", min_kills);
+    println!("{}", "─".repeat(80));
+
+    for (i, offender) in offenders.iter().enumerate() {
+        let time_span = offender
+            .last_killed
+            .signed_duration_since(offender.first_killed);
+        let time_span_str = if time_span.num_days() > 0 {
+            format!("{}d", time_span.num_days())
+        } else if time_span.num_hours() > 0 {
+            format!("{}h", time_span.num_hours())
+        } else {
+            format!("{}m", time_span.num_minutes())
+        };
+
+        println!(
+            "{}. {} on port {} (killed {} times over {})",
+            i + 1,
+            offender.process_name,
+            offender.port,
+            offender.kill_count,
+            time_span_str
+        );
+
+        if let Some(ref group) = offender.process_group {
+            println!("   Group: {}", group);
+        }
+        if let Some(ref project) = offender.project_name {
+            println!("   Project: {}", project);
+        }
+        println!(
+            "   First killed: {}",
+            offender.first_killed.format("%Y-%m-%d %H:%M")
+        );
+        println!(
+            "   Last killed: {}",
+            offender.last_killed.format("%Y-%m-%d %H:%M")
+        );
+        println!();
+    }
+
+    println!("💡 Consider adding these to your ignore lists to avoid repeated kills!");
+}
+
+pub fn print_time_patterns(patterns: &TimePatterns) {
+    println!("📊 Time Patterns Analysis:");
+    println!("{}", "─".repeat(50));
+    println!("Total kills: {}", patterns.total_kills);
+
+    if let Some(peak_hour) = patterns.peak_hour {
+        println!("Peak hour: {}:00", peak_hour);
+    }
+
+    if let Some(peak_day) = patterns.peak_day {
+        println!("Peak day: {}", peak_day);
+    }
+
+    println!();
+    println!("📈 Hour Distribution:");
+    let mut hour_entries: Vec<_> = patterns.hour_distribution.iter().collect();
+    hour_entries.sort_by_key(|(hour, _)| *hour);
+
+    for (hour, count) in hour_entries {
+        let bar_length = (*count as f32 / patterns.total_kills as f32 * 20.0) as usize;
+        let bar = "█".repeat(bar_length);
+        println!("{:2}:00 │{} {} kills", hour, bar, count);
+    }
+
+    println!();
+    println!("📅 Day Distribution:");
+    let day_names = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    for (day, count) in &patterns.day_distribution {
+        let day_name = day_names[*day as usize];
+        let bar_length = (*count as f32 / patterns.total_kills as f32 * 20.0) as usize;
+        let bar = "█".repeat(bar_length);
+        println!("{} │{} {} kills", day_name, bar, count);
+    }
+}
+
+pub fn print_history_statistics(stats: &HistoryStatistics) {
+    println!("📊 History Statistics:");
+    println!("{}", "─".repeat(50));
+    println!("Total kills: {}", stats.total_kills);
+    println!("Unique processes: {}", stats.unique_processes);
+    println!("Unique ports: {}", stats.unique_ports);
+    println!("Unique projects: {}", stats.unique_projects);
+    println!("Average kills per day: {:.1}", stats.average_kills_per_day);
+
+    if let Some((name, count)) = &stats.most_killed_process {
+        println!("Most killed process: {} ({} times)", name, count);
+    }
+
+    if let Some((port, count)) = &stats.most_killed_port {
+        println!("Most killed port: {} ({} times)", port, count);
+    }
+
+    if let Some((project, count)) = &stats.most_killed_project {
+        println!("Most killed project: {} ({} times)", project, count);
+    }
+
+    if let Some(oldest) = &stats.oldest_kill {
+        println!("Oldest kill: {}", oldest.format("%Y-%m-%d %H:%M"));
+    }
+
+    if let Some(newest) = &stats.newest_kill {
+        println!("Newest kill: {}", newest.format("%Y-%m-%d %H:%M"));
+    }
+}
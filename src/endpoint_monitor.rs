@@ -1,14 +1,24 @@
 use crate::cli::Args;
+use crate::endpoint_spool::{backoff_delay, random_jitter, Spool, SpoolStats};
 use crate::process_monitor::ProcessMonitor;
 use crate::security_audit::SecurityAuditor;
+use crate::smart_filter::SmartFilter;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use tokio::time::{interval, sleep};
 
+/// Delay before the first retry within one delivery attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+/// Delay before replaying the spool after the first failed delivery attempt
+const REPLAY_BASE_DELAY: Duration = Duration::from_secs(5);
+/// Upper bound for both retry and replay backoff
+const MAX_BACKOFF_DELAY: Duration = Duration::from_secs(300);
+
 /// Data structure for endpoint monitoring payload
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EndpointPayload {
@@ -20,6 +30,9 @@ pub struct EndpointPayload {
     pub security_audit: Option<SecurityAuditData>,
     pub summary: PortSummary,
     pub custom_fields: HashMap<String, String>,
+    /// Undelivered payloads still queued when this one was built
+    #[serde(default)]
+    pub delivery_queue: Option<SpoolStats>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -59,6 +72,9 @@ pub struct EndpointMonitor {
     _timeout: Duration,
     process_monitor: ProcessMonitor,
     security_auditor: Option<SecurityAuditor>,
+    spool: Spool,
+    consecutive_failures: u32,
+    next_attempt: Option<Instant>,
 }
 
 impl EndpointMonitor {
@@ -104,7 +120,7 @@ impl EndpointMonitor {
             ports_to_scan,
             args.docker,
             args.verbose,
-            None,
+            SmartFilter::from_args(args)?,
             args.performance,
         )?;
 
@@ -126,6 +142,12 @@ impl EndpointMonitor {
             None
         };
 
+        // Undelivered payloads survive restarts and outages in the spool
+        let spool = Spool::open(
+            PathBuf::from(args.get_endpoint_spool_dir()),
+            args.endpoint_spool_max_mb.saturating_mul(1024 * 1024),
+        )?;
+
         Ok(Self {
             client,
             endpoint_url,
@@ -136,6 +158,9 @@ impl EndpointMonitor {
             _timeout: Duration::from_secs(args.endpoint_timeout),
             process_monitor,
             security_auditor,
+            spool,
+            consecutive_failures: 0,
+            next_attempt: None,
         })
     }
 
@@ -152,6 +177,16 @@ impl EndpointMonitor {
         log::info!("  - Scan interval: {}s", args.scan_interval);
         log::info!("  - Send interval: {}s", args.send_interval);
         log::info!("  - Include audit: {}", self.include_audit);
+        log::info!("  - Spool: {}", self.spool.dir().display());
+
+        let backlog = self.spool.stats();
+        if backlog.depth > 0 {
+            log::info!(
+                "Found {} undelivered payload(s) from a previous run (oldest: {})",
+                backlog.depth,
+                format_oldest(&backlog)
+            );
+        }
 
         loop {
             tokio::select! {
@@ -160,12 +195,15 @@ impl EndpointMonitor {
                     if let Err(e) = self.scan_processes().await {
                         log::warn!("Failed to scan processes: {}", e);
                     }
+                    // Replay the backlog as soon as the backoff allows
+                    self.deliver_spool().await;
                 }
                 _ = send_timer.tick() => {
-                    // Send data to endpoint at lower frequency
-                    if let Err(e) = self.send_to_endpoint().await {
-                        log::warn!("Failed to send data to endpoint: {}", e);
+                    // Queue a snapshot at lower frequency, then try to deliver
+                    if let Err(e) = self.enqueue_snapshot().await {
+                        log::warn!("Failed to queue endpoint payload: {}", e);
                     }
+                    self.deliver_spool().await;
                 }
             }
         }
@@ -177,12 +215,77 @@ impl EndpointMonitor {
         Ok(())
     }
 
-    /// Send data to the configured endpoint
-    async fn send_to_endpoint(&self) -> Result<()> {
+    /// Build a snapshot and append it to the spool
+    async fn enqueue_snapshot(&self) -> Result<()> {
         let payload = self.build_payload().await?;
+        let dropped = self.spool.push(&payload)?;
+        if dropped > 0 {
+            log::warn!(
+                "Endpoint spool is over its size cap, dropped {} oldest payload(s)",
+                dropped
+            );
+        }
+        Ok(())
+    }
+
+    /// Deliver spooled payloads oldest first, stopping at the first failure to keep order
+    async fn deliver_spool(&mut self) {
+        if self.next_attempt.is_some_and(|at| Instant::now() < at) {
+            return;
+        }
 
-        for attempt in 1..=self.retries {
-            match self.send_payload(&payload).await {
+        let mut delivered = 0;
+        for item in self.spool.pending() {
+            let payload: EndpointPayload = match self.spool.load(&item) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    log::warn!("Discarding unreadable spool item: {}", e);
+                    let _ = self.spool.remove(&item);
+                    continue;
+                }
+            };
+
+            if let Err(e) = self.send_to_endpoint(&payload).await {
+                self.consecutive_failures += 1;
+                let delay = backoff_delay(
+                    self.consecutive_failures,
+                    REPLAY_BASE_DELAY,
+                    MAX_BACKOFF_DELAY,
+                    random_jitter(),
+                );
+                self.next_attempt = Some(Instant::now() + delay);
+
+                let stats = self.spool.stats();
+                log::warn!(
+                    "{} - {} payload(s) queued, oldest undelivered {}, next attempt in {}s",
+                    e,
+                    stats.depth,
+                    format_oldest(&stats),
+                    delay.as_secs()
+                );
+                return;
+            }
+
+            if let Err(e) = self.spool.remove(&item) {
+                log::warn!("Failed to remove delivered spool item: {}", e);
+            }
+            delivered += 1;
+        }
+
+        if self.consecutive_failures > 0 {
+            log::info!(
+                "Endpoint recovered, replayed {} queued payload(s)",
+                delivered
+            );
+        }
+        self.consecutive_failures = 0;
+        self.next_attempt = None;
+    }
+
+    /// Send one payload, retrying with exponential backoff and jitter
+    async fn send_to_endpoint(&self, payload: &EndpointPayload) -> Result<()> {
+        for attempt in 1..=self.retries.max(1) {
+            match self.send_payload(payload).await {
                 Ok(_) => {
                     log::debug!("Successfully sent data to endpoint (attempt {})", attempt);
                     return Ok(());
@@ -194,7 +297,12 @@ impl EndpointMonitor {
                         e
                     );
                     if attempt < self.retries {
-                        let delay = Duration::from_secs(attempt as u64 * 2); // Exponential backoff
+                        let delay = backoff_delay(
+                            attempt,
+                            RETRY_BASE_DELAY,
+                            MAX_BACKOFF_DELAY,
+                            random_jitter(),
+                        );
                         sleep(delay).await;
                     }
                 }
@@ -203,7 +311,7 @@ impl EndpointMonitor {
 
         Err(anyhow::anyhow!(
             "Failed to send data to endpoint after {} attempts",
-            self.retries
+            self.retries.max(1)
         ))
     }
 
@@ -243,7 +351,7 @@ impl EndpointMonitor {
                     status: "occupied".to_string(),
                     process: Some(process.name.clone()),
                     pid: Some(process.pid as u32),
-                    uptime: None, // ProcessInfo doesn't have uptime field
+                    uptime: process.uptime_seconds(),
                     container: process.container_name.clone(),
                 });
             } else {
@@ -339,10 +447,18 @@ impl EndpointMonitor {
                 suspicious_ports,
             },
             custom_fields: self.custom_fields.clone(),
+            delivery_queue: Some(self.spool.stats()),
         })
     }
 }
 
+fn format_oldest(stats: &SpoolStats) -> String {
+    stats
+        .oldest_undelivered
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| "none".to_string())
+}
+
 /// Helper function to get hostname
 mod hostname {
     use std::ffi::OsString;
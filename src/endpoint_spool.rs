@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// One undelivered item waiting in the spool
+#[derive(Debug, Clone)]
+pub struct SpoolItem {
+    pub path: PathBuf,
+    pub size: u64,
+    pub queued_at: DateTime<Utc>,
+}
+
+/// Queue depth and age reported in logs and in the next payload
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpoolStats {
+    pub depth: usize,
+    pub bytes: u64,
+    pub oldest_undelivered: Option<DateTime<Utc>>,
+}
+
+/// On-disk FIFO of undelivered payloads, one JSON file per item.
+/// File names start with the zero-padded enqueue time so lexical order is delivery order.
+pub struct Spool {
+    dir: PathBuf,
+    max_bytes: u64,
+    sequence: AtomicU64,
+}
+
+impl Spool {
+    pub fn open(dir: PathBuf, max_bytes: u64) -> Result<Self> {
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create spool directory {}", dir.display()))?;
+        Ok(Self {
+            dir,
+            max_bytes,
+            sequence: AtomicU64::new(0),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Append an item, then drop the oldest items until the spool fits its size cap.
+    /// Returns how many items were dropped.
+    pub fn push<T: Serialize>(&self, item: &T) -> Result<usize> {
+        let now = Utc::now();
+        let name = format!(
+            "{:020}-{:06}.json",
+            now.timestamp_nanos_opt().unwrap_or_default(),
+            self.sequence.fetch_add(1, Ordering::Relaxed) % 1_000_000
+        );
+        let json = serde_json::to_vec(item)?;
+
+        // Write under a temporary name so a crash never leaves a truncated item in the queue
+        let tmp = self.dir.join(format!("{}.tmp", name));
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, self.dir.join(name))?;
+
+        self.enforce_cap()
+    }
+
+    /// Pending items, oldest first
+    pub fn pending(&self) -> Vec<SpoolItem> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut items: Vec<SpoolItem> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let queued_at = parse_queued_at(&name)?;
+                let size = entry.metadata().ok()?.len();
+                Some(SpoolItem {
+                    path: entry.path(),
+                    size,
+                    queued_at,
+                })
+            })
+            .collect();
+        items.sort_by(|a, b| a.path.cmp(&b.path));
+        items
+    }
+
+    pub fn load<T: DeserializeOwned>(&self, item: &SpoolItem) -> Result<T> {
+        let content = fs::read(&item.path)?;
+        serde_json::from_slice(&content)
+            .with_context(|| format!("Corrupt spool item {}", item.path.display()))
+    }
+
+    pub fn remove(&self, item: &SpoolItem) -> Result<()> {
+        match fs::remove_file(&item.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn stats(&self) -> SpoolStats {
+        let pending = self.pending();
+        SpoolStats {
+            depth: pending.len(),
+            bytes: pending.iter().map(|item| item.size).sum(),
+            oldest_undelivered: pending.first().map(|item| item.queued_at),
+        }
+    }
+
+    fn enforce_cap(&self) -> Result<usize> {
+        let pending = self.pending();
+        let mut total: u64 = pending.iter().map(|item| item.size).sum();
+        let mut dropped = 0;
+
+        // Always keep the newest item, even if it alone exceeds the cap
+        for item in &pending[..pending.len().saturating_sub(1)] {
+            if total <= self.max_bytes {
+                break;
+            }
+            self.remove(item)?;
+            total = total.saturating_sub(item.size);
+            dropped += 1;
+        }
+        Ok(dropped)
+    }
+}
+
+fn parse_queued_at(name: &str) -> Option<DateTime<Utc>> {
+    let stem = name.strip_suffix(".json")?;
+    let nanos: i64 = stem.split('-').next()?.parse().ok()?;
+    Some(DateTime::from_timestamp_nanos(nanos))
+}
+
+/// Exponential backoff for the given number of consecutive failures, with "equal jitter":
+/// the delay is uniformly spread over the upper half of the exponential step.
+/// `jitter` is a fraction in [0, 1].
+pub fn backoff_delay(failures: u32, base: Duration, max: Duration, jitter: f64) -> Duration {
+    let exponent = failures.saturating_sub(1).min(16);
+    let step = base.saturating_mul(1u32 << exponent).min(max);
+    let half = step / 2;
+    half + half.mul_f64(jitter.clamp(0.0, 1.0))
+}
+
+/// Random fraction in [0, 1] for backoff jitter
+pub fn random_jitter() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(Utc::now().timestamp_nanos_opt().unwrap_or_default() as u128);
+    (hasher.finish() as f64) / (u64::MAX as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+    use serde_json::json;
+
+    fn scratch_spool(name: &str, max_bytes: u64) -> Spool {
+        Spool::open(scratch_dir("spool", name), max_bytes).unwrap()
+    }
+
+    #[test]
+    fn test_spool_replays_in_order_and_enforces_cap() {
+        let spool = scratch_spool("order", 1024 * 1024);
+        for i in 0..3 {
+            spool.push(&json!({ "seq": i })).unwrap();
+        }
+
+        let stats = spool.stats();
+        assert_eq!(stats.depth, 3);
+        let pending = spool.pending();
+        assert_eq!(stats.oldest_undelivered, Some(pending[0].queued_at));
+        let order: Vec<i64> = pending
+            .iter()
+            .map(|item| {
+                spool.load::<serde_json::Value>(item).unwrap()["seq"]
+                    .as_i64()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(order, vec![0, 1, 2]);
+
+        spool.remove(&pending[0]).unwrap();
+        assert_eq!(spool.stats().depth, 2);
+        let _ = fs::remove_dir_all(spool.dir());
+
+        // Each item is 9 bytes, so a 25 byte cap keeps the two newest
+        let spool = scratch_spool("cap", 25);
+        spool.push(&json!({ "seq": 0 })).unwrap();
+        spool.push(&json!({ "seq": 1 })).unwrap();
+        assert_eq!(spool.push(&json!({ "seq": 2 })).unwrap(), 1);
+        let first = spool
+            .load::<serde_json::Value>(&spool.pending()[0])
+            .unwrap();
+        assert_eq!(first["seq"], 1);
+        let _ = fs::remove_dir_all(spool.dir());
+    }
+
+    #[test]
+    fn test_backoff_grows_exponentially_with_bounded_jitter() {
+        let base = Duration::from_secs(2);
+        let max = Duration::from_secs(60);
+
+        assert_eq!(backoff_delay(1, base, max, 1.0), Duration::from_secs(2));
+        assert_eq!(backoff_delay(1, base, max, 0.0), Duration::from_secs(1));
+        assert_eq!(backoff_delay(3, base, max, 1.0), Duration::from_secs(8));
+        assert_eq!(backoff_delay(3, base, max, 0.0), Duration::from_secs(4));
+        assert_eq!(backoff_delay(30, base, max, 1.0), max);
+
+        let jitter = random_jitter();
+        assert!((0.0..=1.0).contains(&jitter));
+    }
+}
@@ -0,0 +1,611 @@
+use crate::cli::Args;
+use crate::endpoint_spool::{backoff_delay, random_jitter};
+use crate::port_guard::PortGuardDaemon;
+use crate::process_monitor::ProcessMonitor;
+use crate::security_audit::SecurityAuditor;
+use crate::smart_filter::SmartFilter;
+use crate::types::{PortConflict, ProcessHistory, ProcessHistoryEntry, ProcessInfo};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{interval, sleep};
+
+/// Header carrying `sha256=<hex HMAC of "<timestamp>.<body>">` when a sink has a secret
+pub const SIGNATURE_HEADER: &str = "X-PortKill-Signature";
+/// Unix time the request was signed at, so receivers can reject replays
+pub const TIMESTAMP_HEADER: &str = "X-PortKill-Timestamp";
+pub const EVENT_HEADER: &str = "X-PortKill-Event";
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    PortOccupied,
+    PortFreed,
+    OwnerChanged,
+    ProcessKilled,
+    GuardConflict,
+    AuditFinding,
+}
+
+impl EventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::PortOccupied => "port_occupied",
+            EventType::PortFreed => "port_freed",
+            EventType::OwnerChanged => "owner_changed",
+            EventType::ProcessKilled => "process_killed",
+            EventType::GuardConflict => "guard_conflict",
+            EventType::AuditFinding => "audit_finding",
+        }
+    }
+}
+
+/// A single change observed between scans
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortEvent {
+    /// Idempotency key, derived from what happened and when it was seen, so retries carry
+    /// the same key but a later, identical-looking change does not
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    pub timestamp: DateTime<Utc>,
+    pub port: Option<u16>,
+    /// Owner after the change (or the killed / suspicious process)
+    pub process: Option<ProcessInfo>,
+    /// Owner before the change
+    pub previous: Option<ProcessInfo>,
+    #[serde(default)]
+    pub details: serde_json::Value,
+}
+
+impl PortEvent {
+    fn new(
+        event_type: EventType,
+        identity: &str,
+        port: Option<u16>,
+        process: Option<ProcessInfo>,
+        previous: Option<ProcessInfo>,
+    ) -> Self {
+        let timestamp = Utc::now();
+        Self {
+            id: event_id(event_type, identity, timestamp),
+            event_type,
+            timestamp,
+            port,
+            process,
+            previous,
+            details: serde_json::Value::Null,
+        }
+    }
+
+    fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Idempotency key for an event type, the facts identifying the event and when it was seen
+pub fn event_id(event_type: EventType, identity: &str, seen_at: DateTime<Utc>) -> String {
+    let digest = Sha256::digest(
+        format!(
+            "{}|{}|{}",
+            event_type.as_str(),
+            identity,
+            seen_at.timestamp_nanos_opt().unwrap_or_default()
+        )
+        .as_bytes(),
+    );
+    to_hex(&digest[..16])
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Identity of a process incarnation; start time distinguishes reused PIDs
+fn process_identity(process: &ProcessInfo) -> String {
+    format!(
+        "{}:{}",
+        process.pid,
+        process
+            .start_time
+            .map(|t| t.timestamp().to_string())
+            .unwrap_or_default()
+    )
+}
+
+/// Compare two consecutive scans and describe what changed
+pub fn diff_scans(
+    previous: &HashMap<u16, ProcessInfo>,
+    current: &HashMap<u16, ProcessInfo>,
+) -> Vec<PortEvent> {
+    let mut events = Vec::new();
+
+    let mut ports: Vec<u16> = previous.keys().chain(current.keys()).copied().collect();
+    ports.sort_unstable();
+    ports.dedup();
+
+    for port in ports {
+        match (previous.get(&port), current.get(&port)) {
+            (None, Some(now)) => events.push(PortEvent::new(
+                EventType::PortOccupied,
+                &format!("{}|{}", port, process_identity(now)),
+                Some(port),
+                Some(now.clone()),
+                None,
+            )),
+            (Some(before), None) => events.push(PortEvent::new(
+                EventType::PortFreed,
+                &format!("{}|{}", port, process_identity(before)),
+                Some(port),
+                None,
+                Some(before.clone()),
+            )),
+            (Some(before), Some(now)) if before.pid != now.pid => events.push(PortEvent::new(
+                EventType::OwnerChanged,
+                &format!(
+                    "{}|{}|{}",
+                    port,
+                    process_identity(before),
+                    process_identity(now)
+                ),
+                Some(port),
+                Some(now.clone()),
+                Some(before.clone()),
+            )),
+            _ => {}
+        }
+    }
+
+    events
+}
+
+fn killed_event(entry: &ProcessHistoryEntry) -> PortEvent {
+    PortEvent::new(
+        EventType::ProcessKilled,
+        &format!(
+            "{}|{}|{}",
+            entry.port,
+            entry.pid,
+            entry.killed_at.timestamp_millis()
+        ),
+        Some(entry.port),
+        None,
+        None,
+    )
+    .with_details(serde_json::to_value(entry).unwrap_or_default())
+}
+
+fn conflict_event(conflict: &PortConflict) -> PortEvent {
+    PortEvent::new(
+        EventType::GuardConflict,
+        &format!(
+            "{}|{}|{}",
+            conflict.port,
+            process_identity(&conflict.existing_process),
+            process_identity(&conflict.new_process)
+        ),
+        Some(conflict.port),
+        Some(conflict.new_process.clone()),
+        Some(conflict.existing_process.clone()),
+    )
+    .with_details(serde_json::json!({
+        "conflict_type": conflict.conflict_type,
+        "resolution": conflict.resolution,
+    }))
+}
+
+/// Compute the `sha256=<hex>` signature of a body sent at `timestamp` (Unix seconds).
+/// The timestamp is signed too, so an old request can't be replayed with a fresh one.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", to_hex(&mac.finalize().into_bytes()))
+}
+
+/// One webhook sink as configured in the webhooks file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookSinkConfig {
+    pub url: String,
+    /// Shared secret used to sign payloads
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Environment variable holding the secret, so it can stay out of the file
+    #[serde(default)]
+    pub secret_env: Option<String>,
+    /// Event types to deliver (empty means all)
+    #[serde(default)]
+    pub events: Vec<EventType>,
+    /// Ports to deliver events for (empty means all)
+    #[serde(default)]
+    pub ports: Vec<u16>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WebhooksFile {
+    #[serde(default)]
+    pub sinks: Vec<WebhookSinkConfig>,
+}
+
+impl WebhookSinkConfig {
+    pub fn accepts(&self, event: &PortEvent) -> bool {
+        let type_ok = self.events.is_empty() || self.events.contains(&event.event_type);
+        let port_ok =
+            self.ports.is_empty() || event.port.is_some_and(|port| self.ports.contains(&port));
+        type_ok && port_ok
+    }
+
+    fn resolved_secret(&self) -> Option<String> {
+        self.secret_env
+            .as_ref()
+            .and_then(|name| std::env::var(name).ok())
+            .or_else(|| self.secret.clone())
+    }
+}
+
+/// Read sink definitions from a webhooks file
+pub fn load_webhook_sinks(path: &Path) -> Result<Vec<WebhookSinkConfig>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read webhooks file {}", path.display()))?;
+    let file: WebhooksFile = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid webhooks file {}", path.display()))?;
+    Ok(file.sinks)
+}
+
+/// Event mode: diffs consecutive scans and posts typed events to webhook sinks
+pub struct EventMonitor {
+    client: Client,
+    sinks: Vec<WebhookSinkConfig>,
+    retries: u32,
+    process_monitor: ProcessMonitor,
+    security_auditor: Option<SecurityAuditor>,
+    port_guard: Option<Arc<PortGuardDaemon>>,
+    last_scan: Option<HashMap<u16, ProcessInfo>>,
+    last_kill_at: Option<DateTime<Utc>>,
+    reported_findings: HashSet<String>,
+}
+
+impl EventMonitor {
+    pub fn new(args: &Args, port_guard: Option<Arc<PortGuardDaemon>>) -> Result<Self> {
+        let mut sinks: Vec<WebhookSinkConfig> = args
+            .webhook
+            .iter()
+            .map(|url| WebhookSinkConfig {
+                url: url.clone(),
+                secret: args.webhook_secret.clone(),
+                secret_env: None,
+                events: Vec::new(),
+                ports: Vec::new(),
+            })
+            .collect();
+        if let Some(path) = args.get_webhook_config_path() {
+            sinks.extend(load_webhook_sinks(Path::new(&path))?);
+        }
+        if sinks.is_empty() {
+            return Err(anyhow::anyhow!(
+                "At least one webhook sink is required for event mode"
+            ));
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(args.endpoint_timeout))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
+        let process_monitor = ProcessMonitor::new_with_performance(
+            update_sender,
+            args.get_ports_to_monitor(),
+            args.docker,
+            args.verbose,
+            SmartFilter::from_args(args)?,
+            args.performance,
+        )?;
+
+        // Only audit when some sink actually wants findings
+        let wants_audit = sinks
+            .iter()
+            .any(|sink| sink.events.is_empty() || sink.events.contains(&EventType::AuditFinding));
+        let security_auditor = wants_audit.then(|| {
+            SecurityAuditor::new(
+                args.get_suspicious_ports(),
+                args.baseline_file.clone(),
+                args.suspicious_only,
+            )
+        });
+
+        // Kills that happened before we started are history, not events
+        let last_kill_at =
+            ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), usize::MAX)
+                .ok()
+                .and_then(|history| {
+                    history
+                        .get_recent_entries(history.len())
+                        .iter()
+                        .map(|entry| entry.killed_at)
+                        .max()
+                });
+
+        Ok(Self {
+            client,
+            sinks,
+            retries: args.endpoint_retries,
+            process_monitor,
+            security_auditor,
+            port_guard,
+            last_scan: None,
+            last_kill_at,
+            reported_findings: HashSet::new(),
+        })
+    }
+
+    pub fn sink_count(&self) -> usize {
+        self.sinks.len()
+    }
+
+    pub async fn run(&mut self, args: &Args) -> Result<()> {
+        let mut scan_timer = interval(Duration::from_secs(args.scan_interval.max(1)));
+
+        log::info!("Starting event monitor:");
+        log::info!("  - Sinks: {}", self.sinks.len());
+        log::info!("  - Scan interval: {}s", args.scan_interval);
+
+        loop {
+            scan_timer.tick().await;
+            match self.collect_events().await {
+                Ok(events) => {
+                    for event in &events {
+                        self.dispatch(event).await;
+                    }
+                }
+                Err(e) => log::warn!("Failed to collect events: {}", e),
+            }
+        }
+    }
+
+    async fn collect_events(&mut self) -> Result<Vec<PortEvent>> {
+        let current = self.process_monitor.scan_processes().await?;
+        let mut events = Vec::new();
+
+        // The first scan is the baseline, so existing listeners aren't reported as new
+        if let Some(previous) = &self.last_scan {
+            events.extend(diff_scans(previous, &current));
+        }
+
+        events.extend(self.new_kills());
+
+        if let Some(guard) = &self.port_guard {
+            events.extend(guard.drain_conflicts().await.iter().map(conflict_event));
+        }
+
+        if let Some(auditor) = &self.security_auditor {
+            match auditor.perform_audit(current.clone()).await {
+                Ok(audit) => {
+                    for finding in audit.suspicious_processes {
+                        let identity = format!(
+                            "{}|{}|{:?}",
+                            finding.port,
+                            process_identity(&finding.process_info),
+                            finding.suspicion_reason
+                        );
+                        if !self.reported_findings.insert(identity.clone()) {
+                            continue;
+                        }
+                        events.push(
+                            PortEvent::new(
+                                EventType::AuditFinding,
+                                &identity,
+                                Some(finding.port),
+                                Some(finding.process_info.clone()),
+                                None,
+                            )
+                            .with_details(serde_json::json!({
+                                "reason": finding.suspicion_reason,
+                                "risk_level": finding.risk_level,
+                            })),
+                        );
+                    }
+                }
+                Err(e) => log::warn!("Security audit failed: {}", e),
+            }
+        }
+
+        self.last_scan = Some(current);
+        Ok(events)
+    }
+
+    /// Kill history entries recorded since the last check, from any port-kill invocation
+    fn new_kills(&mut self) -> Vec<PortEvent> {
+        let Ok(history) =
+            ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), usize::MAX)
+        else {
+            return Vec::new();
+        };
+
+        let entries: Vec<&ProcessHistoryEntry> = history
+            .get_recent_entries(history.len())
+            .iter()
+            .filter(|entry| self.last_kill_at.is_none_or(|last| entry.killed_at > last))
+            .collect();
+        if let Some(newest) = entries.iter().map(|entry| entry.killed_at).max() {
+            self.last_kill_at = Some(newest);
+        }
+        entries.into_iter().map(killed_event).collect()
+    }
+
+    async fn dispatch(&self, event: &PortEvent) {
+        let body = match serde_json::to_vec(event) {
+            Ok(body) => body,
+            Err(e) => {
+                log::warn!(
+                    "Failed to serialize {} event: {}",
+                    event.event_type.as_str(),
+                    e
+                );
+                return;
+            }
+        };
+
+        for sink in self.sinks.iter().filter(|sink| sink.accepts(event)) {
+            if let Err(e) = self.deliver(sink, event, &body).await {
+                log::warn!(
+                    "Failed to deliver {} event {} to {}: {}",
+                    event.event_type.as_str(),
+                    event.id,
+                    sink.url,
+                    e
+                );
+            }
+        }
+    }
+
+    async fn deliver(
+        &self,
+        sink: &WebhookSinkConfig,
+        event: &PortEvent,
+        body: &[u8],
+    ) -> Result<()> {
+        let secret = sink.resolved_secret();
+
+        for attempt in 1..=self.retries.max(1) {
+            let mut request = self
+                .client
+                .post(&sink.url)
+                .header("Content-Type", "application/json")
+                .header(EVENT_HEADER, event.event_type.as_str())
+                .header(IDEMPOTENCY_HEADER, &event.id)
+                .body(body.to_vec());
+            if let Some(secret) = &secret {
+                let timestamp = Utc::now().timestamp();
+                request = request
+                    .header(TIMESTAMP_HEADER, timestamp.to_string())
+                    .header(SIGNATURE_HEADER, sign_payload(secret, timestamp, body));
+            }
+
+            let result = match request.send().await {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response) => Err(anyhow::anyhow!("status {}", response.status())),
+                Err(e) => Err(e.into()),
+            };
+
+            if attempt < self.retries {
+                log::debug!(
+                    "Webhook delivery attempt {} failed: {:?}",
+                    attempt,
+                    result.err()
+                );
+                sleep(backoff_delay(
+                    attempt,
+                    RETRY_BASE_DELAY,
+                    MAX_RETRY_DELAY,
+                    random_jitter(),
+                ))
+                .await;
+            } else {
+                return result;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_scans_emits_typed_events_with_stable_ids() {
+        let previous = HashMap::from([
+            (3000, ProcessInfo::for_test(10, 3000, "node")),
+            (8080, ProcessInfo::for_test(20, 8080, "node")),
+        ]);
+        let current = HashMap::from([
+            (3000, ProcessInfo::for_test(11, 3000, "node")),
+            (5432, ProcessInfo::for_test(30, 5432, "node")),
+        ]);
+
+        let events = diff_scans(&previous, &current);
+        let kinds: Vec<(EventType, Option<u16>)> =
+            events.iter().map(|e| (e.event_type, e.port)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (EventType::OwnerChanged, Some(3000)),
+                (EventType::PortOccupied, Some(5432)),
+                (EventType::PortFreed, Some(8080)),
+            ]
+        );
+        assert_eq!(events[0].previous.as_ref().unwrap().pid, 10);
+
+        // Keys are stable for one event, but the same change seen later is a new event
+        let seen_at = events[1].timestamp;
+        let identity = format!(
+            "5432|{}",
+            process_identity(&ProcessInfo::for_test(30, 5432, "node"))
+        );
+        assert_eq!(
+            events[1].id,
+            event_id(EventType::PortOccupied, &identity, seen_at)
+        );
+        assert_ne!(
+            events[1].id,
+            event_id(
+                EventType::PortOccupied,
+                &identity,
+                seen_at + chrono::Duration::seconds(1)
+            )
+        );
+        assert_ne!(events[0].id, events[1].id);
+        assert!(diff_scans(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_sink_filters_and_signature() {
+        let sink: WebhookSinkConfig = serde_json::from_str(
+            r#"{"url": "http://localhost/hook", "events": ["port_freed"], "ports": [3000]}"#,
+        )
+        .unwrap();
+        let freed = diff_scans(
+            &HashMap::from([(3000, ProcessInfo::for_test(1, 3000, "node"))]),
+            &HashMap::new(),
+        );
+        let other_port = diff_scans(
+            &HashMap::from([(4000, ProcessInfo::for_test(1, 4000, "node"))]),
+            &HashMap::new(),
+        );
+        let occupied = diff_scans(
+            &HashMap::new(),
+            &HashMap::from([(3000, ProcessInfo::for_test(1, 3000, "node"))]),
+        );
+        assert!(sink.accepts(&freed[0]));
+        assert!(!sink.accepts(&other_port[0]));
+        assert!(!sink.accepts(&occupied[0]));
+
+        // HMAC-SHA256 of "<timestamp>.<body>"; a different timestamp changes the signature
+        assert_eq!(
+            sign_payload("Jefe", 1_700_000_000, b"what do ya want for nothing?"),
+            "sha256=1cdd0650c8be1cb0974b1788d458b1e781206cfef59b85faafc582d2e182c57e"
+        );
+        assert_ne!(
+            sign_payload("Jefe", 1_700_000_001, b"what do ya want for nothing?"),
+            sign_payload("Jefe", 1_700_000_000, b"what do ya want for nothing?")
+        );
+    }
+}
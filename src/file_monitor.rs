@@ -59,6 +59,25 @@ impl FileMonitor {
         }
     }
 
+    /// Find all processes holding files (or their working directory) anywhere under a directory
+    pub fn find_processes_in_directory(&self, dir: &str) -> Result<Vec<ProcessInfo>> {
+        let dir = Path::new(dir);
+
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            self.find_processes_with_pattern_windows(&dir.to_string_lossy())
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        {
+            self.find_processes_in_directory_unix(dir)
+        }
+    }
+
     /// Get file information for a process
     pub fn get_process_files(&self, pid: u32) -> Result<Vec<String>> {
         #[cfg(target_os = "windows")]
@@ -137,6 +156,18 @@ impl FileMonitor {
         self.parse_lsof_output_with_pattern(&output_str, pattern)
     }
 
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    fn find_processes_in_directory_unix(&self, dir: &Path) -> Result<Vec<ProcessInfo>> {
+        // +D recurses through the whole tree; lsof exits non-zero when nothing is open
+        let output = Command::new("lsof").arg("+D").arg(dir).output()?;
+
+        let mut processes = self.parse_lsof_output(&String::from_utf8_lossy(&output.stdout))?;
+        // lsof prints one line per open file
+        let mut seen = std::collections::HashSet::new();
+        processes.retain(|p| seen.insert(p.pid));
+        Ok(processes)
+    }
+
     #[cfg(any(target_os = "linux", target_os = "macos"))]
     fn get_process_files_unix(&self, pid: u32) -> Result<Vec<String>> {
         // Use lsof to get files for a specific process
@@ -179,6 +210,9 @@ impl FileMonitor {
                             cpu_usage: None,
                             memory_usage: None,
                             memory_percentage: None,
+                            start_time: crate::system_monitor::process_start_time(pid),
+                            systemd_unit: crate::system_monitor::systemd_unit(pid),
+                            framework: None,
                         });
                     }
                 }
@@ -267,6 +301,9 @@ impl FileMonitor {
                         cpu_usage: None,
                         memory_usage: None,
                         memory_percentage: None,
+                        start_time: crate::system_monitor::process_start_time(pid),
+                        systemd_unit: crate::system_monitor::systemd_unit(pid),
+                        framework: None,
                     });
                 }
             }
@@ -303,6 +340,9 @@ impl FileMonitor {
                             cpu_usage: None,
                             memory_usage: None,
                             memory_percentage: None,
+                            start_time: crate::system_monitor::process_start_time(pid),
+                            systemd_unit: crate::system_monitor::systemd_unit(pid),
+                            framework: None,
                         });
                     }
                 }
@@ -340,6 +380,9 @@ impl FileMonitor {
                             cpu_usage: None,
                             memory_usage: None,
                             memory_percentage: None,
+                            start_time: crate::system_monitor::process_start_time(pid),
+                            systemd_unit: crate::system_monitor::systemd_unit(pid),
+                            framework: None,
                         });
                     }
                 }
@@ -395,6 +438,9 @@ impl FileMonitor {
                         cpu_usage: None,
                         memory_usage: None,
                         memory_percentage: None,
+                        start_time: crate::system_monitor::process_start_time(pid),
+                        systemd_unit: crate::system_monitor::systemd_unit(pid),
+                        framework: None,
                     });
                 }
             }
@@ -414,6 +460,36 @@ mod tests {
         assert!(true); // Basic creation test
     }
 
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    fn test_find_processes_in_directory() {
+        let dir = std::env::temp_dir().join(format!("port-kill-fm-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        let file = dir.join("nested/held.log");
+        std::fs::write(&file, "x").unwrap();
+
+        let mut child = match Command::new("tail").arg("-f").arg(&file).spawn() {
+            Ok(child) => child,
+            Err(_) => return,
+        };
+        std::thread::sleep(std::time::Duration::from_millis(300));
+
+        let monitor = FileMonitor::new();
+        let result = monitor.find_processes_in_directory(&dir.to_string_lossy());
+        let _ = child.kill();
+        let _ = child.wait();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let processes = result.expect("holder lookup failed");
+        assert_eq!(
+            processes
+                .iter()
+                .filter(|p| p.pid == child.id() as i32)
+                .count(),
+            1
+        );
+    }
+
     #[test]
     fn test_find_processes_with_nonexistent_file() {
         let monitor = FileMonitor::new();
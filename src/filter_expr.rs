@@ -0,0 +1,861 @@
+use crate::types::ProcessInfo;
+use regex::{Regex, RegexBuilder};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// A `--where` expression, e.g.
+/// `name == node and project == api and memory > 500MB`
+///
+/// ```text
+/// expr       := or
+/// or         := and (("or" | "||") and)*
+/// and        := unary (("and" | "&&") unary)*
+/// unary      := ("not" | "!") unary | "(" expr ")" | comparison
+/// comparison := field op value | field "in" (range | "[" item ("," item)* "]")
+/// op         := == != < <= > >= ~ !~
+/// ```
+pub struct FilterExpr {
+    source: String,
+    expr: Expr,
+    /// Command line, user and bind address per (pid, port); looking them up spawns a
+    /// process. Pruned by `forget_gone` after each scan
+    lookups: Mutex<HashMap<(i32, u16, Field), Vec<String>>>,
+}
+
+/// A parse error and the character column (0-based) it points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub source: String,
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "invalid --where expression at column {}: {}",
+            self.position + 1,
+            self.message
+        )?;
+        writeln!(f, "  {}", self.source)?;
+        write!(f, "  {}^", " ".repeat(self.position))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Field {
+    Port,
+    Pid,
+    Name,
+    Command,
+    Group,
+    Project,
+    Framework,
+    Container,
+    Cpu,
+    Memory,
+    User,
+    Bind,
+}
+
+const FIELDS: &[(&str, Field)] = &[
+    ("port", Field::Port),
+    ("pid", Field::Pid),
+    ("name", Field::Name),
+    ("command", Field::Command),
+    ("group", Field::Group),
+    ("project", Field::Project),
+    ("framework", Field::Framework),
+    ("container", Field::Container),
+    ("cpu", Field::Cpu),
+    ("memory", Field::Memory),
+    ("user", Field::User),
+    ("bind", Field::Bind),
+];
+
+impl Field {
+    fn is_numeric(self) -> bool {
+        matches!(self, Field::Port | Field::Pid | Field::Cpu | Field::Memory)
+    }
+
+    fn name(self) -> &'static str {
+        FIELDS
+            .iter()
+            .find(|(_, field)| *field == self)
+            .map(|(name, _)| *name)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+    NotMatch,
+    In,
+}
+
+#[derive(Debug, Clone)]
+enum Item {
+    Number(f64),
+    Range(f64, f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Items(Vec<Item>),
+    Pattern(Regex),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Field, Op, Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(word) => format!("'{}'", word),
+            Token::Quoted(text) => format!("\"{}\"", text),
+            Token::Op(op) => format!("'{}'", op_symbol(*op)),
+            Token::And => "'and'".to_string(),
+            Token::Or => "'or'".to_string(),
+            Token::Not => "'not'".to_string(),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::LBracket => "'['".to_string(),
+            Token::RBracket => "']'".to_string(),
+            Token::Comma => "','".to_string(),
+        }
+    }
+}
+
+fn op_symbol(op: Op) -> &'static str {
+    match op {
+        Op::Eq => "==",
+        Op::Ne => "!=",
+        Op::Lt => "<",
+        Op::Le => "<=",
+        Op::Gt => ">",
+        Op::Ge => ">=",
+        Op::Match => "~",
+        Op::NotMatch => "!~",
+        Op::In => "in",
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || "_.-/:*@%+".contains(c)
+}
+
+/// Split an expression into tokens, each with the column it starts at
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, (usize, String)> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let next = chars.get(i + 1).copied();
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            ',' => Token::Comma,
+            '~' => Token::Op(Op::Match),
+            '=' if next == Some('=') => {
+                i += 1;
+                Token::Op(Op::Eq)
+            }
+            '=' => Token::Op(Op::Eq),
+            '!' if next == Some('=') => {
+                i += 1;
+                Token::Op(Op::Ne)
+            }
+            '!' if next == Some('~') => {
+                i += 1;
+                Token::Op(Op::NotMatch)
+            }
+            '!' => Token::Not,
+            '<' | '>' => {
+                let or_equal = next == Some('=');
+                if or_equal {
+                    i += 1;
+                }
+                Token::Op(match (c, or_equal) {
+                    ('<', false) => Op::Lt,
+                    ('<', true) => Op::Le,
+                    ('>', false) => Op::Gt,
+                    _ => Op::Ge,
+                })
+            }
+            '&' if next == Some('&') => {
+                i += 1;
+                Token::And
+            }
+            '|' if next == Some('|') => {
+                i += 1;
+                Token::Or
+            }
+            '"' | '\'' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err((start, "unterminated string".to_string())),
+                        Some(&q) if q == c => break,
+                        Some('\\') if chars.get(i + 1) == Some(&c) => {
+                            text.push(c);
+                            i += 2;
+                        }
+                        Some(&ch) => {
+                            text.push(ch);
+                            i += 1;
+                        }
+                    }
+                }
+                Token::Quoted(text)
+            }
+            c if is_word_char(c) => {
+                while i + 1 < chars.len() && is_word_char(chars[i + 1]) {
+                    i += 1;
+                }
+                let word: String = chars[start..=i].iter().collect();
+                match word.to_ascii_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "in" => Token::Op(Op::In),
+                    _ => Token::Word(word),
+                }
+            }
+            other => return Err((start, format!("unexpected character '{}'", other))),
+        };
+        tokens.push((start, token));
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    /// Column just past the end of the input, for "expected ... at end" errors
+    end: usize,
+}
+
+type ParseResult<T> = Result<T, (usize, String)>;
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|(column, _)| *column)
+            .unwrap_or(self.end)
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn found(&self) -> String {
+        self.peek()
+            .map(Token::describe)
+            .unwrap_or_else(|| "the end of the expression".to_string())
+    }
+
+    fn parse_or(&mut self) -> ParseResult<Expr> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> ParseResult<Expr> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = Expr::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> ParseResult<Expr> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::LParen) => {
+                let open = self.column();
+                self.pos += 1;
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some((_, Token::RParen)) => Ok(inner),
+                    _ => {
+                        self.pos -= 1;
+                        Err((
+                            self.column(),
+                            format!(
+                                "expected ')' to close the '(' at column {}, found {}",
+                                open + 1,
+                                self.found()
+                            ),
+                        ))
+                    }
+                }
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> ParseResult<Expr> {
+        let column = self.column();
+        let field = match self.next() {
+            Some((_, Token::Word(word))) => {
+                let lower = word.to_ascii_lowercase();
+                FIELDS
+                    .iter()
+                    .find(|(name, _)| *name == lower)
+                    .map(|(_, field)| *field)
+                    .ok_or_else(|| (column, unknown_field(&word)))?
+            }
+            _ => {
+                self.pos -= 1;
+                return Err((
+                    column,
+                    format!("expected a field name, found {}", self.found()),
+                ));
+            }
+        };
+
+        let op_column = self.column();
+        let op = match self.next() {
+            Some((_, Token::Op(op))) => op,
+            _ => {
+                self.pos -= 1;
+                return Err((
+                    op_column,
+                    format!(
+                        "expected an operator (==, !=, <, <=, >, >=, ~, !~, in) after '{}', found {}",
+                        field.name(),
+                        self.found()
+                    ),
+                ));
+            }
+        };
+        match op {
+            Op::Lt | Op::Le | Op::Gt | Op::Ge if !field.is_numeric() => {
+                return Err((
+                    op_column,
+                    format!(
+                        "'{}' compares numbers, but {} is text",
+                        op_symbol(op),
+                        field.name()
+                    ),
+                ));
+            }
+            Op::Match | Op::NotMatch if field.is_numeric() => {
+                return Err((
+                    op_column,
+                    format!(
+                        "'{}' matches text, but {} is a number",
+                        op_symbol(op),
+                        field.name()
+                    ),
+                ));
+            }
+            _ => {}
+        }
+
+        let value_column = self.column();
+        let operand = match (op, self.next()) {
+            (Op::Match | Op::NotMatch, Some((_, Token::Word(text) | Token::Quoted(text)))) => {
+                let regex = RegexBuilder::new(&text)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| (value_column, format!("invalid regex: {}", e)))?;
+                Operand::Pattern(regex)
+            }
+            (Op::In, Some((_, Token::LBracket))) => {
+                let mut items = Vec::new();
+                loop {
+                    let item_column = self.column();
+                    match self.next() {
+                        Some((_, Token::Word(text) | Token::Quoted(text))) => {
+                            items.push(parse_item(field, &text).map_err(|e| (item_column, e))?)
+                        }
+                        _ => {
+                            self.pos -= 1;
+                            return Err((
+                                item_column,
+                                format!("expected a value in the list, found {}", self.found()),
+                            ));
+                        }
+                    }
+                    let separator_column = self.column();
+                    match self.next() {
+                        Some((_, Token::Comma)) => continue,
+                        Some((_, Token::RBracket)) => break,
+                        _ => {
+                            self.pos -= 1;
+                            return Err((
+                                separator_column,
+                                format!("expected ',' or ']', found {}", self.found()),
+                            ));
+                        }
+                    }
+                }
+                Operand::Items(items)
+            }
+            (_, Some((_, Token::Word(text) | Token::Quoted(text)))) => Operand::Items(vec![
+                parse_item(field, &text).map_err(|e| (value_column, e))?,
+            ]),
+            _ => {
+                self.pos -= 1;
+                return Err((
+                    value_column,
+                    format!(
+                        "expected a value after '{}', found {}",
+                        op_symbol(op),
+                        self.found()
+                    ),
+                ));
+            }
+        };
+        Ok(Expr::Compare(field, op, operand))
+    }
+}
+
+fn unknown_field(word: &str) -> String {
+    let names: Vec<&str> = FIELDS.iter().map(|(name, _)| *name).collect();
+    format!(
+        "unknown field '{}'; expected one of {}",
+        word,
+        names.join(", ")
+    )
+}
+
+/// Parse a literal for `field`: numbers for numeric fields (ports and pids may be
+/// ranges like `3000..3999` or `3000-3999`; memory takes KB/MB/GB), text otherwise
+fn parse_item(field: Field, text: &str) -> Result<Item, String> {
+    if !field.is_numeric() {
+        return Ok(Item::Text(text.to_string()));
+    }
+    if matches!(field, Field::Port | Field::Pid) {
+        let range = text.split_once("..").or_else(|| text.split_once('-'));
+        if let Some((low, high)) = range {
+            let low = parse_number(field, low)?;
+            let high = parse_number(field, high)?;
+            if low > high {
+                return Err(format!("range {} is empty: {} > {}", text, low, high));
+            }
+            return Ok(Item::Range(low, high));
+        }
+    }
+    parse_number(field, text).map(Item::Number)
+}
+
+fn parse_number(field: Field, text: &str) -> Result<f64, String> {
+    let lower = text.trim().to_ascii_lowercase();
+    let split = lower
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(lower.len());
+    let (digits, unit) = lower.split_at(split);
+    let number: f64 = digits
+        .parse()
+        .map_err(|_| format!("expected a number for {}, found '{}'", field.name(), text))?;
+    let scale = match (field, unit) {
+        (_, "") => 1.0,
+        (Field::Cpu, "%") => 1.0,
+        (Field::Memory, "b") => 1.0,
+        (Field::Memory, "k" | "kb" | "kib") => 1024.0,
+        (Field::Memory, "m" | "mb" | "mib") => 1024.0 * 1024.0,
+        (Field::Memory, "g" | "gb" | "gib") => 1024.0 * 1024.0 * 1024.0,
+        (Field::Memory, _) => {
+            return Err(format!(
+                "unknown memory unit '{}'; use B, KB, MB or GB",
+                unit
+            ))
+        }
+        _ => {
+            return Err(format!(
+                "expected a number for {}, found '{}'",
+                field.name(),
+                text
+            ))
+        }
+    };
+    Ok(number * scale)
+}
+
+impl FilterExpr {
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let error = |(position, message): (usize, String)| ParseError {
+            source: source.to_string(),
+            position,
+            message,
+        };
+        let tokens = tokenize(source).map_err(error)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: source.chars().count(),
+        };
+        if parser.peek().is_none() {
+            return Err(error((0, "the expression is empty".to_string())));
+        }
+        let expr = parser.parse_or().map_err(error)?;
+        if parser.peek().is_some() {
+            return Err(error((
+                parser.column(),
+                format!(
+                    "expected 'and', 'or' or the end of the expression, found {}",
+                    parser.found()
+                ),
+            )));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+            lookups: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the expression reads CPU or memory, which are only collected with
+    /// performance metrics on
+    pub fn uses_metrics(&self) -> bool {
+        fn walk(expr: &Expr) -> bool {
+            match expr {
+                Expr::Or(a, b) | Expr::And(a, b) => walk(a) || walk(b),
+                Expr::Not(inner) => walk(inner),
+                Expr::Compare(field, _, _) => matches!(field, Field::Cpu | Field::Memory),
+            }
+        }
+        walk(&self.expr)
+    }
+
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        self.eval(&self.expr, process)
+    }
+
+    fn eval(&self, expr: &Expr, process: &ProcessInfo) -> bool {
+        match expr {
+            Expr::Or(a, b) => self.eval(a, process) || self.eval(b, process),
+            Expr::And(a, b) => self.eval(a, process) && self.eval(b, process),
+            Expr::Not(inner) => !self.eval(inner, process),
+            Expr::Compare(field, op, operand) => {
+                if field.is_numeric() {
+                    let Some(value) = numeric_value(*field, process) else {
+                        return false;
+                    };
+                    compare_number(value, *op, operand)
+                } else {
+                    let values = self.text_values(*field, process);
+                    // A process without the field (no project, no container) never matches
+                    !values.is_empty()
+                        && match op {
+                            Op::Ne | Op::NotMatch => {
+                                values.iter().all(|v| compare_text(v, *op, operand))
+                            }
+                            _ => values.iter().any(|v| compare_text(v, *op, operand)),
+                        }
+                }
+            }
+        }
+    }
+
+    /// Drop looked-up values for processes that are no longer listening
+    pub fn forget_gone(&self, processes: &HashMap<u16, ProcessInfo>) {
+        let mut lookups = self.lookups.lock().unwrap_or_else(|e| e.into_inner());
+        lookups.retain(|(pid, port, _), _| {
+            processes
+                .get(port)
+                .is_some_and(|process| process.pid == *pid)
+        });
+    }
+
+    fn text_values(&self, field: Field, process: &ProcessInfo) -> Vec<String> {
+        let single = |value: Option<&String>| value.cloned().into_iter().collect();
+        match field {
+            Field::Name => vec![process.name.clone()],
+            Field::Command if process.command_line.is_some() => {
+                process.command_line.clone().into_iter().collect()
+            }
+            Field::Group => single(process.process_group.as_ref()),
+            Field::Project => single(process.project_name.as_ref()),
+            Field::Framework => single(process.framework.as_ref()),
+            Field::Container => process
+                .container_name
+                .iter()
+                .chain(process.container_id.iter())
+                .cloned()
+                .collect(),
+            Field::Command | Field::User | Field::Bind => {
+                let mut lookups = self.lookups.lock().unwrap_or_else(|e| e.into_inner());
+                lookups
+                    .entry((process.pid, process.port, field))
+                    .or_insert_with(|| match field {
+                        // Quick scans only record the executable name
+                        Field::Command => vec![crate::supervisor::command_line(process.pid)
+                            .unwrap_or_else(|| process.command.clone())],
+                        Field::User => process_user(process.pid).into_iter().collect(),
+                        _ => bind_addresses(process.pid, process.port),
+                    })
+                    .clone()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Debug for FilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FilterExpr").field(&self.source).finish()
+    }
+}
+
+fn numeric_value(field: Field, process: &ProcessInfo) -> Option<f64> {
+    match field {
+        Field::Port => Some(process.port as f64),
+        Field::Pid => Some(process.pid as f64),
+        Field::Cpu => process.cpu_usage,
+        Field::Memory => process.memory_usage.map(|bytes| bytes as f64),
+        _ => None,
+    }
+}
+
+fn compare_number(value: f64, op: Op, operand: &Operand) -> bool {
+    let Operand::Items(items) = operand else {
+        return false;
+    };
+    let hit = |item: &Item| match item {
+        Item::Number(n) => value == *n,
+        Item::Range(low, high) => (*low..=*high).contains(&value),
+        Item::Text(_) => false,
+    };
+    match (op, items.first()) {
+        (Op::Eq | Op::In, _) => items.iter().any(hit),
+        (Op::Ne, _) => !items.iter().any(hit),
+        (Op::Lt, Some(Item::Number(n))) => value < *n,
+        (Op::Le, Some(Item::Number(n))) => value <= *n,
+        (Op::Gt, Some(Item::Number(n))) => value > *n,
+        (Op::Ge, Some(Item::Number(n))) => value >= *n,
+        (Op::Lt, Some(Item::Range(low, _))) => value < *low,
+        (Op::Le, Some(Item::Range(_, high))) => value <= *high,
+        (Op::Gt, Some(Item::Range(_, high))) => value > *high,
+        (Op::Ge, Some(Item::Range(low, _))) => value >= *low,
+        _ => false,
+    }
+}
+
+fn compare_text(value: &str, op: Op, operand: &Operand) -> bool {
+    match operand {
+        Operand::Pattern(regex) => regex.is_match(value) == (op == Op::Match),
+        Operand::Items(items) => {
+            let hit = items
+                .iter()
+                .any(|item| matches!(item, Item::Text(text) if text.eq_ignore_ascii_case(value)));
+            if op == Op::Ne {
+                !hit
+            } else {
+                hit
+            }
+        }
+    }
+}
+
+/// The user a process runs as
+fn process_user(pid: i32) -> Option<String> {
+    if cfg!(target_os = "windows") {
+        return None;
+    }
+    let output = std::process::Command::new("ps")
+        .args(["-o", "user=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let user = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!user.is_empty()).then_some(user)
+}
+
+/// The addresses a process listens on for `port`; `*` is reported as 0.0.0.0
+fn bind_addresses(pid: i32, port: u16) -> Vec<String> {
+    if cfg!(target_os = "windows") {
+        return Vec::new();
+    }
+    let Ok(output) = std::process::Command::new("lsof")
+        .args([
+            "-nP",
+            "-a",
+            "-p",
+            &pid.to_string(),
+            &format!("-iTCP:{}", port),
+            "-sTCP:LISTEN",
+            "-Fn",
+        ])
+        .output()
+    else {
+        return Vec::new();
+    };
+    parse_lsof_names(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// `n127.0.0.1:3000`, `n*:3000` and `n[::1]:3000` lines from `lsof -Fn`
+fn parse_lsof_names(output: &str) -> Vec<String> {
+    let mut addresses: Vec<String> = output
+        .lines()
+        .filter_map(|line| line.strip_prefix('n'))
+        .filter_map(|name| name.rsplit_once(':').map(|(address, _)| address))
+        .map(
+            |address| match address.trim_matches(|c| c == '[' || c == ']') {
+                "*" => "0.0.0.0".to_string(),
+                other => other.to_string(),
+            },
+        )
+        .collect();
+    addresses.dedup();
+    addresses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(name: &str, port: u16) -> ProcessInfo {
+        ProcessInfo {
+            command_line: Some(format!("{} server.js --port {}", name, port)),
+            process_group: Some("Node.js".to_string()),
+            project_name: Some("api".to_string()),
+            cpu_usage: Some(12.5),
+            memory_usage: Some(700 * 1024 * 1024),
+            ..ProcessInfo::for_test(4242, port, name)
+        }
+    }
+
+    #[test]
+    fn test_evaluates_expressions() {
+        let node = process("node", 3001);
+        let cases = [
+            ("name == node and project == api and memory > 500MB", true),
+            ("name == NODE && memory > 1GB", false),
+            ("port in 3000..3999 and not group == Database", true),
+            ("port in [80, 443, 8000-8100]", false),
+            ("command ~ 'server\\.js' or cpu >= 50%", true),
+            ("(name != node or port == 3001) and container == web", false),
+            ("!(cpu < 10) && pid >= 4000", true),
+            ("command !~ vite", true),
+            ("project in [web, 'API']", true),
+        ];
+        for (source, expected) in cases {
+            let expr = FilterExpr::parse(source).unwrap();
+            assert_eq!(expr.matches(&node), expected, "{}", source);
+        }
+        assert!(FilterExpr::parse("memory > 1gb").unwrap().uses_metrics());
+        assert!(!FilterExpr::parse("port == 3000").unwrap().uses_metrics());
+
+        assert_eq!(
+            parse_lsof_names("p12\nf20\nn*:3000\nf21\nn[::1]:3000\n"),
+            vec!["0.0.0.0".to_string(), "::1".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_forgets_lookups_for_processes_that_are_gone() {
+        let expr = FilterExpr::parse("command ~ node").unwrap();
+        let listener = ProcessInfo::for_test(i32::MAX, 3000, "node");
+        assert!(expr.matches(&listener));
+
+        let mut processes = HashMap::from([(3000, listener)]);
+        expr.forget_gone(&processes);
+        assert_eq!(expr.lookups.lock().unwrap().len(), 1);
+
+        processes.insert(3000, ProcessInfo::for_test(4242, 3000, "node"));
+        expr.forget_gone(&processes);
+        assert!(expr.lookups.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reports_parse_errors_with_positions() {
+        let cases = [
+            (
+                "name == node and memory >",
+                25,
+                "expected a value after '>'",
+            ),
+            ("nmae == node", 0, "unknown field 'nmae'"),
+            ("name > 3", 5, "'>' compares numbers, but name is text"),
+            ("memory > 5 parsecs", 11, "expected 'and', 'or'"),
+            ("memory > 5XB", 9, "unknown memory unit 'xb'"),
+            (
+                "(port == 1 or port == 2",
+                23,
+                "expected ')' to close the '(' at column 1",
+            ),
+            ("name ~ '(unclosed'", 7, "invalid regex"),
+            ("port in [3000, ]", 15, "expected a value in the list"),
+            ("name == \"node", 8, "unterminated string"),
+            ("port == 3000 $ 1", 13, "unexpected character '$'"),
+        ];
+        for (source, position, message) in cases {
+            let error = FilterExpr::parse(source).unwrap_err();
+            assert_eq!(error.position, position, "{}: {}", source, error.message);
+            assert!(
+                error.message.contains(message),
+                "{}: {}",
+                source,
+                error.message
+            );
+        }
+
+        let rendered = FilterExpr::parse("port >= x").unwrap_err().to_string();
+        assert_eq!(
+            rendered,
+            "invalid --where expression at column 9: expected a number for port, found 'x'\n  port >= x\n          ^"
+        );
+    }
+}
@@ -0,0 +1,372 @@
+use crate::cli::{HistoryArgs, HistoryFormat, HistoryReport};
+use crate::console_app::{print_frequent_offenders, print_history_statistics, print_time_patterns};
+use crate::output::{self, CommandError};
+use crate::types::{
+    format_duration_short, parse_duration_secs, ProcessHistory, ProcessHistoryEntry,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+/// Which history entries a `history` query keeps
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub ports: Vec<u16>,
+    pub name: Option<String>,
+    pub group: Option<String>,
+    pub project: Option<String>,
+    pub killed_by: Option<String>,
+}
+
+impl HistoryFilter {
+    pub fn from_args(args: &HistoryArgs, now: DateTime<Utc>) -> Result<Self> {
+        let bound = |flag: &str, value: &Option<String>| -> Result<Option<DateTime<Utc>>> {
+            let Some(value) = value.as_deref() else {
+                return Ok(None);
+            };
+            parse_time_bound(value, now).map(Some).ok_or_else(|| {
+                anyhow!(
+                    "invalid {} '{}': use RFC 3339, YYYY-MM-DD[ HH:MM] or an age like 2h or 7d",
+                    flag,
+                    value
+                )
+            })
+        };
+        let filter = Self {
+            since: bound("--since", &args.since)?,
+            until: bound("--until", &args.until)?,
+            ports: args.port.clone(),
+            name: args.name.clone(),
+            group: args.group.clone(),
+            project: args.project.clone(),
+            killed_by: args.by.clone(),
+        };
+        if let (Some(since), Some(until)) = (filter.since, filter.until) {
+            if since >= until {
+                return Err(anyhow!("--since must be earlier than --until"));
+            }
+        }
+        Ok(filter)
+    }
+
+    pub fn matches(&self, entry: &ProcessHistoryEntry) -> bool {
+        let same = |wanted: &Option<String>, actual: Option<&String>| {
+            wanted
+                .as_ref()
+                .is_none_or(|w| actual.is_some_and(|a| a.eq_ignore_ascii_case(w)))
+        };
+        self.since.is_none_or(|since| entry.killed_at >= since)
+            && self.until.is_none_or(|until| entry.killed_at < until)
+            && (self.ports.is_empty() || self.ports.contains(&entry.port))
+            && self.name.as_ref().is_none_or(|name| {
+                entry
+                    .process_name
+                    .to_lowercase()
+                    .contains(&name.to_lowercase())
+            })
+            && same(&self.group, entry.process_group.as_ref())
+            && same(&self.project, entry.project_name.as_ref())
+            && self.killed_by.as_ref().is_none_or(|by| {
+                by.eq_ignore_ascii_case(&entry.killed_by)
+                    || by.eq_ignore_ascii_case(entry.actor.as_str())
+            })
+    }
+}
+
+/// Accepts RFC 3339, a local date or date and time, or an age such as `90m` / `7d` before `now`
+pub fn parse_time_bound(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Some(secs) = parse_duration_secs(value) {
+        return Some(now - Duration::seconds(secs as i64));
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_row(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Run `port-kill history`: load the whole log, filter it and print the chosen report
+pub fn run(args: &HistoryArgs) -> Result<()> {
+    let filter = HistoryFilter::from_args(args, Utc::now()).map_err(CommandError::usage)?;
+    let history =
+        ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), usize::MAX)
+            .map_err(|e| anyhow!("failed to read history: {}", e))?;
+    let history = history.filtered(|entry| filter.matches(entry));
+
+    match args.report {
+        HistoryReport::Entries => {
+            let entries = history.get_recent_entries(args.limit.unwrap_or(usize::MAX));
+            print_entries(entries, args.format)
+        }
+        HistoryReport::Stats => {
+            let stats = history.get_statistics();
+            match args.format {
+                HistoryFormat::Table => print_history_statistics(&stats),
+                HistoryFormat::Json => output::print("stats", &stats)?,
+                HistoryFormat::Csv => {
+                    println!("metric,value");
+                    let mut rows = vec![
+                        ("total_kills".to_string(), stats.total_kills.to_string()),
+                        (
+                            "unique_processes".to_string(),
+                            stats.unique_processes.to_string(),
+                        ),
+                        ("unique_ports".to_string(), stats.unique_ports.to_string()),
+                        (
+                            "unique_projects".to_string(),
+                            stats.unique_projects.to_string(),
+                        ),
+                        (
+                            "average_kills_per_day".to_string(),
+                            format!("{:.2}", stats.average_kills_per_day),
+                        ),
+                    ];
+                    if let Some(oldest) = stats.oldest_kill {
+                        rows.push(("oldest_kill".to_string(), oldest.to_rfc3339()));
+                    }
+                    if let Some(newest) = stats.newest_kill {
+                        rows.push(("newest_kill".to_string(), newest.to_rfc3339()));
+                    }
+                    for (name, count) in &stats.top_processes {
+                        rows.push((format!("process:{}", name), count.to_string()));
+                    }
+                    for (port, count) in &stats.top_ports {
+                        rows.push((format!("port:{}", port), count.to_string()));
+                    }
+                    for (project, count) in &stats.top_projects {
+                        rows.push((format!("project:{}", project), count.to_string()));
+                    }
+                    for (metric, value) in rows {
+                        println!("{}", csv_row(&[metric, value]));
+                    }
+                }
+            }
+            Ok(())
+        }
+        HistoryReport::Offenders => {
+            let offenders = history.get_frequent_offenders(args.min_kills);
+            match args.format {
+                HistoryFormat::Table => print_frequent_offenders(&offenders, args.min_kills),
+                HistoryFormat::Json => output::print("offenders", &offenders)?,
+                HistoryFormat::Csv => {
+                    println!("process_name,port,kill_count,first_killed,last_killed,process_group,project_name");
+                    for offender in &offenders {
+                        println!(
+                            "{}",
+                            csv_row(&[
+                                offender.process_name.clone(),
+                                offender.port.to_string(),
+                                offender.kill_count.to_string(),
+                                offender.first_killed.to_rfc3339(),
+                                offender.last_killed.to_rfc3339(),
+                                offender.process_group.clone().unwrap_or_default(),
+                                offender.project_name.clone().unwrap_or_default(),
+                            ])
+                        );
+                    }
+                }
+            }
+            Ok(())
+        }
+        HistoryReport::Patterns => {
+            let patterns = history.get_time_patterns();
+            match args.format {
+                HistoryFormat::Table => {
+                    if history.is_empty() {
+                        println!("ℹ️  No history entries match the given filters");
+                    } else {
+                        print_time_patterns(&patterns);
+                    }
+                }
+                HistoryFormat::Json => output::print("patterns", &patterns)?,
+                HistoryFormat::Csv => {
+                    println!("dimension,bucket,kills");
+                    let mut hours: Vec<_> = patterns.hour_distribution.iter().collect();
+                    hours.sort();
+                    for (hour, count) in hours {
+                        println!("hour,{},{}", hour, count);
+                    }
+                    let mut days: Vec<_> = patterns.day_distribution.iter().collect();
+                    days.sort_by_key(|(day, _)| day.num_days_from_monday());
+                    for (day, count) in days {
+                        println!("day,{},{}", day, count);
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_entries(entries: &[ProcessHistoryEntry], format: HistoryFormat) -> Result<()> {
+    match format {
+        HistoryFormat::Json => output::print("history", &entries)?,
+        HistoryFormat::Csv => {
+            println!("killed_at,port,pid,process_name,process_group,project_name,killed_by,actor,user,tty,outcome,signal,attempts,lifetime_secs,error,command_line,working_directory");
+            for entry in entries {
+                println!(
+                    "{}",
+                    csv_row(&[
+                        entry.killed_at.to_rfc3339(),
+                        entry.port.to_string(),
+                        entry.pid.to_string(),
+                        entry.process_name.clone(),
+                        entry.process_group.clone().unwrap_or_default(),
+                        entry.project_name.clone().unwrap_or_default(),
+                        entry.killed_by.clone(),
+                        entry.actor.as_str().to_string(),
+                        entry.user.clone().unwrap_or_default(),
+                        entry.tty.clone().unwrap_or_default(),
+                        entry.outcome.as_str().to_string(),
+                        entry.signal.clone().unwrap_or_default(),
+                        entry.attempts.to_string(),
+                        entry
+                            .lifetime_secs
+                            .map(|secs| secs.to_string())
+                            .unwrap_or_default(),
+                        entry.error.clone().unwrap_or_default(),
+                        entry.command_line.clone().unwrap_or_default(),
+                        entry.working_directory.clone().unwrap_or_default(),
+                    ])
+                );
+            }
+        }
+        HistoryFormat::Table => {
+            if entries.is_empty() {
+                println!("ℹ️  No history entries match the given filters");
+                return Ok(());
+            }
+            println!(
+                "{:<17} {:>6} {:>8}  {:<20} {:<16} {:<14} {:>7}  BY",
+                "KILLED AT", "PORT", "PID", "PROCESS", "PROJECT", "OUTCOME", "LIVED"
+            );
+            println!("{}", "─".repeat(104));
+            for entry in entries {
+                println!(
+                    "{:<17} {:>6} {:>8}  {:<20} {:<16} {:<14} {:>7}  {}/{}",
+                    entry
+                        .killed_at
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M"),
+                    entry.port,
+                    entry.pid,
+                    entry.process_name,
+                    entry.project_name.as_deref().unwrap_or("-"),
+                    entry.outcome.as_str(),
+                    entry
+                        .lifetime_secs
+                        .map(format_duration_short)
+                        .unwrap_or_else(|| "-".to_string()),
+                    entry.actor.as_str(),
+                    entry.killed_by
+                );
+            }
+            println!("{}", "─".repeat(104));
+            println!("{} entries", entries.len());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{KillActor, KillOutcome};
+
+    fn entry(port: u16, name: &str, hours_ago: i64, killed_by: &str) -> ProcessHistoryEntry {
+        ProcessHistoryEntry {
+            pid: 100,
+            port,
+            process_name: name.to_string(),
+            process_group: Some("Node.js".to_string()),
+            project_name: Some("web".to_string()),
+            killed_at: Utc::now() - Duration::hours(hours_ago),
+            killed_by: killed_by.to_string(),
+            command_line: None,
+            working_directory: None,
+            outcome: KillOutcome::Terminated,
+            signal: Some("SIGTERM".to_string()),
+            attempts: 1,
+            error: None,
+            lifetime_secs: None,
+            actor: KillActor::Cli,
+            user: None,
+            tty: None,
+        }
+    }
+
+    #[test]
+    fn test_filter_combines_time_port_name_and_actor() {
+        let now = Utc::now();
+        let filter = HistoryFilter {
+            since: parse_time_bound("24h", now),
+            ports: vec![3000, 3001],
+            name: Some("NODE".to_string()),
+            killed_by: Some("user".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&entry(3000, "node", 2, "user")));
+        assert!(!filter.matches(&entry(3000, "node", 48, "user")));
+        assert!(!filter.matches(&entry(8080, "node", 2, "user")));
+        assert!(!filter.matches(&entry(3001, "python", 2, "user")));
+        assert!(!filter.matches(&entry(3001, "node", 2, "guard")));
+
+        let mut history = ProcessHistory::new(100);
+        history.add_entry(entry(3000, "node", 2, "user"));
+        history.add_entry(entry(3000, "node", 3, "user"));
+        history.add_entry(entry(8080, "node", 1, "user"));
+        let subset = history.filtered(|e| filter.matches(e));
+        assert_eq!(subset.get_statistics().total_kills, 2);
+        assert_eq!(subset.get_frequent_offenders(2).len(), 1);
+    }
+
+    #[test]
+    fn test_time_bounds_and_csv_quoting() {
+        let now = DateTime::parse_from_rfc3339("2026-10-18T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_time_bound("2h", now), Some(now - Duration::hours(2)));
+        assert_eq!(
+            parse_time_bound("2026-10-01T08:30:00+02:00", now).map(|t| t.to_rfc3339()),
+            Some("2026-10-01T06:30:00+00:00".to_string())
+        );
+        assert!(parse_time_bound("2026-10-01", now).is_some());
+        assert!(parse_time_bound("last tuesday", now).is_none());
+
+        assert_eq!(csv_field("node"), "node");
+        assert_eq!(
+            csv_field("npm run dev, --port 3000"),
+            "\"npm run dev, --port 3000\""
+        );
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
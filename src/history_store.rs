@@ -0,0 +1,406 @@
+use crate::types::{KillActor, ProcessHistoryEntry};
+use chrono::{Duration, Utc};
+use fs2::FileExt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Where the kill history lives and when its segments rotate
+#[derive(Debug, Clone)]
+pub struct HistoryConfig {
+    pub path: PathBuf,
+    /// Rotate once the active segment reaches this size
+    pub max_bytes: u64,
+    /// Rotate once the oldest entry in the active segment is older than this
+    pub max_age_days: Option<u64>,
+    /// Rotated segments kept next to the active one (`history.jsonl.1` is the newest)
+    pub max_segments: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            path: default_history_path(),
+            max_bytes: 5 * 1024 * 1024,
+            max_age_days: Some(30),
+            max_segments: 5,
+        }
+    }
+}
+
+pub fn default_history_path() -> PathBuf {
+    if let Ok(path) = std::env::var("PORT_KILL_HISTORY_FILE") {
+        return PathBuf::from(path);
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".port-kill/history.jsonl")
+}
+
+/// The JSON array file written by versions before the append-only log
+pub fn legacy_history_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".port-kill-history.json")
+}
+
+static CONFIG: OnceLock<HistoryConfig> = OnceLock::new();
+
+/// Set the process-wide history settings; only the first call takes effect
+pub fn configure(config: HistoryConfig) {
+    let _ = CONFIG.set(config);
+}
+
+pub fn config() -> HistoryConfig {
+    CONFIG.get().cloned().unwrap_or_default()
+}
+
+static ACTOR: OnceLock<KillActor> = OnceLock::new();
+
+/// Record kills made by this process as coming from `actor`; only the first call takes effect
+pub fn set_actor(actor: KillActor) {
+    let _ = ACTOR.set(actor);
+}
+
+/// The actor for this process. `PORT_KILL_ACTOR` wins so wrappers such as the
+/// dashboard can attribute the console binary's kills to themselves.
+pub fn actor() -> KillActor {
+    std::env::var("PORT_KILL_ACTOR")
+        .ok()
+        .and_then(|value| KillActor::parse(&value))
+        .or_else(|| ACTOR.get().copied())
+        .unwrap_or(KillActor::Cli)
+}
+
+/// Contexts such as "guard" or "alert" name their actor; anything else is this process's actor
+pub fn actor_for(killed_by: &str) -> KillActor {
+    match KillActor::parse(killed_by) {
+        Some(KillActor::Cli) | None => actor(),
+        Some(actor) => actor,
+    }
+}
+
+pub fn invoking_user() -> Option<String> {
+    ["USER", "USERNAME", "LOGNAME"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|v| !v.is_empty()))
+}
+
+/// The controlling terminal, when the kill came from an interactive session
+pub fn invoking_tty() -> Option<String> {
+    use std::io::IsTerminal;
+    if !std::io::stdin().is_terminal() {
+        return None;
+    }
+    ["/proc/self/fd/0", "/dev/fd/0"]
+        .iter()
+        .find_map(|link| fs::read_link(link).ok())
+        .map(|path| path.display().to_string())
+        .filter(|path| path.starts_with("/dev/"))
+        .or_else(|| std::env::var("SSH_TTY").ok())
+}
+
+/// Append-only JSON Lines history shared by every port-kill process.
+/// Writers take an exclusive lock on a sidecar lock file, so rotation can rename
+/// segments without invalidating anyone's lock.
+pub struct HistoryStore {
+    config: HistoryConfig,
+    legacy_path: Option<PathBuf>,
+}
+
+impl HistoryStore {
+    pub fn new(config: HistoryConfig) -> Self {
+        Self {
+            config,
+            legacy_path: None,
+        }
+    }
+
+    /// Store at `path` with the configured rotation settings
+    pub fn open(path: &Path) -> Self {
+        let mut config = config();
+        // Entries from the old whole-file format are migrated only into the default location
+        let legacy_path = (path == config.path).then(legacy_history_path);
+        config.path = path.to_path_buf();
+        Self {
+            config,
+            legacy_path,
+        }
+    }
+
+    fn lock(&self, exclusive: bool) -> io::Result<File> {
+        if let Some(parent) = self.config.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let lock_path = PathBuf::from(format!("{}.lock", self.config.path.display()));
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path)?;
+        if exclusive {
+            file.lock_exclusive()?;
+        } else {
+            file.lock_shared()?;
+        }
+        Ok(file)
+    }
+
+    fn segment_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.config.path.clone()
+        } else {
+            PathBuf::from(format!("{}.{}", self.config.path.display(), index))
+        }
+    }
+
+    /// Append entries as one write while holding the lock, rotating first if needed
+    pub fn append(&self, entries: &[ProcessHistoryEntry]) -> io::Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        self.migrate_legacy()?;
+        let lock = self.lock(true)?;
+        self.rotate_if_needed()?;
+
+        let buffer = encode(entries)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.config.path)?;
+        file.write_all(&buffer)?;
+        file.sync_data()?;
+
+        drop(lock);
+        Ok(())
+    }
+
+    fn rotate_if_needed(&self) -> io::Result<()> {
+        let Ok(metadata) = fs::metadata(&self.config.path) else {
+            return Ok(());
+        };
+        let too_big = metadata.len() >= self.config.max_bytes;
+        let too_old = self.config.max_age_days.is_some_and(|days| {
+            first_entry(&self.config.path)
+                .is_some_and(|first| Utc::now() - first.killed_at > Duration::days(days as i64))
+        });
+        if !too_big && !too_old {
+            return Ok(());
+        }
+
+        if self.config.max_segments == 0 {
+            return fs::remove_file(&self.config.path);
+        }
+
+        // Shift every segment one slot older, dropping the one that falls off the end
+        let _ = fs::remove_file(self.segment_path(self.config.max_segments));
+        for index in (0..self.config.max_segments).rev() {
+            let from = self.segment_path(index);
+            if from.exists() {
+                fs::rename(&from, self.segment_path(index + 1))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Every entry across all segments, oldest first
+    pub fn load(&self) -> io::Result<Vec<ProcessHistoryEntry>> {
+        self.migrate_legacy()?;
+        let lock = self.lock(false)?;
+
+        let mut entries = Vec::new();
+        for index in (0..=self.config.max_segments).rev() {
+            entries.extend(read_segment(&self.segment_path(index)));
+        }
+        drop(lock);
+        Ok(entries)
+    }
+
+    /// Move the entries of the old whole-file history into the log, once. They go in front
+    /// of the oldest segment while holding the lock, and the old file is renamed aside so
+    /// no later load merges it again. A file that doesn't parse is left where it is.
+    fn migrate_legacy(&self) -> io::Result<()> {
+        let Some(legacy) = self.legacy_path.as_ref().filter(|path| path.exists()) else {
+            return Ok(());
+        };
+        let lock = self.lock(true)?;
+        // Another process may have migrated it while we waited for the lock
+        let Ok(content) = fs::read_to_string(legacy) else {
+            return Ok(());
+        };
+        let mut entries: Vec<ProcessHistoryEntry> = match serde_json::from_str(&content) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!("Not migrating {}: {}", legacy.display(), e);
+                return Ok(());
+            }
+        };
+        entries.sort_by_key(|entry| entry.killed_at);
+
+        let oldest = (0..=self.config.max_segments)
+            .rev()
+            .map(|index| self.segment_path(index))
+            .find(|path| path.exists())
+            .unwrap_or_else(|| self.config.path.clone());
+        let mut buffer = encode(&entries)?;
+        match fs::read(&oldest) {
+            Ok(existing) => buffer.extend(existing),
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            Err(_) => {}
+        }
+        let staging = PathBuf::from(format!("{}.migrating", oldest.display()));
+        fs::write(&staging, &buffer)?;
+        fs::rename(&staging, &oldest)?;
+        fs::rename(legacy, format!("{}.migrated", legacy.display()))?;
+
+        drop(lock);
+        Ok(())
+    }
+
+    /// Remove all segments, and the legacy file if it was never migrated
+    pub fn clear(&self) -> io::Result<()> {
+        let lock = self.lock(true)?;
+        for index in 0..=self.config.max_segments {
+            match fs::remove_file(self.segment_path(index)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        if let Some(legacy) = &self.legacy_path {
+            let _ = fs::remove_file(legacy);
+        }
+        drop(lock);
+        Ok(())
+    }
+}
+
+fn encode(entries: &[ProcessHistoryEntry]) -> io::Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    for entry in entries {
+        serde_json::to_writer(&mut buffer, entry)?;
+        buffer.push(b'\n');
+    }
+    Ok(buffer)
+}
+
+fn first_entry(path: &Path) -> Option<ProcessHistoryEntry> {
+    let file = File::open(path).ok()?;
+    let line = BufReader::new(file).lines().next()?.ok()?;
+    serde_json::from_str(&line).ok()
+}
+
+/// Parse one segment, skipping lines that don't parse (e.g. a write cut short by a crash)
+fn read_segment(path: &Path) -> Vec<ProcessHistoryEntry> {
+    let Ok(file) = File::open(path) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+    use crate::types::KillOutcome;
+
+    fn entry(port: u16, age_days: i64) -> ProcessHistoryEntry {
+        ProcessHistoryEntry {
+            pid: port as i32,
+            port,
+            process_name: "node".to_string(),
+            process_group: None,
+            project_name: None,
+            killed_at: Utc::now() - Duration::days(age_days),
+            killed_by: "user".to_string(),
+            command_line: None,
+            working_directory: None,
+            outcome: KillOutcome::Terminated,
+            signal: Some("SIGTERM".to_string()),
+            attempts: 1,
+            error: None,
+            lifetime_secs: None,
+            actor: KillActor::Cli,
+            user: None,
+            tty: None,
+        }
+    }
+
+    fn scratch_store(name: &str, max_bytes: u64, max_segments: usize) -> HistoryStore {
+        HistoryStore::new(HistoryConfig {
+            path: scratch_dir("history", name).join("history.jsonl"),
+            max_bytes,
+            max_age_days: Some(7),
+            max_segments,
+        })
+    }
+
+    #[test]
+    fn test_concurrent_writers_do_not_lose_entries() {
+        let store = std::sync::Arc::new(scratch_store("concurrent", 1024 * 1024, 5));
+        let handles: Vec<_> = (0..4)
+            .map(|writer| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        store.append(&[entry(writer * 100 + i, 0)]).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(store.load().unwrap().len(), 100);
+        store.clear().unwrap();
+        assert!(store.load().unwrap().is_empty());
+        let _ = fs::remove_dir_all(store.config.path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_rotation_keeps_order_and_drops_oldest_segments() {
+        // Every append after the first rotates, so each segment holds one entry
+        let store = scratch_store("rotate", 1, 2);
+        for port in 1..=4 {
+            store.append(&[entry(port, 0)]).unwrap();
+        }
+        let ports: Vec<u16> = store.load().unwrap().iter().map(|e| e.port).collect();
+        assert_eq!(ports, vec![2, 3, 4]);
+
+        // Age-based rotation moves a segment with stale entries aside
+        let store = scratch_store("age", 1024 * 1024, 2);
+        store.append(&[entry(1, 10)]).unwrap();
+        store.append(&[entry(2, 0)]).unwrap();
+        assert!(store.segment_path(1).exists());
+        assert_eq!(read_segment(&store.config.path).len(), 1);
+        assert_eq!(store.load().unwrap().len(), 2);
+        let _ = fs::remove_dir_all(store.config.path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_legacy_history_is_migrated_once() {
+        let mut store = scratch_store("legacy", 1024 * 1024, 2);
+        let dir = store.config.path.parent().unwrap().to_path_buf();
+        let legacy = dir.join("legacy.json");
+        fs::write(
+            &legacy,
+            serde_json::to_string(&[entry(2, 2), entry(1, 3)]).unwrap(),
+        )
+        .unwrap();
+        store.legacy_path = Some(legacy.clone());
+
+        store.append(&[entry(3, 0)]).unwrap();
+        let ports: Vec<u16> = store.load().unwrap().iter().map(|e| e.port).collect();
+        assert_eq!(ports, vec![1, 2, 3]);
+        assert!(!legacy.exists());
+        assert!(dir.join("legacy.json.migrated").exists());
+
+        // A second load doesn't merge the old entries again
+        assert_eq!(store.load().unwrap().len(), 3);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,18 +1,36 @@
+pub mod alert_rules;
 pub mod cache;
 pub mod cli;
+pub mod commands;
+pub mod config;
 pub mod console_app;
 pub mod endpoint_monitor;
+pub mod endpoint_spool;
+pub mod event_monitor;
 pub mod file_monitor;
+pub mod filter_expr;
+pub mod history_query;
+pub mod history_store;
+pub mod metrics_exporter;
+pub mod output;
 pub mod port_guard;
 pub mod preset_manager;
 pub mod process_monitor;
+pub mod process_rules;
+pub mod project_ports;
+pub mod remote;
 pub mod scripting;
 pub mod security_audit;
 pub mod smart_filter;
+pub mod supervisor;
 pub mod system_monitor;
+pub mod tui;
 pub mod types;
 pub mod update_check;
 
+#[cfg(test)]
+mod test_support;
+
 // macOS-specific modules (only compiled on macOS)
 #[cfg(target_os = "macos")]
 pub mod app;
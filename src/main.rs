@@ -1,8 +1,6 @@
 #[cfg(target_os = "macos")]
 use anyhow::Result;
 #[cfg(target_os = "macos")]
-use clap::Parser;
-#[cfg(target_os = "macos")]
 use log::info;
 #[cfg(target_os = "macos")]
 use port_kill::cache::output::print_or_json;
@@ -10,115 +8,29 @@ use port_kill::cache::output::print_or_json;
 use port_kill::cache::{
     clean::clean_caches,
     doctor::doctor,
-    list::{list_caches, print_list_table},
+    list::{list_caches, list_caches_streaming, print_list_table},
     restore::restore_last_backup,
 };
-use port_kill::update_check;
 #[cfg(target_os = "macos")]
-use port_kill::{app::PortKillApp, cli::Args};
+use port_kill::{app::PortKillApp, cli::Command, commands::dispatch_subcommand, output};
 
 #[cfg(target_os = "macos")]
 fn main() -> Result<()> {
     // Parse command-line arguments
-    let mut args = Args::parse();
-
-    // Handle update check
-    if args.check_updates {
-        let current_version = env!("CARGO_PKG_VERSION");
-        match update_check::check_for_updates(current_version) {
-            Ok(Some(update_info)) => {
-                update_check::print_update_check_result(&update_info);
-                return Ok(());
-            }
-            Ok(None) => {
-                println!("✅ You're running the latest version ({})", current_version);
-                return Ok(());
-            }
-            Err(e) => {
-                eprintln!("⚠️  Could not check for updates: {}", e);
-                return Ok(());
-            }
-        }
-    }
-
-    // Check for updates in background (non-blocking)
-    let current_version = env!("CARGO_PKG_VERSION");
-    if let Ok(Some(update_info)) = update_check::check_for_updates(current_version) {
-        update_check::print_update_notification(&update_info);
-    }
-
-    // Handle preset functionality
-    if args.list_presets {
-        match Args::list_available_presets() {
-            Ok(presets_list) => {
-                println!("{}", presets_list);
-                return Ok(());
-            }
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
-        }
-    }
-
-    // Save preset
-    if let Some(name) = args.save_preset.clone() {
-        let desc = args
-            .preset_desc
-            .clone()
-            .unwrap_or_else(|| "User-defined preset".to_string());
-        let preset = args.build_preset_from_args(name.clone(), desc);
-        let mut mgr = port_kill::preset_manager::PresetManager::new();
-        if let Err(e) = mgr.load_presets() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        mgr.add_preset(preset);
-        if let Err(e) = mgr.save_presets() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        println!("✅ Saved preset '{}'.", name);
-        return Ok(());
-    }
+    let mut args = port_kill::config::parse_args();
 
-    // Delete preset
-    if let Some(name) = args.delete_preset.clone() {
-        let mut mgr = port_kill::preset_manager::PresetManager::new();
-        if let Err(e) = mgr.load_presets() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        match mgr.remove_preset(&name) {
-            Some(_) => {
-                if let Err(e) = mgr.save_presets() {
-                    eprintln!("Error: {}", e);
-                    std::process::exit(1);
-                }
-                println!("🗑️  Deleted preset '{}'.", name);
-            }
-            None => {
-                eprintln!("Preset '{}' not found.", name);
-                std::process::exit(1);
-            }
+    // Handle the one-shot commands shared with the other binaries
+    if let Some(result) = dispatch_subcommand(&mut args) {
+        if let Err(e) = result {
+            std::process::exit(output::report_error(
+                args.wants_json(),
+                args.output_kind(),
+                &e,
+            ));
         }
         return Ok(());
     }
 
-    // Apply preset if specified
-    if let Some(preset_name) = args.preset.clone() {
-        if let Err(e) = args.load_preset(&preset_name) {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-    }
-
-    // Validate arguments
-    if let Err(e) = args.validate() {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
-    }
-
     // Set up logging level based on log_level argument
     let log_level = if args.verbose {
         // Verbose flag overrides log_level for backward compatibility
@@ -135,23 +47,22 @@ fn main() -> Result<()> {
     info!("Monitoring: {}", args.get_port_description());
 
     // Handle cache subcommand: route to console-like behavior
-    if let Some(cache_cmd) = args.cache.clone() {
-        let c = cache_cmd.args();
+    if let Some(c) = args.command.as_ref().and_then(Command::cache_args) {
         if c.list || c.dry_run {
+            if c.stream {
+                let resp = tokio::runtime::Runtime::new()
+                    .unwrap()
+                    .block_on(list_caches_streaming(&c.selection(), c.stale_days, c.json));
+                if !c.json {
+                    print_list_table(&resp);
+                }
+                return Ok(());
+            }
             let resp = tokio::runtime::Runtime::new()
                 .unwrap()
-                .block_on(list_caches(
-                    &c.lang,
-                    c.npx,
-                    c.js_pm,
-                    c.hf,
-                    c.torch,
-                    c.vercel,
-                    c.cloudflare,
-                    c.stale_days,
-                ));
+                .block_on(list_caches(&c.selection(), c.stale_days));
             if c.json {
-                print_or_json(&resp, true);
+                print_or_json("cache.list", &resp, true);
             } else {
                 print_list_table(&resp);
             }
@@ -161,34 +72,32 @@ fn main() -> Result<()> {
             let resp = tokio::runtime::Runtime::new()
                 .unwrap()
                 .block_on(clean_caches(
-                    &c.lang,
-                    c.npx,
-                    c.js_pm,
+                    &c.selection(),
                     c.safe_delete,
                     c.force,
-                    c.hf,
-                    c.torch,
-                    c.vercel,
-                    c.cloudflare,
+                    c.kill_holders,
                     c.stale_days,
                 ));
-            print_or_json(&resp, c.json);
+            print_or_json("cache.clean", &resp, c.json);
             return Ok(());
         }
         if c.restore_last {
             let resp = tokio::runtime::Runtime::new()
                 .unwrap()
                 .block_on(restore_last_backup());
-            print_or_json(&resp, c.json);
+            print_or_json("cache.restore", &resp, c.json);
             return Ok(());
         }
         if c.doctor {
             let report = tokio::runtime::Runtime::new().unwrap().block_on(doctor());
-            print_or_json(&report, c.json);
+            print_or_json("cache.doctor", &report, c.json);
             return Ok(());
         }
     }
 
+    // Kills from the menu are recorded as tray kills
+    port_kill::history_store::set_actor(port_kill::types::KillActor::Tray);
+
     // Create and run the application
     let app = PortKillApp::new(args)?;
     app.run()?;
@@ -200,126 +109,28 @@ fn main() -> Result<()> {
 #[cfg(target_os = "windows")]
 use anyhow::Result;
 #[cfg(target_os = "windows")]
-use clap::Parser;
-#[cfg(target_os = "windows")]
 use log::info;
 #[cfg(target_os = "windows")]
-use port_kill::{cli::Args, console_app::ConsolePortKillApp};
+use port_kill::{commands::dispatch_subcommand, console_app::ConsolePortKillApp, output};
 
 #[cfg(target_os = "windows")]
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command-line arguments
-    let mut args = Args::parse();
-
-    // Handle self-update
-    if args.self_update {
-        match port_kill::update_check::self_update() {
-            Ok(()) => return Ok(()),
-            Err(e) => {
-                eprintln!("⚠️  Self-update failed: {}", e);
-                return Ok(());
-            }
-        }
-    }
-
-    // Handle update check
-    if args.check_updates {
-        let current_version = env!("CARGO_PKG_VERSION");
-        match port_kill::update_check::check_for_updates(current_version) {
-            Ok(Some(update_info)) => {
-                port_kill::update_check::print_update_check_result(&update_info);
-                return Ok(());
-            }
-            Ok(None) => {
-                println!("✅ You're running the latest version ({})", current_version);
-                return Ok(());
-            }
-            Err(e) => {
-                eprintln!("⚠️  Could not check for updates: {}", e);
-                return Ok(());
-            }
-        }
-    }
-
-    // Check for updates in background (non-blocking)
-    let current_version = env!("CARGO_PKG_VERSION");
-    if let Ok(Some(update_info)) = port_kill::update_check::check_for_updates(current_version) {
-        port_kill::update_check::print_update_notification(&update_info);
-    }
-
-    // Handle preset functionality
-    if args.list_presets {
-        match Args::list_available_presets() {
-            Ok(presets_list) => {
-                println!("{}", presets_list);
-                return Ok(());
-            }
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
-        }
-    }
-
-    // Save preset
-    if let Some(name) = args.save_preset.clone() {
-        let desc = args
-            .preset_desc
-            .clone()
-            .unwrap_or_else(|| "User-defined preset".to_string());
-        let preset = args.build_preset_from_args(name.clone(), desc);
-        let mut mgr = port_kill::preset_manager::PresetManager::new();
-        if let Err(e) = mgr.load_presets() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        mgr.add_preset(preset);
-        if let Err(e) = mgr.save_presets() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        println!("✅ Saved preset '{}'.", name);
-        return Ok(());
-    }
+    let mut args = port_kill::config::parse_args();
 
-    // Delete preset
-    if let Some(name) = args.delete_preset.clone() {
-        let mut mgr = port_kill::preset_manager::PresetManager::new();
-        if let Err(e) = mgr.load_presets() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        match mgr.remove_preset(&name) {
-            Some(_) => {
-                if let Err(e) = mgr.save_presets() {
-                    eprintln!("Error: {}", e);
-                    std::process::exit(1);
-                }
-                println!("🗑️  Deleted preset '{}'.", name);
-            }
-            None => {
-                eprintln!("Preset '{}' not found.", name);
-                std::process::exit(1);
-            }
+    // Handle the one-shot commands shared with the other binaries
+    if let Some(result) = dispatch_subcommand(&mut args) {
+        if let Err(e) = result {
+            std::process::exit(output::report_error(
+                args.wants_json(),
+                args.output_kind(),
+                &e,
+            ));
         }
         return Ok(());
     }
 
-    // Apply preset if specified
-    if let Some(preset_name) = args.preset.clone() {
-        if let Err(e) = args.load_preset(&preset_name) {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-    }
-
-    // Validate arguments
-    if let Err(e) = args.validate() {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
-    }
-
     // Set up logging level based on log_level argument
     let log_level = if args.verbose {
         // Verbose flag overrides log_level for backward compatibility
@@ -346,126 +157,28 @@ async fn main() -> Result<()> {
 #[cfg(target_os = "linux")]
 use anyhow::Result;
 #[cfg(target_os = "linux")]
-use clap::Parser;
-#[cfg(target_os = "linux")]
 use log::info;
 #[cfg(target_os = "linux")]
-use port_kill::{cli::Args, console_app::ConsolePortKillApp};
+use port_kill::{commands::dispatch_subcommand, console_app::ConsolePortKillApp, output};
 
 #[cfg(target_os = "linux")]
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command-line arguments
-    let mut args = Args::parse();
-
-    // Handle self-update
-    if args.self_update {
-        match port_kill::update_check::self_update() {
-            Ok(()) => return Ok(()),
-            Err(e) => {
-                eprintln!("⚠️  Self-update failed: {}", e);
-                return Ok(());
-            }
-        }
-    }
-
-    // Handle update check
-    if args.check_updates {
-        let current_version = env!("CARGO_PKG_VERSION");
-        match port_kill::update_check::check_for_updates(current_version) {
-            Ok(Some(update_info)) => {
-                port_kill::update_check::print_update_check_result(&update_info);
-                return Ok(());
-            }
-            Ok(None) => {
-                println!("✅ You're running the latest version ({})", current_version);
-                return Ok(());
-            }
-            Err(e) => {
-                eprintln!("⚠️  Could not check for updates: {}", e);
-                return Ok(());
-            }
-        }
-    }
-
-    // Check for updates in background (non-blocking)
-    let current_version = env!("CARGO_PKG_VERSION");
-    if let Ok(Some(update_info)) = port_kill::update_check::check_for_updates(current_version) {
-        port_kill::update_check::print_update_notification(&update_info);
-    }
-
-    // Handle preset functionality
-    if args.list_presets {
-        match Args::list_available_presets() {
-            Ok(presets_list) => {
-                println!("{}", presets_list);
-                return Ok(());
-            }
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
-        }
-    }
+    let mut args = port_kill::config::parse_args();
 
-    // Save preset
-    if let Some(name) = args.save_preset.clone() {
-        let desc = args
-            .preset_desc
-            .clone()
-            .unwrap_or_else(|| "User-defined preset".to_string());
-        let preset = args.build_preset_from_args(name.clone(), desc);
-        let mut mgr = port_kill::preset_manager::PresetManager::new();
-        if let Err(e) = mgr.load_presets() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        mgr.add_preset(preset);
-        if let Err(e) = mgr.save_presets() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        println!("✅ Saved preset '{}'.", name);
-        return Ok(());
-    }
-
-    // Delete preset
-    if let Some(name) = args.delete_preset.clone() {
-        let mut mgr = port_kill::preset_manager::PresetManager::new();
-        if let Err(e) = mgr.load_presets() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        match mgr.remove_preset(&name) {
-            Some(_) => {
-                if let Err(e) = mgr.save_presets() {
-                    eprintln!("Error: {}", e);
-                    std::process::exit(1);
-                }
-                println!("🗑️  Deleted preset '{}'.", name);
-            }
-            None => {
-                eprintln!("Preset '{}' not found.", name);
-                std::process::exit(1);
-            }
+    // Handle the one-shot commands shared with the other binaries
+    if let Some(result) = dispatch_subcommand(&mut args) {
+        if let Err(e) = result {
+            std::process::exit(output::report_error(
+                args.wants_json(),
+                args.output_kind(),
+                &e,
+            ));
         }
         return Ok(());
     }
 
-    // Apply preset if specified
-    if let Some(preset_name) = args.preset.clone() {
-        if let Err(e) = args.load_preset(&preset_name) {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-    }
-
-    // Validate arguments
-    if let Err(e) = args.validate() {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
-    }
-
     // Set up logging level based on log_level argument
     let log_level = if args.verbose {
         // Verbose flag overrides log_level for backward compatibility
@@ -1,150 +1,51 @@
 use anyhow::Result;
-use clap::Parser;
 use log::info;
 use port_kill::cache::output::print_or_json;
 use port_kill::cache::{
     clean::clean_caches,
     doctor::doctor,
-    list::{list_caches, print_list_table},
+    list::{list_caches, list_caches_streaming, print_list_table},
     restore::restore_last_backup,
 };
-use port_kill::update_check;
+use port_kill::output;
 use port_kill::{
-    cli::Args,
+    cli::{Args, Command},
+    commands::dispatch_subcommand,
     console_app::ConsolePortKillApp,
     scripting::{load_script_file, ScriptEngine},
 };
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     // Parse command-line arguments
-    let mut args = Args::parse();
-
-    // Handle self-update
-    if args.self_update {
-        match update_check::self_update() {
-            Ok(()) => return Ok(()),
-            Err(e) => {
-                eprintln!("⚠️  Self-update failed: {}", e);
-                return Ok(());
-            }
-        }
-    }
-
-    // Handle update check
-    if args.check_updates {
-        let current_version = env!("CARGO_PKG_VERSION");
-        match update_check::check_for_updates(current_version) {
-            Ok(Some(update_info)) => {
-                update_check::print_update_check_result(&update_info);
-                return Ok(());
-            }
-            Ok(None) => {
-                println!("✅ You're running the latest version ({})", current_version);
-                return Ok(());
-            }
-            Err(e) => {
-                eprintln!("⚠️  Could not check for updates: {}", e);
-                return Ok(());
-            }
-        }
-    }
-
-    // Check for updates in background (non-blocking)
-    let current_version = env!("CARGO_PKG_VERSION");
-    if let Ok(Some(update_info)) = update_check::check_for_updates(current_version) {
-        update_check::print_update_notification(&update_info);
-    }
-
-    // Handle preset functionality
-    if args.list_presets {
-        match Args::list_available_presets() {
-            Ok(presets_list) => {
-                println!("{}", presets_list);
-                return Ok(());
-            }
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
-        }
-    }
-
-    // Save preset
-    if let Some(name) = args.save_preset.clone() {
-        let desc = args
-            .preset_desc
-            .clone()
-            .unwrap_or_else(|| "User-defined preset".to_string());
-        let preset = args.build_preset_from_args(name.clone(), desc);
-        let mut mgr = port_kill::preset_manager::PresetManager::new();
-        if let Err(e) = mgr.load_presets() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        mgr.add_preset(preset);
-        if let Err(e) = mgr.save_presets() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        println!("✅ Saved preset '{}'.", name);
-        return Ok(());
-    }
-
-    // Delete preset
-    if let Some(name) = args.delete_preset.clone() {
-        let mut mgr = port_kill::preset_manager::PresetManager::new();
-        if let Err(e) = mgr.load_presets() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        match mgr.remove_preset(&name) {
-            Some(_) => {
-                if let Err(e) = mgr.save_presets() {
-                    eprintln!("Error: {}", e);
-                    std::process::exit(1);
-                }
-                println!("🗑️  Deleted preset '{}'.", name);
-            }
-            None => {
-                eprintln!("Preset '{}' not found.", name);
-                std::process::exit(1);
-            }
-        }
-        return Ok(());
-    }
+    let args = port_kill::config::parse_args();
+    let json = args.wants_json();
+    let kind = args.output_kind();
 
-    // Apply preset if specified
-    if let Some(preset_name) = args.preset.clone() {
-        if let Err(e) = args.load_preset(&preset_name) {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
+    if let Err(e) = run(args).await {
+        std::process::exit(output::report_error(json, kind, &e));
     }
+}
 
-    // Validate arguments
-    if let Err(e) = args.validate() {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+async fn run(mut args: Args) -> Result<()> {
+    // Handle the one-shot commands shared with the other binaries
+    if let Some(result) = dispatch_subcommand(&mut args) {
+        return result;
     }
 
     // Handle cache subcommand
-    if let Some(cache_cmd) = args.cache.clone() {
-        let c = cache_cmd.args();
+    if let Some(c) = args.command.as_ref().and_then(Command::cache_args) {
         if c.list || c.dry_run {
-            let resp = list_caches(
-                &c.lang,
-                c.npx,
-                c.js_pm,
-                c.hf,
-                c.torch,
-                c.vercel,
-                c.cloudflare,
-                c.stale_days,
-            )
-            .await;
+            if c.stream {
+                let resp = list_caches_streaming(&c.selection(), c.stale_days, c.json).await;
+                if !c.json {
+                    print_list_table(&resp);
+                }
+                return Ok(());
+            }
+            let resp = list_caches(&c.selection(), c.stale_days).await;
             if c.json {
-                print_or_json(&resp, true);
+                print_or_json("cache.list", &resp, true);
             } else {
                 print_list_table(&resp);
             }
@@ -152,29 +53,24 @@ async fn main() -> Result<()> {
         }
         if c.clean {
             let resp = clean_caches(
-                &c.lang,
-                c.npx,
-                c.js_pm,
+                &c.selection(),
                 c.safe_delete,
                 c.force,
-                c.hf,
-                c.torch,
-                c.vercel,
-                c.cloudflare,
+                c.kill_holders,
                 c.stale_days,
             )
             .await;
-            print_or_json(&resp, c.json);
+            print_or_json("cache.clean", &resp, c.json);
             return Ok(());
         }
         if c.restore_last {
             let resp = restore_last_backup().await;
-            print_or_json(&resp, c.json);
+            print_or_json("cache.restore", &resp, c.json);
             return Ok(());
         }
         if c.doctor {
             let report = doctor().await;
-            print_or_json(&report, c.json);
+            print_or_json("cache.doctor", &report, c.json);
             return Ok(());
         }
     }
@@ -279,10 +175,16 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if args.guard_status {
+        let app = ConsolePortKillApp::new(args)?;
+        app.show_guard_status().await?;
+        return Ok(());
+    }
+
     // Handle remote mode
-    if let Some(remote_host) = args.get_remote_host() {
+    if args.is_remote() {
         let app = ConsolePortKillApp::new(args)?;
-        app.run_remote_mode(&remote_host).await?;
+        app.run_remote_mode().await?;
         return Ok(());
     }
 
@@ -317,7 +219,11 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Handle scripting commands
+    // Handle scripting commands; their kills are recorded as script kills
+    if args.script.is_some() || args.script_file.is_some() {
+        port_kill::history_store::set_actor(port_kill::types::KillActor::Script);
+    }
+
     if let Some(ref script) = args.script {
         let script_content = script.clone();
         let app = ConsolePortKillApp::new(args)?;
@@ -3,13 +3,16 @@
 
 use port_kill::{
     cli::Args,
+    commands::dispatch_subcommand,
     console_app::ConsolePortKillApp,
+    output,
     types::{ProcessInfo, StatusBarInfo},
-    process_monitor::{get_processes_on_ports, kill_all_processes, kill_single_process},
+    process_monitor::{
+        check_kills, get_processes_on_ports, kill_all_processes, kill_single_process,
+    },
 };
 use libappindicator::{AppIndicator, AppIndicatorStatus};
 use anyhow::Result;
-use clap::Parser;
 use log::{error, info};
 use std::env;
 use std::process;
@@ -25,12 +28,14 @@ use gtk::{Menu, MenuItem, SeparatorMenuItem};
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command-line arguments
-    let args = Args::parse();
+    let mut args = port_kill::config::parse_args();
     
-    // Validate arguments
-    if let Err(e) = args.validate() {
-        eprintln!("Error: {}", e);
-        process::exit(1);
+    // Handle the one-shot commands shared with the other binaries
+    if let Some(result) = dispatch_subcommand(&mut args) {
+        if let Err(e) = result {
+            std::process::exit(output::report_error(args.wants_json(), args.output_kind(), &e));
+        }
+        return Ok(());
     }
 
     // Set up logging level based on log_level argument
@@ -80,6 +85,7 @@ async fn main() -> Result<()> {
 
 async fn start_tray_mode(args: Args) -> Result<()> {
     info!("Starting Linux tray mode...");
+    port_kill::history_store::set_actor(port_kill::types::KillActor::Tray);
     
     // Initialize GTK before creating tray items
     if gtk::init().is_err() {
@@ -123,7 +129,9 @@ async fn start_tray_mode(args: Args) -> Result<()> {
     kill_all_item.connect_activate(move |_| {
         info!("Kill All Processes clicked");
         let ports_to_kill = args_clone.get_ports_to_monitor();
-        if let Err(e) = kill_all_processes(&ports_to_kill, &args_clone) {
+        if let Err(e) =
+            kill_all_processes(&ports_to_kill, &args_clone).and_then(|kills| check_kills(&kills))
+        {
             error!("Failed to kill all processes: {}", e);
         }
     });
@@ -314,7 +322,9 @@ fn create_process_menu_with_verbose(args: &Args, processes: &HashMap<u16, Proces
         
         menu_item.connect_activate(move |_| {
             info!("Killing process on port {} (PID: {})", port_clone, pid_to_kill);
-            if let Err(e) = kill_single_process(pid_to_kill, &args_clone) {
+            if let Err(e) =
+                kill_single_process(pid_to_kill, &args_clone).and_then(|kills| check_kills(&kills))
+            {
                 error!("Failed to kill process on port {}: {}", port_clone, e);
             } else {
                 info!("Successfully killed process on port {}", port_clone);
@@ -1,20 +1,20 @@
 use port_kill::{
-    cli::Args,
+    cli::{Args, Command},
+    commands::dispatch_subcommand,
     console_app::ConsolePortKillApp,
+    output,
     types::{ProcessInfo, StatusBarInfo},
-    process_monitor::{get_processes_on_ports, kill_all_processes},
+    process_monitor::{check_kills, get_processes_on_ports, kill_all_processes},
 };
 use port_kill::cache::{
-    list::{list_caches, print_list_table},
+    list::{list_caches, list_caches_streaming, print_list_table},
     clean::clean_caches,
     restore::restore_last_backup,
     doctor::doctor,
 };
 use port_kill::cache::output::print_or_json;
-use port_kill::update_check;
 use tray_item::TrayItem;
 use anyhow::Result;
-use clap::Parser;
 use log::{error, info};
 use std::collections::HashMap;
 use std::thread;
@@ -23,12 +23,14 @@ use std::time::Duration;
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command line arguments
-    let args = Args::parse();
+    let mut args = port_kill::config::parse_args();
     
-    // Validate arguments
-    if let Err(e) = args.validate() {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+    // Handle the one-shot commands shared with the other binaries
+    if let Some(result) = dispatch_subcommand(&mut args) {
+        if let Err(e) = result {
+            std::process::exit(output::report_error(args.wants_json(), args.output_kind(), &e));
+        }
+        return Ok(());
     }
     
     // Set up logging level based on log_level argument
@@ -46,67 +48,37 @@ async fn main() -> Result<()> {
     info!("Starting Port Kill application on Windows...");
     info!("Monitoring: {}", args.get_port_description());
     
-    // Handle self-update
-    if args.self_update {
-        match update_check::self_update() {
-            Ok(()) => return Ok(()),
-            Err(e) => {
-                eprintln!("⚠️  Self-update failed: {}", e);
-                return Ok(());
-            }
-        }
-    }
-
-    // Handle explicit update check
-    if args.check_updates {
-        let current_version = env!("CARGO_PKG_VERSION");
-        match update_check::check_for_updates(current_version) {
-            Ok(Some(update_info)) => {
-                update_check::print_update_check_result(&update_info);
-                return Ok(());
-            }
-            Ok(None) => {
-                println!("✅ You're running the latest version ({})", current_version);
-                return Ok(());
-            }
-            Err(e) => {
-                eprintln!("⚠️  Could not check for updates: {}", e);
-                return Ok(());
-            }
-        }
-    }
-
-    // Background update notification (non-blocking)
-    let current_version = env!("CARGO_PKG_VERSION");
-    if let Ok(Some(update_info)) = update_check::check_for_updates(current_version) {
-        update_check::print_update_notification(&update_info);
-    }
-
     // Handle cache subcommand on Windows too (parity with console binary)
-    if let Some(cache_cmd) = args.cache.clone() {
-        let c = cache_cmd.args();
+    if let Some(c) = args.command.as_ref().and_then(Command::cache_args) {
         if c.list || c.dry_run {
-            let resp = list_caches(&c.lang, c.npx, c.js_pm, c.hf, c.torch, c.vercel, c.cloudflare, c.stale_days).await;
+            if c.stream {
+                let resp = list_caches_streaming(&c.selection(), c.stale_days, c.json).await;
+                if !c.json {
+                    print_list_table(&resp);
+                }
+                return Ok(());
+            }
+            let resp = list_caches(&c.selection(), c.stale_days).await;
             if c.json {
-                print_or_json(&resp, true);
+                print_or_json("cache.list", &resp, true);
             } else {
                 print_list_table(&resp);
             }
             return Ok(());
         }
         if c.clean {
-            let resp = clean_caches(&c.lang, c.npx, c.js_pm, c.safe_delete, c.force, c.hf, c.torch, c.vercel, c.cloudflare, c.stale_days).await;
-            print_or_json(&resp, c.json);
+            let resp = clean_caches(&c.selection(), c.safe_delete, c.force, c.kill_holders, c.stale_days).await;
+            print_or_json("cache.clean", &resp, c.json);
             return Ok(());
         }
         if c.restore_last {
             let resp = restore_last_backup().await;
-            print_or_json(&resp, c.json);
+            print_or_json("cache.restore", &resp, c.json);
             return Ok(());
         }
         if c.doctor {
             let report = doctor().await;
-            print_or_json(&report, c.json);
+            print_or_json("cache.doctor", &report, c.json);
             return Ok(());
         }
     }
@@ -131,6 +103,7 @@ async fn main() -> Result<()> {
 
 fn run_windows_tray_mode(args: Args) -> Result<()> {
     info!("Starting Windows tray mode...");
+    port_kill::history_store::set_actor(port_kill::types::KillActor::Tray);
     
     // Create the tray item using the embedded icon resource (ID: 1)
     let mut tray = TrayItem::new("Port Kill", tray_item::IconSource::Resource("1"))
@@ -170,7 +143,9 @@ fn run_windows_tray_mode(args: Args) -> Result<()> {
                 "kill_all" => {
                     info!("Kill All Processes clicked");
                     let ports_to_kill = args.get_ports_to_monitor();
-                    if let Err(e) = kill_all_processes(&ports_to_kill, &args) {
+                    if let Err(e) = kill_all_processes(&ports_to_kill, &args)
+                        .and_then(|kills| check_kills(&kills))
+                    {
                         error!("Failed to kill all processes: {}", e);
                     } else {
                         println!("✅ All processes killed successfully");
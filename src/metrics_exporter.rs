@@ -0,0 +1,567 @@
+use crate::cli::Args;
+use crate::port_guard::PortGuardDaemon;
+use crate::process_monitor::ProcessMonitor;
+use crate::security_audit::SecurityAuditor;
+use crate::smart_filter::SmartFilter;
+use crate::types::{ProcessHistory, ProcessInfo, SecurityAuditResult};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+use tokio::time::interval;
+
+/// Free ports are only exported as explicit zero series for reasonably small port sets
+const MAX_FREE_PORT_SERIES: usize = 256;
+
+/// Name, help text and value extractor for a per-process gauge
+type ProcessMetric = (&'static str, &'static str, fn(&ProcessInfo) -> Option<f64>);
+
+/// Everything one scrape reports, gathered once per scan interval
+#[derive(Debug, Default)]
+pub struct MetricsSnapshot {
+    pub monitored_ports: Vec<u16>,
+    pub processes: HashMap<u16, ProcessInfo>,
+    pub audit: Option<SecurityAuditResult>,
+    /// Kills per (process, group, project), from `KillCounter`
+    pub kills: Option<BTreeMap<(String, String, String), u64>>,
+    pub conflicts_resolved: Option<usize>,
+}
+
+/// Kill counts that only ever grow. The history file rotates and can be truncated, so
+/// re-counting it on every scan would make `port_kill_kills_total` drop; instead each entry
+/// is counted once, when the exporter first sees it.
+#[derive(Debug, Default)]
+pub struct KillCounter {
+    counts: BTreeMap<(String, String, String), u64>,
+    /// Newest `killed_at` counted so far
+    watermark: Option<DateTime<Utc>>,
+    /// (pid, port) of the entries counted at exactly `watermark`
+    at_watermark: HashSet<(i32, u16)>,
+}
+
+impl KillCounter {
+    /// Count the entries of `history` newer than anything counted before
+    pub fn observe(&mut self, history: &ProcessHistory) {
+        let mut entries: Vec<_> = history.get_recent_entries(history.len()).iter().collect();
+        entries.sort_by_key(|e| e.killed_at);
+        for entry in entries {
+            match self.watermark {
+                Some(mark) if entry.killed_at < mark => continue,
+                Some(mark) if entry.killed_at == mark => {
+                    if !self.at_watermark.insert((entry.pid, entry.port)) {
+                        continue;
+                    }
+                }
+                _ => {
+                    self.watermark = Some(entry.killed_at);
+                    self.at_watermark.clear();
+                    self.at_watermark.insert((entry.pid, entry.port));
+                }
+            }
+            let key = (
+                entry.process_name.clone(),
+                entry.process_group.clone().unwrap_or_default(),
+                entry.project_name.clone().unwrap_or_default(),
+            );
+            *self.counts.entry(key).or_insert(0) += 1;
+        }
+    }
+
+    pub fn counts(&self) -> &BTreeMap<(String, String, String), u64> {
+        &self.counts
+    }
+}
+
+/// Prometheus exporter serving the latest scan in text exposition format
+pub struct MetricsExporter {
+    listen_addr: String,
+    process_monitor: ProcessMonitor,
+    security_auditor: SecurityAuditor,
+    port_guard: Option<Arc<PortGuardDaemon>>,
+    kill_counter: KillCounter,
+    body: Arc<RwLock<String>>,
+}
+
+impl MetricsExporter {
+    /// Create a new metrics exporter
+    pub fn new(args: &Args, port_guard: Option<Arc<PortGuardDaemon>>) -> Result<Self> {
+        let listen_addr = args
+            .metrics_listen
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Listen address is required for the metrics exporter"))?
+            .clone();
+
+        // CPU and memory always come from SystemMonitor, so performance sampling is forced on
+        let (update_sender, _update_receiver) = crossbeam_channel::bounded(100);
+        let process_monitor = ProcessMonitor::new_with_performance(
+            update_sender,
+            args.get_ports_to_monitor(),
+            args.docker,
+            args.verbose,
+            SmartFilter::from_args(args)?,
+            true,
+        )?;
+
+        let suspicious_ports: Vec<u16> = args
+            .suspicious_ports
+            .split(',')
+            .filter_map(|s| s.trim().parse().ok())
+            .collect();
+        let security_auditor = SecurityAuditor::new(
+            suspicious_ports,
+            args.baseline_file.clone(),
+            args.suspicious_only,
+        );
+
+        Ok(Self {
+            listen_addr,
+            process_monitor,
+            security_auditor,
+            port_guard,
+            kill_counter: KillCounter::default(),
+            body: Arc::new(RwLock::new(String::new())),
+        })
+    }
+
+    /// Bind the listener, then refresh the exported snapshot every scan interval
+    pub async fn run(&mut self, args: &Args) -> Result<()> {
+        let listener = TcpListener::bind(&self.listen_addr)
+            .await
+            .with_context(|| format!("Failed to listen on {}", self.listen_addr))?;
+
+        log::info!("Starting metrics exporter:");
+        log::info!("  - Listening on: {}", listener.local_addr()?);
+        log::info!("  - Scan interval: {}s", args.scan_interval);
+
+        // Serve an initial snapshot before the first scrape can arrive
+        self.refresh().await;
+        tokio::spawn(serve(listener, self.body.clone()));
+
+        let mut scan_timer = interval(Duration::from_secs(args.scan_interval.max(1)));
+        scan_timer.tick().await;
+        loop {
+            scan_timer.tick().await;
+            self.refresh().await;
+        }
+    }
+
+    /// Rescan and re-render the exported text
+    async fn refresh(&mut self) {
+        match self.collect().await {
+            Ok(snapshot) => *self.body.write().await = render_metrics(&snapshot),
+            Err(e) => log::warn!("Failed to collect metrics: {}", e),
+        }
+    }
+
+    async fn collect(&mut self) -> Result<MetricsSnapshot> {
+        let processes = self.process_monitor.scan_processes().await?;
+
+        let audit = match self.security_auditor.perform_audit(processes.clone()).await {
+            Ok(audit) => Some(audit),
+            Err(e) => {
+                log::warn!("Security audit failed: {}", e);
+                None
+            }
+        };
+
+        // Kills can come from any port-kill invocation, so read the shared history file
+        let kills =
+            ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), usize::MAX)
+                .ok()
+                .map(|history| {
+                    self.kill_counter.observe(&history);
+                    self.kill_counter.counts().clone()
+                });
+
+        let conflicts_resolved = match &self.port_guard {
+            Some(guard) => Some(guard.get_status().await.conflicts_resolved),
+            None => None,
+        };
+
+        Ok(MetricsSnapshot {
+            monitored_ports: self.process_monitor.get_ports_to_monitor().clone(),
+            processes,
+            audit,
+            kills,
+            conflicts_resolved,
+        })
+    }
+}
+
+/// Answer HTTP requests on `listener` with whatever `body` currently holds
+pub async fn serve(listener: TcpListener, body: Arc<RwLock<String>>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let body = body.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, body).await {
+                        log::debug!("Metrics connection error: {}", e);
+                    }
+                });
+            }
+            Err(e) => log::warn!("Failed to accept metrics connection: {}", e),
+        }
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, body: Arc<RwLock<String>>) -> Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 8192 {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+
+    let (status, content_type, content) = match (method, path) {
+        ("GET", "/metrics") => (
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            body.read().await.clone(),
+        ),
+        ("GET", _) => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not found, metrics are served at /metrics\n".to_string(),
+        ),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Only GET is supported\n".to_string(),
+        ),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        content.len(),
+        content
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Escape a label value as required by the Prometheus text format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn labels(pairs: &[(&str, &str)]) -> String {
+    let rendered: Vec<String> = pairs
+        .iter()
+        .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+        .collect();
+    format!("{{{}}}", rendered.join(","))
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Render a snapshot in the Prometheus text exposition format
+pub fn render_metrics(snapshot: &MetricsSnapshot) -> String {
+    let mut out = String::new();
+
+    let mut occupied: Vec<(&u16, &ProcessInfo)> = snapshot.processes.iter().collect();
+    occupied.sort_by_key(|(port, _)| **port);
+
+    header(
+        &mut out,
+        "port_kill_ports_monitored",
+        "gauge",
+        "Number of ports being monitored",
+    );
+    let _ = writeln!(
+        out,
+        "port_kill_ports_monitored {}",
+        snapshot.monitored_ports.len()
+    );
+
+    header(
+        &mut out,
+        "port_kill_ports_occupied",
+        "gauge",
+        "Number of monitored ports with a listening process",
+    );
+    let _ = writeln!(out, "port_kill_ports_occupied {}", occupied.len());
+
+    header(
+        &mut out,
+        "port_kill_port_occupied",
+        "gauge",
+        "Whether a port is occupied, labelled with its owner",
+    );
+    for (port, process) in &occupied {
+        let port = port.to_string();
+        let _ = writeln!(
+            out,
+            "port_kill_port_occupied{} 1",
+            labels(&[
+                ("port", &port),
+                ("process", &process.name),
+                ("group", process.process_group.as_deref().unwrap_or("")),
+                ("project", process.project_name.as_deref().unwrap_or("")),
+                ("container", process.container_name.as_deref().unwrap_or("")),
+            ])
+        );
+    }
+    if snapshot.monitored_ports.len() <= MAX_FREE_PORT_SERIES {
+        for port in &snapshot.monitored_ports {
+            if !snapshot.processes.contains_key(port) {
+                let _ = writeln!(
+                    out,
+                    "port_kill_port_occupied{} 0",
+                    labels(&[
+                        ("port", &port.to_string()),
+                        ("process", ""),
+                        ("group", ""),
+                        ("project", ""),
+                        ("container", ""),
+                    ])
+                );
+            }
+        }
+    }
+
+    let process_metrics: [ProcessMetric; 4] = [
+        (
+            "port_kill_process_cpu_percent",
+            "CPU usage of the process owning a port",
+            |p| p.cpu_usage,
+        ),
+        (
+            "port_kill_process_memory_bytes",
+            "Resident memory of the process owning a port",
+            |p| p.memory_usage.map(|m| m as f64),
+        ),
+        (
+            "port_kill_process_memory_percent",
+            "Memory of the process owning a port as a share of total memory",
+            |p| p.memory_percentage,
+        ),
+        (
+            "port_kill_process_uptime_seconds",
+            "Seconds since the process owning a port started",
+            |p| p.uptime_seconds().map(|s| s as f64),
+        ),
+    ];
+    for (name, help, value_of) in process_metrics {
+        header(&mut out, name, "gauge", help);
+        for (port, process) in &occupied {
+            if let Some(value) = value_of(process) {
+                let _ = writeln!(
+                    out,
+                    "{}{} {}",
+                    name,
+                    labels(&[
+                        ("port", &port.to_string()),
+                        ("pid", &process.pid.to_string()),
+                        ("process", &process.name),
+                    ]),
+                    value
+                );
+            }
+        }
+    }
+
+    if let Some(audit) = &snapshot.audit {
+        header(
+            &mut out,
+            "port_kill_audit_security_score",
+            "gauge",
+            "Security score from the last audit (0-100)",
+        );
+        let _ = writeln!(
+            out,
+            "port_kill_audit_security_score {}",
+            audit.security_score
+        );
+
+        header(
+            &mut out,
+            "port_kill_audit_suspicious_processes",
+            "gauge",
+            "Suspicious processes found by the last audit, by risk level",
+        );
+        let mut by_risk: BTreeMap<String, usize> = ["low", "medium", "high", "critical"]
+            .iter()
+            .map(|risk| (risk.to_string(), 0))
+            .collect();
+        for suspicious in &audit.suspicious_processes {
+            *by_risk
+                .entry(format!("{:?}", suspicious.risk_level).to_lowercase())
+                .or_insert(0) += 1;
+        }
+        for (risk, count) in by_risk {
+            let _ = writeln!(
+                out,
+                "port_kill_audit_suspicious_processes{} {}",
+                labels(&[("risk", &risk)]),
+                count
+            );
+        }
+    }
+
+    if let Some(kills) = &snapshot.kills {
+        header(
+            &mut out,
+            "port_kill_kills_total",
+            "counter",
+            "Processes killed according to the kill history",
+        );
+        for ((process, group, project), count) in kills {
+            let _ = writeln!(
+                out,
+                "port_kill_kills_total{} {}",
+                labels(&[("process", process), ("group", group), ("project", project)]),
+                count
+            );
+        }
+    }
+
+    if let Some(conflicts) = snapshot.conflicts_resolved {
+        header(
+            &mut out,
+            "port_kill_guard_conflicts_resolved_total",
+            "counter",
+            "Port conflicts resolved by the port guard",
+        );
+        let _ = writeln!(
+            out,
+            "port_kill_guard_conflicts_resolved_total {}",
+            conflicts
+        );
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ProcessHistoryEntry;
+
+    fn node_process() -> ProcessInfo {
+        ProcessInfo {
+            name: "node \"dev\"".to_string(),
+            process_group: Some("Node.js".to_string()),
+            project_name: Some("shop".to_string()),
+            cpu_usage: Some(12.5),
+            memory_usage: Some(1024),
+            ..ProcessInfo::for_test(4242, 3000, "node")
+        }
+    }
+
+    fn snapshot() -> MetricsSnapshot {
+        let mut history = ProcessHistory::new(10);
+        let mut second = node_process();
+        second.pid += 1;
+        history.add_entry(ProcessHistoryEntry::new(&node_process(), "cli".to_string()));
+        history.add_entry(ProcessHistoryEntry::new(&second, "cli".to_string()));
+        let mut kills = KillCounter::default();
+        kills.observe(&history);
+
+        MetricsSnapshot {
+            monitored_ports: vec![3000, 3001],
+            processes: HashMap::from([(3000, node_process())]),
+            audit: None,
+            kills: Some(kills.counts().clone()),
+            conflicts_resolved: Some(3),
+        }
+    }
+
+    #[test]
+    fn test_render_metrics_text_format() {
+        let text = render_metrics(&snapshot());
+
+        assert!(text.contains("# TYPE port_kill_port_occupied gauge"));
+        assert!(text.contains(
+            "port_kill_port_occupied{port=\"3000\",process=\"node \\\"dev\\\"\",group=\"Node.js\",project=\"shop\",container=\"\"} 1"
+        ));
+        assert!(text.contains("port_kill_port_occupied{port=\"3001\",process=\"\",group=\"\",project=\"\",container=\"\"} 0"));
+        assert!(text.contains(
+            "port_kill_process_cpu_percent{port=\"3000\",pid=\"4242\",process=\"node \\\"dev\\\"\"} 12.5"
+        ));
+        assert!(!text.contains("port_kill_process_memory_percent{"));
+        assert!(text.contains(
+            "port_kill_kills_total{process=\"node \\\"dev\\\"\",group=\"Node.js\",project=\"shop\"} 2"
+        ));
+        assert!(text.contains("port_kill_guard_conflicts_resolved_total 3"));
+    }
+
+    #[test]
+    fn test_kill_counter_survives_history_rotation() {
+        let entry = |pid: i32, secs_ago: i64| {
+            let mut process = node_process();
+            process.pid = pid;
+            let mut entry = ProcessHistoryEntry::new(&process, "cli".to_string());
+            entry.killed_at = Utc::now() - chrono::Duration::seconds(secs_ago);
+            entry
+        };
+        let (old, recent, newer) = (entry(1, 30), entry(2, 20), entry(3, 10));
+        let count = |kills: &KillCounter| kills.counts().values().sum::<u64>();
+
+        let mut history = ProcessHistory::new(10);
+        history.add_entry(old);
+        history.add_entry(recent.clone());
+        let mut kills = KillCounter::default();
+        kills.observe(&history);
+        kills.observe(&history);
+        assert_eq!(count(&kills), 2);
+
+        // After rotation only the latest entries are left in the file
+        let mut rotated = ProcessHistory::new(10);
+        rotated.add_entry(recent);
+        rotated.add_entry(newer);
+        kills.observe(&rotated);
+        assert_eq!(count(&kills), 3);
+    }
+
+    #[tokio::test]
+    async fn test_serve_answers_local_curl() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let body = Arc::new(RwLock::new(render_metrics(&snapshot())));
+        tokio::spawn(serve(listener, body));
+
+        let output = tokio::task::spawn_blocking(move || {
+            std::process::Command::new("curl")
+                .args(["-s", "-i", &format!("http://{}/metrics", addr)])
+                .output()
+        })
+        .await
+        .unwrap();
+        // Skip where curl is not installed
+        let Ok(output) = output else {
+            return;
+        };
+
+        let response = String::from_utf8_lossy(&output.stdout);
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4"));
+        assert!(response.contains("port_kill_ports_occupied 1"));
+    }
+}
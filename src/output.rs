@@ -0,0 +1,411 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fmt;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Bumped only for breaking changes to the envelope or to an existing `kind`'s data;
+/// new fields can appear at any time
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema for the envelope and the data of every `kind`
+pub const SCHEMA: &str = include_str!("../schema/output.v1.schema.json");
+
+/// Exit codes shared by every command, with or without `--json`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitCode {
+    Success,
+    /// The command could not do its job (I/O, scan or kill failure, unreachable host)
+    Failure,
+    /// Invalid flags, preset, expression or config file
+    Usage,
+    /// Some of the work succeeded: a kill that missed processes, hosts that didn't answer
+    Partial,
+    /// `check` found project ports already in use
+    Conflict,
+}
+
+impl ExitCode {
+    pub fn code(self) -> i32 {
+        match self {
+            ExitCode::Success => 0,
+            ExitCode::Failure => 1,
+            ExitCode::Usage => 2,
+            ExitCode::Partial => 3,
+            ExitCode::Conflict => 4,
+        }
+    }
+
+    /// The exit code behind a process status, e.g. one reported by a remote envelope
+    pub fn from_code(code: i64) -> Self {
+        match code {
+            0 => ExitCode::Success,
+            2 => ExitCode::Usage,
+            3 => ExitCode::Partial,
+            4 => ExitCode::Conflict,
+            _ => ExitCode::Failure,
+        }
+    }
+}
+
+/// An error that carries the exit code it should end the process with. Anything
+/// else that reaches `main` exits with `ExitCode::Failure`.
+#[derive(Debug)]
+pub struct CommandError {
+    pub exit_code: ExitCode,
+    pub message: String,
+}
+
+impl CommandError {
+    pub fn with_code(exit_code: ExitCode, message: impl fmt::Display) -> anyhow::Error {
+        anyhow::Error::new(Self {
+            exit_code,
+            message: message.to_string(),
+        })
+    }
+
+    pub fn usage(message: impl fmt::Display) -> anyhow::Error {
+        Self::with_code(ExitCode::Usage, message)
+    }
+
+    pub fn partial(message: impl fmt::Display) -> anyhow::Error {
+        Self::with_code(ExitCode::Partial, message)
+    }
+
+    pub fn conflict(message: impl fmt::Display) -> anyhow::Error {
+        Self::with_code(ExitCode::Conflict, message)
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// The exit code `error` should end the process with
+pub fn exit_code_of(error: &anyhow::Error) -> ExitCode {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<CommandError>())
+        .map(|e| e.exit_code)
+        .unwrap_or(ExitCode::Failure)
+}
+
+#[derive(Debug, Serialize)]
+pub struct Envelope<'a, T: Serialize> {
+    pub schema_version: u32,
+    /// What `data` holds, e.g. "processes", "stats" or "kill"
+    pub kind: &'a str,
+    pub tool_version: &'static str,
+    pub generated_at: DateTime<Utc>,
+    pub ok: bool,
+    pub exit_code: i32,
+    pub data: Option<T>,
+    pub error: Option<ErrorBody>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub code: ExitCode,
+    pub message: String,
+}
+
+static EMITTED: AtomicBool = AtomicBool::new(false);
+
+fn envelope<'a, T: Serialize>(
+    kind: &'a str,
+    data: Option<T>,
+    error: Option<(ExitCode, String)>,
+) -> Envelope<'a, T> {
+    let exit_code = error.as_ref().map_or(ExitCode::Success, |(code, _)| *code);
+    Envelope {
+        schema_version: SCHEMA_VERSION,
+        kind,
+        tool_version: env!("CARGO_PKG_VERSION"),
+        generated_at: Utc::now(),
+        ok: exit_code == ExitCode::Success,
+        exit_code: exit_code.code(),
+        data,
+        error: error.map(|(code, message)| ErrorBody { code, message }),
+    }
+}
+
+fn emit<T: Serialize>(envelope: &Envelope<T>) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(envelope)?;
+    // Only one envelope per run, even when stdout is gone (`| head`)
+    EMITTED.store(true, Ordering::SeqCst);
+    writeln!(std::io::stdout().lock(), "{}", json)?;
+    Ok(())
+}
+
+/// Print `data` as the result of a `--json` command
+pub fn print<T: Serialize>(kind: &str, data: &T) -> anyhow::Result<()> {
+    emit(&envelope(kind, Some(data), None))
+}
+
+/// Print `data` for a command that did not fully succeed (a kill that missed processes,
+/// ports found in use), and return the error that ends the process with `exit_code`
+pub fn print_failed<T: Serialize>(
+    kind: &str,
+    data: &T,
+    exit_code: ExitCode,
+    message: impl fmt::Display,
+) -> anyhow::Error {
+    let message = message.to_string();
+    match print_with_error(kind, data, exit_code, &message) {
+        Ok(()) => CommandError::with_code(exit_code, message),
+        Err(e) => e,
+    }
+}
+
+/// Print `data` with `exit_code` and `message` in the envelope, for callers that end
+/// the process themselves
+pub fn print_with_error<T: Serialize>(
+    kind: &str,
+    data: &T,
+    exit_code: ExitCode,
+    message: &str,
+) -> anyhow::Result<()> {
+    emit(&envelope(
+        kind,
+        Some(data),
+        Some((exit_code, message.to_string())),
+    ))
+}
+
+/// Report a failed command and return the exit code. In JSON mode this is an envelope
+/// on stdout, unless the command already printed one.
+pub fn report_error(json: bool, kind: &str, error: &anyhow::Error) -> i32 {
+    let exit_code = exit_code_of(error);
+    if json && !EMITTED.load(Ordering::SeqCst) {
+        let body = envelope::<()>(kind, None, Some((exit_code, format!("{:#}", error))));
+        if emit(&body).is_ok() {
+            return exit_code.code();
+        }
+    }
+    eprintln!("Error: {:#}", error);
+    exit_code.code()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    /// Just enough of JSON Schema for the published schema: `$ref`, `type` (single or
+    /// list), `enum`, `const`, `properties`, `required`, `items`,
+    /// `additionalProperties`, `oneOf` and `allOf` with `if`/`then`
+    fn check(schema: &Value, root: &Value, value: &Value, path: &str) -> Result<(), String> {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let name = reference.trim_start_matches("#/$defs/");
+            return check(&root["$defs"][name], root, value, path);
+        }
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Value::String(t) => vec![t.as_str()],
+                Value::Array(list) => list.iter().filter_map(Value::as_str).collect(),
+                _ => vec![],
+            };
+            let matches = types.iter().any(|t| match *t {
+                "null" => value.is_null(),
+                "boolean" => value.is_boolean(),
+                "integer" => value.is_i64() || value.is_u64(),
+                "number" => value.is_number(),
+                "string" => value.is_string(),
+                "array" => value.is_array(),
+                "object" => value.is_object(),
+                _ => false,
+            });
+            if !matches {
+                return Err(format!("{}: expected {:?}, got {}", path, types, value));
+            }
+        }
+        if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+            if !options.contains(value) {
+                return Err(format!("{}: {} is not one of {:?}", path, value, options));
+            }
+        }
+        if let Some(constant) = schema.get("const") {
+            if constant != value {
+                return Err(format!("{}: expected {}", path, constant));
+            }
+        }
+        if let Some(options) = schema.get("oneOf").and_then(Value::as_array) {
+            let matching = options
+                .iter()
+                .filter(|option| check(option, root, value, path).is_ok())
+                .count();
+            if matching != 1 {
+                return Err(format!("{}: matches {} of oneOf", path, matching));
+            }
+        }
+        for part in schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            match (part.get("if"), part.get("then")) {
+                (Some(condition), Some(then)) => {
+                    if check(condition, root, value, path).is_ok() {
+                        check(then, root, value, path)?;
+                    }
+                }
+                _ => check(part, root, value, path)?,
+            }
+        }
+        if let Some(object) = value.as_object() {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for key in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                let key = key.as_str().unwrap();
+                if !object.contains_key(key) {
+                    return Err(format!("{}: missing required '{}'", path, key));
+                }
+            }
+            for (key, item) in object {
+                let item_path = format!("{}.{}", path, key);
+                match properties.and_then(|p| p.get(key)) {
+                    Some(property) => check(property, root, item, &item_path)?,
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            return Err(format!("{}: unexpected property", item_path))
+                        }
+                        Some(extra @ Value::Object(_)) => check(extra, root, item, &item_path)?,
+                        _ => {}
+                    },
+                }
+            }
+        }
+        if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+            for (index, item) in array.iter().enumerate() {
+                check(items, root, item, &format!("{}[{}]", path, index))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(value: &Value) -> Result<(), String> {
+        let schema: Value = serde_json::from_str(SCHEMA).expect("schema is valid JSON");
+        check(&schema, &schema, value, "$")
+    }
+
+    fn to_value<T: Serialize>(envelope: Envelope<T>) -> Value {
+        serde_json::to_value(&envelope).unwrap()
+    }
+
+    #[test]
+    fn test_envelopes_match_the_published_schema() {
+        use crate::types::*;
+
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        assert_eq!(
+            schema["properties"]["schema_version"]["const"],
+            json!(SCHEMA_VERSION)
+        );
+
+        let process = ProcessInfo {
+            command_line: Some("node node_modules/.bin/vite".to_string()),
+            working_directory: Some("/home/dev/web".to_string()),
+            process_group: Some("Node.js".to_string()),
+            project_name: Some("web".to_string()),
+            framework: Some("Vite".to_string()),
+            cpu_usage: Some(1.5),
+            memory_usage: Some(52_428_800),
+            memory_percentage: Some(0.6),
+            start_time: Some(Utc::now()),
+            systemd_unit: Some(SystemdUnit {
+                name: "web.service".to_string(),
+                user: true,
+            }),
+            ..ProcessInfo::for_test(4242, 5173, "node")
+        };
+        let entry = ProcessHistoryEntry::new(&process, "user".to_string())
+            .with_report(&KillReport::stopped(KillOutcome::ForceKilled, "SIGKILL", 2));
+        let mut history = ProcessHistory::new(100);
+        history.add_entry(entry.clone());
+        history.add_entry(entry.clone());
+
+        validate(&to_value(envelope(
+            "processes",
+            Some(vec![process.clone()]),
+            None,
+        )))
+        .unwrap();
+        validate(&to_value(envelope(
+            "history",
+            Some(vec![entry.clone()]),
+            None,
+        )))
+        .unwrap();
+        validate(&to_value(envelope(
+            "stats",
+            Some(history.get_statistics()),
+            None,
+        )))
+        .unwrap();
+        validate(&to_value(envelope(
+            "offenders",
+            Some(history.get_frequent_offenders(2)),
+            None,
+        )))
+        .unwrap();
+        validate(&to_value(envelope(
+            "patterns",
+            Some(history.get_time_patterns()),
+            None,
+        )))
+        .unwrap();
+        validate(&to_value(envelope(
+            "suggestions",
+            Some(history.get_ignore_suggestions(2)),
+            None,
+        )))
+        .unwrap();
+        validate(&to_value(envelope(
+            "root_cause",
+            Some(history.get_root_cause_analysis()),
+            None,
+        )))
+        .unwrap();
+        validate(&to_value(envelope(
+            "kill",
+            Some(json!({"kills": [entry], "respawns": []})),
+            Some((ExitCode::Partial, "1 of 2 processes survived".to_string())),
+        )))
+        .unwrap();
+        validate(&to_value(envelope::<()>(
+            "audit",
+            None,
+            Some((ExitCode::Usage, "bad flag".to_string())),
+        )))
+        .unwrap();
+
+        // The schema is strict enough to catch a wrong shape
+        let wrong = to_value(envelope("stats", Some(vec![process]), None));
+        assert!(validate(&wrong).is_err());
+        let mut unknown = to_value(envelope("processes", Some(Vec::<ProcessInfo>::new()), None));
+        unknown["kind"] = json!("nonsense");
+        assert!(validate(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_exit_codes_follow_the_error() {
+        assert_eq!(exit_code_of(&anyhow::anyhow!("boom")), ExitCode::Failure);
+        let usage = CommandError::usage("unknown preset 'x'").context("loading presets");
+        assert_eq!(exit_code_of(&usage), ExitCode::Usage);
+        assert_eq!(ExitCode::Conflict.code(), 4);
+        assert_eq!(
+            serde_json::to_value(ExitCode::Partial).unwrap(),
+            json!("partial")
+        );
+    }
+}
@@ -8,12 +8,16 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::{sleep, Duration as TokioDuration};
 
-use crate::process_monitor::ProcessMonitor;
+use crate::process_monitor::{kill_and_record, ProcessMonitor};
 use crate::types::{
-    GuardStatus, PortConflict, PortConflictType, PortReservation, PortResolution, ProcessInfo,
+    GuardStatus, KillActor, PortConflict, PortConflictType, PortReservation, PortResolution,
+    ProcessHistory, ProcessHistoryEntry, ProcessInfo,
 };
 use std::collections::HashSet;
 
+/// Handled conflicts kept for event consumers before the oldest are dropped
+const MAX_RECENT_CONFLICTS: usize = 100;
+
 /// Port Guard daemon that proactively prevents port conflicts
 pub struct PortGuardDaemon {
     watched_ports: Vec<u16>,
@@ -21,6 +25,7 @@ pub struct PortGuardDaemon {
     reservation_file: String,
     auto_resolve: bool,
     conflicts_resolved: Arc<Mutex<usize>>,
+    recent_conflicts: Arc<Mutex<Vec<PortConflict>>>,
     is_running: Arc<Mutex<bool>>,
     process_monitor: Arc<Mutex<ProcessMonitor>>,
     intercepted_commands: Arc<Mutex<HashSet<String>>>,
@@ -41,6 +46,7 @@ impl PortGuardDaemon {
             reservation_file,
             auto_resolve,
             conflicts_resolved: Arc::new(Mutex::new(0)),
+            recent_conflicts: Arc::new(Mutex::new(Vec::new())),
             is_running: Arc::new(Mutex::new(false)),
             process_monitor,
             intercepted_commands: Arc::new(Mutex::new(HashSet::new())),
@@ -120,8 +126,10 @@ impl PortGuardDaemon {
         }
 
         // Check for conflicts
-        for (port, processes) in port_processes {
+        for (port, mut processes) in port_processes {
             if processes.len() > 1 {
+                // The process that has been running longest is the existing one
+                processes.sort_by(|a, b| started_order(a, b));
                 // Multiple processes on the same port - this is a conflict
                 let conflict = PortConflict {
                     port,
@@ -154,15 +162,12 @@ impl PortGuardDaemon {
                 "🔔 Port conflict on {} - manual resolution required",
                 conflict.port
             );
+            self.record_conflict(conflict).await;
             return Ok(());
         }
 
         // Auto-resolve by killing the older process
-        let older_process = if conflict.existing_process.pid < conflict.new_process.pid {
-            &conflict.existing_process
-        } else {
-            &conflict.new_process
-        };
+        let older_process = older_of(&conflict.existing_process, &conflict.new_process);
 
         info!(
             "🔧 Auto-resolving port conflict on {} by killing process {} (PID: {})",
@@ -170,8 +175,9 @@ impl PortGuardDaemon {
         );
 
         // Kill the older process
-        if let Err(e) = self.kill_process(older_process.pid).await {
+        if let Err(e) = self.kill_process(older_process).await {
             warn!("Failed to kill process {}: {}", older_process.pid, e);
+            self.record_conflict(conflict).await;
             return Err(e);
         }
 
@@ -185,9 +191,24 @@ impl PortGuardDaemon {
         }
 
         info!("✅ Port conflict resolved on port {}", conflict.port);
+        self.record_conflict(conflict).await;
         Ok(())
     }
 
+    /// Remember a handled conflict until an event consumer picks it up
+    async fn record_conflict(&self, conflict: PortConflict) {
+        let mut recent = self.recent_conflicts.lock().await;
+        recent.push(conflict);
+        if recent.len() > MAX_RECENT_CONFLICTS {
+            recent.remove(0);
+        }
+    }
+
+    /// Take the conflicts handled since the last call
+    pub async fn drain_conflicts(&self) -> Vec<PortConflict> {
+        std::mem::take(&mut *self.recent_conflicts.lock().await)
+    }
+
     /// Reserve a port for a specific project
     pub async fn reserve_port(
         &self,
@@ -303,65 +324,13 @@ impl PortGuardDaemon {
         Ok(())
     }
 
-    /// Kill a process by PID
-    async fn kill_process(&self, pid: i32) -> Result<()> {
-        #[cfg(target_os = "macos")]
-        {
-            use std::process::Command;
-            let output = Command::new("kill")
-                .arg("-TERM")
-                .arg(pid.to_string())
-                .output()?;
-
-            if !output.status.success() {
-                // Try SIGKILL if SIGTERM fails
-                let output = Command::new("kill")
-                    .arg("-KILL")
-                    .arg(pid.to_string())
-                    .output()?;
-
-                if !output.status.success() {
-                    return Err(anyhow::anyhow!("Failed to kill process {}", pid));
-                }
-            }
-        }
-
-        #[cfg(target_os = "linux")]
-        {
-            use std::process::Command;
-            let output = Command::new("kill")
-                .arg("-TERM")
-                .arg(pid.to_string())
-                .output()?;
-
-            if !output.status.success() {
-                // Try SIGKILL if SIGTERM fails
-                let output = Command::new("kill")
-                    .arg("-KILL")
-                    .arg(pid.to_string())
-                    .output()?;
-
-                if !output.status.success() {
-                    return Err(anyhow::anyhow!("Failed to kill process {}", pid));
-                }
-            }
-        }
-
-        #[cfg(target_os = "windows")]
-        {
-            use std::process::Command;
-            let output = Command::new("taskkill")
-                .arg("/PID")
-                .arg(pid.to_string())
-                .arg("/F")
-                .output()?;
-
-            if !output.status.success() {
-                return Err(anyhow::anyhow!("Failed to kill process {}", pid));
-            }
-        }
-
-        Ok(())
+    /// Kill a conflicting process and record it in the kill history as a guard kill
+    async fn kill_process(&self, process: &ProcessInfo) -> Result<()> {
+        let entry = ProcessHistoryEntry::new(process, "guard".to_string());
+        let force_pid = self.process_monitor.lock().await.force_pid();
+        tokio::task::spawn_blocking(move || kill_and_record(entry, force_pid))
+            .await?
+            .into_result()
     }
 
     /// Intercept a command and check for port conflicts before execution
@@ -489,7 +458,7 @@ impl PortGuardDaemon {
                 process_to_kill.name, process_to_kill.pid, port
             );
 
-            self.kill_process(process_to_kill.pid).await?;
+            self.kill_process(process_to_kill).await?;
 
             // Wait a moment for the process to die
             sleep(TokioDuration::from_millis(500)).await;
@@ -524,6 +493,90 @@ impl PortGuardDaemon {
     }
 }
 
+/// Order two processes by start time, falling back to PID only when a start time is unknown
+fn started_order(a: &ProcessInfo, b: &ProcessInfo) -> std::cmp::Ordering {
+    match (a.start_time, b.start_time) {
+        (Some(a_start), Some(b_start)) => a_start.cmp(&b_start),
+        _ => a.pid.cmp(&b.pid),
+    }
+}
+
+/// The process that has been running longer
+fn older_of<'a>(a: &'a ProcessInfo, b: &'a ProcessInfo) -> &'a ProcessInfo {
+    if started_order(a, b) == std::cmp::Ordering::Greater {
+        b
+    } else {
+        a
+    }
+}
+
+/// Guard status as seen from another process: reservations come from the daemon's
+/// file, and the conflicts it resolved from the guard kills in the history
+pub fn status_from_disk(
+    watched_ports: Vec<u16>,
+    reservation_file: &str,
+    auto_resolve: bool,
+    history: &ProcessHistory,
+) -> Result<GuardStatus> {
+    let mut active_reservations: Vec<PortReservation> = if Path::new(reservation_file).exists() {
+        let content = fs::read_to_string(reservation_file)?;
+        serde_json::from_str::<HashMap<u16, PortReservation>>(&content)?
+            .into_values()
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let now = Utc::now();
+    active_reservations.retain(|r| !matches!(r.expires_at, Some(at) if at <= now));
+    active_reservations.sort_by_key(|r| r.port);
+
+    let guard_kills: Vec<&ProcessHistoryEntry> = history
+        .get_recent_entries(usize::MAX)
+        .iter()
+        .filter(|entry| entry.actor == KillActor::Guard)
+        .collect();
+
+    Ok(GuardStatus {
+        is_active: guard_daemon_running(),
+        watched_ports,
+        active_reservations,
+        conflicts_resolved: guard_kills
+            .iter()
+            .filter(|entry| entry.outcome.succeeded())
+            .count(),
+        last_activity: guard_kills.iter().map(|entry| entry.killed_at).max(),
+        auto_resolve_enabled: auto_resolve,
+    })
+}
+
+/// Whether a port-kill guard daemon (`--guard-mode` or `--guard PORT`) is running
+fn guard_daemon_running() -> bool {
+    #[cfg(unix)]
+    {
+        let Ok(output) = std::process::Command::new("ps")
+            .args(["-eo", "pid=,args="])
+            .output()
+        else {
+            return false;
+        };
+        let own_pid = std::process::id();
+        String::from_utf8_lossy(&output.stdout).lines().any(|line| {
+            let mut parts = line.trim().splitn(2, char::is_whitespace);
+            let pid = parts.next().and_then(|pid| pid.parse::<u32>().ok());
+            let args = parts.next().unwrap_or("");
+            pid != Some(own_pid)
+                && args.contains("port-kill")
+                && args
+                    .split_whitespace()
+                    .any(|arg| arg == "--guard-mode" || arg == "--guard")
+        })
+    }
+    #[cfg(not(unix))]
+    {
+        false
+    }
+}
+
 /// Check if a port is available for binding
 pub async fn is_port_available(port: u16) -> bool {
     use std::net::Ipv4Addr;
@@ -545,3 +598,29 @@ pub async fn find_available_port(start_port: u16, max_attempts: u16) -> Option<u
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: i32, started_secs_ago: Option<i64>) -> ProcessInfo {
+        ProcessInfo {
+            start_time: started_secs_ago.map(|secs| Utc::now() - Duration::seconds(secs)),
+            ..ProcessInfo::for_test(pid, 3000, "node")
+        }
+    }
+
+    #[test]
+    fn test_older_process_uses_start_time_over_pid() {
+        // After PID wrap-around the long-running process can have the larger PID
+        let long_running = process(60000, Some(3600));
+        let fresh = process(120, Some(5));
+        assert_eq!(older_of(&long_running, &fresh).pid, 60000);
+        assert_eq!(older_of(&fresh, &long_running).pid, 60000);
+
+        // Without start times the PID is the only hint left
+        let unknown_a = process(200, None);
+        let unknown_b = process(100, Some(10));
+        assert_eq!(older_of(&unknown_a, &unknown_b).pid, 100);
+    }
+}
@@ -1,17 +1,28 @@
-use anyhow::Result;
+use crate::cli::{PresetAction, PresetArgs};
+use crate::filter_expr::FilterExpr;
+use crate::project_ports::{ProjectPorts, PROJECT_PRESET};
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 /// Represents a port preset configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct PortPreset {
     /// Name of the preset
     pub name: String,
     /// Description of what this preset is for
+    #[serde(default)]
     pub description: String,
+    /// Presets this one builds on, applied in order before its own settings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extends: Vec<String>,
     /// List of ports to monitor
+    #[serde(default)]
     pub ports: Vec<u16>,
     /// Ports to ignore (comma-separated)
     pub ignore_ports: Option<Vec<u16>>,
@@ -23,16 +34,276 @@ pub struct PortPreset {
     pub ignore_groups: Option<Vec<String>>,
     /// Only show processes from specific groups
     pub only_groups: Option<Vec<String>>,
+    /// Only show processes matching this `--where` expression
+    #[serde(rename = "where", default, skip_serializing_if = "Option::is_none")]
+    pub where_expr: Option<String>,
     /// Enable smart filtering
+    #[serde(default)]
     pub smart_filter: bool,
     /// Enable Docker container monitoring
+    #[serde(default)]
     pub docker: bool,
     /// Show process IDs
+    #[serde(default)]
     pub show_pid: bool,
     /// Enable performance metrics
+    #[serde(default)]
     pub performance: bool,
     /// Show project context
+    #[serde(default)]
     pub show_context: bool,
+    /// Port Guard settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guard: Option<GuardSettings>,
+    /// How kills are carried out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kill: Option<KillPolicy>,
+    /// Endpoint monitoring settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<EndpointSettings>,
+}
+
+/// Port Guard settings a preset can carry (`--guard-mode`, `--guard-ports`, ...)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct GuardSettings {
+    pub enabled: Option<bool>,
+    pub ports: Option<Vec<u16>>,
+    pub auto_resolve: Option<bool>,
+    pub reservation_file: Option<String>,
+    pub intercept_commands: Option<bool>,
+}
+
+/// Kill behaviour a preset can carry (`--safe`, `--respawn-window`, ...)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct KillPolicy {
+    pub safe: Option<bool>,
+    pub respawn_window: Option<u64>,
+    pub stop_supervisor: Option<bool>,
+    pub force_pid: Option<bool>,
+}
+
+/// Endpoint monitoring settings a preset can carry (`--monitor-endpoint`, ...)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EndpointSettings {
+    pub url: Option<String>,
+    pub auth: Option<String>,
+    pub fields: Option<Vec<String>>,
+    pub include_audit: Option<bool>,
+    pub send_interval: Option<u64>,
+    pub scan_interval: Option<u64>,
+    pub retries: Option<u32>,
+    pub timeout: Option<u64>,
+}
+
+/// The kind of value a preset setting holds, for validation
+#[derive(Clone, Copy)]
+enum FieldKind {
+    Text,
+    Flag,
+    Ports,
+    Names,
+    Url,
+    /// A `--where` filter expression
+    Expr,
+    /// A whole number up to the given maximum
+    Number(u64),
+    Table(&'static [(&'static str, FieldKind)]),
+}
+
+const GUARD_FIELDS: &[(&str, FieldKind)] = &[
+    ("enabled", FieldKind::Flag),
+    ("ports", FieldKind::Ports),
+    ("auto_resolve", FieldKind::Flag),
+    ("reservation_file", FieldKind::Text),
+    ("intercept_commands", FieldKind::Flag),
+];
+
+const KILL_FIELDS: &[(&str, FieldKind)] = &[
+    ("safe", FieldKind::Flag),
+    ("respawn_window", FieldKind::Number(3600)),
+    ("stop_supervisor", FieldKind::Flag),
+    ("force_pid", FieldKind::Flag),
+];
+
+const ENDPOINT_FIELDS: &[(&str, FieldKind)] = &[
+    ("url", FieldKind::Url),
+    ("auth", FieldKind::Text),
+    ("fields", FieldKind::Names),
+    ("include_audit", FieldKind::Flag),
+    ("send_interval", FieldKind::Number(u64::MAX)),
+    ("scan_interval", FieldKind::Number(u64::MAX)),
+    ("retries", FieldKind::Number(u32::MAX as u64)),
+    ("timeout", FieldKind::Number(u64::MAX)),
+];
+
+const PRESET_FIELDS: &[(&str, FieldKind)] = &[
+    ("name", FieldKind::Text),
+    ("description", FieldKind::Text),
+    ("extends", FieldKind::Names),
+    ("ports", FieldKind::Ports),
+    ("ignore_ports", FieldKind::Ports),
+    ("ignore_processes", FieldKind::Names),
+    ("ignore_patterns", FieldKind::Names),
+    ("ignore_groups", FieldKind::Names),
+    ("only_groups", FieldKind::Names),
+    ("where", FieldKind::Expr),
+    ("smart_filter", FieldKind::Flag),
+    ("docker", FieldKind::Flag),
+    ("show_pid", FieldKind::Flag),
+    ("performance", FieldKind::Flag),
+    ("show_context", FieldKind::Flag),
+    ("guard", FieldKind::Table(GUARD_FIELDS)),
+    ("kill", FieldKind::Table(KILL_FIELDS)),
+    ("endpoint", FieldKind::Table(ENDPOINT_FIELDS)),
+];
+
+/// Check a preset as written (before inheritance) against the schema, returning
+/// one message per problem. `null` stands for "not set" everywhere.
+fn check_fields(
+    path: &str,
+    fields: &[(&str, FieldKind)],
+    object: &Map<String, Value>,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (key, value) in object {
+        let field = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        let Some((_, kind)) = fields.iter().find(|(name, _)| name == key) else {
+            let hint = fields
+                .iter()
+                .map(|(name, _)| *name)
+                .filter(|name| edit_distance(name, key) <= 2)
+                .min_by_key(|name| edit_distance(name, key))
+                .map(|name| format!(" (did you mean `{}`?)", name))
+                .unwrap_or_default();
+            errors.push(format!("unknown setting `{}`{}", field, hint));
+            continue;
+        };
+        if value.is_null() {
+            continue;
+        }
+        let expected = match kind {
+            FieldKind::Text if !value.is_string() => Some("a string".to_string()),
+            FieldKind::Flag if !value.is_boolean() => Some("true or false".to_string()),
+            FieldKind::Ports => match value.as_array() {
+                Some(items) if items.iter().all(is_port) => None,
+                _ => Some("a list of port numbers (1-65535)".to_string()),
+            },
+            FieldKind::Names => match value {
+                // `extends = "node"` is shorthand for `extends = ["node"]`
+                Value::String(_) if key == "extends" => None,
+                Value::Array(items) if items.iter().all(Value::is_string) => None,
+                _ => Some("a list of strings".to_string()),
+            },
+            FieldKind::Url => match value.as_str() {
+                Some(url) if url.starts_with("http://") || url.starts_with("https://") => None,
+                _ => Some("an http:// or https:// URL".to_string()),
+            },
+            FieldKind::Expr => match value.as_str() {
+                Some(expr) => {
+                    if let Err(e) = FilterExpr::parse(expr) {
+                        errors.push(format!(
+                            "`{}` is not a valid expression: {} at column {}",
+                            field,
+                            e.message,
+                            e.position + 1
+                        ));
+                    }
+                    None
+                }
+                None => Some("a filter expression string".to_string()),
+            },
+            FieldKind::Number(max) => match value.as_u64() {
+                Some(n) if n <= *max => None,
+                _ if *max == u64::MAX => Some("a whole number".to_string()),
+                _ => Some(format!("a whole number up to {}", max)),
+            },
+            FieldKind::Table(sub) => match value.as_object() {
+                Some(table) => {
+                    errors.extend(check_fields(&field, sub, table));
+                    None
+                }
+                None => Some("a table of settings".to_string()),
+            },
+            _ => None,
+        };
+        if let Some(expected) = expected {
+            errors.push(format!("`{}` must be {}, got {}", field, expected, value));
+        }
+    }
+    errors
+}
+
+fn is_port(value: &Value) -> bool {
+    value.as_u64().is_some_and(|p| (1..=65535).contains(&p))
+}
+
+/// Levenshtein distance, for "did you mean" hints on misspelt settings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { prev } else { prev + 1 };
+            prev = row[j + 1];
+            row[j + 1] = cost.min(row[j] + 1).min(prev + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Layer `overlay` on top of `base`: lists are combined, tables merged key by key,
+/// `where` expressions and-ed, anything else replaced. Unset (`null`) values leave
+/// the base alone.
+fn merge_preset_values(base: &mut Map<String, Value>, overlay: &Map<String, Value>) {
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (_, Value::Null) => {}
+            (Some(Value::String(existing)), Value::String(expr)) if key == "where" => {
+                *existing = format!("({}) and ({})", existing, expr);
+            }
+            (Some(Value::Array(existing)), Value::Array(items)) => {
+                for item in items {
+                    if !existing.contains(item) {
+                        existing.push(item.clone());
+                    }
+                }
+            }
+            (Some(Value::Object(existing)), Value::Object(table)) => {
+                merge_preset_values(existing, table);
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// The `extends` of a raw preset; a single name is allowed as shorthand
+fn extends_of(object: &Map<String, Value>) -> Vec<String> {
+    match object.get("extends") {
+        Some(Value::String(name)) => vec![name.clone()],
+        Some(Value::Array(names)) => names
+            .iter()
+            .filter_map(|n| n.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn valid_preset_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
 }
 
 impl PortPreset {
@@ -41,17 +312,22 @@ impl PortPreset {
         Self {
             name,
             description,
+            extends: Vec::new(),
             ports,
             ignore_ports: None,
             ignore_processes: None,
             ignore_patterns: None,
             ignore_groups: None,
             only_groups: None,
+            where_expr: None,
             smart_filter: false,
             docker: false,
             show_pid: false,
             performance: false,
             show_context: false,
+            guard: None,
+            kill: None,
+            endpoint: None,
         }
     }
 
@@ -68,17 +344,22 @@ impl PortPreset {
         Self {
             name,
             description,
+            extends: Vec::new(),
             ports,
             ignore_ports,
             ignore_processes,
             ignore_patterns,
             ignore_groups,
             only_groups: None,
+            where_expr: None,
             smart_filter: false,
             docker: false,
             show_pid: false,
             performance: false,
             show_context: false,
+            guard: None,
+            kill: None,
+            endpoint: None,
         }
     }
 
@@ -92,24 +373,37 @@ impl PortPreset {
         Self {
             name,
             description,
+            extends: Vec::new(),
             ports,
             ignore_ports: None,
             ignore_processes: None,
             ignore_patterns: None,
             ignore_groups: None,
             only_groups: None,
+            where_expr: None,
             smart_filter,
             docker: false,
             show_pid: false,
             performance: false,
             show_context: false,
+            guard: None,
+            kill: None,
+            endpoint: None,
         }
     }
 }
 
 /// Manages port presets
 pub struct PresetManager {
+    /// Built-in and user presets, with inheritance resolved
     presets: HashMap<String, PortPreset>,
+    /// User presets exactly as stored in `presets.json`, `extends` and all
+    user: BTreeMap<String, Value>,
+    /// Stored presets that failed validation, with their problems
+    invalid: BTreeMap<String, Vec<String>>,
+    /// Implicit `project` preset, discovered from the working directory the first time
+    /// it is asked for; never saved
+    project: OnceLock<Option<PortPreset>>,
     config_path: String,
 }
 
@@ -121,11 +415,15 @@ impl PresetManager {
 
         Self {
             presets: HashMap::new(),
+            user: BTreeMap::new(),
+            invalid: BTreeMap::new(),
+            project: OnceLock::new(),
             config_path,
         }
     }
 
-    /// Load presets from file
+    /// Load presets from file. Stored presets that fail validation are skipped with a
+    /// warning; asking for one by name reports its problems (see `require_preset`).
     pub fn load_presets(&mut self) -> Result<()> {
         // First, load default presets
         self.load_default_presets();
@@ -133,30 +431,179 @@ impl PresetManager {
         // Then try to load user presets from file
         if Path::new(&self.config_path).exists() {
             let content = fs::read_to_string(&self.config_path)?;
-            let user_presets: HashMap<String, PortPreset> = serde_json::from_str(&content)?;
+            self.user = serde_json::from_str(&content)
+                .map_err(|e| anyhow!("{}: {}", self.config_path, e))?;
 
-            // Merge user presets (they override defaults)
-            for (name, preset) in user_presets {
-                self.presets.insert(name, preset);
+            // Resolve against the built-ins first, then merge (user presets override defaults)
+            let mut resolved = Vec::new();
+            self.invalid.clear();
+            for name in self.user.keys() {
+                match self.resolve_user_preset(name) {
+                    Ok(preset) => resolved.push(preset),
+                    Err(problems) => {
+                        eprintln!(
+                            "⚠️  Skipping invalid preset '{}' in {}: {}",
+                            name,
+                            self.config_path,
+                            problems.join("; ")
+                        );
+                        self.invalid.insert(name.clone(), problems);
+                    }
+                }
+            }
+            for preset in resolved {
+                self.presets.insert(preset.name.clone(), preset);
             }
         }
 
         Ok(())
     }
 
-    /// Save presets to file
+    /// Save user presets to file
     pub fn save_presets(&self) -> Result<()> {
         // Create directory if it doesn't exist
         if let Some(parent) = Path::new(&self.config_path).parent() {
             fs::create_dir_all(parent)?;
         }
 
-        let content = serde_json::to_string_pretty(&self.presets)?;
+        let content = serde_json::to_string_pretty(&self.user)?;
         fs::write(&self.config_path, content)?;
 
         Ok(())
     }
 
+    /// Validate a stored user preset and resolve what it inherits
+    fn resolve_user_preset(&self, name: &str) -> std::result::Result<PortPreset, Vec<String>> {
+        let Some(Value::Object(object)) = self.user.get(name) else {
+            return Err(vec!["must be a JSON object".to_string()]);
+        };
+        let mut errors = check_fields("", PRESET_FIELDS, object);
+        if !valid_preset_name(name) {
+            errors.push("names may only contain letters, digits, '-', '_' and '.'".to_string());
+        }
+        if let Some(Value::String(stored)) = object.get("name") {
+            if stored != name {
+                errors.push(format!(
+                    "`name` is '{}' but the preset is saved as '{}'",
+                    stored, name
+                ));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let merged = self
+            .resolve_value(name, &mut Vec::new())
+            .map_err(|e| vec![e])?;
+        let preset: PortPreset =
+            serde_json::from_value(Value::Object(merged)).map_err(|e| vec![e.to_string()])?;
+        if preset.ports.is_empty() {
+            return Err(vec![
+                "has no ports: set `ports` or extend a preset that has them".to_string(),
+            ]);
+        }
+        Ok(preset)
+    }
+
+    /// A preset's settings with everything it extends layered underneath.
+    /// `chain` holds the presets being resolved, to catch cycles.
+    fn resolve_value(
+        &self,
+        name: &str,
+        chain: &mut Vec<String>,
+    ) -> std::result::Result<Map<String, Value>, String> {
+        if let Some(start) = chain.iter().position(|n| n == name) {
+            let mut cycle = chain[start..].to_vec();
+            cycle.push(name.to_string());
+            return Err(format!("`extends` forms a cycle: {}", cycle.join(" → ")));
+        }
+        let own = match self.user.get(name) {
+            Some(Value::Object(object)) => object.clone(),
+            Some(_) => return Err(format!("extends '{}', which is not a JSON object", name)),
+            None => {
+                let preset = self
+                    .presets
+                    .get(name)
+                    .or_else(|| self.project_preset(name))
+                    .ok_or_else(|| format!("extends unknown preset '{}'", name))?;
+                match serde_json::to_value(preset) {
+                    Ok(Value::Object(object)) => object,
+                    _ => return Err(format!("could not read preset '{}'", name)),
+                }
+            }
+        };
+
+        let parents = extends_of(&own);
+        chain.push(name.to_string());
+        let mut merged = Map::new();
+        for parent in &parents {
+            let mut base = self.resolve_value(parent, chain)?;
+            base.remove("extends");
+            base.remove("description");
+            merge_preset_values(&mut merged, &base);
+        }
+        chain.pop();
+
+        merge_preset_values(&mut merged, &own);
+        merged.insert("name".to_string(), Value::String(name.to_string()));
+        merged.insert("extends".to_string(), serde_json::json!(parents));
+        if !own.contains_key("description") && !parents.is_empty() {
+            merged.insert(
+                "description".to_string(),
+                Value::String(format!("Extends {}", parents.join(" + "))),
+            );
+        }
+        Ok(merged)
+    }
+
+    /// Add a preset read from a file. The name comes from `name`, the file's
+    /// `name` field or `fallback_name`, in that order. Returns the name and the
+    /// settings left out of the import (see `IMPORT_DROPPED`).
+    pub fn import_preset(
+        &mut self,
+        value: Value,
+        name: Option<&str>,
+        fallback_name: &str,
+        force: bool,
+    ) -> Result<(String, Vec<String>)> {
+        let Value::Object(mut object) = value else {
+            return Err(anyhow!("a preset file must hold a single JSON object"));
+        };
+        let name = name
+            .map(String::from)
+            .or_else(|| object.get("name").and_then(Value::as_str).map(String::from))
+            .unwrap_or_else(|| fallback_name.to_string());
+        if !force && self.get_preset(&name).is_some() {
+            return Err(anyhow!(
+                "preset '{}' already exists; pass --force to replace it",
+                name
+            ));
+        }
+
+        object.insert("name".to_string(), Value::String(name.clone()));
+        let dropped = drop_endpoint_target(&mut object);
+        let previous = self.user.insert(name.clone(), Value::Object(object));
+        match self.resolve_user_preset(&name) {
+            Ok(preset) => {
+                self.presets.insert(name.clone(), preset);
+                self.invalid.remove(&name);
+                Ok((name, dropped))
+            }
+            Err(errors) => {
+                match previous {
+                    Some(previous) => self.user.insert(name.clone(), previous),
+                    None => self.user.remove(&name),
+                };
+                Err(anyhow!(
+                    "invalid preset '{}':\n  {}",
+                    name,
+                    errors.join("\n  ")
+                ))
+            }
+        }
+    }
+
     /// Load default presets
     fn load_default_presets(&mut self) {
         // Development preset - common dev ports
@@ -252,23 +699,71 @@ impl PresetManager {
         self.presets.insert("minimal".to_string(), minimal_preset);
     }
 
-    /// Get a preset by name
+    /// A preset by name, or why it can't be used: unknown, or stored but invalid
+    pub fn require_preset(&self, name: &str) -> Result<&PortPreset> {
+        if let Some(problems) = self.invalid.get(name) {
+            return Err(anyhow!(
+                "invalid preset '{}' in {}:\n  {}",
+                name,
+                self.config_path,
+                problems.join("\n  ")
+            ));
+        }
+        self.get_preset(name).ok_or_else(|| {
+            anyhow!(
+                "Preset '{}' not found. Use --list-presets to see available presets.",
+                name
+            )
+        })
+    }
+
+    /// Get a preset by name; a saved `project` preset wins over the discovered one
     pub fn get_preset(&self, name: &str) -> Option<&PortPreset> {
-        self.presets.get(name)
+        self.presets.get(name).or_else(|| self.project_preset(name))
+    }
+
+    /// The discovered `project` preset when `name` asks for it; scans the project only then
+    fn project_preset(&self, name: &str) -> Option<&PortPreset> {
+        if name != PROJECT_PRESET {
+            return None;
+        }
+        self.project
+            .get_or_init(|| ProjectPorts::current().map(|project| project.to_preset()))
+            .as_ref()
     }
 
     /// Get all preset names
     pub fn get_preset_names(&self) -> Vec<String> {
-        self.presets.keys().cloned().collect()
+        let mut names: Vec<String> = self.presets.keys().cloned().collect();
+        if let Some(project) = self.project_preset(PROJECT_PRESET) {
+            if !self.presets.contains_key(&project.name) {
+                names.push(project.name.clone());
+            }
+        }
+        names
+    }
+
+    /// Every preset, sorted by name
+    pub fn sorted_presets(&self) -> Vec<&PortPreset> {
+        let mut names = self.get_preset_names();
+        names.sort();
+        names
+            .iter()
+            .filter_map(|name| self.get_preset(name))
+            .collect()
     }
 
     /// Add or update a preset
     pub fn add_preset(&mut self, preset: PortPreset) {
+        if let Ok(value) = serde_json::to_value(&preset) {
+            self.user.insert(preset.name.clone(), value);
+        }
         self.presets.insert(preset.name.clone(), preset);
     }
 
     /// Remove a preset
     pub fn remove_preset(&mut self, name: &str) -> Option<PortPreset> {
+        self.user.remove(name);
         self.presets.remove(name)
     }
 
@@ -279,12 +774,15 @@ impl PresetManager {
         output.push_str(&"=".repeat(50));
         output.push('\n');
 
-        let mut preset_names: Vec<_> = self.presets.keys().collect();
+        let mut preset_names = self.get_preset_names();
         preset_names.sort();
 
-        for name in preset_names {
-            if let Some(preset) = self.presets.get(name) {
+        for name in &preset_names {
+            if let Some(preset) = self.get_preset(name) {
                 output.push_str(&format!("• {}: {}\n", name, preset.description));
+                if !preset.extends.is_empty() {
+                    output.push_str(&format!("  Extends: {}\n", preset.extends.join(", ")));
+                }
                 output.push_str(&format!(
                     "  Ports: {}\n",
                     preset
@@ -334,6 +832,125 @@ impl PresetManager {
     }
 }
 
+/// Endpoint settings an imported preset may not bring along: a shared file must not
+/// decide where this machine's reports go, or what credentials they carry
+const IMPORT_DROPPED: &[&str] = &["url", "auth"];
+
+/// Remove `IMPORT_DROPPED` from a preset's `endpoint` table, returning the keys removed
+fn drop_endpoint_target(object: &mut Map<String, Value>) -> Vec<String> {
+    let Some(Value::Object(endpoint)) = object.get_mut("endpoint") else {
+        return Vec::new();
+    };
+    let dropped: Vec<String> = IMPORT_DROPPED
+        .iter()
+        .filter(|key| endpoint.remove(**key).is_some())
+        .map(|key| format!("endpoint.{}", key))
+        .collect();
+    if endpoint.is_empty() {
+        object.remove("endpoint");
+    }
+    dropped
+}
+
+/// `preset import` reads local files only: a path or a `file://` URL
+fn local_preset_path(source: &str) -> Result<PathBuf> {
+    if let Some(path) = source.strip_prefix("file://") {
+        return Ok(PathBuf::from(
+            path.strip_prefix("localhost").unwrap_or(path),
+        ));
+    }
+    if source.contains("://") {
+        return Err(anyhow!(
+            "only local files can be imported; download {} first",
+            source
+        ));
+    }
+    Ok(PathBuf::from(source))
+}
+
+fn read_preset_file(source: &str) -> Result<(Value, String)> {
+    let path = local_preset_path(source)?;
+    let content = fs::read_to_string(&path)
+        .map_err(|e| anyhow!("failed to read {}: {}", path.display(), e))?;
+    let value = serde_json::from_str(&content).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().trim_end_matches(".preset").to_string())
+        .unwrap_or_default();
+    Ok((value, stem))
+}
+
+/// Run `port-kill preset`: export, import or validate presets
+pub fn run(args: &PresetArgs) -> Result<()> {
+    let mut manager = PresetManager::new();
+    manager.load_presets()?;
+
+    match &args.action {
+        PresetAction::Export(export) => {
+            let mut preset = manager.require_preset(&export.name)?.clone();
+            // Exported presets are standalone: inherited settings are already merged in
+            preset.extends.clear();
+            let json = serde_json::to_string_pretty(&preset)?;
+            match &export.output {
+                Some(path) => {
+                    fs::write(path, format!("{}\n", json))?;
+                    println!("✅ Exported preset '{}' to {}", preset.name, path.display());
+                }
+                None => println!("{}", json),
+            }
+        }
+        PresetAction::Import(import) => {
+            let (value, stem) = read_preset_file(&import.source)?;
+            let (name, dropped) =
+                manager.import_preset(value, import.name.as_deref(), &stem, import.force)?;
+            manager.save_presets()?;
+            println!(
+                "✅ Imported preset '{}' into {}",
+                name,
+                manager.get_config_path()
+            );
+            if !dropped.is_empty() {
+                println!(
+                    "⚠️  Left out {}: set endpoint targets yourself with --save-preset or the config file",
+                    dropped.join(", ")
+                );
+            }
+        }
+        PresetAction::Validate(validate) => match &validate.file {
+            Some(source) => {
+                let (value, stem) = read_preset_file(source)?;
+                let (name, dropped) = manager.import_preset(value, None, &stem, true)?;
+                println!("✅ {} is a valid preset ('{}')", source, name);
+                if !dropped.is_empty() {
+                    println!("ℹ️  Importing it would leave out {}", dropped.join(", "));
+                }
+            }
+            None if !manager.invalid.is_empty() => {
+                let problems: Vec<String> = manager
+                    .invalid
+                    .iter()
+                    .flat_map(|(name, problems)| {
+                        problems
+                            .iter()
+                            .map(move |problem| format!("preset '{}': {}", name, problem))
+                    })
+                    .collect();
+                return Err(anyhow!(
+                    "invalid presets in {}:\n  {}",
+                    manager.get_config_path(),
+                    problems.join("\n  ")
+                ));
+            }
+            None => println!(
+                "✅ {}: {} user preset(s) are valid",
+                manager.get_config_path(),
+                manager.user.len()
+            ),
+        },
+    }
+    Ok(())
+}
+
 impl Default for PresetManager {
     fn default() -> Self {
         Self::new()
@@ -386,4 +1003,146 @@ mod tests {
         assert!(names.contains(&"dev".to_string()));
         assert!(names.contains(&"system".to_string()));
     }
+
+    fn manager_with(user: Value) -> PresetManager {
+        let mut manager = PresetManager::new();
+        manager.load_default_presets();
+        manager.user = serde_json::from_value(user).unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_preset_extends_merges_lists_and_settings() {
+        let manager = manager_with(serde_json::json!({
+            "api": {
+                "extends": "node",
+                "ignore_processes": ["nodemon"],
+                "kill": { "safe": true, "respawn_window": 10 }
+            },
+            "stack": {
+                "extends": ["api", "database"],
+                "description": "API and its databases",
+                "docker": true
+            }
+        }));
+
+        let api = manager.resolve_user_preset("api").unwrap();
+        assert_eq!(api.ports, vec![3000, 5000, 8000, 8080, 9000]);
+        assert_eq!(api.extends, vec!["node".to_string()]);
+        assert_eq!(api.description, "Extends node");
+        assert_eq!(api.ignore_processes, Some(vec!["nodemon".to_string()]));
+
+        let stack = manager.resolve_user_preset("stack").unwrap();
+        assert_eq!(stack.description, "API and its databases");
+        assert!(stack.ports.contains(&3000) && stack.ports.contains(&5432));
+        assert!(stack.docker);
+        assert_eq!(stack.kill.as_ref().and_then(|k| k.respawn_window), Some(10));
+
+        let mut args = crate::cli::Args::default();
+        args.apply_preset(&stack);
+        assert!(args.safe);
+        assert_eq!(args.respawn_window, 10);
+    }
+
+    #[test]
+    fn test_preset_validation_errors() {
+        let mut manager = manager_with(serde_json::json!({
+            "typo": {
+                "ports": [3000, 70000],
+                "ignore_proceses": ["Chrome"],
+                "kill": { "respawn_window": 99999 },
+                "endpoint": { "url": "ftp://example.com" }
+            },
+            "a": { "extends": "b" },
+            "b": { "extends": ["a"], "ports": [1] },
+            "orphan": { "extends": "nope" },
+            "empty": { "description": "nothing to watch" }
+        }));
+
+        let errors = manager.resolve_user_preset("typo").unwrap_err();
+        let expect = [
+            "`ports` must be a list of port numbers (1-65535)",
+            "unknown setting `ignore_proceses` (did you mean `ignore_processes`?)",
+            "`kill.respawn_window` must be a whole number up to 3600, got 99999",
+            "`endpoint.url` must be an http:// or https:// URL",
+        ];
+        for message in expect {
+            assert!(
+                errors.iter().any(|e| e.contains(message)),
+                "missing '{}' in {:?}",
+                message,
+                errors
+            );
+        }
+        assert_eq!(
+            manager.resolve_user_preset("a").unwrap_err(),
+            vec!["`extends` forms a cycle: a → b → a".to_string()]
+        );
+        assert_eq!(
+            manager.resolve_user_preset("orphan").unwrap_err(),
+            vec!["extends unknown preset 'nope'".to_string()]
+        );
+        assert!(manager.resolve_user_preset("empty").unwrap_err()[0].contains("has no ports"));
+
+        // Imports are checked the same way and refuse to clobber without --force
+        let preset = serde_json::json!({ "name": "dev", "ports": [4000] });
+        assert!(manager
+            .import_preset(preset.clone(), None, "file", false)
+            .is_err());
+        assert_eq!(
+            manager.import_preset(preset, None, "file", true).unwrap().0,
+            "dev"
+        );
+        assert_eq!(manager.get_preset("dev").unwrap().ports, vec![4000]);
+        let bad = serde_json::json!({ "ports": [4000], "smart_filter": "yes" });
+        assert!(manager.import_preset(bad, None, "shared", false).is_err());
+        assert!(!manager.user.contains_key("shared"));
+    }
+
+    #[test]
+    fn test_invalid_stored_preset_is_skipped_until_requested() {
+        let dir =
+            std::env::temp_dir().join(format!("port-kill-presets-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("presets.json");
+        fs::write(
+            &path,
+            r#"{"api": {"ports": [4000]}, "broken": {"ports": [70000]}}"#,
+        )
+        .unwrap();
+        let mut manager = PresetManager::new();
+        manager.config_path = path.display().to_string();
+
+        manager.load_presets().unwrap();
+        assert_eq!(manager.require_preset("api").unwrap().ports, vec![4000]);
+        assert!(manager.get_preset("broken").is_none());
+        let err = manager.require_preset("broken").unwrap_err().to_string();
+        assert!(err.contains("invalid preset 'broken'"), "{}", err);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_import_leaves_out_endpoint_target() {
+        let mut manager = PresetManager::new();
+        manager.load_default_presets();
+        let shared = serde_json::json!({
+            "name": "shared",
+            "ports": [4000],
+            "endpoint": { "url": "https://collector.example", "auth": "Bearer x", "retries": 5 }
+        });
+
+        let (name, dropped) = manager.import_preset(shared, None, "file", false).unwrap();
+        assert_eq!(name, "shared");
+        assert_eq!(dropped, vec!["endpoint.url", "endpoint.auth"]);
+        let endpoint = manager
+            .get_preset("shared")
+            .unwrap()
+            .endpoint
+            .clone()
+            .unwrap();
+        assert_eq!((endpoint.url, endpoint.auth), (None, None));
+        assert_eq!(endpoint.retries, Some(5));
+        assert!(manager.user["shared"]["endpoint"].get("url").is_none());
+    }
 }
@@ -1,6 +1,9 @@
-use crate::smart_filter::{FilterStats, SmartFilter};
+use crate::smart_filter::{FilterStats, FilteredProcess, SmartFilter};
 use crate::system_monitor::SystemMonitor;
-use crate::types::{ProcessHistory, ProcessHistoryEntry, ProcessInfo, ProcessUpdate};
+use crate::types::{
+    KillOutcome, KillReport, ProcessHistory, ProcessHistoryEntry, ProcessInfo, ProcessUpdate,
+    SystemdUnit,
+};
 use anyhow::{Context, Result};
 use crossbeam_channel::Sender;
 use log::{error, info, warn};
@@ -15,6 +18,42 @@ use tokio::time::sleep;
 
 const MONITORING_INTERVAL: Duration = Duration::from_secs(2);
 
+/// The unit a kill of `pid` should stop instead: only when `pid` is the main process of a
+/// persistent unit, and never with `--force-pid`
+fn unit_to_stop(pid: i32, force_pid: bool) -> Option<SystemdUnit> {
+    if force_pid {
+        return None;
+    }
+    crate::system_monitor::systemd_unit(pid)
+        .filter(|unit| crate::system_monitor::unit_owns_pid(unit, pid))
+}
+
+/// Stop a systemd unit; killing its main PID would only make systemd restart it
+fn stop_systemd_unit(pid: i32, unit: &SystemdUnit) -> KillReport {
+    info!(
+        "Process {} belongs to {}, stopping the unit",
+        pid,
+        unit.describe()
+    );
+    let command = unit.stop_command();
+    let output = match Command::new(&command[0]).args(&command[1..]).output() {
+        Ok(output) => output,
+        Err(e) => return KillReport::failed(0, format!("Failed to execute systemctl: {}", e)),
+    };
+    if output.status.success() {
+        KillReport::stopped(KillOutcome::Terminated, "systemctl stop", 1)
+    } else {
+        KillReport::failed(
+            1,
+            format!(
+                "`{}` failed: {}",
+                command.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        )
+    }
+}
+
 pub struct ProcessMonitor {
     update_sender: Sender<ProcessUpdate>,
     current_processes: HashMap<u16, ProcessInfo>,
@@ -23,8 +62,12 @@ pub struct ProcessMonitor {
     verbose: bool,
     history: ProcessHistory,
     smart_filter: Option<SmartFilter>,
+    /// Listeners the smart filter hid on the last scan
+    filtered_processes: Vec<FilteredProcess>,
     system_monitor: SystemMonitor,
     performance_enabled: bool,
+    /// `--force-pid`: signal systemd-managed processes instead of stopping their unit
+    force_pid: bool,
 }
 
 impl ProcessMonitor {
@@ -43,8 +86,10 @@ impl ProcessMonitor {
             history: ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), 100)
                 .unwrap_or_else(|_| ProcessHistory::new(100)),
             smart_filter: None,
+            filtered_processes: Vec::new(),
             system_monitor: SystemMonitor::new(),
             performance_enabled: false,
+            force_pid: false,
         })
     }
 
@@ -64,8 +109,10 @@ impl ProcessMonitor {
             history: ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), 100)
                 .unwrap_or_else(|_| ProcessHistory::new(100)),
             smart_filter: Some(smart_filter),
+            filtered_processes: Vec::new(),
             system_monitor: SystemMonitor::new(),
             performance_enabled: false,
+            force_pid: false,
         })
     }
 
@@ -86,11 +133,22 @@ impl ProcessMonitor {
             history: ProcessHistory::load_from_file(&ProcessHistory::get_history_file_path(), 100)
                 .unwrap_or_else(|_| ProcessHistory::new(100)),
             smart_filter,
+            filtered_processes: Vec::new(),
             system_monitor: SystemMonitor::new(),
             performance_enabled,
+            force_pid: false,
         })
     }
 
+    /// Signal systemd-managed processes directly instead of stopping their unit
+    pub fn set_force_pid(&mut self, force: bool) {
+        self.force_pid = force;
+    }
+
+    pub fn force_pid(&self) -> bool {
+        self.force_pid
+    }
+
     pub async fn start_monitoring(&mut self) -> Result<()> {
         let port_description = if self.ports_to_monitor.len() <= 10 {
             format!(
@@ -136,95 +194,32 @@ impl ProcessMonitor {
         }
     }
 
+    /// Scan and remember the result, so later kills can record what they stopped
+    pub async fn refresh_processes(&mut self) -> Result<HashMap<u16, ProcessInfo>> {
+        let processes = self.scan_processes().await?;
+        self.current_processes = processes.clone();
+        Ok(processes)
+    }
+
     pub async fn scan_processes(&mut self) -> Result<HashMap<u16, ProcessInfo>> {
         // Use the optimized batch scanning approach instead of iterating one by one
         let args = crate::cli::Args {
-            start_port: 2000,
-            end_port: 6000,
-            ports: None,
-            ignore_ports: None,
-            ignore_processes: None,
-            ignore_patterns: None,
-            ignore_groups: None,
-            smart_filter: false,
-            only_groups: None,
-            console: false,
             verbose: self.verbose,
             docker: self.docker_enabled,
-            show_pid: false,
-            log_level: crate::cli::LogLevel::Info,
-            show_history: false,
-            clear_history: false,
-            show_filters: false,
             performance: self.performance_enabled,
-            show_context: false,
-            kill_all: false,
-            kill_group: None,
-            kill_project: None,
-            restart: false,
-            show_tree: false,
-            json: false,
-            reset: false,
-            show_offenders: false,
-            show_patterns: false,
-            show_suggestions: false,
-            show_stats: false,
-            show_root_cause: false,
-            guard_mode: false,
-            guard_ports: "3000,3001,3002,8000,8080,9000".to_string(),
-            auto_resolve: false,
-            reservation_file: "~/.port-kill/reservations.json".to_string(),
-            intercept_commands: false,
-            reserve_port: None,
-            project_name: None,
-            process_name: None,
-            audit: false,
-            security_mode: false,
-            suspicious_ports: "8444,4444,9999,14444,5555,6666,7777".to_string(),
-            baseline_file: None,
-            suspicious_only: false,
-            remote: None,
-            monitor_endpoint: None,
-            send_interval: 30,
-            scan_interval: 2,
-            endpoint_auth: None,
-            endpoint_fields: None,
-            endpoint_include_audit: false,
-            endpoint_retries: 3,
-            endpoint_timeout: 10,
-            script: None,
-            script_file: None,
-            script_lang: "js".to_string(),
-            clear: None,
-            guard: None,
-            allow: None,
-            kill: None,
-            kill_file: None,
-            kill_ext: None,
-            list_file: None,
-            list: false,
-            safe: false,
-            positional_ports: vec![],
-            preset: None,
-            list_presets: false,
-            save_preset: None,
-            preset_desc: None,
-            delete_preset: None,
-            check_updates: false,
-            self_update: false,
-            cache: None,
+            ..Default::default()
         };
-        
+
         let (_count, mut processes) = get_processes_on_ports(&self.ports_to_monitor, &args);
+        crate::process_rules::forget_gone(&processes);
 
         // Refresh system information for performance metrics
         if self.performance_enabled {
             self.system_monitor.refresh();
-            
+
             // Add performance metrics to each process
             for process_info in processes.values_mut() {
-                if let Some(cpu_usage) =
-                    self.system_monitor.get_process_cpu_usage(process_info.pid)
+                if let Some(cpu_usage) = self.system_monitor.get_process_cpu_usage(process_info.pid)
                 {
                     process_info.cpu_usage = Some(cpu_usage);
                 }
@@ -237,14 +232,14 @@ impl ProcessMonitor {
                     process_info.memory_percentage = Some(memory_percentage);
                 }
             }
-            
+
             // Clean up old processes from system monitor
             self.system_monitor.cleanup_old_processes();
         }
 
         // Apply smart filtering if enabled
         if let Some(ref filter) = self.smart_filter {
-            filter.filter_processes(&mut processes);
+            self.filtered_processes = filter.partition_processes(&mut processes);
         }
 
         Ok(processes)
@@ -370,11 +365,13 @@ impl ProcessMonitor {
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            start_time: crate::system_monitor::process_start_time(pid),
+            systemd_unit: crate::system_monitor::systemd_unit(pid),
+            framework: None,
         };
 
         // Determine process group and project name
-        process_info.process_group = process_info.determine_process_group();
-        process_info.project_name = process_info.extract_project_name();
+        process_info.classify();
 
         // Enhance process name with better context
         process_info.name = Self::enhance_process_name(&process_info);
@@ -421,11 +418,13 @@ impl ProcessMonitor {
                             cpu_usage: None,
                             memory_usage: None,
                             memory_percentage: None,
+                            start_time: crate::system_monitor::process_start_time(pid),
+                            systemd_unit: crate::system_monitor::systemd_unit(pid),
+                            framework: None,
                         };
 
                         // Determine process group and project name
-                        process_info.process_group = process_info.determine_process_group();
-                        process_info.project_name = process_info.extract_project_name();
+                        process_info.classify();
 
                         return Ok(process_info);
                     }
@@ -452,11 +451,13 @@ impl ProcessMonitor {
                         cpu_usage: None,
                         memory_usage: None,
                         memory_percentage: None,
+                        start_time: crate::system_monitor::process_start_time(pid),
+                        systemd_unit: crate::system_monitor::systemd_unit(pid),
+                        framework: None,
                     };
 
                     // Determine process group and project name
-                    process_info.process_group = process_info.determine_process_group();
-                    process_info.project_name = process_info.extract_project_name();
+                    process_info.classify();
 
                     return Ok(process_info);
                 }
@@ -494,11 +495,13 @@ impl ProcessMonitor {
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            start_time: crate::system_monitor::process_start_time(pid),
+            systemd_unit: crate::system_monitor::systemd_unit(pid),
+            framework: None,
         };
 
         // Determine process group and project name
-        process_info.process_group = process_info.determine_process_group();
-        process_info.project_name = process_info.extract_project_name();
+        process_info.classify();
 
         // Enhance process name with better context
         process_info.name = Self::enhance_process_name(&process_info);
@@ -800,6 +803,20 @@ impl ProcessMonitor {
         context: &str,
         add_to_history: bool,
     ) -> Result<()> {
+        self.kill_process_recorded(pid, context, add_to_history)
+            .await
+            .0
+            .into_result()
+    }
+
+    /// Kill `pid` and return the report together with the history entry the attempt
+    /// was recorded as (when the process was known and `add_to_history` is set)
+    pub async fn kill_process_recorded(
+        &mut self,
+        pid: i32,
+        context: &str,
+        add_to_history: bool,
+    ) -> (KillReport, Option<ProcessHistoryEntry>) {
         info!("Attempting to kill process {}", pid);
 
         // Find the process info before killing it
@@ -809,11 +826,42 @@ impl ProcessMonitor {
             .find(|p| p.pid == pid)
             .cloned();
 
+        let report = self.terminate(pid).await;
+
+        // Record the attempt, failed or not, if we found the process info and add_to_history is true
+        let mut recorded = None;
+        if add_to_history {
+            if let Some(process_info) = process_info {
+                let history_entry = ProcessHistoryEntry::new(&process_info, context.to_string())
+                    .with_report(&report);
+                info!("Added process {} to history", pid);
+
+                // Append to the shared history log
+                if let Err(e) = ProcessHistory::append_to_file(
+                    &ProcessHistory::get_history_file_path(),
+                    std::slice::from_ref(&history_entry),
+                ) {
+                    warn!("Failed to save history to file: {}", e);
+                }
+                self.history.add_entry(history_entry.clone());
+                recorded = Some(history_entry);
+            }
+        }
+
+        (report, recorded)
+    }
+
+    /// Stop a process (or the container or systemd unit it runs in) and report how it went
+    async fn terminate(&self, pid: i32) -> KillReport {
+        if let Some(unit) = unit_to_stop(pid, self.force_pid) {
+            return stop_systemd_unit(pid, &unit);
+        }
+
         #[cfg(not(target_os = "windows"))]
         {
             // Check if this is a Docker container process (Unix-like systems only)
             if self.docker_enabled {
-                if let Some(container_id) = self.find_container_id_for_pid(pid).await? {
+                if let Ok(Some(container_id)) = self.find_container_id_for_pid(pid).await {
                     info!(
                         "Process {} is in Docker container {}, stopping container",
                         pid, container_id
@@ -826,20 +874,26 @@ impl ProcessMonitor {
         #[cfg(target_os = "windows")]
         {
             // Windows: Use taskkill
-            let output = Command::new("taskkill")
+            let output = match Command::new("taskkill")
                 .args(&["/PID", &pid.to_string(), "/F"])
                 .output()
-                .context("Failed to execute taskkill command")?;
+            {
+                Ok(output) => output,
+                Err(e) => {
+                    return KillReport::failed(
+                        0,
+                        format!("Failed to execute taskkill command: {}", e),
+                    )
+                }
+            };
 
             if output.status.success() {
                 info!("Successfully killed process {} on Windows", pid);
+                KillReport::stopped(KillOutcome::ForceKilled, "taskkill /F", 1)
             } else {
                 let stderr = String::from_utf8_lossy(&output.stderr);
                 error!("Failed to kill process {} on Windows: {}", pid, stderr);
-                return Err(anyhow::anyhow!(
-                    "Failed to kill process on Windows: {}",
-                    stderr
-                ));
+                KillReport::failed(1, format!("Failed to kill process on Windows: {}", stderr))
             }
         }
 
@@ -854,66 +908,70 @@ impl ProcessMonitor {
                     sleep(Duration::from_millis(500)).await;
 
                     // Check if process is still running
-                    if self.is_process_running(pid).await {
-                        warn!(
-                            "Process {} still running after SIGTERM, sending SIGKILL",
-                            pid
-                        );
+                    if !Self::is_process_running(pid) {
+                        info!("Process {} terminated successfully with SIGTERM", pid);
+                        return KillReport::stopped(KillOutcome::Terminated, "SIGTERM", 1);
+                    }
 
-                        // Send SIGKILL if process is still alive
-                        match kill(Pid::from_raw(pid), Signal::SIGKILL) {
-                            Ok(_) => {
-                                info!("Sent SIGKILL to process {}", pid);
-                            }
-                            Err(e) => {
-                                error!("Failed to send SIGKILL to process {}: {}", pid, e);
-                                return Err(anyhow::anyhow!("Failed to kill process: {}", e));
-                            }
+                    warn!(
+                        "Process {} still running after SIGTERM, sending SIGKILL",
+                        pid
+                    );
+
+                    // Send SIGKILL if process is still alive
+                    match kill(Pid::from_raw(pid), Signal::SIGKILL) {
+                        Ok(_) => {
+                            info!("Sent SIGKILL to process {}", pid);
+                            KillReport::stopped(KillOutcome::ForceKilled, "SIGKILL", 2)
                         }
-                    } else {
-                        info!("Process {} terminated successfully with SIGTERM", pid);
+                        Err(nix::errno::Errno::ESRCH) => {
+                            // It exited between the check and the second signal
+                            KillReport::stopped(KillOutcome::Terminated, "SIGTERM", 2)
+                        }
+                        Err(e) => {
+                            error!("Failed to send SIGKILL to process {}: {}", pid, e);
+                            KillReport::failed(2, format!("Failed to kill process: {}", e))
+                        }
+                    }
+                }
+                Err(nix::errno::Errno::ESRCH) => {
+                    info!("Process {} had already exited", pid);
+                    KillReport {
+                        outcome: KillOutcome::AlreadyExited,
+                        signal: None,
+                        attempts: 1,
+                        error: None,
                     }
                 }
                 Err(e) => {
                     error!("Failed to send SIGTERM to process {}: {}", pid, e);
-                    return Err(anyhow::anyhow!("Failed to kill process: {}", e));
+                    KillReport::failed(1, format!("Failed to kill process: {}", e))
                 }
             }
         }
-
-        // Add to history if we found the process info and add_to_history is true
-        if add_to_history {
-            if let Some(process_info) = process_info {
-                let history_entry = ProcessHistoryEntry::new(&process_info, context.to_string());
-                self.history.add_entry(history_entry);
-                info!("Added process {} to history", pid);
-
-                // Save history to file
-                if let Err(e) = self
-                    .history
-                    .save_to_file(&ProcessHistory::get_history_file_path())
-                {
-                    warn!("Failed to save history to file: {}", e);
-                }
-            }
-        }
-
-        Ok(())
     }
 
     #[cfg(not(target_os = "windows"))]
-    async fn stop_docker_container(&self, container_id: &str) -> Result<()> {
+    async fn stop_docker_container(&self, container_id: &str) -> KillReport {
         info!("Stopping Docker container: {}", container_id);
 
         // First try graceful stop
-        let stop_output = Command::new("docker")
+        let stop_output = match Command::new("docker")
             .args(&["stop", container_id])
             .output()
-            .context("Failed to execute docker stop command")?;
+        {
+            Ok(output) => output,
+            Err(e) => {
+                return KillReport::failed(
+                    0,
+                    format!("Failed to execute docker stop command: {}", e),
+                )
+            }
+        };
 
         if stop_output.status.success() {
             info!("Docker container {} stopped gracefully", container_id);
-            return Ok(());
+            return KillReport::stopped(KillOutcome::Terminated, "docker stop", 1);
         }
 
         // If graceful stop failed, try force remove
@@ -921,29 +979,65 @@ impl ProcessMonitor {
             "Graceful stop failed, force removing container: {}",
             container_id
         );
-        let remove_output = Command::new("docker")
+        let remove_output = match Command::new("docker")
             .args(&["rm", "-f", container_id])
             .output()
-            .context("Failed to execute docker rm command")?;
+        {
+            Ok(output) => output,
+            Err(e) => {
+                return KillReport::failed(1, format!("Failed to execute docker rm command: {}", e))
+            }
+        };
 
         if remove_output.status.success() {
             info!("Docker container {} force removed", container_id);
-            Ok(())
+            KillReport::stopped(KillOutcome::ForceKilled, "docker rm -f", 2)
         } else {
             let error_msg = String::from_utf8_lossy(&remove_output.stderr);
-            Err(anyhow::anyhow!(
-                "Failed to remove Docker container {}: {}",
-                container_id,
-                error_msg
-            ))
+            KillReport::failed(
+                2,
+                format!(
+                    "Failed to remove Docker container {}: {}",
+                    container_id, error_msg
+                ),
+            )
         }
     }
 
     pub async fn kill_all_processes(&mut self) -> Result<()> {
+        let killed = self.kill_all_processes_recorded().await?;
+        let errors: Vec<String> = killed
+            .iter()
+            .filter(|entry| !entry.outcome.succeeded())
+            .map(|entry| {
+                format!(
+                    "Port {} (PID {}): {}",
+                    entry.port,
+                    entry.pid,
+                    entry.error.as_deref().unwrap_or("process is still running")
+                )
+            })
+            .collect();
+
+        if !errors.is_empty() {
+            let error_msg = errors.join("; ");
+            return Err(anyhow::anyhow!(
+                "Some processes failed to kill: {}",
+                error_msg
+            ));
+        }
+
+        info!("All processes killed successfully");
+        Ok(())
+    }
+
+    /// Kill every monitored process and return the attempts as recorded in the history,
+    /// failed ones included
+    pub async fn kill_all_processes_recorded(&mut self) -> Result<Vec<ProcessHistoryEntry>> {
         info!("Killing all monitored processes");
 
         let processes = self.scan_processes().await?;
-        let mut errors = Vec::new();
+        let mut killed = Vec::new();
 
         for (port, process_info) in processes {
             info!(
@@ -951,41 +1045,26 @@ impl ProcessMonitor {
                 port, process_info.pid
             );
 
-            // Add to history before killing
-            let history_entry = ProcessHistoryEntry::new(&process_info, "bulk".to_string());
-            self.history.add_entry(history_entry);
-
-            if let Err(e) = self
-                .kill_process_with_context(process_info.pid, "bulk", false)
-                .await
-            {
-                errors.push(format!("Port {} (PID {}): {}", port, process_info.pid, e));
-            }
+            let report = self.terminate(process_info.pid).await;
+            let history_entry =
+                ProcessHistoryEntry::new(&process_info, "bulk".to_string()).with_report(&report);
+            self.history.add_entry(history_entry.clone());
+            killed.push(history_entry);
         }
 
-        if !errors.is_empty() {
-            let error_msg = errors.join("; ");
-            return Err(anyhow::anyhow!(
-                "Some processes failed to kill: {}",
-                error_msg
-            ));
-        }
-
-        // Save history to file after killing all processes
-        if let Err(e) = self
-            .history
-            .save_to_file(&ProcessHistory::get_history_file_path())
+        // Append history for the whole batch, including attempts that failed
+        if let Err(e) =
+            ProcessHistory::append_to_file(&ProcessHistory::get_history_file_path(), &killed)
         {
             warn!("Failed to save history to file: {}", e);
         }
 
-        info!("All processes killed successfully");
-        Ok(())
+        Ok(killed)
     }
 
     #[cfg(not(target_os = "windows"))]
-    async fn is_process_running(&self, pid: i32) -> bool {
-        let output = Command::new("ps").args(&["-p", &pid.to_string()]).output();
+    fn is_process_running(pid: i32) -> bool {
+        let output = Command::new("ps").args(["-p", &pid.to_string()]).output();
 
         match output {
             Ok(output) => output.status.success(),
@@ -1003,11 +1082,7 @@ impl ProcessMonitor {
 
     pub fn clear_history(&mut self) {
         self.history.clear();
-        // Save empty history to file
-        if let Err(e) = self
-            .history
-            .save_to_file(&ProcessHistory::get_history_file_path())
-        {
+        if let Err(e) = ProcessHistory::clear_file(&ProcessHistory::get_history_file_path()) {
             warn!("Failed to save cleared history to file: {}", e);
         }
     }
@@ -1018,6 +1093,11 @@ impl ProcessMonitor {
             .map(|filter| filter.get_filter_stats())
     }
 
+    /// Listeners hidden by the smart filter on the last scan, with the reason
+    pub fn get_filtered_processes(&self) -> &[FilteredProcess] {
+        &self.filtered_processes
+    }
+
     /// Get current processes
     pub fn get_processes(&self) -> &HashMap<u16, ProcessInfo> {
         &self.current_processes
@@ -1081,6 +1161,7 @@ impl ProcessMonitor {
                 "Docker" => "Docker Container".to_string(),
                 "Web Server" => "Web Server".to_string(),
                 "Database" => "Database Server".to_string(),
+                "Service" => "Systemd Service".to_string(),
                 _ => original_name.clone(),
             }
         } else {
@@ -1151,7 +1232,7 @@ fn get_processes_on_ports_unix(
 ) {
     const MAX_PORTS_PER_LSOF: usize = 100;
     const LARGE_RANGE_THRESHOLD: usize = 200; // If more than 200 ports, use optimized scanning
-    
+
     let mut processes = std::collections::HashMap::new();
     let ports_filter: HashSet<u16> = ports.iter().copied().collect();
     let ignore_ports = args.get_ignore_ports_set();
@@ -1312,10 +1393,7 @@ fn parse_netstat_output(
 
         // Check ignore lists
         if ignore_ports.contains(&port) {
-            log::info!(
-                "Ignoring port {} (ignored by user configuration)",
-                port
-            );
+            log::info!("Ignoring port {} (ignored by user configuration)", port);
             continue;
         }
 
@@ -1358,10 +1436,12 @@ fn parse_netstat_output(
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            start_time: crate::system_monitor::process_start_time(pid),
+            systemd_unit: crate::system_monitor::systemd_unit(pid),
+            framework: None,
         };
 
-        process_info.process_group = process_info.determine_process_group();
-        process_info.project_name = process_info.extract_project_name();
+        process_info.classify();
 
         processes.insert(port, process_info);
     }
@@ -1460,17 +1540,22 @@ fn parse_lsof_output(
             cpu_usage: None,
             memory_usage: None,
             memory_percentage: None,
+            start_time: crate::system_monitor::process_start_time(pid),
+            systemd_unit: crate::system_monitor::systemd_unit(pid),
+            framework: None,
         };
 
-        process_info.process_group = process_info.determine_process_group();
-        process_info.project_name = process_info.extract_project_name();
+        process_info.classify();
 
         processes.insert(port, process_info);
     }
 }
 
 #[cfg(target_os = "windows")]
-pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Result<()> {
+pub fn kill_all_processes(
+    ports: &[u16],
+    args: &crate::cli::Args,
+) -> anyhow::Result<Vec<ProcessHistoryEntry>> {
     let port_list = ports
         .iter()
         .map(|p| p.to_string())
@@ -1511,8 +1596,8 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
                 let parts: Vec<&str> = line.split_whitespace().collect();
                 if let Some(pid_str) = parts.last() {
                     if let Ok(pid) = pid_str.parse::<i32>() {
-                        if !pids_to_kill.contains(&pid) {
-                            pids_to_kill.push(pid);
+                        if !pids_to_kill.iter().any(|(p, _)| *p == pid) {
+                            pids_to_kill.push((pid, port));
                         }
                     }
                 }
@@ -1522,25 +1607,32 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
 
     if pids_to_kill.is_empty() {
         log::info!("No processes found to kill on the specified ports");
-        return Ok(());
+        return Ok(Vec::new());
     }
 
     log::info!("Found {} processes to kill", pids_to_kill.len());
 
-    for pid in pids_to_kill {
+    let mut killed = Vec::new();
+    for (pid, port) in pids_to_kill {
         log::info!("Attempting to kill process PID: {}", pid);
-        match kill_process(pid) {
+        let entry = ProcessHistoryEntry::for_pid(pid, port, "unknown", "bulk");
+        let report = kill_and_record(entry.clone(), args.force_pid);
+        match report.clone().into_result() {
             Ok(_) => log::info!("Successfully killed process PID: {}", pid),
             Err(e) => log::error!("Failed to kill process {}: {}", pid, e),
         }
+        killed.push(entry.with_report(&report));
     }
 
     log::info!("Finished killing all processes");
-    Ok(())
+    Ok(killed)
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Result<()> {
+pub fn kill_all_processes(
+    ports: &[u16],
+    args: &crate::cli::Args,
+) -> anyhow::Result<Vec<ProcessHistoryEntry>> {
     // Build port range string for lsof
     let port_list = ports
         .iter()
@@ -1592,7 +1684,7 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
                     ignore_ports.contains(&port) || ignore_processes.contains(&name);
 
                 if !should_ignore {
-                    pids_to_kill.push(pid);
+                    pids_to_kill.push((pid, port, name));
                 } else {
                     log::info!("Ignoring process {} (PID {}) on port {} during kill operation (ignored by user configuration)", name, pid, port);
                 }
@@ -1602,7 +1694,7 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
 
     if pids_to_kill.is_empty() {
         log::info!("No processes found to kill (all were ignored or none found)");
-        return Ok(());
+        return Ok(Vec::new());
     }
 
     log::info!(
@@ -1610,28 +1702,40 @@ pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Res
         pids_to_kill.len()
     );
 
-    for pid in pids_to_kill {
+    let mut killed = Vec::new();
+    for (pid, port, name) in pids_to_kill {
         log::info!("Attempting to kill process PID: {}", pid);
-        match kill_process(pid) {
+        let entry = ProcessHistoryEntry::for_pid(pid, port, &name, "bulk");
+        let report = kill_and_record(entry.clone(), args.force_pid);
+        match report.clone().into_result() {
             Ok(_) => log::info!("Successfully killed process PID: {}", pid),
             Err(e) => log::error!("Failed to kill process {}: {}", pid, e),
         }
+        killed.push(entry.with_report(&report));
     }
 
     log::info!("Finished killing all processes");
-    Ok(())
+    Ok(killed)
 }
 
 #[cfg(target_os = "windows")]
-pub fn kill_single_process(pid: i32, _args: &crate::cli::Args) -> anyhow::Result<()> {
+pub fn kill_single_process(
+    pid: i32,
+    args: &crate::cli::Args,
+) -> anyhow::Result<Vec<ProcessHistoryEntry>> {
     log::info!("Killing single process PID: {}", pid);
     // On Windows, simplified version - just kill the process
     // Process filtering is done at a higher level
-    kill_process(pid)
+    let entry = ProcessHistoryEntry::for_pid(pid, 0, "unknown", "user");
+    let report = kill_and_record(entry.clone(), args.force_pid);
+    Ok(vec![entry.with_report(&report)])
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn kill_single_process(pid: i32, args: &crate::cli::Args) -> anyhow::Result<()> {
+pub fn kill_single_process(
+    pid: i32,
+    args: &crate::cli::Args,
+) -> anyhow::Result<Vec<ProcessHistoryEntry>> {
     log::info!("Killing single process PID: {}", pid);
 
     // Check if this process should be ignored
@@ -1642,6 +1746,8 @@ pub fn kill_single_process(pid: i32, args: &crate::cli::Args) -> anyhow::Result<
     let output = std::process::Command::new("ps")
         .args(&["-p", &pid.to_string(), "-o", "comm="])
         .output();
+    let mut name = "unknown".to_string();
+    let mut listening_port = 0;
 
     if let Ok(output) = output {
         let process_name = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -1653,7 +1759,10 @@ pub fn kill_single_process(pid: i32, args: &crate::cli::Args) -> anyhow::Result<
                 process_name,
                 pid
             );
-            return Ok(());
+            return Ok(Vec::new());
+        }
+        if !process_name.is_empty() {
+            name = process_name;
         }
     }
 
@@ -1674,7 +1783,10 @@ pub fn kill_single_process(pid: i32, args: &crate::cli::Args) -> anyhow::Result<
                             port,
                             pid
                         );
-                        return Ok(());
+                        return Ok(Vec::new());
+                    }
+                    if listening_port == 0 && line.contains("LISTEN") {
+                        listening_port = port;
                     }
                 }
             }
@@ -1682,10 +1794,28 @@ pub fn kill_single_process(pid: i32, args: &crate::cli::Args) -> anyhow::Result<
     }
 
     // Process is not ignored, proceed with killing
-    kill_process(pid)
+    let entry = ProcessHistoryEntry::for_pid(pid, listening_port, &name, "user");
+    let report = kill_and_record(entry.clone(), args.force_pid);
+    Ok(vec![entry.with_report(&report)])
 }
 
-fn kill_process(pid: i32) -> anyhow::Result<()> {
+fn kill_process(pid: i32, force_pid: bool) -> KillReport {
+    if let Some(unit) = unit_to_stop(pid, force_pid) {
+        let report = stop_systemd_unit(pid, &unit);
+        if report.outcome.succeeded() {
+            return report;
+        }
+        // e.g. an unprivileged user and a system unit: the process must not be left running
+        let reason = report.error.unwrap_or_default();
+        warn!("{}; signalling PID {} instead", reason, pid);
+        eprintln!(
+            "⚠️  Could not stop {} ({}); killing PID {} instead, so systemd may restart it",
+            unit.describe(),
+            reason,
+            pid
+        );
+    }
+
     #[cfg(not(target_os = "windows"))]
     {
         use nix::sys::signal::{kill, Signal};
@@ -1696,6 +1826,15 @@ fn kill_process(pid: i32) -> anyhow::Result<()> {
         // First try SIGTERM (graceful termination)
         match kill(Pid::from_raw(pid), Signal::SIGTERM) {
             Ok(_) => log::info!("SIGTERM sent to PID: {}", pid),
+            Err(nix::errno::Errno::ESRCH) => {
+                log::info!("PID {} had already exited", pid);
+                return KillReport {
+                    outcome: KillOutcome::AlreadyExited,
+                    signal: None,
+                    attempts: 1,
+                    error: None,
+                };
+            }
             Err(e) => {
                 // Don't fail immediately, just log the error and continue
                 log::warn!(
@@ -1709,29 +1848,31 @@ fn kill_process(pid: i32) -> anyhow::Result<()> {
         // Wait a bit for graceful termination
         std::thread::sleep(std::time::Duration::from_millis(500));
 
-        // Check if process is still running
-        let still_running = std::process::Command::new("ps")
-            .args(&["-p", &pid.to_string()])
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false);
-
-        if still_running {
-            // Process still running, send SIGKILL
-            log::info!("Process {} still running, sending SIGKILL", pid);
-            match kill(Pid::from_raw(pid), Signal::SIGKILL) {
-                Ok(_) => log::info!("SIGKILL sent to PID: {}", pid),
-                Err(e) => {
-                    // Log error but don't fail the entire operation
-                    log::warn!(
-                        "Failed to send SIGKILL to PID {}: {} (process may be protected)",
-                        pid,
-                        e
-                    );
-                }
+        if !ProcessMonitor::is_process_running(pid) {
+            log::info!("Process {} terminated gracefully", pid);
+            return KillReport::stopped(KillOutcome::Terminated, "SIGTERM", 1);
+        }
+
+        // Process still running, send SIGKILL
+        log::info!("Process {} still running, sending SIGKILL", pid);
+        match kill(Pid::from_raw(pid), Signal::SIGKILL) {
+            Ok(_) => log::info!("SIGKILL sent to PID: {}", pid),
+            Err(e) => {
+                // Log error but don't fail the entire operation
+                log::warn!(
+                    "Failed to send SIGKILL to PID {}: {} (process may be protected)",
+                    pid,
+                    e
+                );
+                return KillReport::failed(2, format!("Failed to send SIGKILL: {}", e));
             }
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        if ProcessMonitor::is_process_running(pid) {
+            KillReport::failed(2, "still running after SIGKILL")
         } else {
-            log::info!("Process {} terminated gracefully", pid);
+            KillReport::stopped(KillOutcome::ForceKilled, "SIGKILL", 2)
         }
     }
 
@@ -1750,16 +1891,55 @@ fn kill_process(pid: i32) -> anyhow::Result<()> {
             Ok(output) => {
                 if output.status.success() {
                     log::info!("Successfully killed process PID: {}", pid);
+                    KillReport::stopped(KillOutcome::ForceKilled, "taskkill /F", 1)
                 } else {
                     let stderr = String::from_utf8_lossy(&output.stderr);
                     log::warn!("Failed to kill process PID {}: {}", pid, stderr);
+                    KillReport::failed(1, stderr.trim().to_string())
                 }
             }
             Err(e) => {
                 log::warn!("Failed to execute taskkill for PID {}: {}", pid, e);
+                KillReport::failed(0, format!("Failed to execute taskkill: {}", e))
             }
         }
     }
+}
+
+/// Kill the process `entry` describes and append the attempt, with its outcome, to the
+/// shared history log. Build the entry before calling so it captures the start time.
+pub fn kill_and_record(entry: ProcessHistoryEntry, force_pid: bool) -> KillReport {
+    let report = kill_process(entry.pid, force_pid);
+    let entry = entry.with_report(&report);
+    if let Err(e) =
+        ProcessHistory::append_to_file(&ProcessHistory::get_history_file_path(), &[entry])
+    {
+        log::warn!("Failed to save history to file: {}", e);
+    }
+    report
+}
 
-    Ok(())
+/// `Err` when any of `kills` left its process running, naming each failure. Callers
+/// that print success must check this, not just the `Result` of the kill itself.
+pub fn check_kills(kills: &[ProcessHistoryEntry]) -> Result<()> {
+    let failures: Vec<String> = kills
+        .iter()
+        .filter(|entry| !entry.outcome.succeeded())
+        .map(|entry| {
+            format!(
+                "PID {}: {}",
+                entry.pid,
+                entry.error.as_deref().unwrap_or("still running")
+            )
+        })
+        .collect();
+    if failures.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "{} of {} process(es) could not be killed ({})",
+        failures.len(),
+        kills.len(),
+        failures.join("; ")
+    ))
 }
@@ -0,0 +1,827 @@
+use crate::types::ProcessInfo;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Overrides `~/.port-kill/rules.toml`
+pub const RULES_FILE_ENV: &str = "PORT_KILL_RULES";
+
+/// Built-in rules, in the same format as the user's file. Frameworks come first so
+/// they can name the group too; the first rule that sets a group or framework wins.
+const BUILTIN_RULES: &str = r#"
+[[rule]]
+framework = "Vite"
+group = "Node.js"
+argv = '(^|[\s/])vite(\.js)?(\s|$)|node_modules/vite/'
+
+[[rule]]
+framework = "Next.js"
+group = "Node.js"
+argv = '(^|[\s/])next\s+(dev|start)\b|node_modules/next/|^next-server\b'
+
+[[rule]]
+framework = "Django"
+group = "Python"
+argv = 'manage\.py\s+runserver\b|django-admin\s+runserver\b'
+
+[[rule]]
+framework = "uvicorn"
+group = "Python"
+argv = '(^|[\s/])uvicorn(\s|$)|-m\s+uvicorn\b'
+
+[[rule]]
+framework = "Rails"
+group = "Ruby"
+argv = '(^|[\s/])rails\s+(s|server)\b'
+
+# puma retitles itself, so `rails server` shows up as "puma 6.4.0 (tcp://...) [app]"
+[[rule]]
+framework = "Rails"
+group = "Ruby"
+argv = '^puma\s'
+cwd_file = "config/application.rb"
+
+[[rule]]
+framework = "Spring Boot"
+group = "Java"
+argv = 'org\.springframework\.boot|spring-boot|\bbootRun\b'
+
+[[rule]]
+framework = "cargo-watch"
+group = "Rust"
+argv = '(^|[\s/])cargo(-watch|\s+watch)(\s|$)'
+
+[[rule]]
+group = "Node.js"
+executable = '^(node|nodejs|npm|npx|yarn|pnpm)$'
+
+[[rule]]
+group = "Python"
+executable = '^(python[0-9.]*|pypy[0-9.]*|uvicorn|gunicorn|hypercorn|daphne)$'
+
+[[rule]]
+group = "Java"
+executable = '^(java|javaw)$'
+
+[[rule]]
+group = "Go"
+executable = '^go$'
+
+# `go run` builds into a temporary go-build directory
+[[rule]]
+group = "Go"
+path = '/go-build[0-9]*/'
+
+[[rule]]
+group = "Rust"
+executable = '^(cargo|cargo-watch)$'
+
+[[rule]]
+group = "Rust"
+path = '/target/(debug|release)/'
+
+[[rule]]
+group = "PHP"
+executable = '^(php[0-9.]*|php-fpm[0-9.]*|frankenphp)$'
+
+[[rule]]
+group = "Ruby"
+executable = '^(ruby[0-9.]*|puma|rails|unicorn|thin|rackup|falcon)$'
+
+[[rule]]
+group = "Ruby"
+argv = '^puma\s'
+
+[[rule]]
+group = "Docker"
+executable = '^(docker|dockerd|docker-proxy|containerd.*|com\.docke.*|podman|vpnkit.*)$'
+
+[[rule]]
+group = "Web Server"
+executable = '^(nginx|apache2?|httpd|caddy|traefik|haproxy|lighttpd|envoy)$'
+
+# lsof cuts command names to 9 characters, hence redis-ser and clickhous
+[[rule]]
+group = "Database"
+executable = '^(postgres|postmaster|mysqld|mariadbd|redis-ser(ver)?|mongod|mongos|memcached|clickhous.*|cockroach|etcd)$'
+"#;
+
+/// Files that mark the root of a project, most specific first. The project is named
+/// after the nearest one above the working directory.
+const MANIFESTS: &[&str] = &[
+    "package.json",
+    "Cargo.toml",
+    "pyproject.toml",
+    "go.mod",
+    "composer.json",
+    "Gemfile",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "mix.exs",
+    "deno.json",
+    "manage.py",
+    "setup.py",
+    "requirements.txt",
+    ".git",
+];
+
+/// One `[[rule]]` as written
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    group: Option<String>,
+    framework: Option<String>,
+    /// Regex on the executable name (also tried against argv[0] and the listed name)
+    executable: Option<String>,
+    /// Regex on the full executable path
+    path: Option<String>,
+    /// Regex on the whole command line
+    argv: Option<String>,
+    /// `NAME` to require a variable, `NAME=regex` to match its value
+    env: Option<String>,
+    /// A path, relative to the process's working directory, that must exist
+    cwd_file: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default)]
+    rule: Vec<RuleSpec>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    group: Option<String>,
+    framework: Option<String>,
+    executable: Option<Regex>,
+    path: Option<Regex>,
+    argv: Option<Regex>,
+    env: Option<(String, Option<Regex>)>,
+    cwd_file: Option<PathBuf>,
+}
+
+fn compile(pattern: &str, label: &str) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| anyhow!("{}: invalid regex: {}", label, e))
+}
+
+impl Rule {
+    fn compile(spec: RuleSpec, label: &str) -> Result<Self> {
+        if spec.group.is_none() && spec.framework.is_none() {
+            return Err(anyhow!("{}: sets neither `group` nor `framework`", label));
+        }
+        if spec.executable.is_none()
+            && spec.path.is_none()
+            && spec.argv.is_none()
+            && spec.env.is_none()
+            && spec.cwd_file.is_none()
+        {
+            return Err(anyhow!(
+                "{}: needs at least one of `executable`, `path`, `argv`, `env` or `cwd_file`",
+                label
+            ));
+        }
+        let regex = |pattern: Option<String>, field: &str| {
+            pattern
+                .map(|p| compile(&p, &format!("{} `{}`", label, field)))
+                .transpose()
+        };
+        let env = match spec.env {
+            Some(env) => Some(match env.split_once('=') {
+                Some((name, value)) => (
+                    name.trim().to_string(),
+                    Some(compile(value, &format!("{} `env`", label))?),
+                ),
+                None => (env.trim().to_string(), None),
+            }),
+            None => None,
+        };
+        Ok(Self {
+            group: spec.group,
+            framework: spec.framework,
+            executable: regex(spec.executable, "executable")?,
+            path: regex(spec.path, "path")?,
+            argv: regex(spec.argv, "argv")?,
+            env,
+            cwd_file: spec.cwd_file.map(PathBuf::from),
+        })
+    }
+
+    /// Every matcher the rule sets has to hold
+    fn matches(&self, context: &ProcessContext) -> bool {
+        if let Some(ref executable) = self.executable {
+            if !context.names().iter().any(|name| executable.is_match(name)) {
+                return false;
+            }
+        }
+        if let Some(ref path) = self.path {
+            let paths = [
+                context.executable.as_deref(),
+                context.argv.first().map(String::as_str),
+            ];
+            if !paths.iter().flatten().any(|p| path.is_match(p)) {
+                return false;
+            }
+        }
+        if let Some(ref argv) = self.argv {
+            if !argv.is_match(&context.command_line()) {
+                return false;
+            }
+        }
+        if let Some((ref name, ref value)) = self.env {
+            match (context.env.get(name), value) {
+                (None, _) => return false,
+                (Some(actual), Some(value)) if !value.is_match(actual) => return false,
+                _ => {}
+            }
+        }
+        if let Some(ref file) = self.cwd_file {
+            match context.cwd {
+                Some(ref cwd) if cwd.join(file).exists() => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+/// The rules in the order they are tried: the user's, then the built-ins
+#[derive(Debug)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN_RULES, "built-in rules").expect("built-in rules are valid")
+    }
+
+    fn parse(content: &str, source: &str) -> Result<Self> {
+        let file: RulesFile =
+            toml::from_str(content).with_context(|| format!("Invalid TOML in {}", source))?;
+        let rules = file
+            .rule
+            .into_iter()
+            .enumerate()
+            .map(|(index, spec)| Rule::compile(spec, &format!("rule {}", index + 1)))
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("Invalid rule in {}", source))?;
+        Ok(Self { rules })
+    }
+
+    /// The user's rules from `path`, ahead of the built-ins
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut set = Self::parse(&content, &path.display().to_string())?;
+        set.rules.extend(Self::builtin().rules);
+        Ok(set)
+    }
+
+    /// Environment variables some rule matches on; the rest of a process's
+    /// environment is never kept
+    pub fn env_names(&self) -> HashSet<&str> {
+        self.rules
+            .iter()
+            .filter_map(|rule| rule.env.as_ref().map(|(name, _)| name.as_str()))
+            .collect()
+    }
+
+    pub fn classify(&self, process: &ProcessInfo, context: &ProcessContext) -> Classification {
+        let mut group = None;
+        let mut framework = None;
+        for rule in &self.rules {
+            if group.is_some() && framework.is_some() {
+                break;
+            }
+            let wanted = (group.is_none() && rule.group.is_some())
+                || (framework.is_none() && rule.framework.is_some());
+            if !wanted || !rule.matches(context) {
+                continue;
+            }
+            if framework.is_none() && rule.framework.is_some() {
+                framework = rule.framework.clone();
+                // A framework rule's group goes with it, ahead of anything found so far
+                if rule.group.is_some() {
+                    group = rule.group.clone();
+                }
+            } else if group.is_none() {
+                group = rule.group.clone();
+            }
+        }
+        // A systemd unit with nothing more specific is a service; the unit itself stays
+        // on `systemd_unit`, so a unit-run database is still a Database
+        if group.is_none() && process.systemd_unit.is_some() {
+            group = Some("Service".to_string());
+        }
+        Classification {
+            group,
+            framework,
+            project: context.cwd.as_deref().and_then(find_project),
+        }
+    }
+}
+
+/// `~/.port-kill/rules.toml`, or the file named by `PORT_KILL_RULES`
+pub fn rules_file_path() -> PathBuf {
+    std::env::var(RULES_FILE_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            let home = std::env::var("HOME")
+                .or_else(|_| std::env::var("USERPROFILE"))
+                .unwrap_or_else(|_| "/tmp".to_string());
+            PathBuf::from(home).join(".port-kill").join("rules.toml")
+        })
+}
+
+/// The active rules, loaded once. A broken rules file is reported and skipped
+/// rather than stopping every scan.
+pub fn rules() -> &'static RuleSet {
+    static RULES: OnceLock<RuleSet> = OnceLock::new();
+    RULES.get_or_init(|| {
+        let path = rules_file_path();
+        if !path.is_file() {
+            return RuleSet::builtin();
+        }
+        RuleSet::load(&path).unwrap_or_else(|e| {
+            eprintln!("⚠️  Ignoring {}: {:#}", path.display(), e);
+            RuleSet::builtin()
+        })
+    })
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Classification {
+    pub group: Option<String>,
+    pub framework: Option<String>,
+    pub project: Option<String>,
+}
+
+/// Classifications per PID, start time and command (a reused PID starts later), so
+/// rescans don't re-read /proc or run `ps` and `lsof`. Monitor scans prune it with
+/// `forget_gone`; one-off scans don't, so it is also capped at `CACHE_LIMIT` entries
+type CacheKey = (i32, Option<DateTime<Utc>>, String);
+
+const CACHE_LIMIT: usize = 4096;
+
+fn cache() -> &'static Mutex<HashMap<CacheKey, Classification>> {
+    static CACHE: OnceLock<Mutex<HashMap<CacheKey, Classification>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn cache_key(process: &ProcessInfo) -> CacheKey {
+    (process.pid, process.start_time, process.command.clone())
+}
+
+/// Classify a listener with the active rules
+pub fn classify(process: &ProcessInfo) -> Classification {
+    let key = cache_key(process);
+    if let Some(found) = cache().lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return found.clone();
+    }
+    let rules = rules();
+    let context = ProcessContext::collect(process, &rules.env_names());
+    let classification = rules.classify(process, &context);
+    let mut cache = cache().lock().unwrap_or_else(|e| e.into_inner());
+    if cache.len() >= CACHE_LIMIT {
+        cache.clear();
+    }
+    cache.insert(key, classification.clone());
+    classification
+}
+
+/// Drop cached classifications for processes a scan no longer found
+pub fn forget_gone(processes: &HashMap<u16, ProcessInfo>) {
+    let live: HashSet<CacheKey> = processes.values().map(cache_key).collect();
+    cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|key, _| live.contains(key));
+}
+
+/// What the rules look at
+#[derive(Debug, Clone, Default)]
+pub struct ProcessContext {
+    /// Listed name, e.g. from lsof (possibly truncated)
+    pub name: String,
+    pub executable: Option<String>,
+    pub argv: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub cwd: Option<PathBuf>,
+}
+
+impl ProcessContext {
+    /// Read what the OS exposes about `process`, falling back to what the scan recorded.
+    /// The environment is only readable through /proc, i.e. on Linux, and only the
+    /// variables in `env_names` are kept.
+    pub fn collect(process: &ProcessInfo, env_names: &HashSet<&str>) -> Self {
+        let mut context = Self::from_os(process.pid, env_names);
+        context.name = process.command.clone();
+        if context.argv.is_empty() {
+            if let Some(ref command_line) = process.command_line {
+                context.argv = command_line.split_whitespace().map(String::from).collect();
+            }
+        }
+        if context.cwd.is_none() {
+            context.cwd = process.working_directory.as_ref().map(PathBuf::from);
+        }
+        context
+    }
+
+    #[cfg(target_os = "linux")]
+    fn from_os(pid: i32, env_names: &HashSet<&str>) -> Self {
+        let proc_dir = PathBuf::from(format!("/proc/{}", pid));
+        let split = |raw: Vec<u8>| -> Vec<String> {
+            raw.split(|b| *b == 0)
+                .filter(|part| !part.is_empty())
+                .map(|part| String::from_utf8_lossy(part).into_owned())
+                .collect()
+        };
+        let mut argv = std::fs::read(proc_dir.join("cmdline"))
+            .map(split)
+            .unwrap_or_default();
+        // Processes that retitle themselves (puma, nginx workers) put it all in argv[0]
+        if argv.len() == 1 && argv[0].contains(' ') {
+            argv = argv[0].split_whitespace().map(String::from).collect();
+        }
+        Self {
+            name: String::new(),
+            executable: std::fs::read_link(proc_dir.join("exe"))
+                .ok()
+                .map(|path| path.to_string_lossy().into_owned()),
+            argv,
+            env: if env_names.is_empty() {
+                HashMap::new()
+            } else {
+                std::fs::read(proc_dir.join("environ"))
+                    .map(split)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|pair| {
+                        let (name, value) = pair.split_once('=')?;
+                        env_names
+                            .contains(name)
+                            .then(|| (name.to_string(), value.to_string()))
+                    })
+                    .collect()
+            },
+            cwd: std::fs::read_link(proc_dir.join("cwd")).ok(),
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn from_os(pid: i32, _env_names: &HashSet<&str>) -> Self {
+        use std::process::Command;
+        let ps = |field: &str| {
+            Command::new("ps")
+                .args(["-o", field, "-p", &pid.to_string()])
+                .output()
+                .ok()
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let cwd = Command::new("lsof")
+            .args(["-a", "-p", &pid.to_string(), "-d", "cwd", "-Fn"])
+            .output()
+            .ok()
+            .and_then(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .find_map(|line| line.strip_prefix('n').map(PathBuf::from))
+            });
+        Self {
+            name: String::new(),
+            // `comm` is the full executable path on macOS
+            executable: ps("comm="),
+            argv: ps("command=")
+                .map(|command| command.split_whitespace().map(String::from).collect())
+                .unwrap_or_default(),
+            env: HashMap::new(),
+            cwd,
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    fn from_os(_pid: i32, _env_names: &HashSet<&str>) -> Self {
+        Self::default()
+    }
+
+    /// Lowercased names to try `executable` rules against, without `.exe`
+    fn names(&self) -> Vec<String> {
+        let basename = |path: &str| {
+            path.rsplit(['/', '\\'])
+                .next()
+                .unwrap_or(path)
+                .to_lowercase()
+                .trim_end_matches(".exe")
+                .to_string()
+        };
+        let mut names: Vec<String> = [
+            self.executable.as_deref(),
+            self.argv.first().map(String::as_str),
+            Some(self.name.as_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|name| !name.is_empty())
+        .map(basename)
+        .collect();
+        names.dedup();
+        names
+    }
+
+    fn command_line(&self) -> String {
+        if self.argv.is_empty() {
+            self.name.clone()
+        } else {
+            self.argv.join(" ")
+        }
+    }
+}
+
+/// Name of the project `cwd` belongs to: the nearest directory at or above it holding
+/// a manifest, named as the manifest says when it can be read, else after the directory.
+/// Stops below the home directory so a stray `~/package.json` doesn't claim everything;
+/// without any manifest the project is `cwd`'s own directory name.
+pub fn find_project(cwd: &Path) -> Option<String> {
+    let home = std::env::var("HOME").ok().map(PathBuf::from);
+    for dir in cwd.ancestors() {
+        if dir.parent().is_none() || Some(dir) == home.as_deref() {
+            break;
+        }
+        for manifest in MANIFESTS {
+            let path = dir.join(manifest);
+            if path.exists() {
+                return manifest_name(&path).or_else(|| {
+                    dir.file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                });
+            }
+        }
+    }
+    cwd.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+/// The project name a manifest declares, if it declares one
+fn manifest_name(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    if !matches!(
+        file_name,
+        "package.json" | "composer.json" | "Cargo.toml" | "pyproject.toml" | "go.mod"
+    ) {
+        return None;
+    }
+    let content = std::fs::read_to_string(path).ok()?;
+    let name = match file_name {
+        "package.json" | "composer.json" => serde_json::from_str::<serde_json::Value>(&content)
+            .ok()?
+            .get("name")?
+            .as_str()?
+            .to_string(),
+        "go.mod" => content
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("module "))?
+            .trim()
+            .rsplit('/')
+            .next()?
+            .to_string(),
+        _ => {
+            let table: toml::Table = content.parse().ok()?;
+            let lookup = |path: &[&str]| {
+                let mut value = table.get(path[0])?;
+                for key in &path[1..] {
+                    value = value.get(key)?;
+                }
+                value.as_str().map(String::from)
+            };
+            lookup(&["package", "name"])
+                .or_else(|| lookup(&["project", "name"]))
+                .or_else(|| lookup(&["tool", "poetry", "name"]))?
+        }
+    };
+    (!name.trim().is_empty()).then(|| name.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(executable: &str, argv: &str) -> ProcessContext {
+        ProcessContext {
+            name: executable
+                .rsplit('/')
+                .next()
+                .unwrap()
+                .chars()
+                .take(9)
+                .collect(),
+            executable: Some(executable.to_string()),
+            argv: argv.split_whitespace().map(String::from).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_classifies_by_rules() {
+        let rules = RuleSet::builtin();
+        let classify = |executable: &str, argv: &str| {
+            let c = rules.classify(
+                &ProcessInfo::for_test(4242, 3000, "x"),
+                &context(executable, argv),
+            );
+            (c.group, c.framework)
+        };
+        let some = |s: &str| Some(s.to_string());
+
+        // Substrings of other names no longer count
+        assert_eq!(
+            classify("/usr/bin/mongod", "mongod --port 27017").0,
+            some("Database")
+        );
+        assert_eq!(classify("/opt/google/chrome/chrome", "chrome").0, None);
+        assert_eq!(
+            classify("/usr/bin/nodemon-helper", "nodemon-helper").0,
+            None
+        );
+        assert_eq!(
+            classify("/usr/bin/redis-server", "redis-server *:6379").0,
+            some("Database")
+        );
+
+        assert_eq!(
+            classify(
+                "/usr/bin/node",
+                "node /app/node_modules/.bin/vite --port 5173"
+            ),
+            (some("Node.js"), some("Vite"))
+        );
+        assert_eq!(
+            classify(
+                "/usr/bin/node",
+                "node /app/node_modules/next/dist/bin/next dev"
+            ),
+            (some("Node.js"), some("Next.js"))
+        );
+        assert_eq!(
+            classify(
+                "/usr/bin/python3.12",
+                "python3 manage.py runserver 0.0.0.0:8000"
+            ),
+            (some("Python"), some("Django"))
+        );
+        assert_eq!(
+            classify(
+                "/venv/bin/python",
+                "/venv/bin/python /venv/bin/uvicorn main:app"
+            ),
+            (some("Python"), some("uvicorn"))
+        );
+        assert_eq!(
+            classify("/usr/bin/ruby", "/usr/bin/ruby bin/rails server -p 3000"),
+            (some("Ruby"), some("Rails"))
+        );
+        assert_eq!(
+            classify(
+                "/usr/lib/jvm/bin/java",
+                "java -cp /app/lib/spring-boot-3.2.jar com.example.App"
+            ),
+            (some("Java"), some("Spring Boot"))
+        );
+        assert_eq!(
+            classify("/home/u/.cargo/bin/cargo-watch", "cargo-watch -x run"),
+            (some("Rust"), some("cargo-watch"))
+        );
+        assert_eq!(
+            classify(
+                "/tmp/go-build1234/b001/exe/main",
+                "/tmp/go-build1234/b001/exe/main"
+            )
+            .0,
+            some("Go")
+        );
+
+        // A systemd unit doesn't hide what the process is
+        let mut redis = ProcessInfo::for_test(4242, 3000, "redis-server");
+        redis.systemd_unit = Some(crate::types::SystemdUnit {
+            name: "redis-server.service".to_string(),
+            user: false,
+        });
+        let redis_context = context("/usr/bin/redis-server", "redis-server *:6379");
+        assert_eq!(
+            rules.classify(&redis, &redis_context).group,
+            some("Database")
+        );
+        let unknown = context("/usr/bin/acmed", "acmed --listen 3000");
+        assert_eq!(rules.classify(&redis, &unknown).group, some("Service"));
+
+        // User rules go first, and can match on the environment
+        let user = RuleSet::parse(
+            "[[rule]]\nframework = \"Phoenix\"\ngroup = \"Elixir\"\nenv = \"MIX_ENV=dev|test\"\n",
+            "rules.toml",
+        )
+        .unwrap();
+        let mut phoenix = context(
+            "/usr/lib/erlang/erts/bin/beam.smp",
+            "beam.smp -- phx.server",
+        );
+        phoenix.env.insert("MIX_ENV".to_string(), "dev".to_string());
+        let c = user.classify(&ProcessInfo::for_test(4242, 3000, "beam.smp"), &phoenix);
+        assert_eq!((c.group, c.framework), (some("Elixir"), some("Phoenix")));
+
+        let error =
+            RuleSet::parse("[[rule]]\ngroup = \"X\"\nargv = \"(\"\n", "rules.toml").unwrap_err();
+        assert!(format!("{:#}", error).contains("rule 1 `argv`: invalid regex"));
+        assert!(RuleSet::parse("[[rule]]\ngroup = \"X\"\n", "rules.toml").is_err());
+    }
+
+    #[test]
+    fn test_forgets_classifications_of_processes_that_are_gone() {
+        let listener = ProcessInfo::for_test(4242, 3000, "acmed");
+        classify(&listener);
+        assert!(cache().lock().unwrap().contains_key(&cache_key(&listener)));
+
+        let restarted = ProcessInfo {
+            start_time: Some(Utc::now()),
+            ..listener.clone()
+        };
+        forget_gone(&HashMap::from([(3000, restarted)]));
+        assert!(!cache().lock().unwrap().contains_key(&cache_key(&listener)));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_keeps_only_the_environment_rules_match_on() {
+        let own = ProcessInfo::for_test(std::process::id() as i32, 3000, "test");
+        let context = ProcessContext::collect(&own, &HashSet::from(["PATH"]));
+        assert_eq!(context.env.keys().collect::<Vec<_>>(), vec!["PATH"]);
+        assert!(ProcessContext::collect(&own, &HashSet::new())
+            .env
+            .is_empty());
+
+        let user = RuleSet::parse(
+            "[[rule]]\ngroup = \"Elixir\"\nenv = \"MIX_ENV=dev\"\n",
+            "rules.toml",
+        )
+        .unwrap();
+        assert!(user.env_names().contains("MIX_ENV"));
+        assert!(!RuleSet::builtin().env_names().contains("PATH"));
+    }
+
+    #[test]
+    fn test_finds_project_from_nearest_manifest() {
+        let root = std::env::temp_dir().join(format!(
+            "port-kill-rules-test-project-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        let web = root.join("repo/packages/web/src/routes");
+        std::fs::create_dir_all(&web).unwrap();
+        std::fs::create_dir_all(root.join("repo/.git")).unwrap();
+        std::fs::write(
+            root.join("repo/packages/web/package.json"),
+            r#"{"name":"@acme/web"}"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("repo/services/api")).unwrap();
+        std::fs::write(
+            root.join("repo/services/api/pyproject.toml"),
+            "[tool.poetry]\nname = \"billing-api\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("repo/tools/gen")).unwrap();
+
+        assert_eq!(find_project(&web).as_deref(), Some("@acme/web"));
+        assert_eq!(
+            find_project(&root.join("repo/services/api")).as_deref(),
+            Some("billing-api")
+        );
+        // Nothing closer than the repository root
+        assert_eq!(
+            find_project(&root.join("repo/tools/gen")).as_deref(),
+            Some("repo")
+        );
+        // A plain directory is a project of its own name
+        std::fs::create_dir_all(root.join("plain/site")).unwrap();
+        assert_eq!(
+            find_project(&root.join("plain/site")).as_deref(),
+            Some("site")
+        );
+
+        // The project comes along with the classification
+        let mut api = context("/usr/bin/python3", "python3 -m uvicorn app:app");
+        api.cwd = Some(root.join("repo/services/api"));
+        let c = RuleSet::builtin().classify(&ProcessInfo::for_test(4242, 3000, "python3"), &api);
+        assert_eq!(c.project.as_deref(), Some("billing-api"));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
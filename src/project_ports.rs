@@ -0,0 +1,831 @@
+use crate::cli::{Args, CheckArgs};
+use crate::output::{self, ExitCode};
+use crate::preset_manager::PortPreset;
+use crate::process_monitor::get_processes_on_ports;
+use crate::types::ProcessInfo;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Name of the preset built from the current project's files
+pub const PROJECT_PRESET: &str = "project";
+
+const COMPOSE_FILES: &[&str] = &[
+    "docker-compose.yml",
+    "docker-compose.yaml",
+    "compose.yml",
+    "compose.yaml",
+];
+const ENV_FILES: &[&str] = &[
+    ".env",
+    ".env.local",
+    ".env.development",
+    ".env.development.local",
+];
+const VITE_CONFIGS: &[&str] = &[
+    "vite.config.ts",
+    "vite.config.js",
+    "vite.config.mts",
+    "vite.config.mjs",
+    "vite.config.cjs",
+];
+const NEXT_CONFIGS: &[&str] = &["next.config.js", "next.config.mjs", "next.config.ts"];
+
+/// Files that mark a directory as a project root, besides the ones we parse
+const ROOT_MARKERS: &[&str] = &[".git", crate::config::PROJECT_CONFIG_FILE];
+
+/// Dev servers we recognise in scripts and Procfiles, with the port they use
+/// when none is given. `None` means the command starts no server.
+fn command_tool(command: &str) -> Option<(&'static str, Option<u16>)> {
+    static TOOLS: OnceLock<Vec<(Regex, &'static str, u16)>> = OnceLock::new();
+    static VITE: OnceLock<Regex> = OnceLock::new();
+
+    let vite = VITE.get_or_init(|| Regex::new(r"\bvite(?:\s+(\w+))?").unwrap());
+    if let Some(caps) = vite.captures(command) {
+        return match caps.get(1).map(|m| m.as_str()) {
+            None | Some("dev") | Some("serve") => Some(("vite", Some(5173))),
+            Some("preview") => Some(("vite-preview", Some(4173))),
+            Some(_) => Some(("vite", None)),
+        };
+    }
+
+    let tools = TOOLS.get_or_init(|| {
+        [
+            (r"\bnext\s+(?:dev|start)\b", "next", 3000),
+            (r"\breact-scripts\s+start\b", "create-react-app", 3000),
+            (r"\bnuxi?\s+dev\b", "nuxt", 3000),
+            (r"\bng\s+serve\b", "angular", 4200),
+            (r"\bastro\s+dev\b", "astro", 4321),
+            (
+                r"\bstorybook\s+dev\b|\bstart-storybook\b",
+                "storybook",
+                6006,
+            ),
+            (r"\bgatsby\s+develop\b", "gatsby", 8000),
+            (r"\bvue-cli-service\s+serve\b", "vue-cli", 8080),
+            (r"\bwebpack(?:-dev-server|\s+serve)\b", "webpack", 8080),
+            (r"\brails\s+(?:s|server)\b", "rails", 3000),
+            (r"\bmanage\.py\s+runserver\b", "django", 8000),
+            (r"\bflask\s+run\b", "flask", 5000),
+            (r"\b(?:uvicorn|gunicorn)\b", "python-asgi", 8000),
+        ]
+        .into_iter()
+        .map(|(pattern, tool, port)| (Regex::new(pattern).unwrap(), tool, port))
+        .collect()
+    });
+    tools
+        .iter()
+        .find(|(re, _, _)| re.is_match(command))
+        .map(|(_, tool, port)| (*tool, Some(*port)))
+}
+
+/// Ports a command line asks for explicitly
+fn explicit_ports(command: &str) -> Vec<u16> {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            r"(?:--port|-p)(?:=|\s+)(\d+)\b",
+            r"\bPORT=(\d+)\b",
+            r"\brunserver\s+(?:\S*:)?(\d+)\b",
+            r"(?:-b|--bind)(?:=|\s+)\S*:(\d+)\b",
+        ]
+        .iter()
+        .map(|p| Regex::new(p).unwrap())
+        .collect()
+    });
+    let mut ports: Vec<u16> = patterns
+        .iter()
+        .flat_map(|re| re.captures_iter(command))
+        .filter_map(|caps| parse_port(&caps[1]))
+        .collect();
+    ports.dedup();
+    ports
+}
+
+fn parse_port(value: &str) -> Option<u16> {
+    value.trim().parse::<u16>().ok().filter(|p| *p > 0)
+}
+
+/// One place a project says it uses a port
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct DiscoveredPort {
+    pub port: u16,
+    /// File the port was found in, relative to the project root
+    pub file: String,
+    /// Where in the file, e.g. `services.web.ports` or `scripts.dev`
+    pub detail: String,
+    /// A framework's default rather than a port written in the file
+    pub inferred: bool,
+    /// A service the project connects to (`DB_PORT=5432`) rather than one it serves.
+    /// Reported by `check`, but never counted as blocked or offered for killing.
+    pub dependency: bool,
+}
+
+/// A port as one of the parsers found it, tagged with the tool it belongs to
+struct Finding {
+    port: u16,
+    detail: String,
+    tool: Option<&'static str>,
+    inferred: bool,
+    dependency: bool,
+}
+
+impl Finding {
+    fn explicit(port: u16, detail: impl Into<String>, tool: Option<&'static str>) -> Self {
+        Self {
+            port,
+            detail: detail.into(),
+            tool,
+            inferred: false,
+            dependency: false,
+        }
+    }
+
+    fn inferred(port: u16, detail: impl Into<String>, tool: &'static str) -> Self {
+        Self {
+            port,
+            detail: detail.into(),
+            tool: Some(tool),
+            inferred: true,
+            dependency: false,
+        }
+    }
+
+    fn dependency(port: u16, detail: impl Into<String>) -> Self {
+        Self {
+            port,
+            detail: detail.into(),
+            tool: None,
+            inferred: false,
+            dependency: true,
+        }
+    }
+}
+
+/// Findings for a script or Procfile entry: its explicit ports, or the default
+/// of the dev server it starts
+fn command_findings(label: &str, command: &str) -> Vec<Finding> {
+    let tool = command_tool(command);
+    let explicit = explicit_ports(command);
+    if !explicit.is_empty() {
+        return explicit
+            .into_iter()
+            .map(|port| Finding::explicit(port, label, tool.map(|(name, _)| name)))
+            .collect();
+    }
+    match tool {
+        Some((name, Some(port))) => vec![Finding::inferred(
+            port,
+            format!("{} ({} default)", label, name),
+            name,
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// Host ports published by a compose file, per service. Handles the short
+/// (`"127.0.0.1:8080:80"`, ranges, `${VAR:-3000}`) and long (`published:`) forms.
+fn parse_compose(content: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut services_indent: Option<usize> = None;
+    let mut service_indent: Option<usize> = None;
+    let mut service = String::new();
+    let mut ports_indent: Option<usize> = None;
+
+    for raw in content.lines() {
+        let line = strip_yaml_comment(raw);
+        let text = line.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+
+        if let Some(pi) = ports_indent {
+            let in_block = indent > pi || (indent == pi && text.starts_with('-'));
+            if in_block {
+                let item = text.trim_start_matches('-').trim();
+                let published = match item.split_once(':') {
+                    Some(("published", value)) => compose_published(value),
+                    Some((key, _)) if is_yaml_key(key) => Vec::new(),
+                    _ if text.starts_with('-') => compose_host_ports(item),
+                    _ => Vec::new(),
+                };
+                let detail = format!("services.{}.ports", service);
+                for port in published {
+                    findings.push(Finding::explicit(port, detail.clone(), None));
+                }
+                continue;
+            }
+            ports_indent = None;
+        }
+
+        match services_indent {
+            None => {
+                if text == "services:" {
+                    services_indent = Some(indent);
+                }
+            }
+            Some(si) if indent <= si => {
+                services_indent = (text == "services:").then_some(indent);
+                service_indent = None;
+            }
+            Some(_) => {
+                let si = *service_indent.get_or_insert(indent);
+                if indent == si {
+                    service = text.trim_end_matches(':').trim_matches('"').to_string();
+                } else if let Some(rest) = text.strip_prefix("ports:") {
+                    let rest = rest.trim();
+                    if let Some(inline) = rest.strip_prefix('[') {
+                        let detail = format!("services.{}.ports", service);
+                        for item in inline.trim_end_matches(']').split(',') {
+                            for port in compose_host_ports(item) {
+                                findings.push(Finding::explicit(port, detail.clone(), None));
+                            }
+                        }
+                    } else {
+                        ports_indent = Some(indent);
+                    }
+                }
+            }
+        }
+    }
+    findings
+}
+
+fn strip_yaml_comment(line: &str) -> &str {
+    match line.find(" #") {
+        Some(i) => &line[..i],
+        None if line.trim_start().starts_with('#') => "",
+        None => line,
+    }
+}
+
+fn is_yaml_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && key.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+/// Resolve `${VAR:-default}` to its default and drop quotes and the `/tcp` suffix
+fn compose_value(value: &str) -> String {
+    static INTERPOLATION: OnceLock<Regex> = OnceLock::new();
+    let interpolation = INTERPOLATION
+        .get_or_init(|| Regex::new(r"\$\{[A-Za-z_][A-Za-z0-9_]*:?-([^}]*)\}").unwrap());
+
+    let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+    let value = interpolation.replace_all(value, "$1");
+    value.split('/').next().unwrap_or_default().to_string()
+}
+
+/// The host side of a short-form compose port; container-only entries publish a random port
+fn compose_host_ports(entry: &str) -> Vec<u16> {
+    let entry = compose_value(entry);
+    let Some((host, _container)) = entry.rsplit_once(':') else {
+        return Vec::new();
+    };
+    compose_published(host.rsplit(':').next().unwrap_or(host))
+}
+
+/// A published port or port range (`8000-8002`)
+fn compose_published(value: &str) -> Vec<u16> {
+    let host = compose_value(value);
+    match host.split_once('-') {
+        Some((start, end)) => match (parse_port(start), parse_port(end)) {
+            (Some(start), Some(end)) if start <= end && end - start < 100 => {
+                (start..=end).collect()
+            }
+            _ => Vec::new(),
+        },
+        None => parse_port(&host).into_iter().collect(),
+    }
+}
+
+/// `PORT=3000` and `*_PORT=5432` assignments in a dotenv file. `PORT` is the app's own;
+/// any other `*_PORT` (`DB_PORT`, `REDIS_PORT`) usually points at a service it talks to.
+fn parse_env_file(content: &str) -> Vec<Finding> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            if key != "PORT" && !key.ends_with("_PORT") {
+                return None;
+            }
+            let value = value.split(" #").next().unwrap_or_default();
+            let port = parse_port(value.trim().trim_matches(|c| c == '"' || c == '\''))?;
+            let detail = format!("{}={}", key, port);
+            Some(if key == "PORT" {
+                Finding::explicit(port, detail, None)
+            } else {
+                Finding::dependency(port, detail)
+            })
+        })
+        .collect()
+}
+
+fn parse_package_json(content: &str) -> Vec<Finding> {
+    let Ok(package) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    let Some(scripts) = package.get("scripts").and_then(|s| s.as_object()) else {
+        return Vec::new();
+    };
+    scripts
+        .iter()
+        .filter_map(|(name, command)| Some((name, command.as_str()?)))
+        .flat_map(|(name, command)| command_findings(&format!("scripts.{}", name), command))
+        .collect()
+}
+
+fn parse_procfile(content: &str) -> Vec<Finding> {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .flat_map(|(name, command)| command_findings(name.trim(), command))
+        .collect()
+}
+
+/// `server.port` and `preview.port` in a Vite config
+fn parse_vite_config(content: &str) -> Vec<Finding> {
+    static PORT: OnceLock<Regex> = OnceLock::new();
+    let port = PORT.get_or_init(|| Regex::new(r"\bport\s*:\s*(\d+)").unwrap());
+    port.captures_iter(content)
+        .filter_map(|caps| {
+            let start = caps.get(0)?.start();
+            let before = &content[..start];
+            let preview =
+                before.rfind("preview").unwrap_or(0) > before.rfind("server").unwrap_or(0);
+            let (detail, tool) = if preview {
+                ("preview.port", "vite-preview")
+            } else {
+                ("server.port", "vite")
+            };
+            Some(Finding::explicit(parse_port(&caps[1])?, detail, Some(tool)))
+        })
+        .collect()
+}
+
+/// `port 3000` or `port ENV.fetch("PORT") { 3000 }` in config/puma.rb
+fn parse_puma_config(content: &str) -> Vec<Finding> {
+    static PORT: OnceLock<Regex> = OnceLock::new();
+    let port = PORT.get_or_init(|| {
+        Regex::new(r#"(?m)^\s*port\s+(?:ENV\.fetch\(\s*["']PORT["']\s*\)\s*\{\s*)?(\d+)"#).unwrap()
+    });
+    port.captures_iter(content)
+        .filter_map(|caps| parse_port(&caps[1]))
+        .map(|p| Finding::explicit(p, "port", Some("rails")))
+        .collect()
+}
+
+fn gemfile_uses_rails(content: &str) -> bool {
+    content.lines().any(|line| {
+        let line = line.trim();
+        line.starts_with("gem \"rails\"") || line.starts_with("gem 'rails'")
+    })
+}
+
+/// The ports a project expects to use, and where each came from
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectPorts {
+    pub root: PathBuf,
+    pub ports: Vec<DiscoveredPort>,
+}
+
+impl ProjectPorts {
+    /// Scan the project's own files for ports. Framework defaults are dropped
+    /// when the same framework is given an explicit port somewhere else.
+    pub fn discover(root: &Path) -> Self {
+        let read = |name: &str| fs::read_to_string(root.join(name)).ok();
+        let mut found: Vec<(String, Finding)> = Vec::new();
+        let mut add = |file: &str, findings: Vec<Finding>| {
+            found.extend(findings.into_iter().map(|f| (file.to_string(), f)));
+        };
+
+        for file in COMPOSE_FILES {
+            if let Some(content) = read(file) {
+                add(file, parse_compose(&content));
+            }
+        }
+        for file in ENV_FILES {
+            if let Some(content) = read(file) {
+                add(file, parse_env_file(&content));
+            }
+        }
+        if let Some(content) = read("package.json") {
+            add("package.json", parse_package_json(&content));
+        }
+        if let Some(content) = read("Procfile") {
+            add("Procfile", parse_procfile(&content));
+        }
+        for file in VITE_CONFIGS {
+            if let Some(content) = read(file) {
+                add(file, parse_vite_config(&content));
+            }
+        }
+        for file in NEXT_CONFIGS {
+            if root.join(file).is_file() {
+                add(
+                    file,
+                    vec![Finding::inferred(3000, "Next.js default", "next")],
+                );
+            }
+        }
+        if root.join("manage.py").is_file() {
+            add(
+                "manage.py",
+                vec![Finding::inferred(8000, "runserver default", "django")],
+            );
+        }
+        if let Some(content) = read("config/puma.rb") {
+            add("config/puma.rb", parse_puma_config(&content));
+        }
+        if read("Gemfile").is_some_and(|c| gemfile_uses_rails(&c)) {
+            add(
+                "Gemfile",
+                vec![Finding::inferred(3000, "rails server default", "rails")],
+            );
+        }
+
+        let configured: Vec<&'static str> = found
+            .iter()
+            .filter(|(_, f)| !f.inferred)
+            .filter_map(|(_, f)| f.tool)
+            .collect();
+        let mut ports: Vec<DiscoveredPort> = Vec::new();
+        for (file, finding) in found {
+            if finding.inferred && finding.tool.is_some_and(|t| configured.contains(&t)) {
+                continue;
+            }
+            let port = DiscoveredPort {
+                port: finding.port,
+                file,
+                detail: finding.detail,
+                inferred: finding.inferred,
+                dependency: finding.dependency,
+            };
+            if !ports.contains(&port) {
+                ports.push(port);
+            }
+        }
+
+        Self {
+            root: root.to_path_buf(),
+            ports,
+        }
+    }
+
+    /// Discover ports for the project containing the working directory
+    pub fn current() -> Option<Self> {
+        let cwd = std::env::current_dir().ok()?;
+        let project = Self::discover(&find_project_root(&cwd)?);
+        (!project.ports.is_empty()).then_some(project)
+    }
+
+    /// Distinct ports the project serves, sorted
+    pub fn port_numbers(&self) -> Vec<u16> {
+        let mut ports: Vec<u16> = self
+            .ports
+            .iter()
+            .filter(|p| !p.dependency)
+            .map(|p| p.port)
+            .collect();
+        ports.sort_unstable();
+        ports.dedup();
+        ports
+    }
+
+    /// Distinct ports only ever named as services the project connects to, sorted
+    pub fn dependency_ports(&self) -> Vec<u16> {
+        let own = self.port_numbers();
+        let mut ports: Vec<u16> = self
+            .ports
+            .iter()
+            .filter(|p| p.dependency && !own.contains(&p.port))
+            .map(|p| p.port)
+            .collect();
+        ports.sort_unstable();
+        ports.dedup();
+        ports
+    }
+
+    /// The sources that mention `port`
+    pub fn sources(&self, port: u16) -> Vec<&DiscoveredPort> {
+        self.ports.iter().filter(|p| p.port == port).collect()
+    }
+
+    fn project_name(&self) -> String {
+        self.root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.root.display().to_string())
+    }
+
+    /// The implicit `project` preset
+    pub fn to_preset(&self) -> PortPreset {
+        let mut files: Vec<&str> = self.ports.iter().map(|p| p.file.as_str()).collect();
+        files.dedup();
+        PortPreset::new(
+            PROJECT_PRESET.to_string(),
+            format!(
+                "Ports discovered in {} ({})",
+                self.project_name(),
+                files.join(", ")
+            ),
+            self.port_numbers(),
+        )
+    }
+}
+
+/// The nearest directory, starting at `start`, that holds a file we know how to read
+/// or marks a project (`.git`, `.port-kill.toml`)
+pub fn find_project_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| {
+            COMPOSE_FILES
+                .iter()
+                .chain(ENV_FILES)
+                .chain(VITE_CONFIGS)
+                .chain(NEXT_CONFIGS)
+                .chain(&["package.json", "Procfile", "manage.py", "Gemfile"])
+                .chain(ROOT_MARKERS)
+                .any(|name| dir.join(name).exists())
+        })
+        .map(Path::to_path_buf)
+}
+
+/// One row of `port-kill check`
+#[derive(Debug, Serialize)]
+struct PortCheck<'a> {
+    port: u16,
+    blocked: bool,
+    /// A service the project uses; being in use is expected
+    dependency: bool,
+    sources: Vec<&'a DiscoveredPort>,
+    process: Option<&'a ProcessInfo>,
+}
+
+/// Run `port-kill check`: report which of the project's ports are taken and by whom.
+/// Returns `Ok(false)` when at least one port is blocked, which exits with
+/// `ExitCode::Conflict`.
+pub fn run(check: &CheckArgs, args: &Args) -> Result<bool> {
+    let project = if let Some(preset) = &args.preset {
+        // `--preset` was already applied to the arguments
+        ProjectPorts {
+            root: std::env::current_dir()?,
+            ports: args
+                .get_ports_to_monitor()
+                .into_iter()
+                .map(|port| DiscoveredPort {
+                    port,
+                    file: format!("preset {}", preset),
+                    detail: String::new(),
+                    inferred: false,
+                    dependency: false,
+                })
+                .collect(),
+        }
+    } else {
+        let start = match &check.path {
+            Some(path) => path.clone(),
+            None => std::env::current_dir()?,
+        };
+        let root = find_project_root(&start).ok_or_else(|| {
+            anyhow!(
+                "no project found in {} or its parents (looked for package.json, docker-compose.yml, .env, Procfile, ...)",
+                start.display()
+            )
+        })?;
+        ProjectPorts::discover(&root)
+    };
+
+    let ports = project.port_numbers();
+    let dependencies = project.dependency_ports();
+    // The basic scan needs no async runtime, which the tray binary doesn't have here
+    let scan_args = Args {
+        verbose: false,
+        ..args.clone()
+    };
+    let scanned: Vec<u16> = ports.iter().chain(&dependencies).copied().collect();
+    let (_, processes) = get_processes_on_ports(&scanned, &scan_args);
+    let checks: Vec<PortCheck> = scanned
+        .iter()
+        .map(|&port| {
+            let dependency = dependencies.contains(&port);
+            PortCheck {
+                port,
+                blocked: !dependency && processes.contains_key(&port),
+                dependency,
+                sources: project.sources(port),
+                process: processes.get(&port),
+            }
+        })
+        .collect();
+    let blocked = checks.iter().filter(|c| c.blocked).count();
+
+    if check.json {
+        let data = serde_json::json!({
+            "project": project.root,
+            "ports": checks,
+            "blocked": blocked,
+        });
+        if blocked > 0 {
+            output::print_with_error(
+                "check",
+                &data,
+                ExitCode::Conflict,
+                &format!("{} of {} project port(s) in use", blocked, ports.len()),
+            )?;
+        } else {
+            output::print("check", &data)?;
+        }
+        return Ok(blocked == 0);
+    }
+
+    println!(
+        "🔎 {}: {} port(s) from the project's files",
+        project.root.display(),
+        ports.len()
+    );
+    if checks.is_empty() {
+        println!("ℹ️  No ports found. Add a PORT= to .env or a port to your dev script.");
+        return Ok(true);
+    }
+    println!();
+    println!("{:<6}  {:<8}  {:<28}  SOURCE", "PORT", "STATUS", "PROCESS");
+    for check in &checks {
+        let (status, process) = match check.process {
+            Some(p) => {
+                let owner = match (&p.container_name, &p.systemd_unit) {
+                    (Some(container), _) => format!("{} (docker: {})", p.name, container),
+                    (None, Some(unit)) => format!("{} ({})", p.name, unit.describe()),
+                    (None, None) => format!("{} (PID {})", p.name, p.pid),
+                };
+                let status = if check.dependency {
+                    "in use"
+                } else {
+                    "blocked"
+                };
+                (status, owner)
+            }
+            None => ("free", String::new()),
+        };
+        let sources = check
+            .sources
+            .iter()
+            .map(|s| {
+                if s.detail.is_empty() {
+                    s.file.clone()
+                } else {
+                    format!("{} {}", s.file, s.detail)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        let sources = if check.dependency {
+            format!("{} (dependency)", sources)
+        } else {
+            sources
+        };
+        println!(
+            "{:<6}  {:<8}  {:<28}  {}",
+            check.port, status, process, sources
+        );
+    }
+    println!();
+    if blocked == 0 {
+        println!("✅ All {} port(s) are free", ports.len());
+    } else {
+        println!(
+            "❌ {} of {} port(s) blocked. Free them with `port-kill {}`",
+            blocked,
+            ports.len(),
+            checks
+                .iter()
+                .filter(|c| c.blocked)
+                .map(|c| c.port.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+    }
+    Ok(blocked == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ports(findings: &[Finding]) -> Vec<u16> {
+        findings.iter().map(|f| f.port).collect()
+    }
+
+    #[test]
+    fn test_parses_each_source_format() {
+        let compose = r#"
+services:
+  web:
+    build: .
+    ports:
+      - "3000:3000"
+      - 127.0.0.1:9229:9229 # debugger
+      - "${API_PORT:-8080}:80"
+      - "9000"
+  db:
+    image: postgres
+    ports:
+      - target: 5432
+        published: "5433"
+    expose:
+      - "6000"
+  cache:
+    ports: ["6379:6379/tcp"]
+volumes:
+  data:
+"#;
+        let found = parse_compose(compose);
+        assert_eq!(ports(&found), vec![3000, 9229, 8080, 5433, 6379]);
+        assert_eq!(found[3].detail, "services.db.ports");
+
+        let env = "# dev\nPORT=4000\nexport DB_PORT='5432' # local\nAPI_URL=http://x:1\n";
+        assert_eq!(ports(&parse_env_file(env)), vec![4000, 5432]);
+        let dependencies: Vec<bool> = parse_env_file(env).iter().map(|f| f.dependency).collect();
+        assert_eq!(dependencies, vec![false, true]);
+
+        let package = r#"{"scripts": {
+            "dev": "next dev -p 3100",
+            "storybook": "storybook dev",
+            "web": "vite",
+            "build": "vite build",
+            "api": "PORT=4001 node server.js"
+        }}"#;
+        let found = parse_package_json(package);
+        let mut script_ports = ports(&found);
+        script_ports.sort_unstable();
+        assert_eq!(script_ports, vec![3100, 4001, 5173, 6006]);
+        assert!(found.iter().any(|f| f.inferred && f.tool == Some("vite")));
+
+        let procfile = "web: bundle exec rails server -p 5000\nworker: bundle exec sidekiq\napi: python manage.py runserver 0.0.0.0:8001\n";
+        assert_eq!(ports(&parse_procfile(procfile)), vec![5000, 8001]);
+
+        let vite = "export default defineConfig({\n  server: { port: 5180 },\n  preview: { port: 4180 },\n})";
+        let found = parse_vite_config(vite);
+        assert_eq!(ports(&found), vec![5180, 4180]);
+        assert_eq!(found[1].tool, Some("vite-preview"));
+
+        let puma = "threads 5, 5\nport ENV.fetch(\"PORT\") { 3001 }\n";
+        assert_eq!(ports(&parse_puma_config(puma)), vec![3001]);
+    }
+
+    #[test]
+    fn test_discovers_project_and_drops_overridden_defaults() {
+        let root = std::env::temp_dir().join(format!(
+            "port-kill-project-test-discover-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::create_dir_all(root.join("src/components")).unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{"scripts": {"dev": "vite", "start": "next start"}}"#,
+        )
+        .unwrap();
+        fs::write(root.join("vite.config.ts"), "server: { port: 5200 }").unwrap();
+        fs::write(root.join("next.config.js"), "module.exports = {}").unwrap();
+        fs::write(root.join(".env.local"), "PORT=3000\nDB_PORT=5432\n").unwrap();
+        fs::write(root.join("Gemfile"), "gem \"rails\", \"~> 7.1\"\n").unwrap();
+        fs::write(root.join("config/puma.rb"), "port 3002\n").unwrap();
+
+        let nested = root.join("src/components");
+        assert_eq!(find_project_root(&nested), Some(root.clone()));
+
+        let project = ProjectPorts::discover(&root);
+        // Vite's 5173 default gives way to vite.config.ts; Rails' 3000 to puma.rb
+        assert_eq!(project.port_numbers(), vec![3000, 3002, 5200]);
+        // The database the app talks to is reported, but is not the project's to free
+        assert_eq!(project.dependency_ports(), vec![5432]);
+        let sources: Vec<&str> = project
+            .sources(3000)
+            .iter()
+            .map(|s| s.file.as_str())
+            .collect();
+        assert_eq!(
+            sources,
+            vec![".env.local", "package.json", "next.config.js"]
+        );
+
+        let preset = project.to_preset();
+        assert_eq!(preset.name, PROJECT_PRESET);
+        assert_eq!(preset.ports, vec![3000, 3002, 5200]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::types::ProcessInfo;
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Quote `arg` for a POSIX shell. ssh joins its arguments with spaces and hands the
+/// result to the remote user's shell, so every argument has to survive that on its own.
+pub fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:,=@%+".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// The command line the remote shell runs
+pub fn remote_command(binary: &str, args: &[String]) -> String {
+    std::iter::once(binary)
+        .chain(args.iter().map(String::as_str))
+        .map(shell_quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Hosts listed in an inventory file: one ssh destination per line (`web1`,
+/// `deploy@10.0.0.5`, or an alias from ~/.ssh/config), `#` starts a comment
+pub fn load_inventory(path: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read host inventory {}", path.display()))?;
+    let mut hosts = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        validate_host(line).with_context(|| format!("{}:{}", path.display(), number + 1))?;
+        hosts.push(line.to_string());
+    }
+    Ok(hosts)
+}
+
+/// Reject destinations ssh would read as options or that could not be one host
+pub fn validate_host(host: &str) -> Result<()> {
+    if host.is_empty() {
+        return Err(anyhow!("empty host name"));
+    }
+    if host.starts_with('-') {
+        return Err(anyhow!("invalid host '{}': must not start with '-'", host));
+    }
+    if host.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(anyhow!("invalid host '{}': contains whitespace", host));
+    }
+    Ok(())
+}
+
+/// One listener on a remote host
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteProcess {
+    pub host: String,
+    #[serde(flatten)]
+    pub process: ProcessInfo,
+}
+
+/// What came back from one host
+#[derive(Debug)]
+pub struct HostScan {
+    pub host: String,
+    pub result: Result<Vec<ProcessInfo>>,
+}
+
+/// Runs port-kill-console on other machines over ssh
+#[derive(Debug, Clone)]
+pub struct RemoteRunner {
+    /// The ssh client; `ssh` from PATH unless overridden
+    pub ssh: String,
+    /// port-kill-console on the remote side, looked up on the remote PATH by default
+    pub binary: String,
+    pub timeout: Duration,
+}
+
+impl RemoteRunner {
+    pub fn new(binary: &str, timeout: Duration) -> Self {
+        Self {
+            ssh: "ssh".to_string(),
+            binary: binary.to_string(),
+            timeout,
+        }
+    }
+
+    /// Run the remote binary with `args` and return its stdout
+    pub async fn run(&self, host: &str, args: &[String]) -> Result<String> {
+        let output = self.output(host, args).await?;
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+        Err(self.failure(&output))
+    }
+
+    /// Run the remote binary in JSON mode and return the envelope it printed, whatever
+    /// its exit code. Fails only when ssh or the binary itself could not run.
+    pub async fn run_json(&self, host: &str, args: &[String]) -> Result<serde_json::Value> {
+        let mut remote_args = vec!["--json".to_string()];
+        remote_args.extend(args.iter().cloned());
+        let output = self.output(host, &remote_args).await?;
+        match find_envelope(&String::from_utf8_lossy(&output.stdout)) {
+            Some(envelope) => Ok(envelope),
+            None if output.status.success() => Err(anyhow!(
+                "{} on {} printed no JSON envelope; is it up to date?",
+                self.binary,
+                host
+            )),
+            None => Err(self.failure(&output)),
+        }
+    }
+
+    async fn output(&self, host: &str, args: &[String]) -> Result<std::process::Output> {
+        validate_host(host)?;
+        let connect_timeout = self.timeout.as_secs().clamp(1, 10);
+        let child = Command::new(&self.ssh)
+            .args(["-o", "BatchMode=yes"])
+            .arg("-o")
+            .arg(format!("ConnectTimeout={}", connect_timeout))
+            .arg("--")
+            .arg(host)
+            .arg(remote_command(&self.binary, args))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to run {}", self.ssh))?;
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| anyhow!("timed out after {}s", self.timeout.as_secs()))??;
+        Ok(output)
+    }
+
+    /// Why a remote run failed, from its exit code and the end of its stderr
+    fn failure(&self, output: &std::process::Output) -> anyhow::Error {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // Skip the remote side's log lines; the last line says what went wrong
+        let reason = stderr
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or("no error output")
+            .trim()
+            .to_string();
+        match output.status.code() {
+            // ssh itself failed: unreachable, auth refused, ...
+            Some(255) => anyhow!("ssh failed: {}", reason),
+            Some(127) => anyhow!(
+                "{} not found on the remote host (set --remote-bin): {}",
+                self.binary,
+                reason
+            ),
+            Some(code) => anyhow!("remote command exited with {}: {}", code, reason),
+            None => anyhow!("remote command was killed: {}", reason),
+        }
+    }
+
+    /// List the listeners on `host`. `args` are the filters to apply there; the scan
+    /// runs remotely in JSON mode.
+    pub async fn scan(&self, host: &str, args: &[String]) -> Result<Vec<ProcessInfo>> {
+        let mut remote_args = vec![
+            "--json".to_string(),
+            "--log-level".to_string(),
+            "none".to_string(),
+        ];
+        remote_args.extend(args.iter().cloned());
+        let output = self.run(host, &remote_args).await?;
+        parse_processes(&output)
+    }
+
+    /// Scan every host at once; results come back in the order of `hosts`
+    pub async fn scan_all(&self, hosts: &[String], args: &[String]) -> Vec<HostScan> {
+        let tasks: Vec<_> = hosts
+            .iter()
+            .map(|host| {
+                let runner = self.clone();
+                let host = host.clone();
+                let args = args.to_vec();
+                tokio::spawn(async move { runner.scan(&host, &args).await })
+            })
+            .collect();
+        let mut scans = Vec::with_capacity(hosts.len());
+        for (host, task) in hosts.iter().zip(tasks) {
+            let result = task
+                .await
+                .unwrap_or_else(|e| Err(anyhow!("scan task failed: {}", e)));
+            scans.push(HostScan {
+                host: host.clone(),
+                result,
+            });
+        }
+        scans
+    }
+}
+
+/// The JSON envelope in a remote command's stdout. Anything before it (update notices,
+/// banners from the login shell) is skipped.
+pub fn find_envelope(output: &str) -> Option<serde_json::Value> {
+    let mut offset = 0;
+    for line in output.split_inclusive('\n') {
+        if line.trim_start().starts_with('{') {
+            let value = serde_json::Deserializer::from_str(&output[offset..])
+                .into_iter::<serde_json::Value>()
+                .next()?
+                .ok()?;
+            return value.get("schema_version").is_some().then_some(value);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Listeners from the remote `--json` output: an envelope of kind `processes`, or one
+/// object per line from versions before the envelope
+pub fn parse_processes(output: &str) -> Result<Vec<ProcessInfo>> {
+    if let Some(mut envelope) = find_envelope(output) {
+        if envelope["ok"] != true {
+            return Err(anyhow!(
+                "remote scan failed: {}",
+                envelope["error"]["message"]
+                    .as_str()
+                    .unwrap_or("unknown error")
+            ));
+        }
+        return serde_json::from_value(envelope["data"].take())
+            .context("Unexpected output from remote port-kill");
+    }
+
+    let mut processes = Vec::new();
+    for line in output.lines().map(str::trim) {
+        if !line.starts_with('{') {
+            continue;
+        }
+        let process: ProcessInfo = serde_json::from_str(line)
+            .with_context(|| format!("Unexpected output from remote port-kill: {}", line))?;
+        processes.push(process);
+    }
+    Ok(processes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quotes_arguments_for_the_remote_shell() {
+        assert_eq!(shell_quote("--ports"), "--ports");
+        assert_eq!(shell_quote("3000,8000"), "3000,8000");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("it's; rm -rf ~"), r"'it'\''s; rm -rf ~'");
+        assert_eq!(
+            remote_command(
+                "port-kill-console",
+                &[
+                    "--where".to_string(),
+                    "name ~ 'node' and port > 3000".to_string()
+                ]
+            ),
+            r"port-kill-console --where 'name ~ '\''node'\'' and port > 3000'"
+        );
+
+        assert!(validate_host("deploy@web-1.example.com").is_ok());
+        assert!(validate_host("-oProxyCommand=touch /tmp/x").is_err());
+        assert!(validate_host("web1 web2").is_err());
+    }
+
+    #[tokio::test]
+    async fn scans_hosts_through_ssh() {
+        let dir =
+            std::env::temp_dir().join(format!("port-kill-remote-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Stands in for ssh: checks the destination and echoes the remote command back
+        // inside the process name, so the test sees exactly what the remote shell gets
+        let ssh = dir.join("ssh");
+        std::fs::write(
+            &ssh,
+            r#"#!/bin/sh
+while [ "$1" != "--" ]; do shift; done
+host="$2"; command="$3"
+case "$host" in
+  down) echo "ssh: connect to host down port 22: Connection refused" >&2; exit 255 ;;
+esac
+echo "Welcome to $host"
+eval "set -- $command"
+printf '{"pid":41,"port":3000,"command":"node","name":"%s","container_id":null,"container_name":null,"command_line":null,"working_directory":null,"process_group":"Node.js","project_name":null,"cpu_usage":null,"memory_usage":null,"memory_percentage":null}\n' "$host:$#:$6"
+"#,
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&ssh, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let mut runner = RemoteRunner::new("port-kill-console", Duration::from_secs(10));
+        runner.ssh = ssh.to_string_lossy().into_owned();
+        let hosts = vec!["web1".to_string(), "down".to_string()];
+        let filter = vec!["--where".to_string(), "name ~ 'a b'; exit 1".to_string()];
+        let scans = runner.scan_all(&hosts, &filter).await;
+
+        assert_eq!(scans[0].host, "web1");
+        let processes = scans[0].result.as_ref().unwrap();
+        assert_eq!(processes.len(), 1);
+        // The expression arrived as one argument, untouched
+        assert_eq!(processes[0].name, "web1:6:name ~ 'a b'; exit 1");
+        assert_eq!(processes[0].framework, None);
+
+        let error = scans[1].result.as_ref().unwrap_err().to_string();
+        assert!(
+            error.contains("ssh failed: ssh: connect to host down"),
+            "{}",
+            error
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // Current versions answer with an envelope, after whatever the login shell printed
+        let output = format!(
+            "Last login: today\n{}\n",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "schema_version": 1,
+                "kind": "processes",
+                "ok": true,
+                "exit_code": 0,
+                "data": [processes[0]],
+                "error": null,
+            }))
+            .unwrap()
+        );
+        assert_eq!(
+            parse_processes(&output).unwrap(),
+            vec![processes[0].clone()]
+        );
+    }
+}
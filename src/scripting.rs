@@ -23,6 +23,7 @@ pub struct ScriptEngine {
     _last_processes: HashMap<u16, ProcessInfo>, // Track last known processes to detect changes
     port_guards: HashMap<u16, GuardConfig>,     // Port guard configurations
     file_guards: HashMap<String, GuardConfig>,  // File guard configurations
+    variables: HashMap<String, String>,         // Values bound to {placeholders}
 }
 
 impl ScriptEngine {
@@ -36,9 +37,16 @@ impl ScriptEngine {
             _last_processes: HashMap::new(),
             port_guards: HashMap::new(),
             file_guards: HashMap::new(),
+            variables: HashMap::new(),
         }
     }
 
+    /// Bind values to `{key}` placeholders in command arguments
+    pub fn with_variables(mut self, variables: HashMap<String, String>) -> Self {
+        self.variables = variables;
+        self
+    }
+
     /// Execute a script
     pub async fn execute(&mut self, script: &str) -> Result<()> {
         match self.args.script_lang.as_str() {
@@ -88,6 +96,9 @@ impl ScriptEngine {
                 if command.is_empty() {
                     continue;
                 }
+                // Bound after splitting, so a value can never add commands of its own
+                let command = self.bind_variables(command);
+                let command = command.as_str();
 
                 if command.starts_with("onPort(") {
                     self.parse_on_port_command(command).await?;
@@ -128,6 +139,28 @@ impl ScriptEngine {
         Ok(())
     }
 
+    /// Replace `{key}` placeholders in the arguments of one command, leaving its name alone
+    fn bind_variables(&self, command: &str) -> String {
+        let Some(open) = command.find('(') else {
+            return command.to_string();
+        };
+        let (name, mut rest) = command.split_at(open);
+        let mut bound = name.to_string();
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+                break;
+            };
+            bound.push_str(&rest[..start]);
+            match self.variables.get(&rest[start + 1..end]) {
+                Some(value) => bound.push_str(value),
+                None => bound.push_str(&rest[start..=end]),
+            }
+            rest = &rest[end + 1..];
+        }
+        bound.push_str(rest);
+        bound
+    }
+
     /// Parse onPort command
     async fn parse_on_port_command(&mut self, line: &str) -> Result<()> {
         // Simple parsing: onPort(3000, callback)
@@ -159,7 +192,9 @@ impl ScriptEngine {
         if let Some(pid_str) = self.extract_pid_from_kill(line) {
             if let Ok(pid) = pid_str.parse::<i32>() {
                 println!("🔪 Killing process with PID: {}", pid);
-                if let Err(e) = crate::process_monitor::kill_single_process(pid, &self.args) {
+                if let Err(e) = crate::process_monitor::kill_single_process(pid, &self.args)
+                    .and_then(|kills| crate::process_monitor::check_kills(&kills))
+                {
                     println!("❌ Failed to kill process PID {}: {}", pid, e);
                 } else {
                     println!("✅ Successfully killed process PID {}", pid);
@@ -187,6 +222,7 @@ impl ScriptEngine {
                 let ports_to_kill = vec![port];
                 if let Err(e) =
                     crate::process_monitor::kill_all_processes(&ports_to_kill, &self.args)
+                        .and_then(|kills| crate::process_monitor::check_kills(&kills))
                 {
                     println!("❌ Failed to clear processes on port {}: {}", port, e);
                 } else {
@@ -680,6 +716,8 @@ mod tests {
                 ignore_groups: None,
                 smart_filter: false,
                 only_groups: None,
+                where_expr: None,
+                tui: false,
                 console: false,
                 verbose: false,
                 docker: false,
@@ -716,6 +754,10 @@ mod tests {
                 baseline_file: None,
                 suspicious_only: false,
                 remote: None,
+                guard_status: false,
+                hosts: None,
+                remote_bin: "port-kill-console".to_string(),
+                remote_timeout: 30,
                 monitor_endpoint: None,
                 send_interval: 30,
                 scan_interval: 2,
@@ -724,6 +766,16 @@ mod tests {
                 endpoint_include_audit: false,
                 endpoint_retries: 3,
                 endpoint_timeout: 10,
+                webhook: Vec::new(),
+                webhook_secret: None,
+                webhook_config: None,
+                history_file: None,
+                history_max_mb: 5,
+                history_max_age_days: 30,
+                alert_rules: None,
+                metrics_listen: None,
+                endpoint_spool_dir: "~/.port-kill/endpoint-spool".to_string(),
+                endpoint_spool_max_mb: 50,
                 script: None,
                 script_file: None,
                 clear: None,
@@ -735,6 +787,9 @@ mod tests {
                 list_file: None,
                 list: false,
                 safe: false,
+                respawn_window: 5,
+                stop_supervisor: false,
+                force_pid: false,
                 positional_ports: vec![],
                 preset: None,
                 list_presets: false,
@@ -743,7 +798,10 @@ mod tests {
                 delete_preset: None,
                 check_updates: false,
                 self_update: false,
-                cache: None,
+                rollback: false,
+                release_source: None,
+                release_pubkey: None,
+                command: None,
             },
         );
 
@@ -780,6 +838,8 @@ mod tests {
                 ignore_groups: None,
                 smart_filter: false,
                 only_groups: None,
+                where_expr: None,
+                tui: false,
                 console: false,
                 verbose: false,
                 docker: false,
@@ -816,6 +876,10 @@ mod tests {
                 baseline_file: None,
                 suspicious_only: false,
                 remote: None,
+                guard_status: false,
+                hosts: None,
+                remote_bin: "port-kill-console".to_string(),
+                remote_timeout: 30,
                 monitor_endpoint: None,
                 send_interval: 30,
                 scan_interval: 2,
@@ -824,6 +888,16 @@ mod tests {
                 endpoint_include_audit: false,
                 endpoint_retries: 3,
                 endpoint_timeout: 10,
+                webhook: Vec::new(),
+                webhook_secret: None,
+                webhook_config: None,
+                history_file: None,
+                history_max_mb: 5,
+                history_max_age_days: 30,
+                alert_rules: None,
+                metrics_listen: None,
+                endpoint_spool_dir: "~/.port-kill/endpoint-spool".to_string(),
+                endpoint_spool_max_mb: 50,
                 script: None,
                 script_file: None,
                 clear: None,
@@ -835,6 +909,9 @@ mod tests {
                 list_file: None,
                 list: false,
                 safe: false,
+                respawn_window: 5,
+                stop_supervisor: false,
+                force_pid: false,
                 positional_ports: vec![],
                 preset: None,
                 list_presets: false,
@@ -843,7 +920,10 @@ mod tests {
                 delete_preset: None,
                 check_updates: false,
                 self_update: false,
-                cache: None,
+                rollback: false,
+                release_source: None,
+                release_pubkey: None,
+                command: None,
             },
         );
 
@@ -863,4 +943,27 @@ mod tests {
         );
         assert_eq!(engine.extract_seconds_from_wait("wait(5)"), Some("5"));
     }
+
+    #[test]
+    fn test_bound_variables_stay_inside_their_command() {
+        let engine = ScriptEngine::new(
+            Arc::new(Mutex::new(
+                ProcessMonitor::new(crossbeam_channel::bounded(100).0, vec![3000], false, false)
+                    .unwrap(),
+            )),
+            Args::default(),
+        )
+        .with_variables(HashMap::from([
+            ("name".to_string(), "x\"); kill(1); log(\"".to_string()),
+            ("pid".to_string(), "{name}".to_string()),
+        ]));
+
+        // The hostile name is one log argument, not a kill command
+        let bound = engine.bind_variables("log(\"{name}\")");
+        assert_eq!(bound, "log(\"x\"); kill(1); log(\"\")");
+        // Values are not expanded again and unknown placeholders are left as written
+        assert_eq!(engine.bind_variables("kill({pid})"), "kill({name})");
+        assert_eq!(engine.bind_variables("log({port})"), "log({port})");
+        assert_eq!(engine.bind_variables("{name}(1)"), "{name}(1)");
+    }
 }
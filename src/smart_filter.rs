@@ -1,7 +1,239 @@
+use crate::cli::Args;
+use crate::filter_expr::FilterExpr;
 use crate::types::ProcessInfo;
 use anyhow::Result;
 use regex::Regex;
-use std::collections::HashSet;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// Daemons that ship with the desktop or OS and listen on ports of their own.
+/// Names are compared case-insensitively; lsof truncates to 9 characters on macOS.
+const KNOWN_DESKTOP_DAEMONS: &[&str] = &[
+    // Linux
+    "cupsd",
+    "cups-browsed",
+    "avahi-daemon",
+    "systemd-resolved",
+    "systemd-timesyncd",
+    "dnsmasq",
+    "chronyd",
+    "ntpd",
+    "colord",
+    "rpcbind",
+    "rpc.statd",
+    "smbd",
+    "nmbd",
+    "kdeconnectd",
+    "gsd-sharing",
+    "gnome-remote-desktop-daemon",
+    "xrdp",
+    // macOS
+    "launchd",
+    "rapportd",
+    "ControlCenter",
+    "ControlCe",
+    "AirPlayXPCHelper",
+    "AirPlayXP",
+    "sharingd",
+    "mDNSResponder",
+    "mDNSRespo",
+    "remoted",
+    "identityservicesd",
+    "UserEventAgent",
+    // Windows
+    "System",
+    "svchost",
+    "lsass",
+    "wininit",
+    "services",
+    "spoolsv",
+    "winlogon",
+];
+
+/// Processes that front user containers; they run as root from system paths but
+/// the ports belong to whatever the user started
+const CONTAINER_FRONTENDS: &[&str] = &[
+    "docker-proxy",
+    "com.docker.backend",
+    "com.docker.vpnkit",
+    "vpnkit",
+    "rootlesskit",
+    "slirp4netns",
+    "gvproxy",
+    "conmon",
+];
+
+/// Directories that only hold binaries installed by the OS
+const SYSTEM_EXECUTABLE_DIRS: &[&str] = &[
+    "/usr/sbin/",
+    "/sbin/",
+    "/lib/systemd/",
+    "/usr/lib/systemd/",
+    "/usr/libexec/",
+    "/System/Library/",
+    "/Library/Apple/",
+];
+
+/// The lowest uid handed to people rather than services
+const FIRST_USER_UID: u32 = if cfg!(target_os = "macos") { 501 } else { 1000 };
+
+/// Why the smart filter treats a listener as a system process
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "rule", content = "detail", rename_all = "snake_case")]
+pub enum SystemReason {
+    /// On the list of known desktop and OS daemons
+    KnownDaemon(String),
+    /// Runs in a systemd system service (`system.slice`)
+    SystemService(String),
+    /// Its executable lives in an OS directory such as /usr/sbin
+    SystemPath(String),
+    /// Owned by a service account rather than a person
+    SystemUser(u32),
+}
+
+impl SystemReason {
+    pub fn describe(&self) -> String {
+        match self {
+            SystemReason::KnownDaemon(name) => format!("known desktop daemon ({})", name),
+            SystemReason::SystemService(unit) => format!("systemd system service ({})", unit),
+            SystemReason::SystemPath(path) => format!("system executable ({})", path),
+            SystemReason::SystemUser(uid) => {
+                format!("owned by system account (uid {} < {})", uid, FIRST_USER_UID)
+            }
+        }
+    }
+}
+
+/// What the OS tells us about a process, gathered separately so classification is testable
+#[derive(Debug, Clone, Default)]
+pub struct ProcessFacts {
+    pub executable: Option<String>,
+    pub uid: Option<u32>,
+    pub cgroup: Option<String>,
+}
+
+impl ProcessFacts {
+    #[cfg(target_os = "linux")]
+    pub fn collect(pid: i32) -> Self {
+        let proc_dir = std::path::PathBuf::from(format!("/proc/{}", pid));
+        let uid = std::fs::read_to_string(proc_dir.join("status"))
+            .ok()
+            .and_then(|status| {
+                status
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Uid:"))
+                    .and_then(|ids| ids.split_whitespace().next()?.parse().ok())
+            });
+        Self {
+            // Needs the same user or root; the uid and cgroup are readable by anyone
+            executable: std::fs::read_link(proc_dir.join("exe"))
+                .ok()
+                .map(|path| path.to_string_lossy().into_owned()),
+            uid,
+            cgroup: std::fs::read_to_string(proc_dir.join("cgroup")).ok(),
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn collect(pid: i32) -> Self {
+        // `comm` is the full executable path on macOS
+        let output = std::process::Command::new("ps")
+            .args(["-o", "uid=,comm=", "-p", &pid.to_string()])
+            .output();
+        let Ok(output) = output else {
+            return Self::default();
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let line = stdout.trim();
+        let (uid, executable) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        Self {
+            executable: Some(executable.trim().to_string()).filter(|e| !e.is_empty()),
+            uid: uid.trim().parse().ok(),
+            cgroup: None,
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    pub fn collect(_pid: i32) -> Self {
+        Self::default()
+    }
+}
+
+/// Decide whether a listener is a system process, and why. Container frontends
+/// never are; otherwise the first matching rule wins, most specific first.
+pub fn classify_process(process: &ProcessInfo, facts: &ProcessFacts) -> Option<SystemReason> {
+    let executable = facts.executable.clone().or_else(|| {
+        let command_line = process.command_line.as_deref()?;
+        let first = command_line.split_whitespace().next()?;
+        (first.starts_with('/') || first.contains(":\\")).then(|| first.to_string())
+    });
+    let base_name = executable
+        .as_deref()
+        .and_then(|e| e.rsplit(['/', '\\']).next())
+        .map(|name| name.trim_end_matches(".exe").to_string());
+    let names: Vec<&str> = std::iter::once(process.name.as_str())
+        .chain(base_name.as_deref())
+        .collect();
+
+    if process.container_id.is_some() || names.iter().any(|name| CONTAINER_FRONTENDS.contains(name))
+    {
+        return None;
+    }
+
+    if let Some(daemon) = KNOWN_DESKTOP_DAEMONS
+        .iter()
+        .find(|daemon| names.iter().any(|name| name.eq_ignore_ascii_case(daemon)))
+    {
+        return Some(SystemReason::KnownDaemon(daemon.to_string()));
+    }
+
+    let system_unit = process
+        .systemd_unit
+        .as_ref()
+        .filter(|unit| !unit.user)
+        .map(|unit| unit.name.clone())
+        .or_else(|| facts.cgroup.as_deref().and_then(system_slice_service));
+    if let Some(unit) = system_unit {
+        return Some(SystemReason::SystemService(unit));
+    }
+
+    if let Some(executable) = executable {
+        let lower = executable.to_ascii_lowercase();
+        if SYSTEM_EXECUTABLE_DIRS
+            .iter()
+            .any(|dir| executable.starts_with(dir))
+            || lower.contains("\\windows\\system32\\")
+            || lower.contains("\\windows\\syswow64\\")
+        {
+            return Some(SystemReason::SystemPath(executable));
+        }
+    }
+
+    facts
+        .uid
+        .filter(|uid| *uid < FIRST_USER_UID)
+        .map(SystemReason::SystemUser)
+}
+
+/// The `.service` a cgroup file places a process in under `system.slice`
+fn system_slice_service(cgroup: &str) -> Option<String> {
+    cgroup.lines().find_map(|line| {
+        let path = line.splitn(3, ':').nth(2)?;
+        let mut parts = path.split('/').skip_while(|part| *part != "system.slice");
+        parts.next()?;
+        parts
+            .find(|part| part.ends_with(".service"))
+            .map(String::from)
+    })
+}
+
+/// A listener the filter hid, and the rule that hid it
+#[derive(Debug, Clone, Serialize)]
+pub struct FilteredProcess {
+    pub process: ProcessInfo,
+    pub reason: String,
+}
 
 pub struct SmartFilter {
     ignore_ports: HashSet<u16>,
@@ -9,6 +241,13 @@ pub struct SmartFilter {
     ignore_patterns: Vec<Regex>,
     ignore_groups: HashSet<String>,
     only_groups: Option<HashSet<String>>,
+    /// Auto-detect system processes (`--smart-filter`)
+    detect_system: bool,
+    /// Classification per PID and name (PIDs get reused), so each scan doesn't
+    /// re-read /proc or run `ps`; entries for processes gone from a scan are dropped
+    system_cache: Mutex<HashMap<(i32, String), Option<SystemReason>>>,
+    /// Only show processes matching this expression (`--where`)
+    where_expr: Option<FilterExpr>,
 }
 
 impl SmartFilter {
@@ -36,51 +275,157 @@ impl SmartFilter {
             ignore_patterns: compiled_patterns,
             ignore_groups,
             only_groups,
+            detect_system: false,
+            system_cache: Mutex::new(HashMap::new()),
+            where_expr: None,
         })
     }
 
+    /// The filter the ignore flags, `--smart-filter` and `--where` ask for, or None
+    /// when none of them is set. Every mode that scans listeners builds it this way
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        let ignore_ports = args.get_ignore_ports_set();
+        let ignore_processes = args.get_ignore_processes_set();
+        let ignore_groups = args.get_ignore_groups_set();
+
+        // Check if any filtering is needed
+        if !args.smart_filter
+            && ignore_ports.is_empty()
+            && ignore_processes.is_empty()
+            && args.ignore_patterns.is_none()
+            && ignore_groups.is_empty()
+            && args.only_groups.is_none()
+            && args.where_expr.is_none()
+        {
+            return Ok(None);
+        }
+
+        let filter = Self::new(
+            ignore_ports,
+            ignore_processes,
+            args.ignore_patterns.clone(),
+            ignore_groups,
+            args.get_only_groups_set(),
+        )?
+        .with_system_detection(args.smart_filter)
+        .with_where(args.get_where_filter()?);
+
+        Ok(Some(filter))
+    }
+
+    /// Also hide processes the classifier recognises as system processes
+    pub fn with_system_detection(mut self, enabled: bool) -> Self {
+        self.detect_system = enabled;
+        self
+    }
+
+    /// Hide processes that don't match a `--where` expression
+    pub fn with_where(mut self, expr: Option<FilterExpr>) -> Self {
+        self.where_expr = expr;
+        self
+    }
+
     pub fn should_ignore_process(&self, process_info: &ProcessInfo) -> bool {
+        self.ignore_reason(process_info).is_some()
+    }
+
+    /// Why a process is hidden, or None when it is shown
+    pub fn ignore_reason(&self, process_info: &ProcessInfo) -> Option<String> {
         // Check port ignore list
         if self.ignore_ports.contains(&process_info.port) {
-            return true;
+            return Some(format!("port {} is ignored", process_info.port));
         }
 
         // Check process name ignore list
         if self.ignore_processes.contains(&process_info.name) {
-            return true;
+            return Some(format!("process '{}' is ignored", process_info.name));
         }
 
         // Check pattern matching against both name and command
         for pattern in &self.ignore_patterns {
             if pattern.is_match(&process_info.name) || pattern.is_match(&process_info.command) {
-                return true;
+                return Some(format!("matches ignore pattern '{}'", pattern.as_str()));
             }
         }
 
         // Check group ignore list
         if let Some(ref group) = process_info.process_group {
             if self.ignore_groups.contains(group) {
-                return true;
+                return Some(format!("group '{}' is ignored", group));
             }
         }
 
         // Check only_groups filter (if specified, only show these groups)
         if let Some(ref only_groups) = self.only_groups {
-            if let Some(ref group) = process_info.process_group {
-                if !only_groups.contains(group) {
-                    return true;
+            match process_info.process_group {
+                Some(ref group) if !only_groups.contains(group) => {
+                    return Some(format!("group '{}' is not in --only-groups", group));
                 }
-            } else {
+                Some(_) => {}
                 // Process has no group but only_groups is specified
-                return true;
+                None => return Some("has no group and --only-groups is set".to_string()),
             }
         }
 
-        false
+        if self.detect_system {
+            if let Some(reason) = self.system_reason(process_info) {
+                return Some(format!("system process: {}", reason.describe()));
+            }
+        }
+
+        if let Some(ref expr) = self.where_expr {
+            if !expr.matches(process_info) {
+                return Some(format!("does not match --where '{}'", expr.source()));
+            }
+        }
+
+        None
+    }
+
+    fn system_reason(&self, process_info: &ProcessInfo) -> Option<SystemReason> {
+        let mut cache = self.system_cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache
+            .entry((process_info.pid, process_info.name.clone()))
+            .or_insert_with(|| {
+                classify_process(process_info, &ProcessFacts::collect(process_info.pid))
+            })
+            .clone()
     }
 
     pub fn filter_processes(&self, processes: &mut std::collections::HashMap<u16, ProcessInfo>) {
-        processes.retain(|_, process_info| !self.should_ignore_process(process_info));
+        self.partition_processes(processes);
+    }
+
+    /// Remove hidden processes from `processes` and return them with the reason
+    pub fn partition_processes(
+        &self,
+        processes: &mut std::collections::HashMap<u16, ProcessInfo>,
+    ) -> Vec<FilteredProcess> {
+        if self.detect_system {
+            let mut cache = self.system_cache.lock().unwrap_or_else(|e| e.into_inner());
+            cache.retain(|(pid, name), _| {
+                processes
+                    .values()
+                    .any(|process| process.pid == *pid && &process.name == name)
+            });
+        }
+        if let Some(ref expr) = self.where_expr {
+            expr.forget_gone(processes);
+        }
+
+        let mut hidden = Vec::new();
+        processes.retain(|_, process_info| match self.ignore_reason(process_info) {
+            Some(reason) => {
+                hidden.push(FilteredProcess {
+                    process: process_info.clone(),
+                    reason,
+                });
+                false
+            }
+            None => true,
+        });
+        hidden.sort_by_key(|filtered| filtered.process.port);
+        hidden
     }
 
     pub fn get_filter_stats(&self) -> FilterStats {
@@ -90,17 +435,21 @@ impl SmartFilter {
             ignore_patterns_count: self.ignore_patterns.len(),
             ignore_groups_count: self.ignore_groups.len(),
             only_groups_count: self.only_groups.as_ref().map_or(0, |g| g.len()),
+            system_detection: self.detect_system,
+            where_expr: self.where_expr.as_ref().map(|e| e.source().to_string()),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FilterStats {
     pub ignore_ports_count: usize,
     pub ignore_processes_count: usize,
     pub ignore_patterns_count: usize,
     pub ignore_groups_count: usize,
     pub only_groups_count: usize,
+    pub system_detection: bool,
+    pub where_expr: Option<String>,
 }
 
 impl FilterStats {
@@ -110,6 +459,8 @@ impl FilterStats {
             || self.ignore_patterns_count > 0
             || self.ignore_groups_count > 0
             || self.only_groups_count > 0
+            || self.system_detection
+            || self.where_expr.is_some()
     }
 
     pub fn get_description(&self) -> String {
@@ -130,6 +481,12 @@ impl FilterStats {
         if self.only_groups_count > 0 {
             parts.push(format!("{} only-groups", self.only_groups_count));
         }
+        if self.system_detection {
+            parts.push("auto-detected system processes".to_string());
+        }
+        if let Some(ref expr) = self.where_expr {
+            parts.push(format!("where {}", expr));
+        }
 
         if parts.is_empty() {
             "no filters".to_string()
@@ -156,62 +513,11 @@ mod tests {
         .unwrap();
 
         let mut processes = HashMap::new();
-        processes.insert(
-            3000,
-            ProcessInfo {
-                pid: 1234,
-                port: 3000,
-                command: "node".to_string(),
-                name: "node".to_string(),
-                container_id: None,
-                container_name: None,
-                command_line: None,
-                working_directory: None,
-                process_group: None,
-                project_name: None,
-                cpu_usage: None,
-                memory_usage: None,
-                memory_percentage: None,
-            },
-        );
+        processes.insert(3000, ProcessInfo::for_test(1234, 3000, "node"));
 
-        processes.insert(
-            8000,
-            ProcessInfo {
-                pid: 5678,
-                port: 8000,
-                command: "python".to_string(),
-                name: "python".to_string(),
-                container_id: None,
-                container_name: None,
-                command_line: None,
-                working_directory: None,
-                process_group: None,
-                project_name: None,
-                cpu_usage: None,
-                memory_usage: None,
-                memory_percentage: None,
-            },
-        );
+        processes.insert(8000, ProcessInfo::for_test(5678, 8000, "python"));
 
-        processes.insert(
-            9000,
-            ProcessInfo {
-                pid: 9012,
-                port: 9000,
-                command: "rust".to_string(),
-                name: "rust".to_string(),
-                container_id: None,
-                container_name: None,
-                command_line: None,
-                working_directory: None,
-                process_group: None,
-                project_name: None,
-                cpu_usage: None,
-                memory_usage: None,
-                memory_percentage: None,
-            },
-        );
+        processes.insert(9000, ProcessInfo::for_test(9012, 9000, "rust"));
 
         filter.filter_processes(&mut processes);
 
@@ -235,38 +541,16 @@ mod tests {
         processes.insert(
             3000,
             ProcessInfo {
-                pid: 1234,
-                port: 3000,
-                command: "node".to_string(),
-                name: "node".to_string(),
-                container_id: None,
-                container_name: None,
-                command_line: None,
-                working_directory: None,
                 process_group: Some("Node.js".to_string()),
-                project_name: None,
-                cpu_usage: None,
-                memory_usage: None,
-                memory_percentage: None,
+                ..ProcessInfo::for_test(1234, 3000, "node")
             },
         );
 
         processes.insert(
             8000,
             ProcessInfo {
-                pid: 5678,
-                port: 8000,
-                command: "python".to_string(),
-                name: "python".to_string(),
-                container_id: None,
-                container_name: None,
-                command_line: None,
-                working_directory: None,
                 process_group: Some("Python".to_string()),
-                project_name: None,
-                cpu_usage: None,
-                memory_usage: None,
-                memory_percentage: None,
+                ..ProcessInfo::for_test(5678, 8000, "python")
             },
         );
 
@@ -276,4 +560,128 @@ mod tests {
         assert_eq!(processes.len(), 1);
         assert!(processes.contains_key(&3000));
     }
+
+    #[test]
+    fn test_from_args_builds_a_filter_only_when_asked() {
+        assert!(SmartFilter::from_args(&Args::default()).unwrap().is_none());
+
+        let args = Args {
+            where_expr: Some("port == 3000".to_string()),
+            ..Args::default()
+        };
+        let filter = SmartFilter::from_args(&args).unwrap().unwrap();
+        let mut processes = HashMap::new();
+        processes.insert(3000, ProcessInfo::for_test(i32::MAX, 3000, "node"));
+        processes.insert(8080, ProcessInfo::for_test(i32::MAX, 8080, "java"));
+        let hidden = filter.partition_processes(&mut processes);
+        assert_eq!(processes.keys().copied().collect::<Vec<_>>(), vec![3000]);
+        assert_eq!(hidden[0].reason, "does not match --where 'port == 3000'");
+    }
+
+    #[test]
+    fn test_system_cache_forgets_processes_that_are_gone() {
+        let filter = SmartFilter::new(HashSet::new(), HashSet::new(), None, HashSet::new(), None)
+            .unwrap()
+            .with_system_detection(true);
+
+        let mut processes = HashMap::new();
+        processes.insert(3000, ProcessInfo::for_test(i32::MAX, 3000, "node"));
+        filter.partition_processes(&mut processes);
+        assert_eq!(filter.system_cache.lock().unwrap().len(), 1);
+
+        filter.partition_processes(&mut processes);
+        assert_eq!(filter.system_cache.lock().unwrap().len(), 1);
+
+        filter.partition_processes(&mut HashMap::new());
+        assert!(filter.system_cache.lock().unwrap().is_empty());
+    }
+
+    fn facts(executable: &str, uid: u32, cgroup: &str) -> ProcessFacts {
+        ProcessFacts {
+            executable: Some(executable.to_string()),
+            uid: Some(uid),
+            cgroup: Some(cgroup.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_system_process_classification() {
+        let user_session = "0::/user.slice/user-1000.slice/session-2.scope";
+        let cases = [
+            (
+                "cupsd",
+                facts("/usr/sbin/cupsd", 0, "0::/system.slice/cups.service"),
+                Some(SystemReason::KnownDaemon("cupsd".to_string())),
+            ),
+            (
+                "postgres",
+                facts(
+                    "/usr/lib/postgresql/16/bin/postgres",
+                    112,
+                    "0::/system.slice/system-postgresql.slice/postgresql@16-main.service",
+                ),
+                Some(SystemReason::SystemService(
+                    "postgresql@16-main.service".to_string(),
+                )),
+            ),
+            (
+                "nginx",
+                facts("/usr/sbin/nginx", 0, user_session),
+                Some(SystemReason::SystemPath("/usr/sbin/nginx".to_string())),
+            ),
+            (
+                "python3",
+                facts("/usr/bin/python3", 33, user_session),
+                Some(SystemReason::SystemUser(33)),
+            ),
+            // A developer's own Postgres and app server stay visible on 5432 and 8080
+            (
+                "postgres",
+                facts("/opt/homebrew/opt/postgresql@16/bin/postgres", 1000, ""),
+                None,
+            ),
+            (
+                "java",
+                facts(
+                    "/home/dev/.sdkman/candidates/java/current/bin/java",
+                    1000,
+                    user_session,
+                ),
+                None,
+            ),
+            // Root-owned, but it forwards to a container the user started
+            (
+                "docker-proxy",
+                facts(
+                    "/usr/bin/docker-proxy",
+                    0,
+                    "0::/system.slice/docker.service",
+                ),
+                None,
+            ),
+        ];
+        for (name, facts, expected) in cases {
+            assert_eq!(
+                classify_process(&ProcessInfo::for_test(i32::MAX, 5432, name), &facts),
+                expected,
+                "{}",
+                name
+            );
+        }
+
+        let filter = SmartFilter::new(HashSet::new(), HashSet::new(), None, HashSet::new(), None)
+            .unwrap()
+            .with_system_detection(true);
+        let mut processes = HashMap::new();
+        processes.insert(631, ProcessInfo::for_test(i32::MAX, 631, "cupsd"));
+        processes.insert(3000, ProcessInfo::for_test(i32::MAX, 3000, "node"));
+        let hidden = filter.partition_processes(&mut processes);
+        assert!(processes.contains_key(&3000));
+        assert_eq!(hidden.len(), 1);
+        assert_eq!(
+            hidden[0].reason,
+            "system process: known desktop daemon (cupsd)"
+        );
+        assert!(filter.get_filter_stats().is_active());
+    }
 }
@@ -0,0 +1,385 @@
+use crate::cli::Args;
+use crate::process_monitor::get_processes_on_ports;
+use crate::system_monitor::parse_systemd_unit;
+use crate::types::{ProcessInfo, SystemdUnit};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// How long to watch a freed port when none is configured
+pub const DEFAULT_RESPAWN_WINDOW: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Stop walking up the parent chain after this many ancestors
+const MAX_ANCESTORS: usize = 16;
+
+/// Commands that restart their child when it exits
+const WATCHERS: &[&str] = &[
+    "nodemon",
+    "ts-node-dev",
+    "node-dev",
+    "forever",
+    "cargo-watch",
+    "watchexec",
+    "air",
+    "supervisord",
+    "runit",
+];
+
+/// Whatever keeps bringing a process back
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SupervisorKind {
+    SystemdUnit(SystemdUnit),
+    Container { id: String, restart_policy: String },
+    Pm2 { name: String },
+    Launchd { label: String },
+    Watcher { name: String, pid: i32 },
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Supervisor {
+    #[serde(flatten)]
+    pub kind: SupervisorKind,
+    /// Command that stops the supervisor, or the supervised job, for good
+    pub stop_command: Vec<String>,
+}
+
+impl Supervisor {
+    fn new(kind: SupervisorKind) -> Self {
+        let stop_command: Vec<String> = match &kind {
+            SupervisorKind::SystemdUnit(unit) => unit.stop_command(),
+            SupervisorKind::Container { id, .. } => {
+                vec!["docker".into(), "stop".into(), id.clone()]
+            }
+            SupervisorKind::Pm2 { name } => vec!["pm2".into(), "stop".into(), name.clone()],
+            SupervisorKind::Launchd { label } => {
+                vec!["launchctl".into(), "remove".into(), label.clone()]
+            }
+            SupervisorKind::Watcher { pid, .. } => {
+                if cfg!(target_os = "windows") {
+                    vec![
+                        "taskkill".into(),
+                        "/PID".into(),
+                        pid.to_string(),
+                        "/T".into(),
+                        "/F".into(),
+                    ]
+                } else {
+                    vec!["kill".into(), pid.to_string()]
+                }
+            }
+        };
+        Self { kind, stop_command }
+    }
+
+    pub fn describe(&self) -> String {
+        match &self.kind {
+            SupervisorKind::SystemdUnit(unit) => unit.describe(),
+            SupervisorKind::Container { id, restart_policy } => {
+                format!("container {} (restart policy: {})", id, restart_policy)
+            }
+            SupervisorKind::Pm2 { name } => format!("pm2 app {}", name),
+            SupervisorKind::Launchd { label } => format!("launchd job {}", label),
+            SupervisorKind::Watcher { name, pid } => format!("{} (PID {})", name, pid),
+        }
+    }
+
+    pub fn stop_command_line(&self) -> String {
+        self.stop_command.join(" ")
+    }
+
+    pub fn stop(&self) -> Result<()> {
+        let (program, args) = self
+            .stop_command
+            .split_first()
+            .ok_or_else(|| anyhow!("no stop command"))?;
+        let output = Command::new(program).args(args).output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "{} failed: {}",
+                self.stop_command_line(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+}
+
+/// A port that was bound again shortly after its process was killed
+#[derive(Debug, Clone, Serialize)]
+pub struct Respawn {
+    pub port: u16,
+    pub killed_pid: i32,
+    pub process: ProcessInfo,
+    pub after_ms: u64,
+    pub supervisor: Option<Supervisor>,
+}
+
+/// Poll the freed ports for `window` and report every one that a new process binds.
+/// A port still held by the killed pid is not a respawn; that kill simply hasn't landed.
+pub async fn watch_for_respawns(
+    killed: &[(u16, i32)],
+    window: Duration,
+    args: &Args,
+) -> Vec<Respawn> {
+    let killed_pids: HashMap<u16, i32> = killed.iter().copied().collect();
+    let mut ports: Vec<u16> = killed_pids.keys().copied().collect();
+    let mut respawns = Vec::new();
+    let started = Instant::now();
+
+    while !ports.is_empty() && started.elapsed() < window {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let (_, processes) = get_processes_on_ports(&ports, args);
+        ports.retain(|port| {
+            let Some(process) = processes.get(port) else {
+                return true;
+            };
+            if process.pid == killed_pids[port] {
+                return true;
+            }
+            respawns.push(Respawn {
+                port: *port,
+                killed_pid: killed_pids[port],
+                process: process.clone(),
+                after_ms: started.elapsed().as_millis() as u64,
+                supervisor: identify_supervisor(process),
+            });
+            false
+        });
+    }
+    respawns
+}
+
+/// Work out what started `process`: a systemd unit, a container with a restart policy,
+/// pm2, launchd or a file watcher somewhere up the parent chain
+pub fn identify_supervisor(process: &ProcessInfo) -> Option<Supervisor> {
+    let cgroup = std::fs::read_to_string(format!("/proc/{}/cgroup", process.pid)).ok();
+    let container = process
+        .container_id
+        .clone()
+        .or_else(|| cgroup.as_deref().and_then(parse_cgroup_container));
+    if let Some(id) = container {
+        let policy = container_restart_policy(&id).unwrap_or_else(|| "unknown".to_string());
+        if policy != "no" {
+            return Some(Supervisor::new(SupervisorKind::Container {
+                id,
+                restart_policy: policy,
+            }));
+        }
+    }
+
+    let unit = process
+        .systemd_unit
+        .clone()
+        .or_else(|| cgroup.as_deref().and_then(parse_systemd_unit));
+    if let Some(unit) = unit {
+        return Some(Supervisor::new(SupervisorKind::SystemdUnit(unit)));
+    }
+
+    let ancestors = parent_chain(process.pid);
+    for (pid, command) in &ancestors {
+        if command.contains("PM2") && command.contains("God Daemon") {
+            let name = pm2_app_name(process).unwrap_or_else(|| process.name.clone());
+            return Some(Supervisor::new(SupervisorKind::Pm2 { name }));
+        }
+        if let Some(name) = watcher_name(command) {
+            return Some(Supervisor::new(SupervisorKind::Watcher { name, pid: *pid }));
+        }
+    }
+
+    // launchd adopts the jobs it starts, so only look there for direct children of pid 1
+    if cfg!(target_os = "macos") && ancestors.is_empty() {
+        let output = Command::new("launchctl").arg("list").output().ok()?;
+        let label = parse_launchctl_list(&String::from_utf8_lossy(&output.stdout), process.pid)?;
+        return Some(Supervisor::new(SupervisorKind::Launchd { label }));
+    }
+    None
+}
+
+/// Container id from cgroup paths such as `/docker/<id>` or `docker-<id>.scope`
+pub fn parse_cgroup_container(cgroup: &str) -> Option<String> {
+    cgroup.lines().find_map(|line| {
+        let path = line.splitn(3, ':').nth(2)?;
+        path.split('/').find_map(|part| {
+            let id = part
+                .strip_prefix("docker-")
+                .and_then(|rest| rest.strip_suffix(".scope"))
+                .or_else(|| path.contains("/docker/").then_some(part))?;
+            (id.len() >= 12 && id.chars().all(|c| c.is_ascii_hexdigit()))
+                .then(|| id[..12].to_string())
+        })
+    })
+}
+
+fn container_restart_policy(id: &str) -> Option<String> {
+    let output = Command::new("docker")
+        .args([
+            "inspect",
+            "--format",
+            "{{.HostConfig.RestartPolicy.Name}}",
+            id,
+        ])
+        .output()
+        .ok()?;
+    let policy = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !policy.is_empty()).then_some(policy)
+}
+
+fn watcher_name(command: &str) -> Option<String> {
+    command
+        .split_whitespace()
+        .take(3)
+        .filter_map(|arg| arg.rsplit(['/', '\\']).next())
+        .find_map(|arg| {
+            let arg = arg.trim_end_matches(".js").trim_end_matches(".exe");
+            WATCHERS.contains(&arg).then(|| arg.to_string())
+        })
+}
+
+/// Ancestors of `pid` as (pid, command line), nearest first, stopping before pid 1
+pub fn parent_chain(pid: i32) -> Vec<(i32, String)> {
+    let mut chain = Vec::new();
+    let mut current = pid;
+    while chain.len() < MAX_ANCESTORS {
+        let Some(parent) = parent_pid(current) else {
+            break;
+        };
+        if parent <= 1 {
+            break;
+        }
+        chain.push((parent, command_line(parent).unwrap_or_default()));
+        current = parent;
+    }
+    chain
+}
+
+fn parent_pid(pid: i32) -> Option<i32> {
+    if let Ok(stat) = std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+        // Fields after the parenthesised command: state, then ppid
+        let after_comm = &stat[stat.rfind(')')? + 1..];
+        return after_comm.split_whitespace().nth(1)?.parse().ok();
+    }
+    let output = Command::new("ps")
+        .args(["-o", "ppid=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Full command line of a running process
+pub(crate) fn command_line(pid: i32) -> Option<String> {
+    if let Ok(raw) = std::fs::read(format!("/proc/{}/cmdline", pid)) {
+        let args: Vec<String> = raw
+            .split(|b| *b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).to_string())
+            .collect();
+        if !args.is_empty() {
+            return Some(args.join(" "));
+        }
+    }
+    let output = Command::new("ps")
+        .args(["-o", "command=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let command = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!command.is_empty()).then_some(command)
+}
+
+/// Name of the pm2 app running `process`, from `pm2 jlist` or the saved dump
+fn pm2_app_name(process: &ProcessInfo) -> Option<String> {
+    if let Ok(output) = Command::new("pm2").arg("jlist").output() {
+        if let Some(name) = find_pm2_app(&String::from_utf8_lossy(&output.stdout), process) {
+            return Some(name);
+        }
+    }
+    let home = std::env::var("PM2_HOME").unwrap_or_else(|_| {
+        format!(
+            "{}/.pm2",
+            std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string())
+        )
+    });
+    let dump = std::fs::read_to_string(format!("{}/dump.pm2", home)).ok()?;
+    find_pm2_app(&dump, process)
+}
+
+/// Match `process` against a pm2 process list (`pm2 jlist` or `dump.pm2`) by pid, or by
+/// working directory since the dump is written before pids change
+pub fn find_pm2_app(list: &str, process: &ProcessInfo) -> Option<String> {
+    let apps: Vec<serde_json::Value> = serde_json::from_str(list).ok()?;
+    let name_of = |app: &serde_json::Value| {
+        app.get("name")
+            .or_else(|| app.pointer("/pm2_env/name"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+    apps.iter()
+        .find(|app| app.get("pid").and_then(|v| v.as_i64()) == Some(process.pid as i64))
+        .or_else(|| {
+            let cwd = process.working_directory.as_deref()?;
+            apps.iter().find(|app| {
+                app.get("pm_cwd")
+                    .or_else(|| app.pointer("/pm2_env/pm_cwd"))
+                    .and_then(|v| v.as_str())
+                    == Some(cwd)
+            })
+        })
+        .and_then(name_of)
+}
+
+/// Label of the launchd job whose pid is `pid`, from `launchctl list` output
+pub fn parse_launchctl_list(output: &str, pid: i32) -> Option<String> {
+    output.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let job_pid: i32 = fields.next()?.parse().ok()?;
+        let label = fields.nth(1)?;
+        (job_pid == pid).then(|| label.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cgroup_parsing_finds_containers() {
+        let id = "4f2c9a1b7d3e8f60a1b2c3d4e5f60718293a4b5c6d7e8f9012345678abcdef01";
+        assert_eq!(
+            parse_cgroup_container(&format!("0::/system.slice/docker-{}.scope\n", id)),
+            Some(id[..12].to_string())
+        );
+        assert_eq!(
+            parse_cgroup_container(&format!("12:memory:/docker/{}\n", id)),
+            Some(id[..12].to_string())
+        );
+    }
+
+    #[test]
+    fn test_pm2_launchd_and_watcher_matching() {
+        let mut process = ProcessInfo::for_test(4242, 3000, "node");
+        process.working_directory = Some("/srv/api".to_string());
+        let jlist =
+            r#"[{"pid":4242,"name":"api","pm_id":0},{"pid":5000,"name":"worker","pm_id":1}]"#;
+        assert_eq!(find_pm2_app(jlist, &process), Some("api".to_string()));
+
+        // The dump has no live pids, so fall back to the working directory
+        process.pid = 9999;
+        let dump = r#"[{"pm2_env":{"name":"api","pm_cwd":"/srv/api"}}]"#;
+        assert_eq!(find_pm2_app(dump, &process), Some("api".to_string()));
+
+        let launchctl = "PID\tStatus\tLabel\n-\t0\tcom.apple.idle\n4242\t0\tcom.example.web\n";
+        assert_eq!(
+            parse_launchctl_list(launchctl, 4242),
+            Some("com.example.web".to_string())
+        );
+
+        assert_eq!(
+            watcher_name("node /usr/lib/node_modules/nodemon/bin/nodemon.js server.js"),
+            Some("nodemon".to_string())
+        );
+        assert_eq!(watcher_name("/bin/zsh -l"), None);
+    }
+}
@@ -1,30 +1,41 @@
+use crate::types::SystemdUnit;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, OnceLock};
 use sysinfo::{Pid, System};
 
+/// One sysinfo `System` for the whole process. Loading it is expensive, and every monitor
+/// and start-time lookup wants the same data.
+fn shared_system() -> MutexGuard<'static, System> {
+    static SYSTEM: OnceLock<Mutex<System>> = OnceLock::new();
+    SYSTEM
+        .get_or_init(|| Mutex::new(System::new_all()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
 pub struct SystemMonitor {
-    system: System,
     last_cpu_times: HashMap<Pid, f64>,
 }
 
 impl SystemMonitor {
     pub fn new() -> Self {
-        let mut system = System::new_all();
-        system.refresh_all();
+        shared_system().refresh_all();
 
         Self {
-            system,
             last_cpu_times: HashMap::new(),
         }
     }
 
     pub fn refresh(&mut self) {
-        self.system.refresh_all();
+        shared_system().refresh_all();
     }
 
     pub fn get_process_cpu_usage(&mut self, pid: i32) -> Option<f64> {
         let pid = Pid::from_u32(pid as u32);
 
-        if let Some(process) = self.system.process(pid) {
+        if let Some(process) = shared_system().process(pid) {
             let current_cpu_time = process.cpu_usage() as f64;
 
             // Calculate CPU usage since last check
@@ -50,10 +61,12 @@ impl SystemMonitor {
 
     pub fn get_process_memory_usage(&self, pid: i32) -> Option<(u64, f64)> {
         let pid = Pid::from_u32(pid as u32);
+        let system = shared_system();
 
-        if let Some(process) = self.system.process(pid) {
-            let memory_bytes = process.memory() * 1024; // Convert from KB to bytes
-            let total_memory = self.system.total_memory() * 1024; // Convert from KB to bytes
+        if let Some(process) = system.process(pid) {
+            // sysinfo reports bytes since 0.30
+            let memory_bytes = process.memory();
+            let total_memory = system.total_memory();
             let memory_percentage = if total_memory > 0 {
                 (memory_bytes as f64 / total_memory as f64) * 100.0
             } else {
@@ -67,20 +80,20 @@ impl SystemMonitor {
     }
 
     pub fn get_system_info(&self) -> SystemInfo {
+        let system = shared_system();
         SystemInfo {
-            total_memory: self.system.total_memory() * 1024, // Convert to bytes
-            used_memory: self.system.used_memory() * 1024,   // Convert to bytes
-            total_swap: self.system.total_swap() * 1024,     // Convert to bytes
-            used_swap: self.system.used_swap() * 1024,       // Convert to bytes
-            cpu_count: self.system.cpus().len(),
+            total_memory: system.total_memory(),
+            used_memory: system.used_memory(),
+            total_swap: system.total_swap(),
+            used_swap: system.used_swap(),
+            cpu_count: system.cpus().len(),
             load_average: sysinfo::System::load_average(),
         }
     }
 
     pub fn cleanup_old_processes(&mut self) {
         // Remove processes that are no longer running
-        let current_pids: std::collections::HashSet<Pid> = self
-            .system
+        let current_pids: std::collections::HashSet<Pid> = shared_system()
             .processes()
             .iter()
             .map(|(pid, _)| *pid)
@@ -91,6 +104,134 @@ impl SystemMonitor {
     }
 }
 
+/// Clock ticks per second used by /proc; fixed at USER_HZ by the kernel ABI
+#[cfg(target_os = "linux")]
+const PROC_TICKS_PER_SEC: u64 = 100;
+
+/// Extract the start time (in clock ticks since boot) from a `/proc/<pid>/stat` line
+pub fn parse_proc_stat_start_ticks(stat: &str) -> Option<u64> {
+    // The command name is parenthesised and may itself contain spaces or parentheses
+    let after_comm = &stat[stat.rfind(')')? + 1..];
+    // Fields after the command start at field 3 (state); starttime is field 22
+    after_comm.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// Extract the boot time (seconds since the epoch) from `/proc/stat`
+pub fn parse_proc_boot_time(proc_stat: &str) -> Option<i64> {
+    proc_stat
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// When a process started, or None if it no longer exists
+pub fn process_start_time(pid: i32) -> Option<DateTime<Utc>> {
+    #[cfg(target_os = "linux")]
+    {
+        let from_proc = || -> Option<DateTime<Utc>> {
+            let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+            let ticks = parse_proc_stat_start_ticks(&stat)?;
+            let boot = parse_proc_boot_time(&std::fs::read_to_string("/proc/stat").ok()?)?;
+            let millis = boot * 1000 + (ticks * 1000 / PROC_TICKS_PER_SEC) as i64;
+            DateTime::from_timestamp_millis(millis)
+        };
+        if let Some(started) = from_proc() {
+            return Some(started);
+        }
+    }
+
+    let mut system = shared_system();
+    let sys_pid = Pid::from_u32(pid as u32);
+    if !system.refresh_process(sys_pid) {
+        return None;
+    }
+    let started = system.process(sys_pid)?.start_time();
+    DateTime::from_timestamp(started as i64, 0)
+}
+
+/// Units that host unrelated processes (login shells, containers' port proxies), so a
+/// listener found in them must not take the whole unit down with it
+const SHARED_HOST_UNITS: &[&str] = &[
+    "sshd.service",
+    "ssh.service",
+    "docker.service",
+    "containerd.service",
+    "cron.service",
+    "crond.service",
+    "gnome-terminal-server.service",
+    "tmux.service",
+    "screen.service",
+];
+
+/// The `.service` unit a `/proc/<pid>/cgroup` file places a process in. Handles the
+/// unified (v2) and legacy (v1) hierarchies; session scopes and the per-user manager
+/// itself (`user@1000.service`) are not units the process belongs to.
+pub fn parse_systemd_unit(cgroup: &str) -> Option<SystemdUnit> {
+    cgroup.lines().find_map(|line| {
+        // "0::/user.slice/user-1000.slice/user@1000.service/app.slice/web.service"
+        // "1:name=systemd:/system.slice/redis-server.service"
+        let path = line.splitn(3, ':').nth(2)?;
+        let name = path
+            .split('/')
+            .rev()
+            .find(|part| part.ends_with(".service") && !part.starts_with("user@"))?;
+        if SHARED_HOST_UNITS.contains(&name) {
+            return None;
+        }
+        let user = path.split('/').any(|part| part.starts_with("user@"));
+        Some(SystemdUnit {
+            name: name.to_string(),
+            user,
+        })
+    })
+}
+
+/// Read and parse a cgroup file; None when it is missing or names no unit
+pub fn systemd_unit_from_cgroup_file(path: &Path) -> Option<SystemdUnit> {
+    parse_systemd_unit(&std::fs::read_to_string(path).ok()?)
+}
+
+/// The systemd unit that owns `pid`, if any. Always None off Linux.
+pub fn systemd_unit(pid: i32) -> Option<SystemdUnit> {
+    if cfg!(target_os = "linux") {
+        systemd_unit_from_cgroup_file(Path::new(&format!("/proc/{}/cgroup", pid)))
+    } else {
+        None
+    }
+}
+
+/// The unit's main PID from `systemctl show -p MainPID -p Transient` output. None for
+/// transient units (`systemd-run`), which may be wrapping something else entirely.
+fn stoppable_main_pid(show: &str) -> Option<i32> {
+    let mut main_pid = None;
+    for line in show.lines() {
+        match line.trim().split_once('=') {
+            Some(("MainPID", value)) => main_pid = value.parse().ok().filter(|pid| *pid > 0),
+            Some(("Transient", "yes")) => return None,
+            _ => {}
+        }
+    }
+    main_pid
+}
+
+/// Whether stopping `unit` is the way to kill `pid`: only when it is the unit's main
+/// process and the unit is not transient. Helpers and workers get a plain PID kill.
+pub fn unit_owns_pid(unit: &SystemdUnit, pid: i32) -> bool {
+    let mut command = std::process::Command::new("systemctl");
+    if unit.user {
+        command.arg("--user");
+    }
+    match command
+        .args(["show", "-p", "MainPID", "-p", "Transient", &unit.name])
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            stoppable_main_pid(&String::from_utf8_lossy(&output.stdout)) == Some(pid)
+        }
+        _ => false,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SystemInfo {
     pub total_memory: u64,
@@ -161,4 +302,66 @@ mod tests {
         assert_eq!(system_info.format_memory(1048576), "1.0 MB");
         assert_eq!(system_info.format_memory(1073741824), "1.0 GB");
     }
+
+    #[test]
+    fn test_proc_start_time_parsing() {
+        let stat = "4242 (node (dev) server) S 1 4242 4242 0 -1 4194560 1000 0 0 0 \
+                    25 10 0 0 20 0 11 0 123456 1073741824 5000 18446744073709551615";
+        assert_eq!(parse_proc_stat_start_ticks(stat), Some(123456));
+        assert_eq!(parse_proc_stat_start_ticks("garbage"), None);
+
+        let proc_stat = "cpu  1 2 3 4\nintr 0\nbtime 1700000000\nprocesses 10\n";
+        assert_eq!(parse_proc_boot_time(proc_stat), Some(1700000000));
+    }
+
+    #[test]
+    fn test_systemd_unit_from_cgroup_fixtures() {
+        let fixture = |name: &str| {
+            systemd_unit_from_cgroup_file(
+                &Path::new(env!("CARGO_MANIFEST_DIR"))
+                    .join("tests/fixtures/cgroup")
+                    .join(name),
+            )
+        };
+        let unit = |name: &str, user: bool| {
+            Some(SystemdUnit {
+                name: name.to_string(),
+                user,
+            })
+        };
+
+        assert_eq!(
+            fixture("system-service-v2"),
+            unit("postgresql.service", false)
+        );
+        assert_eq!(
+            fixture("system-service-v1"),
+            unit("redis-server.service", false)
+        );
+        assert_eq!(fixture("user-service-v2"), unit("caddy.service", true));
+        assert_eq!(fixture("login-session-v2"), None);
+        assert_eq!(fixture("user-app-scope-v2"), None);
+        assert_eq!(fixture("docker-proxy-v2"), None);
+        assert_eq!(fixture("missing"), None);
+
+        assert_eq!(
+            unit("caddy.service", true).unwrap().stop_command(),
+            ["systemctl", "--user", "stop", "caddy.service"]
+        );
+    }
+
+    #[test]
+    fn test_only_main_pid_of_persistent_unit_is_stoppable() {
+        assert_eq!(stoppable_main_pid("MainPID=812\nTransient=no\n"), Some(812));
+        assert_eq!(stoppable_main_pid("MainPID=0\nTransient=no\n"), None);
+        assert_eq!(stoppable_main_pid("MainPID=4242\nTransient=yes\n"), None);
+        assert_eq!(stoppable_main_pid(""), None);
+    }
+
+    #[test]
+    fn test_own_process_start_time() {
+        let started = process_start_time(std::process::id() as i32).unwrap();
+        let age = Utc::now() - started;
+        assert!(age.num_seconds() >= 0 && age.num_hours() < 24);
+    }
 }
@@ -0,0 +1,15 @@
+use std::path::PathBuf;
+
+/// A fresh, empty directory for the test `name` in `module`, under the system temp dir.
+/// The process id keeps concurrent test runs out of each other's way.
+pub fn scratch_dir(module: &str, name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "port-kill-{}-test-{}-{}",
+        module,
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
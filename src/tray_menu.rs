@@ -128,6 +128,10 @@ impl TrayMenu {
             } else {
                 format!("Kill: Port {}: {}", port, process_info.name)
             };
+            let menu_text = match process_info.format_age() {
+                Some(age) => format!("{} · up {}", menu_text, age),
+                None => menu_text,
+            };
 
             // Create menu item for each process
             let process_item = MenuItem::new(&menu_text, true, None);
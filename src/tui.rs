@@ -0,0 +1,844 @@
+use crate::{
+    cli::Args,
+    file_monitor::FileMonitor,
+    process_monitor::ProcessMonitor,
+    supervisor::{watch_for_respawns, DEFAULT_RESPAWN_WINDOW},
+    types::{format_duration_short, ProcessHistoryEntry, ProcessInfo},
+};
+use anyhow::{anyhow, Result};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState, Tabs},
+    DefaultTerminal, Frame,
+};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(2);
+const HISTORY_LIMIT: usize = 200;
+const DETAIL_HEIGHT: u16 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Port,
+    Process,
+    Group,
+    Project,
+    Pid,
+    Cpu,
+    Memory,
+    Container,
+    Age,
+}
+
+const COLUMNS: [(SortColumn, &str, Constraint); 9] = [
+    (SortColumn::Port, "PORT", Constraint::Length(7)),
+    (SortColumn::Process, "PROCESS", Constraint::Min(14)),
+    (SortColumn::Group, "GROUP", Constraint::Length(12)),
+    (SortColumn::Project, "PROJECT", Constraint::Length(16)),
+    (SortColumn::Pid, "PID", Constraint::Length(8)),
+    (SortColumn::Cpu, "CPU", Constraint::Length(7)),
+    (SortColumn::Memory, "MEM", Constraint::Length(8)),
+    (SortColumn::Container, "CONTAINER", Constraint::Length(14)),
+    (SortColumn::Age, "AGE", Constraint::Length(8)),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Listeners,
+    History,
+}
+
+/// What the detail pane shows beyond the scan: quick scans only record the executable name
+#[derive(Debug, Clone, Default)]
+struct Details {
+    command_line: Option<String>,
+    open_files: Vec<String>,
+}
+
+impl Details {
+    fn collect(pid: i32) -> Self {
+        Self {
+            command_line: crate::supervisor::command_line(pid),
+            open_files: FileMonitor::new()
+                .get_process_files(pid as u32)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// What the event loop should do after a key press
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    None,
+    Quit,
+    Refresh,
+    Kill(Vec<ProcessInfo>),
+    Restart(Vec<ProcessInfo>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Mode {
+    Normal,
+    Search,
+    /// Waiting for y/n before running the action
+    Confirm(Action),
+}
+
+/// Everything on screen; kept apart from the terminal so key handling can be tested
+pub struct TuiState {
+    processes: Vec<ProcessInfo>,
+    history: Vec<ProcessHistoryEntry>,
+    sort: SortColumn,
+    descending: bool,
+    query: String,
+    mode: Mode,
+    tab: Tab,
+    /// Ports picked with space; kill and restart act on these, or on the cursor row
+    selected: BTreeSet<u16>,
+    table: TableState,
+    history_table: TableState,
+    show_detail: bool,
+    /// Detail pane contents per PID, refreshed with each scan
+    details: HashMap<i32, Details>,
+    status: String,
+}
+
+impl Default for TuiState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TuiState {
+    pub fn new() -> Self {
+        Self {
+            processes: Vec::new(),
+            history: Vec::new(),
+            sort: SortColumn::Port,
+            descending: false,
+            query: String::new(),
+            mode: Mode::Normal,
+            tab: Tab::Listeners,
+            selected: BTreeSet::new(),
+            table: TableState::default().with_selected(Some(0)),
+            history_table: TableState::default().with_selected(Some(0)),
+            show_detail: false,
+            details: HashMap::new(),
+            status: String::new(),
+        }
+    }
+
+    /// Take a new scan, keeping the cursor on the same port when it is still there
+    pub fn set_processes(&mut self, processes: HashMap<u16, ProcessInfo>) {
+        let current = self.current().map(|p| p.port);
+        self.processes = processes.into_values().collect();
+        self.selected
+            .retain(|port| self.processes.iter().any(|p| p.port == *port));
+        self.details.clear();
+        if let Some(port) = current {
+            if let Some(index) = self.visible().iter().position(|p| p.port == port) {
+                self.table.select(Some(index));
+            }
+        }
+        self.clamp_cursor();
+    }
+
+    /// Kill history, newest first
+    pub fn set_history(&mut self, entries: &[ProcessHistoryEntry]) {
+        self.history = entries.iter().rev().cloned().collect();
+    }
+
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = status.into();
+    }
+
+    /// Listeners matching the search, in the chosen order
+    pub fn visible(&self) -> Vec<&ProcessInfo> {
+        let query = self.query.to_lowercase();
+        let mut rows: Vec<&ProcessInfo> = self
+            .processes
+            .iter()
+            .filter(|p| query.is_empty() || matches_query(p, &query))
+            .collect();
+        rows.sort_by(|a, b| {
+            let order = compare(self.sort, a, b).then(a.port.cmp(&b.port));
+            if self.descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        rows
+    }
+
+    pub fn current(&self) -> Option<&ProcessInfo> {
+        let index = self.table.selected()?;
+        self.visible().get(index).copied()
+    }
+
+    /// The selected listeners still on screen, or the one under the cursor
+    fn targets(&self) -> Vec<ProcessInfo> {
+        let visible = self.visible();
+        let picked: Vec<ProcessInfo> = visible
+            .iter()
+            .filter(|p| self.selected.contains(&p.port))
+            .map(|p| (*p).clone())
+            .collect();
+        if picked.is_empty() {
+            self.current().cloned().into_iter().collect()
+        } else {
+            picked
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        match self.tab {
+            Tab::Listeners => self.visible().len(),
+            Tab::History => self.history.len(),
+        }
+    }
+
+    fn cursor_table(&mut self) -> &mut TableState {
+        match self.tab {
+            Tab::Listeners => &mut self.table,
+            Tab::History => &mut self.history_table,
+        }
+    }
+
+    fn clamp_cursor(&mut self) {
+        for tab in [Tab::Listeners, Tab::History] {
+            let previous = std::mem::replace(&mut self.tab, tab);
+            let count = self.row_count();
+            let table = self.cursor_table();
+            let index = table.selected().unwrap_or(0).min(count.saturating_sub(1));
+            table.select(Some(index));
+            self.tab = previous;
+        }
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        let last = self.row_count().saturating_sub(1) as isize;
+        let table = self.cursor_table();
+        let index = (table.selected().unwrap_or(0) as isize + delta).clamp(0, last.max(0));
+        table.select(Some(index as usize));
+    }
+
+    fn sort_by(&mut self, column: SortColumn) {
+        if self.sort == column {
+            self.descending = !self.descending;
+        } else {
+            self.sort = column;
+            // Busiest and oldest first is the useful default for the metric columns
+            self.descending = matches!(
+                column,
+                SortColumn::Cpu | SortColumn::Memory | SortColumn::Age
+            );
+        }
+        self.table.select(Some(0));
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Confirm(action) => {
+                if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                    return action;
+                }
+                self.status = "Cancelled".to_string();
+                Action::None
+            }
+            Mode::Search => {
+                self.mode = Mode::Search;
+                match key.code {
+                    KeyCode::Enter => self.mode = Mode::Normal,
+                    KeyCode::Esc => {
+                        self.query.clear();
+                        self.mode = Mode::Normal;
+                    }
+                    KeyCode::Backspace => {
+                        self.query.pop();
+                        self.table.select(Some(0));
+                    }
+                    KeyCode::Up => self.move_cursor(-1),
+                    KeyCode::Down => self.move_cursor(1),
+                    KeyCode::Char(c) => {
+                        self.query.push(c);
+                        self.table.select(Some(0));
+                    }
+                    _ => {}
+                }
+                self.clamp_cursor();
+                Action::None
+            }
+            Mode::Normal => self.handle_normal_key(key),
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Char('q') => return Action::Quit,
+            KeyCode::Esc if self.query.is_empty() => return Action::Quit,
+            KeyCode::Esc => self.query.clear(),
+            KeyCode::F(5) => return Action::Refresh,
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.tab = match self.tab {
+                    Tab::Listeners => Tab::History,
+                    Tab::History => Tab::Listeners,
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1),
+            KeyCode::PageUp => self.move_cursor(-10),
+            KeyCode::PageDown => self.move_cursor(10),
+            KeyCode::Home | KeyCode::Char('g') => self.cursor_table().select(Some(0)),
+            KeyCode::End | KeyCode::Char('G') => self.move_cursor(isize::MAX / 2),
+            _ if self.tab == Tab::History => {}
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Enter => self.show_detail = !self.show_detail,
+            KeyCode::Char(' ') => {
+                if let Some(port) = self.current().map(|p| p.port) {
+                    if !self.selected.remove(&port) {
+                        self.selected.insert(port);
+                    }
+                    self.move_cursor(1);
+                }
+            }
+            KeyCode::Char('a') => {
+                let ports: BTreeSet<u16> = self.visible().iter().map(|p| p.port).collect();
+                if ports.is_subset(&self.selected) {
+                    self.selected.retain(|port| !ports.contains(port));
+                } else {
+                    self.selected.extend(ports);
+                }
+            }
+            KeyCode::Char('s') => {
+                let index = COLUMNS.iter().position(|c| c.0 == self.sort).unwrap_or(0);
+                self.sort_by(COLUMNS[(index + 1) % COLUMNS.len()].0);
+                self.descending = false;
+            }
+            KeyCode::Char('S') => self.descending = !self.descending,
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = c as usize - '1' as usize;
+                self.sort_by(COLUMNS[index].0);
+            }
+            KeyCode::Char('x') | KeyCode::Char('d') | KeyCode::Delete => {
+                return self.confirm(Action::Kill(self.targets()), "Kill")
+            }
+            KeyCode::Char('r') => return self.confirm(Action::Restart(self.targets()), "Restart"),
+            _ => {}
+        }
+        self.clamp_cursor();
+        Action::None
+    }
+
+    fn confirm(&mut self, action: Action, verb: &str) -> Action {
+        let (Action::Kill(targets) | Action::Restart(targets)) = &action else {
+            return Action::None;
+        };
+        self.status = match targets.as_slice() {
+            [] => return Action::None,
+            [one] => format!(
+                "{} {} (PID {}) on port {}? y/N",
+                verb,
+                one.get_short_name(),
+                one.pid,
+                one.port
+            ),
+            many => format!("{} {} processes? y/N", verb, many.len()),
+        };
+        self.mode = Mode::Confirm(action);
+        Action::None
+    }
+}
+
+fn matches_query(process: &ProcessInfo, query: &str) -> bool {
+    let fields = [
+        Some(process.port.to_string()),
+        Some(process.pid.to_string()),
+        Some(process.name.clone()),
+        process.command_line.clone(),
+        process.process_group.clone(),
+        process.project_name.clone(),
+        process.container_name.clone(),
+    ];
+    fields
+        .iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(query))
+}
+
+fn compare(column: SortColumn, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+    match column {
+        SortColumn::Port => a.port.cmp(&b.port),
+        SortColumn::Process => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        SortColumn::Group => a.process_group.cmp(&b.process_group),
+        SortColumn::Project => a.project_name.cmp(&b.project_name),
+        SortColumn::Pid => a.pid.cmp(&b.pid),
+        SortColumn::Cpu => a
+            .cpu_usage
+            .partial_cmp(&b.cpu_usage)
+            .unwrap_or(Ordering::Equal),
+        SortColumn::Memory => a.memory_usage.cmp(&b.memory_usage),
+        SortColumn::Container => a.container_name.cmp(&b.container_name),
+        SortColumn::Age => a.uptime_seconds().cmp(&b.uptime_seconds()),
+    }
+}
+
+fn format_memory(bytes: u64) -> String {
+    let mb = bytes as f64 / 1024.0 / 1024.0;
+    if mb >= 1024.0 {
+        format!("{:.1}G", mb / 1024.0)
+    } else {
+        format!("{:.0}M", mb)
+    }
+}
+
+fn draw(frame: &mut Frame, state: &mut TuiState) {
+    let detail_height = if state.show_detail && state.tab == Tab::Listeners {
+        DETAIL_HEIGHT
+    } else {
+        0
+    };
+    let [tabs_area, body_area, detail_area, footer_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(detail_height),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let titles = vec![
+        format!(" Listeners ({}) ", state.visible().len()),
+        format!(" History ({}) ", state.history.len()),
+    ];
+    let selected_tab = match state.tab {
+        Tab::Listeners => 0,
+        Tab::History => 1,
+    };
+    frame.render_widget(
+        Tabs::new(titles)
+            .select(selected_tab)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+        tabs_area,
+    );
+
+    match state.tab {
+        Tab::Listeners => draw_listeners(frame, state, body_area),
+        Tab::History => draw_history(frame, state, body_area),
+    }
+    if detail_height > 0 {
+        draw_detail(frame, state, detail_area);
+    }
+    draw_footer(frame, state, footer_area);
+}
+
+fn draw_listeners(frame: &mut Frame, state: &mut TuiState, area: Rect) {
+    let header = Row::new(COLUMNS.iter().map(|(column, title, _)| {
+        if *column == state.sort {
+            let arrow = if state.descending { "▼" } else { "▲" };
+            Cell::from(format!("{}{}", title, arrow))
+        } else {
+            Cell::from(*title)
+        }
+    }))
+    .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let rows: Vec<Row> = state
+        .visible()
+        .into_iter()
+        .map(|p| {
+            let mark = if state.selected.contains(&p.port) {
+                "●"
+            } else {
+                " "
+            };
+            let row = Row::new(vec![
+                format!("{}{}", mark, p.port),
+                p.get_short_name(),
+                p.process_group.clone().unwrap_or_default(),
+                p.project_name.clone().unwrap_or_default(),
+                p.pid.to_string(),
+                p.cpu_usage
+                    .map(|cpu| format!("{:.1}%", cpu))
+                    .unwrap_or_else(|| "-".to_string()),
+                p.memory_usage
+                    .map(format_memory)
+                    .unwrap_or_else(|| "-".to_string()),
+                p.container_name.clone().unwrap_or_default(),
+                p.format_age().unwrap_or_default(),
+            ]);
+            if state.selected.contains(&p.port) {
+                row.style(Style::default().fg(Color::Yellow))
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let table = Table::new(rows, COLUMNS.map(|(_, _, width)| width))
+        .header(header)
+        .block(Block::default().borders(Borders::TOP))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(table, area, &mut state.table);
+}
+
+fn draw_history(frame: &mut Frame, state: &mut TuiState, area: Rect) {
+    let header = Row::new(["WHEN", "PORT", "PROCESS", "PID", "OUTCOME", "BY"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let now = chrono::Utc::now();
+    let rows: Vec<Row> = state
+        .history
+        .iter()
+        .map(|entry| {
+            let ago = (now - entry.killed_at).num_seconds().max(0) as u64;
+            Row::new(vec![
+                format!("{} ago", format_duration_short(ago)),
+                entry.port.to_string(),
+                entry.get_display_name(),
+                entry.pid.to_string(),
+                entry.outcome.as_str().replace('_', " "),
+                format!("{} ({})", entry.killed_by, entry.actor.as_str()),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(6),
+            Constraint::Min(20),
+            Constraint::Length(8),
+            Constraint::Length(14),
+            Constraint::Length(16),
+        ],
+    )
+    .header(header)
+    .block(Block::default().borders(Borders::TOP))
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(table, area, &mut state.history_table);
+}
+
+fn draw_detail(frame: &mut Frame, state: &TuiState, area: Rect) {
+    let Some(process) = state.current() else {
+        return;
+    };
+    let label =
+        |name: &str| Span::styled(format!("{:<11}", name), Style::default().fg(Color::Cyan));
+    let details = state.details.get(&process.pid);
+    let command = details
+        .and_then(|d| d.command_line.clone())
+        .or_else(|| process.command_line.clone())
+        .unwrap_or_else(|| process.command.clone());
+    let mut lines = vec![Line::from(vec![label("Command"), Span::raw(command)])];
+    if let Some(ref dir) = process.working_directory {
+        lines.push(Line::from(vec![label("Directory"), Span::raw(dir.clone())]));
+    }
+    if let Some(ref framework) = process.framework {
+        lines.push(Line::from(vec![
+            label("Framework"),
+            Span::raw(framework.clone()),
+        ]));
+    }
+    if let Some(ref container) = process.container_name {
+        let id = process.container_id.clone().unwrap_or_default();
+        lines.push(Line::from(vec![
+            label("Container"),
+            Span::raw(format!("{} {}", container, id)),
+        ]));
+    }
+    if let Some(ref unit) = process.systemd_unit {
+        lines.push(Line::from(vec![label("Unit"), Span::raw(unit.describe())]));
+    }
+    match details {
+        Some(details) => {
+            let files = &details.open_files;
+            lines.push(Line::from(vec![label(&format!("Files ({})", files.len()))]));
+            lines.extend(files.iter().map(|f| Line::from(format!("  {}", f))));
+        }
+        None => lines.push(Line::from(vec![label("Files"), Span::raw("…")])),
+    }
+    let title = format!(
+        " {} (PID {}) on port {} ",
+        process.get_short_name(),
+        process.pid,
+        process.port
+    );
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::TOP).title(title)),
+        area,
+    );
+}
+
+fn draw_footer(frame: &mut Frame, state: &TuiState, area: Rect) {
+    let text = match &state.mode {
+        Mode::Search => format!("/{}█", state.query),
+        Mode::Confirm(_) => state.status.clone(),
+        Mode::Normal if !state.status.is_empty() => state.status.clone(),
+        Mode::Normal if state.tab == Tab::History => "↑↓ move  Tab listeners  q quit".to_string(),
+        Mode::Normal => {
+            let search = if state.query.is_empty() {
+                String::new()
+            } else {
+                format!("[/{}]  ", state.query)
+            };
+            format!(
+                "{}↑↓ move  / search  space select  a all  x kill  r restart  enter details  1-9 sort  Tab history  q quit",
+                search
+            )
+        }
+    };
+    frame.render_widget(
+        Paragraph::new(text).style(Style::default().add_modifier(Modifier::DIM)),
+        area,
+    );
+}
+
+/// Run the full-screen UI until the user quits. Scans and kills go through `monitor`,
+/// so kills land in the shared history like any other.
+pub async fn run(args: &Args, monitor: Arc<Mutex<ProcessMonitor>>) -> Result<()> {
+    use std::io::IsTerminal;
+    if !std::io::stdout().is_terminal() {
+        return Err(anyhow!("--tui needs an interactive terminal"));
+    }
+
+    // Log lines on stderr would tear the screen
+    let log_level = log::max_level();
+    log::set_max_level(log::LevelFilter::Off);
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, args, monitor).await;
+    ratatui::restore();
+    log::set_max_level(log_level);
+    result
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    args: &Args,
+    monitor: Arc<Mutex<ProcessMonitor>>,
+) -> Result<()> {
+    let mut state = TuiState::new();
+    let (restart_sender, restart_receiver) = std::sync::mpsc::channel::<String>();
+    let mut last_scan: Option<Instant> = None;
+
+    loop {
+        if last_scan.is_none_or(|at| at.elapsed() >= SCAN_INTERVAL) {
+            let mut monitor = monitor.lock().await;
+            match monitor.refresh_processes().await {
+                Ok(processes) => state.set_processes(processes),
+                Err(e) => state.set_status(format!("Scan failed: {}", e)),
+            }
+            state.set_history(monitor.get_history().get_recent_entries(HISTORY_LIMIT));
+            last_scan = Some(Instant::now());
+        }
+        while let Ok(message) = restart_receiver.try_recv() {
+            state.set_status(message);
+        }
+        if state.show_detail {
+            if let Some(pid) = state.current().map(|p| p.pid) {
+                state
+                    .details
+                    .entry(pid)
+                    .or_insert_with(|| Details::collect(pid));
+            }
+        }
+
+        terminal.draw(|frame| draw(frame, &mut state))?;
+
+        if !event::poll(Duration::from_millis(200))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        // Any key dismisses the last message
+        if !matches!(state.mode, Mode::Confirm(_)) {
+            state.set_status("");
+        }
+        match state.handle_key(key) {
+            Action::None => {}
+            Action::Quit => return Ok(()),
+            Action::Refresh => last_scan = None,
+            Action::Kill(targets) => {
+                let (killed, message) = kill(&monitor, &targets).await;
+                state.set_status(format!("{} ({} killed)", message, killed.len()));
+                state.selected.clear();
+                last_scan = None;
+            }
+            Action::Restart(targets) => {
+                let (killed, message) = kill(&monitor, &targets).await;
+                state.selected.clear();
+                last_scan = None;
+                if killed.is_empty() {
+                    state.set_status(message);
+                    continue;
+                }
+                // Restarting relies on a supervisor bringing the process back
+                let window = match args.respawn_window {
+                    0 => DEFAULT_RESPAWN_WINDOW,
+                    secs => Duration::from_secs(secs),
+                };
+                state.set_status(format!(
+                    "Waiting up to {}s for {} port(s) to come back...",
+                    window.as_secs(),
+                    killed.len()
+                ));
+                let args = args.clone();
+                let sender = restart_sender.clone();
+                tokio::spawn(async move {
+                    let respawns = watch_for_respawns(&killed, window, &args).await;
+                    let _ = sender.send(format!(
+                        "{}/{} port(s) came back after restart",
+                        respawns.len(),
+                        killed.len()
+                    ));
+                });
+            }
+        }
+    }
+}
+
+/// Kill each distinct PID once; returns the (port, pid) pairs that went down
+async fn kill(
+    monitor: &Arc<Mutex<ProcessMonitor>>,
+    targets: &[ProcessInfo],
+) -> (Vec<(u16, i32)>, String) {
+    let mut monitor = monitor.lock().await;
+    let mut killed = Vec::new();
+    let mut errors = Vec::new();
+    let mut done = BTreeSet::new();
+    for target in targets {
+        if !done.insert(target.pid) {
+            killed.push((target.port, target.pid));
+            continue;
+        }
+        match monitor.kill_process(target.pid).await {
+            Ok(()) => killed.push((target.port, target.pid)),
+            Err(e) => errors.push(format!("PID {}: {}", target.pid, e)),
+        }
+    }
+    let message = if errors.is_empty() {
+        format!("Stopped {} process(es)", done.len())
+    } else {
+        format!("Failed: {}", errors.join("; "))
+    };
+    (killed, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn process(port: u16, pid: i32, name: &str, memory_mb: u64) -> ProcessInfo {
+        ProcessInfo {
+            command_line: Some(format!("{} --port {}", name, port)),
+            process_group: Some("Node.js".to_string()),
+            project_name: Some(format!("app-{}", port)),
+            cpu_usage: Some(1.0),
+            memory_usage: Some(memory_mb * 1024 * 1024),
+            ..ProcessInfo::for_test(pid, port, name)
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ports(state: &TuiState) -> Vec<u16> {
+        state.visible().iter().map(|p| p.port).collect()
+    }
+
+    #[test]
+    fn test_search_sort_and_multi_select_kill() {
+        let mut state = TuiState::new();
+        state.set_processes(HashMap::from([
+            (3000, process(3000, 10, "node", 300)),
+            (5173, process(5173, 11, "vite", 900)),
+            (8000, process(8000, 12, "python", 50)),
+        ]));
+        assert_eq!(ports(&state), vec![3000, 5173, 8000]);
+
+        // Memory sorts biggest first; pressing it again flips the order
+        state.handle_key(key(KeyCode::Char('7')));
+        assert_eq!(ports(&state), vec![5173, 3000, 8000]);
+        state.handle_key(key(KeyCode::Char('7')));
+        assert_eq!(ports(&state), vec![8000, 3000, 5173]);
+
+        // Incremental search narrows as you type and Esc clears it
+        state.handle_key(key(KeyCode::Char('/')));
+        state.handle_key(key(KeyCode::Char('3')));
+        assert_eq!(ports(&state), vec![3000, 5173]);
+        state.handle_key(key(KeyCode::Char('0')));
+        assert_eq!(ports(&state), vec![3000]);
+        state.handle_key(key(KeyCode::Esc));
+        assert_eq!(ports(&state).len(), 3);
+
+        // Select two rows, then kill asks first and only acts on y
+        state.handle_key(key(KeyCode::Char('1')));
+        state.handle_key(key(KeyCode::Char(' ')));
+        state.handle_key(key(KeyCode::Char(' ')));
+        assert_eq!(state.handle_key(key(KeyCode::Char('x'))), Action::None);
+        assert_eq!(state.status, "Kill 2 processes? y/N");
+        match state.handle_key(key(KeyCode::Char('y'))) {
+            Action::Kill(targets) => {
+                let killed: Vec<u16> = targets.iter().map(|p| p.port).collect();
+                assert_eq!(killed, vec![3000, 5173]);
+            }
+            other => panic!("expected a kill, got {:?}", other),
+        }
+        state.handle_key(key(KeyCode::Char('r')));
+        assert_eq!(state.handle_key(key(KeyCode::Char('n'))), Action::None);
+        assert_eq!(state.status, "Cancelled");
+
+        // A rescan drops selections for ports that went away
+        state.set_processes(HashMap::from([(5173, process(5173, 11, "vite", 900))]));
+        assert_eq!(state.selected, BTreeSet::from([5173]));
+        assert_eq!(state.current().map(|p| p.port), Some(5173));
+    }
+
+    #[test]
+    fn test_renders_table_and_detail_pane() {
+        let mut state = TuiState::new();
+        state.set_processes(HashMap::from([(3000, process(3000, 4242, "node", 300))]));
+        state.details.insert(
+            4242,
+            Details {
+                command_line: None,
+                open_files: vec!["/srv/app/server.js".to_string()],
+            },
+        );
+        state.handle_key(key(KeyCode::Enter));
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+        terminal.draw(|frame| draw(frame, &mut state)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        for expected in [
+            "Listeners (1)",
+            "PORT▲",
+            "app-3000",
+            "300M",
+            "node --port 3000",
+            "/srv/app/server.js",
+        ] {
+            assert!(screen.contains(expected), "missing {}", expected);
+        }
+    }
+}
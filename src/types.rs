@@ -1,7 +1,7 @@
+use crate::history_store::{self, HistoryStore};
 use chrono::{DateTime, Datelike, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -16,9 +16,68 @@ pub struct ProcessInfo {
     pub working_directory: Option<String>,
     pub process_group: Option<String>, // NEW: Group processes by type (e.g., "Node.js", "Python", "Docker")
     pub project_name: Option<String>,  // NEW: Extract project name from working directory
+    #[serde(default)]
+    pub framework: Option<String>, // NEW: Detected framework (e.g. "Vite", "Django")
     pub cpu_usage: Option<f64>,        // NEW: CPU usage percentage
     pub memory_usage: Option<u64>,     // NEW: Memory usage in bytes
     pub memory_percentage: Option<f64>, // NEW: Memory usage percentage
+    #[serde(default)]
+    pub start_time: Option<DateTime<Utc>>, // NEW: When the process started
+    #[serde(default)]
+    pub systemd_unit: Option<SystemdUnit>, // NEW: Owning systemd unit, if any (Linux)
+}
+
+#[cfg(test)]
+impl ProcessInfo {
+    /// A listener for tests: `name` with PID `pid` on `port`, and nothing else known
+    pub fn for_test(pid: i32, port: u16, name: &str) -> Self {
+        Self {
+            pid,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            framework: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            start_time: None,
+            systemd_unit: None,
+        }
+    }
+}
+
+/// A systemd service that owns a process; killing the PID just makes systemd restart it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SystemdUnit {
+    pub name: String,
+    /// Runs under the user's service manager (`systemctl --user`)
+    pub user: bool,
+}
+
+impl SystemdUnit {
+    /// The command that stops the unit for good
+    pub fn stop_command(&self) -> Vec<String> {
+        let mut command = vec!["systemctl".to_string()];
+        if self.user {
+            command.push("--user".to_string());
+        }
+        command.extend(["stop".to_string(), self.name.clone()]);
+        command
+    }
+
+    pub fn describe(&self) -> String {
+        if self.user {
+            format!("systemd user unit {}", self.name)
+        } else {
+            format!("systemd unit {}", self.name)
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -153,70 +212,35 @@ impl StatusBarInfo {
 }
 
 impl ProcessInfo {
-    /// Determine the process group based on the command and name
+    /// Fill in the process group, framework and project from the classification rules
+    /// (see `process_rules`)
+    pub fn classify(&mut self) {
+        let classification = crate::process_rules::classify(self);
+        self.process_group = classification.group;
+        self.framework = classification.framework;
+        self.project_name = classification.project;
+    }
+
+    /// Determine the process group from the classification rules
     pub fn determine_process_group(&self) -> Option<String> {
-        let name_lower = self.name.to_lowercase();
-        let command_lower = self.command.to_lowercase();
-
-        // Check for common development tools
-        if name_lower.contains("node") || command_lower.contains("node") {
-            Some("Node.js".to_string())
-        } else if name_lower.contains("python") || command_lower.contains("python") {
-            Some("Python".to_string())
-        } else if name_lower.contains("java") || command_lower.contains("java") {
-            Some("Java".to_string())
-        } else if name_lower.contains("go") || command_lower.contains("go ") {
-            Some("Go".to_string())
-        } else if name_lower.contains("rust") || command_lower.contains("cargo") {
-            Some("Rust".to_string())
-        } else if name_lower.contains("php") || command_lower.contains("php") {
-            Some("PHP".to_string())
-        } else if name_lower.contains("ruby") || command_lower.contains("ruby") {
-            Some("Ruby".to_string())
-        } else if name_lower.contains("docker") || command_lower.contains("docker") {
-            Some("Docker".to_string())
-        } else if name_lower.contains("nginx") || command_lower.contains("apache") {
-            Some("Web Server".to_string())
-        } else if name_lower.contains("postgres")
-            || name_lower.contains("mysql")
-            || name_lower.contains("redis")
-        {
-            Some("Database".to_string())
-        } else {
-            None
-        }
+        crate::process_rules::classify(self).group
     }
 
-    /// Extract project name from working directory
+    /// Name of the project the process runs in, from the nearest manifest above its
+    /// working directory
     pub fn extract_project_name(&self) -> Option<String> {
-        if let Some(ref work_dir) = self.working_directory {
-            // Get the last part of the path (project folder name)
-            let path_parts: Vec<&str> = work_dir.split('/').collect();
-            if let Some(last_part) = path_parts.last() {
-                if !last_part.is_empty() && *last_part != "~" {
-                    return Some(last_part.to_string());
-                }
-            }
+        crate::process_rules::classify(self).project
+    }
 
-            // Try to find a meaningful project name from the path
-            for part in path_parts.iter().rev() {
-                if !part.is_empty() && *part != "~" && *part != "home" && *part != "Users" {
-                    // Check if this looks like a project directory
-                    if part.contains("project")
-                        || part.contains("app")
-                        || part.contains("service")
-                        || part.contains("api")
-                        || part.contains("frontend")
-                        || part.contains("backend")
-                        || part.contains("client")
-                        || part.contains("server")
-                    {
-                        return Some(part.to_string());
-                    }
-                }
-            }
-        }
-        None
+    /// Whether `name` (e.g. from `--kill-project`) names this process's project: the
+    /// name the manifest declares, or the name of the directory it runs in
+    pub fn matches_project(&self, name: &str) -> bool {
+        self.project_name.as_deref() == Some(name)
+            || self
+                .working_directory
+                .as_deref()
+                .and_then(|dir| std::path::Path::new(dir).file_name())
+                .is_some_and(|dir| dir == name)
     }
 
     /// Get the full project path context
@@ -257,9 +281,13 @@ impl ProcessInfo {
             display_parts.push(format!("[{}]", project));
         }
 
-        // Add process group context
-        if let Some(ref group) = self.process_group {
-            display_parts.push(format!("({})", group));
+        // Add process group and framework context
+        match (&self.process_group, &self.framework) {
+            (Some(group), Some(framework)) => {
+                display_parts.push(format!("({}, {})", group, framework))
+            }
+            (Some(label), None) | (None, Some(label)) => display_parts.push(format!("({})", label)),
+            (None, None) => {}
         }
 
         // Add port context for clarity
@@ -288,6 +316,17 @@ impl ProcessInfo {
         name.to_string()
     }
 
+    /// Seconds since the process started
+    pub fn uptime_seconds(&self) -> Option<u64> {
+        self.start_time
+            .map(|started| (Utc::now() - started).num_seconds().max(0) as u64)
+    }
+
+    /// Compact age for listings, e.g. "3h12m"
+    pub fn format_age(&self) -> Option<String> {
+        self.uptime_seconds().map(format_duration_short)
+    }
+
     /// Get a detailed process description
     pub fn get_detailed_description(&self) -> String {
         let mut parts = Vec::new();
@@ -314,10 +353,172 @@ impl ProcessInfo {
             parts.push(format!("[Docker: {}]", container_name));
         }
 
+        if let Some(ref unit) = self.systemd_unit {
+            let scope = if unit.user {
+                "systemd --user"
+            } else {
+                "systemd"
+            };
+            parts.push(format!("[{}: {}]", scope, unit.name));
+        }
+
         parts.join(" ")
     }
 }
 
+/// Format a duration in seconds as a compact age like "45s", "12m", "3h12m" or "2d4h"
+pub fn format_duration_short(secs: u64) -> String {
+    let (days, hours, mins) = (secs / 86_400, (secs % 86_400) / 3_600, (secs % 3_600) / 60);
+    if days > 0 {
+        format!("{}d{}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h{}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m", mins)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Parse durations like "45", "30s", "5m", "2h" or "1d" into seconds
+pub fn parse_duration_secs(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let number: u64 = number.parse().ok()?;
+    let multiplier = match unit.trim() {
+        "s" | "sec" | "secs" => 1,
+        "m" | "min" | "mins" => 60,
+        "h" | "hr" | "hrs" => 3600,
+        "d" | "day" | "days" => 86400,
+        _ => return None,
+    };
+    number.checked_mul(multiplier)
+}
+
+/// A replacement starting this soon after a kill on the same port counts as a respawn
+pub const RESPAWN_WINDOW_SECS: i64 = 15;
+
+/// Who asked for a kill
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum KillActor {
+    Cli,
+    Tray,
+    Script,
+    Guard,
+    Dashboard,
+    Alert,
+    /// Entries recorded before the actor was tracked
+    #[default]
+    Unknown,
+}
+
+impl KillActor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KillActor::Cli => "cli",
+            KillActor::Tray => "tray",
+            KillActor::Script => "script",
+            KillActor::Guard => "guard",
+            KillActor::Dashboard => "dashboard",
+            KillActor::Alert => "alert",
+            KillActor::Unknown => "unknown",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "cli" | "console" => Some(KillActor::Cli),
+            "tray" => Some(KillActor::Tray),
+            "script" => Some(KillActor::Script),
+            "guard" => Some(KillActor::Guard),
+            "dashboard" => Some(KillActor::Dashboard),
+            "alert" => Some(KillActor::Alert),
+            _ => None,
+        }
+    }
+}
+
+/// How a kill attempt ended
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum KillOutcome {
+    /// Exited after the first, graceful signal
+    Terminated,
+    /// Needed an escalation (SIGKILL) before it went away
+    ForceKilled,
+    /// Was gone before the first signal arrived
+    AlreadyExited,
+    /// Still running, or the signal could not be delivered
+    Failed,
+    /// Entries recorded before outcomes were tracked
+    #[default]
+    Unknown,
+}
+
+impl KillOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KillOutcome::Terminated => "terminated",
+            KillOutcome::ForceKilled => "force_killed",
+            KillOutcome::AlreadyExited => "already_exited",
+            KillOutcome::Failed => "failed",
+            KillOutcome::Unknown => "unknown",
+        }
+    }
+
+    pub fn succeeded(&self) -> bool {
+        matches!(
+            self,
+            KillOutcome::Terminated | KillOutcome::ForceKilled | KillOutcome::AlreadyExited
+        )
+    }
+}
+
+/// What happened when a kill path signalled a process
+#[derive(Debug, Clone, PartialEq)]
+pub struct KillReport {
+    pub outcome: KillOutcome,
+    /// The signal (or command) that finally stopped it, e.g. "SIGTERM", "SIGKILL", "docker stop"
+    pub signal: Option<String>,
+    /// Signals or stop commands sent, including the one that worked
+    pub attempts: u32,
+    pub error: Option<String>,
+}
+
+impl KillReport {
+    pub fn stopped(outcome: KillOutcome, signal: &str, attempts: u32) -> Self {
+        Self {
+            outcome,
+            signal: Some(signal.to_string()),
+            attempts,
+            error: None,
+        }
+    }
+
+    pub fn failed(attempts: u32, error: impl Into<String>) -> Self {
+        Self {
+            outcome: KillOutcome::Failed,
+            signal: None,
+            attempts,
+            error: Some(error.into()),
+        }
+    }
+
+    pub fn into_result(self) -> anyhow::Result<()> {
+        if self.outcome.succeeded() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(self
+                .error
+                .unwrap_or_else(|| "process is still running".to_string())))
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessHistoryEntry {
     pub pid: i32,
@@ -329,6 +530,23 @@ pub struct ProcessHistoryEntry {
     pub killed_by: String, // "user", "bulk", "auto"
     pub command_line: Option<String>,
     pub working_directory: Option<String>,
+    #[serde(default)]
+    pub outcome: KillOutcome,
+    #[serde(default)]
+    pub signal: Option<String>,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// How long the process had been running when it was killed
+    #[serde(default)]
+    pub lifetime_secs: Option<u64>,
+    #[serde(default)]
+    pub actor: KillActor,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub tty: Option<String>,
 }
 
 impl ProcessHistoryEntry {
@@ -340,12 +558,53 @@ impl ProcessHistoryEntry {
             process_group: process_info.process_group.clone(),
             project_name: process_info.project_name.clone(),
             killed_at: Utc::now(),
+            actor: history_store::actor_for(&killed_by),
             killed_by,
             command_line: process_info.command_line.clone(),
             working_directory: process_info.working_directory.clone(),
+            outcome: KillOutcome::Unknown,
+            signal: None,
+            attempts: 0,
+            error: None,
+            lifetime_secs: process_info.uptime_seconds(),
+            user: history_store::invoking_user(),
+            tty: history_store::invoking_tty(),
         }
     }
 
+    /// Entry for kill paths that only know the pid and what it was listening on
+    pub fn for_pid(pid: i32, port: u16, process_name: &str, killed_by: &str) -> Self {
+        let process_info = ProcessInfo {
+            pid,
+            port,
+            command: process_name.to_string(),
+            name: process_name.to_string(),
+            container_id: None,
+            container_name: None,
+            command_line: None,
+            working_directory: None,
+            process_group: None,
+            project_name: None,
+            cpu_usage: None,
+            memory_usage: None,
+            memory_percentage: None,
+            start_time: crate::system_monitor::process_start_time(pid),
+            systemd_unit: crate::system_monitor::systemd_unit(pid),
+            framework: None,
+        };
+        let mut entry = Self::new(&process_info, killed_by.to_string());
+        entry.process_group = process_info.determine_process_group();
+        entry
+    }
+
+    pub fn with_report(mut self, report: &KillReport) -> Self {
+        self.outcome = report.outcome;
+        self.signal = report.signal.clone();
+        self.attempts = report.attempts;
+        self.error = report.error.clone();
+        self
+    }
+
     pub fn get_display_name(&self) -> String {
         if let Some(ref group) = self.process_group {
             if let Some(ref project) = self.project_name {
@@ -450,6 +709,8 @@ pub enum PatternType {
     ResourceIntensive,
     TimeBased,
     ProjectRelated,
+    /// Something brought the process back within seconds of it being killed
+    Respawn,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -567,9 +828,13 @@ pub enum SuspicionReason {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RiskLevel {
+    #[serde(alias = "low")]
     Low,
+    #[serde(alias = "medium")]
     Medium,
+    #[serde(alias = "high")]
     High,
+    #[serde(alias = "critical")]
     Critical,
 }
 
@@ -671,6 +936,14 @@ impl ProcessHistory {
             .collect()
     }
 
+    /// A copy holding only the entries `keep` accepts, so the analyses below run on that subset
+    pub fn filtered(&self, keep: impl Fn(&ProcessHistoryEntry) -> bool) -> ProcessHistory {
+        ProcessHistory {
+            entries: self.entries.iter().filter(|e| keep(e)).cloned().collect(),
+            max_entries: self.max_entries,
+        }
+    }
+
     pub fn clear(&mut self) {
         self.entries.clear();
     }
@@ -683,31 +956,38 @@ impl ProcessHistory {
         self.entries.is_empty()
     }
 
-    pub fn save_to_file(&self, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let json = serde_json::to_string_pretty(&self.entries)?;
-        fs::write(file_path, json)?;
+    /// Append entries to the shared history log without touching what others wrote
+    pub fn append_to_file(
+        file_path: &str,
+        entries: &[ProcessHistoryEntry],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        HistoryStore::open(Path::new(file_path)).append(entries)?;
+        Ok(())
+    }
+
+    /// Delete the history log, including rotated segments
+    pub fn clear_file(file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        HistoryStore::open(Path::new(file_path)).clear()?;
         Ok(())
     }
 
+    /// Load the most recent `max_entries` entries, merged across rotated segments
     pub fn load_from_file(
         file_path: &str,
         max_entries: usize,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        if Path::new(file_path).exists() {
-            let json = fs::read_to_string(file_path)?;
-            let entries: Vec<ProcessHistoryEntry> = serde_json::from_str(&json)?;
-            Ok(Self {
-                entries,
-                max_entries,
-            })
-        } else {
-            Ok(Self::new(max_entries))
+        let mut entries = HistoryStore::open(Path::new(file_path)).load()?;
+        if entries.len() > max_entries {
+            entries.drain(..entries.len() - max_entries);
         }
+        Ok(Self {
+            entries,
+            max_entries,
+        })
     }
 
     pub fn get_history_file_path() -> String {
-        let home_dir = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-        format!("{}/.port-kill-history.json", home_dir)
+        history_store::config().path.to_string_lossy().to_string()
     }
 
     /// Get frequent offenders - processes that have been killed multiple times
@@ -949,6 +1229,7 @@ impl ProcessHistory {
         // Analyze workflow patterns
         patterns.extend(self.analyze_development_patterns());
         patterns.extend(self.analyze_time_patterns());
+        patterns.extend(self.analyze_respawn_patterns());
 
         // Generate smart recommendations
         recommendations.extend(self.generate_process_management_recommendations());
@@ -1104,6 +1385,73 @@ impl ProcessHistory {
         patterns
     }
 
+    /// Kills whose replacement started within `RESPAWN_WINDOW_SECS` of the previous kill on
+    /// the same port, as (previous kill, replacement kill, seconds between them)
+    pub fn find_respawns(&self) -> Vec<(&ProcessHistoryEntry, &ProcessHistoryEntry, i64)> {
+        let mut by_port: HashMap<u16, Vec<&ProcessHistoryEntry>> = HashMap::new();
+        for entry in &self.entries {
+            by_port.entry(entry.port).or_default().push(entry);
+        }
+
+        let mut respawns = Vec::new();
+        for entries in by_port.values_mut() {
+            entries.sort_by_key(|entry| entry.killed_at);
+            for pair in entries.windows(2) {
+                let (killed, replacement) = (pair[0], pair[1]);
+                if !killed.outcome.succeeded() && killed.outcome != KillOutcome::Unknown {
+                    continue;
+                }
+                let Some(lifetime) = replacement.lifetime_secs else {
+                    continue;
+                };
+                let started = replacement.killed_at - chrono::Duration::seconds(lifetime as i64);
+                let gap = (started - killed.killed_at).num_seconds();
+                // Start times are rounded, so allow a second of slack before the kill
+                if (-1..=RESPAWN_WINDOW_SECS).contains(&gap) {
+                    respawns.push((killed, replacement, gap.max(0)));
+                }
+            }
+        }
+        respawns.sort_by_key(|(killed, _, _)| killed.killed_at);
+        respawns
+    }
+
+    /// Processes that came straight back after being killed, usually because a supervisor,
+    /// file watcher or container restart policy owns them
+    fn analyze_respawn_patterns(&self) -> Vec<WorkflowPattern> {
+        let mut by_process: HashMap<(String, u16), Vec<(i64, u32)>> = HashMap::new();
+        for (killed, replacement, gap) in self.find_respawns() {
+            by_process
+                .entry((replacement.process_name.clone(), replacement.port))
+                .or_default()
+                .push((gap, killed.attempts.max(replacement.attempts)));
+        }
+
+        let mut patterns: Vec<WorkflowPattern> = by_process
+            .into_iter()
+            .map(|((process_name, port), respawns)| {
+                let slowest = respawns.iter().map(|(gap, _)| *gap).max().unwrap_or(0);
+                // Needing SIGKILL as well as coming back points at something fighting the kill
+                let forced = respawns.iter().any(|(_, attempts)| *attempts > 1);
+                let confidence = (0.6 + 0.1 * respawns.len() as f64 + if forced { 0.1 } else { 0.0 })
+                    .min(0.95);
+                WorkflowPattern {
+                    pattern_type: PatternType::Respawn,
+                    description: format!(
+                        "Process '{}' on port {} respawned within {}s of being killed",
+                        process_name, port, slowest
+                    ),
+                    affected_processes: vec![process_name],
+                    frequency: format!("{} respawns", respawns.len()),
+                    recommendation: "Killing it is not enough: stop the supervisor, watcher or container restart policy that starts it again.".to_string(),
+                    confidence,
+                }
+            })
+            .collect();
+        patterns.sort_by(|a, b| a.description.cmp(&b.description));
+        patterns
+    }
+
     /// Analyze time-based patterns
     fn analyze_time_patterns(&self) -> Vec<WorkflowPattern> {
         let mut patterns = Vec::new();
@@ -1184,6 +1532,22 @@ impl ProcessHistory {
     fn generate_workflow_recommendations(&self) -> Vec<SmartRecommendation> {
         let mut recommendations = Vec::new();
 
+        let respawns = self.find_respawns();
+        if !respawns.is_empty() {
+            recommendations.push(SmartRecommendation {
+                category: RecommendationCategory::ProcessManagement,
+                title: "Stop Respawning Processes at Their Source".to_string(),
+                description: format!(
+                    "{} kills were followed by the process coming back within {} seconds",
+                    respawns.len(),
+                    RESPAWN_WINDOW_SECS
+                ),
+                action: "Stop the service, watcher or container that restarts them instead of killing the process".to_string(),
+                impact: "Frees the port for good instead of for a few seconds".to_string(),
+                priority: RecommendationPriority::High,
+            });
+        }
+
         // Check for auto-restart patterns
         let auto_restart_conflicts = self.analyze_auto_restart_patterns();
         if !auto_restart_conflicts.is_empty() {
@@ -1224,3 +1588,81 @@ impl ProcessHistory {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kill(port: u16, secs_ago: i64, lifetime_secs: Option<u64>) -> ProcessHistoryEntry {
+        ProcessHistoryEntry {
+            pid: 1000 + secs_ago as i32,
+            port,
+            process_name: "node".to_string(),
+            process_group: None,
+            project_name: None,
+            killed_at: Utc::now() - chrono::Duration::seconds(secs_ago),
+            killed_by: "user".to_string(),
+            command_line: None,
+            working_directory: None,
+            outcome: KillOutcome::Terminated,
+            signal: Some("SIGTERM".to_string()),
+            attempts: 1,
+            error: None,
+            lifetime_secs,
+            actor: KillActor::Cli,
+            user: None,
+            tty: None,
+        }
+    }
+
+    #[test]
+    fn test_matches_project_by_manifest_or_directory_name() {
+        let process = ProcessInfo {
+            project_name: Some("@acme/web".to_string()),
+            working_directory: Some("/home/dev/code/web".to_string()),
+            ..ProcessInfo::for_test(4242, 3000, "node")
+        };
+        assert!(process.matches_project("@acme/web"));
+        assert!(process.matches_project("web"));
+        assert!(!process.matches_project("code"));
+    }
+
+    #[test]
+    fn test_parse_duration_secs() {
+        assert_eq!(parse_duration_secs("5m"), Some(300));
+        assert_eq!(parse_duration_secs("45"), Some(45));
+        assert_eq!(parse_duration_secs("2 days"), Some(172_800));
+        assert_eq!(parse_duration_secs("soon"), None);
+    }
+
+    #[test]
+    fn test_entries_without_kill_details_still_load() {
+        let legacy = r#"{"pid":1,"port":3000,"process_name":"node","process_group":null,"project_name":null,"killed_at":"2025-01-01T00:00:00Z","killed_by":"user","command_line":null,"working_directory":null}"#;
+        let entry: ProcessHistoryEntry = serde_json::from_str(legacy).unwrap();
+        assert_eq!(entry.outcome, KillOutcome::Unknown);
+        assert_eq!(entry.actor, KillActor::Unknown);
+        assert_eq!(entry.lifetime_secs, None);
+    }
+
+    #[test]
+    fn test_respawn_within_seconds_of_kill_is_detected() {
+        let mut history = ProcessHistory::new(100);
+        // Killed 600s ago, back 3s later, killed again after living 597s
+        history.add_entry(kill(3000, 600, Some(3600)));
+        history.add_entry(kill(3000, 0, Some(597)));
+        // A replacement that started long after the previous kill is not a respawn
+        history.add_entry(kill(4000, 600, Some(3600)));
+        history.add_entry(kill(4000, 0, Some(60)));
+
+        let respawns = history.find_respawns();
+        assert_eq!(respawns.len(), 1);
+        assert_eq!(respawns[0].1.port, 3000);
+        assert_eq!(respawns[0].2, 3);
+
+        let analysis = history.get_root_cause_analysis();
+        assert!(analysis
+            .patterns
+            .iter()
+            .any(|p| matches!(p.pattern_type, PatternType::Respawn)));
+    }
+}
@@ -1,8 +1,19 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use minisign_verify::{PublicKey, Signature};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 const GITHUB_API_URL: &str = "https://api.github.com/repos/treadiehq/port-kill/releases/latest";
+const GITHUB_DOWNLOAD_URL: &str = "https://github.com/treadiehq/port-kill/releases/download";
+/// Checksums of every release asset, in `sha256sum` format
+pub const CHECKSUM_MANIFEST: &str = "SHA256SUMS";
+/// minisign signature over the checksum manifest
+pub const MANIFEST_SIGNATURE: &str = "SHA256SUMS.minisig";
+/// Latest version, served by custom release sources next to the assets
+pub const VERSION_FILE: &str = "VERSION";
 const CHECK_INTERVAL_DAYS: u64 = 1; // Check for updates once per day
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -72,10 +83,19 @@ fn fetch_latest_release() -> Result<GitHubRelease> {
     Ok(release)
 }
 
+/// Whether `latest` is newer than `current`, comparing dotted numeric components
 fn compare_versions(current: &str, latest: &str) -> bool {
-    // Simple version comparison (assumes semantic versioning)
-    // This is a basic implementation - could be enhanced with proper semver parsing
-    current != latest
+    let parse = |v: &str| -> Option<Vec<u64>> {
+        v.trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .take(3)
+            .map(|part| part.parse().ok())
+            .collect()
+    };
+    match (parse(current), parse(latest)) {
+        (Some(current), Some(latest)) => latest > current,
+        _ => current != latest,
+    }
 }
 
 fn should_skip_check() -> Result<bool> {
@@ -122,140 +142,364 @@ fn current_timestamp() -> u64 {
         .as_secs()
 }
 
-pub fn self_update() -> Result<()> {
-    let current_version = env!("CARGO_PKG_VERSION");
+/// Where `--self-update` looks for releases
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReleaseSource {
+    /// The latest GitHub release
+    GitHub,
+    /// A base URL serving `VERSION`, `SHA256SUMS` and the release assets
+    Url(String),
+    /// A local directory with the same layout, e.g. to try an update before publishing it
+    Dir(PathBuf),
+}
 
-    // Check for updates
-    let update_info = match check_for_updates(current_version)? {
-        Some(info) => info,
-        None => {
-            println!(
-                "✅ You're already running the latest version ({})",
-                current_version
-            );
-            return Ok(());
+impl ReleaseSource {
+    /// `github`, an http(s) URL, or a directory path (`file://` and `~/` allowed)
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if value.is_empty() || value.eq_ignore_ascii_case("github") {
+            ReleaseSource::GitHub
+        } else if value.starts_with("http://") || value.starts_with("https://") {
+            ReleaseSource::Url(value.trim_end_matches('/').to_string())
+        } else {
+            let path = value.strip_prefix("file://").unwrap_or(value);
+            let path = match path.strip_prefix("~/") {
+                Some(rest) => {
+                    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+                    PathBuf::from(home).join(rest)
+                }
+                None => PathBuf::from(path),
+            };
+            ReleaseSource::Dir(path)
         }
-    };
+    }
 
-    println!(
-        "🔄 Updating from {} to {}...",
-        update_info.current_version, update_info.latest_version
-    );
+    pub fn describe(&self) -> String {
+        match self {
+            ReleaseSource::GitHub => "GitHub releases".to_string(),
+            ReleaseSource::Url(url) => url.clone(),
+            ReleaseSource::Dir(dir) => dir.display().to_string(),
+        }
+    }
+}
 
-    // Get the current executable path
-    let current_exe = std::env::current_exe()?;
-    #[cfg(target_os = "windows")]
-    let current_exe_path = current_exe.to_string_lossy().to_string();
+/// Release source and trust settings for `--self-update`
+#[derive(Debug, Clone)]
+pub struct UpdateConfig {
+    pub source: ReleaseSource,
+    /// minisign public key, as base64 or a path to a `.pub` file. When set, the
+    /// checksum manifest must carry a valid signature from it.
+    pub public_key: Option<String>,
+}
 
-    // Determine platform-specific download URL
-    let download_url = get_platform_download_url()?;
+impl UpdateConfig {
+    /// A plain `http://` source delivers `SHA256SUMS` over the same unauthenticated
+    /// connection as the binary, so only a manifest signature can vouch for it
+    pub fn check_trust(&self) -> Result<()> {
+        match &self.source {
+            ReleaseSource::Url(url) if url.starts_with("http://") && self.public_key.is_none() => {
+                Err(anyhow!(
+                    "Refusing to update from {} over plain http without --release-pubkey; use https or a signing key",
+                    url
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
 
-    // Download the new binary
-    println!("📥 Downloading latest version...");
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(&download_url)
-        .header(reqwest::header::USER_AGENT, "port-kill-updater")
-        .send()?;
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            source: ReleaseSource::GitHub,
+            public_key: None,
+        }
+    }
+}
 
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!(
-            "Failed to download update: HTTP {}",
-            response.status()
-        ));
+/// A release resolved from a source: its version and where its files live
+struct Release {
+    version: String,
+    location: ReleaseSource,
+    notes_url: Option<String>,
+}
+
+impl Release {
+    fn resolve(source: &ReleaseSource) -> Result<Self> {
+        match source {
+            ReleaseSource::GitHub => {
+                let latest = fetch_latest_release()?;
+                Ok(Self {
+                    version: latest.tag_name.trim_start_matches('v').to_string(),
+                    location: ReleaseSource::Url(format!(
+                        "{}/{}",
+                        GITHUB_DOWNLOAD_URL, latest.tag_name
+                    )),
+                    notes_url: Some(latest.html_url),
+                })
+            }
+            other => {
+                let location = other.clone();
+                let version = String::from_utf8(fetch_file(&location, VERSION_FILE)?)
+                    .context("VERSION is not valid UTF-8")?;
+                Ok(Self {
+                    version: version.trim().trim_start_matches('v').to_string(),
+                    location,
+                    notes_url: None,
+                })
+            }
+        }
+    }
+
+    fn fetch(&self, name: &str) -> Result<Vec<u8>> {
+        fetch_file(&self.location, name)
     }
+}
 
-    let new_binary = response.bytes()?;
+fn fetch_file(location: &ReleaseSource, name: &str) -> Result<Vec<u8>> {
+    match location {
+        ReleaseSource::Dir(dir) => {
+            let path = dir.join(name);
+            std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))
+        }
+        ReleaseSource::Url(base) => {
+            let url = format!("{}/{}", base, name);
+            let response = reqwest::blocking::Client::new()
+                .get(&url)
+                .header(reqwest::header::USER_AGENT, "port-kill-updater")
+                .send()?;
+            if !response.status().is_success() {
+                return Err(anyhow!(
+                    "Failed to download {}: HTTP {}",
+                    url,
+                    response.status()
+                ));
+            }
+            Ok(response.bytes()?.to_vec())
+        }
+        ReleaseSource::GitHub => Err(anyhow!("GitHub releases must be resolved first")),
+    }
+}
 
-    // Create a temporary file for the new binary
-    let temp_dir = std::env::temp_dir();
-    let temp_exe = temp_dir.join("port-kill-new.exe");
-    std::fs::write(&temp_exe, new_binary)?;
+/// Release asset for this platform and binary, e.g. `port-kill-console-macos-intel`
+pub fn asset_name(exe_name: &str) -> String {
+    let binary = if exe_name.starts_with("port-kill-console") {
+        "port-kill-console"
+    } else {
+        "port-kill"
+    };
+    let target = if cfg!(target_os = "windows") {
+        "windows"
+    } else if cfg!(all(target_os = "macos", target_arch = "x86_64")) {
+        "macos-intel"
+    } else if cfg!(target_os = "macos") {
+        "macos"
+    } else {
+        "linux"
+    };
+    let ext = if cfg!(target_os = "windows") {
+        ".exe"
+    } else {
+        ""
+    };
+    format!("{}-{}{}", binary, target, ext)
+}
 
-    // Make the new binary executable (Unix systems)
-    #[cfg(not(target_os = "windows"))]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = std::fs::metadata(&temp_exe)?.permissions();
-        perms.set_mode(0o755);
-        std::fs::set_permissions(&temp_exe, perms)?;
+/// The expected SHA-256 of `asset` from a `sha256sum`-style manifest
+pub fn parse_checksum_manifest(manifest: &str, asset: &str) -> Option<String> {
+    manifest.lines().find_map(|line| {
+        let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+        // "<hash>  name" in text mode, "<hash> *name" in binary mode, maybe with a directory
+        let name = name.trim_start().trim_start_matches('*');
+        let name = name.rsplit('/').next()?;
+        (name == asset).then(|| hash.to_ascii_lowercase())
+    })
+}
+
+/// Check downloaded bytes against the manifest entry for `asset`
+pub fn verify_checksum(bytes: &[u8], manifest: &str, asset: &str) -> Result<()> {
+    let expected = parse_checksum_manifest(manifest, asset)
+        .ok_or_else(|| anyhow!("{} does not list {}", CHECKSUM_MANIFEST, asset))?;
+    let actual: String = Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if actual != expected {
+        return Err(anyhow!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            asset,
+            expected,
+            actual
+        ));
     }
+    Ok(())
+}
 
-    // Replace the current binary
-    println!("🔄 Replacing current binary...");
+/// Verify a minisign signature over the checksum manifest
+pub fn verify_manifest_signature(manifest: &[u8], signature: &str, public_key: &str) -> Result<()> {
+    let key = if Path::new(public_key).is_file() {
+        PublicKey::from_file(public_key)
+    } else {
+        PublicKey::from_base64(public_key.trim())
+    }
+    .map_err(|e| anyhow!("Invalid minisign public key: {}", e))?;
+    let signature =
+        Signature::decode(signature).map_err(|e| anyhow!("Invalid manifest signature: {}", e))?;
+    key.verify(manifest, &signature, false)
+        .map_err(|e| anyhow!("{} signature check failed: {}", CHECKSUM_MANIFEST, e))
+}
 
-    // On Windows, we need to use a different approach due to file locking
-    #[cfg(target_os = "windows")]
-    {
-        // Create a batch script to replace the binary after the current process exits
-        let batch_content = format!(
-            r#"@echo off
-timeout /t 2 /nobreak >nul
-move "{}" "{}"
-del "%~f0"
-"#,
-            temp_exe.to_string_lossy(),
-            current_exe_path
-        );
+/// The binary kept next to the executable for `--rollback`
+pub fn previous_binary_path(exe: &Path) -> PathBuf {
+    sibling(exe, &format!("{}.previous", file_name(exe)))
+}
 
-        let batch_file = temp_dir.join("port-kill-update.bat");
-        std::fs::write(&batch_file, batch_content)?;
+fn sibling(exe: &Path, name: &str) -> PathBuf {
+    exe.with_file_name(name)
+}
 
-        // Execute the batch file
-        std::process::Command::new("cmd")
-            .args(&["/c", "start", "/b", &batch_file.to_string_lossy()])
-            .spawn()?;
+fn file_name(exe: &Path) -> String {
+    exe.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "port-kill".to_string())
+}
 
-        println!("✅ Update will complete after you restart the application.");
-        println!("🔗 Release notes: {}", update_info.release_url);
-        return Ok(());
+/// Move the running binary out of the way, keeping it at `dest`. Windows can rename a
+/// running executable but not overwrite it; elsewhere a copy keeps `exe` in place
+/// until the new binary is renamed over it.
+fn set_aside(exe: &Path, dest: &Path) -> Result<()> {
+    if dest.exists() {
+        std::fs::remove_file(dest)?;
+    }
+    if cfg!(target_os = "windows") {
+        std::fs::rename(exe, dest)?;
+    } else {
+        std::fs::copy(exe, dest)?;
     }
+    Ok(())
+}
 
-    // On Unix systems, we can replace directly
+/// Write `bytes` next to `exe` and rename it into place, keeping the old binary for rollback
+pub fn install_binary(exe: &Path, bytes: &[u8]) -> Result<()> {
+    // Staging in the same directory keeps the final rename on one filesystem, so it is atomic
+    let staged = sibling(exe, &format!(".{}.new", file_name(exe)));
+    {
+        let mut file = std::fs::File::create(&staged)
+            .with_context(|| format!("Cannot write to {}", staged.display()))?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+    }
     #[cfg(not(target_os = "windows"))]
     {
-        std::fs::copy(&temp_exe, &current_exe)?;
-        std::fs::remove_file(&temp_exe)?;
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
 
-        println!("✅ Update completed successfully!");
-        println!("🔗 Release notes: {}", update_info.release_url);
-        println!("💡 Restart the application to use the new version.");
+    let previous = previous_binary_path(exe);
+    if let Err(e) = set_aside(exe, &previous) {
+        let _ = std::fs::remove_file(&staged);
+        return Err(e.context("Failed to keep the current binary for rollback"));
+    }
+    if let Err(e) = std::fs::rename(&staged, exe) {
+        if !exe.exists() {
+            let _ = std::fs::rename(&previous, exe);
+        }
+        let _ = std::fs::remove_file(&staged);
+        return Err(anyhow!("Failed to replace {}: {}", exe.display(), e));
     }
+    Ok(())
+}
 
+/// Swap `exe` with the binary the last update replaced; running it again swaps back
+pub fn rollback_binary(exe: &Path) -> Result<()> {
+    let previous = previous_binary_path(exe);
+    if !previous.exists() {
+        return Err(anyhow!(
+            "No previous binary at {}; nothing to roll back to",
+            previous.display()
+        ));
+    }
+    let swap = sibling(exe, &format!(".{}.rollback", file_name(exe)));
+    set_aside(exe, &swap)?;
+    std::fs::rename(&previous, exe)?;
+    std::fs::rename(&swap, &previous)?;
     Ok(())
 }
 
-fn get_platform_download_url() -> Result<String> {
-    let _platform = if cfg!(target_os = "windows") {
-        "windows"
-    } else if cfg!(target_os = "macos") {
-        "macos"
-    } else {
-        "linux"
-    };
+/// Download, verify and install the latest release over `exe`.
+/// Returns the installed version, or None when `current_version` is already the latest.
+pub fn update_from(
+    config: &UpdateConfig,
+    exe: &Path,
+    current_version: &str,
+) -> Result<Option<String>> {
+    config.check_trust()?;
+    let release = Release::resolve(&config.source)?;
+    if !compare_versions(current_version, &release.version) {
+        return Ok(None);
+    }
 
-    // Get the latest release info to construct the download URL
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(GITHUB_API_URL)
-        .header(reqwest::header::USER_AGENT, "port-kill-updater")
-        .send()?;
+    println!(
+        "🔄 Updating from {} to {} ({})...",
+        current_version,
+        release.version,
+        config.source.describe()
+    );
 
-    let release: GitHubRelease = response.json()?;
-    let tag_name = release.tag_name;
+    let manifest = release.fetch(CHECKSUM_MANIFEST).context(
+        "The release has no checksum manifest; refusing to install an unverified binary",
+    )?;
+    if let Some(public_key) = &config.public_key {
+        let signature = release.fetch(MANIFEST_SIGNATURE)?;
+        verify_manifest_signature(&manifest, &String::from_utf8_lossy(&signature), public_key)?;
+        println!("🔏 Checksum manifest signature verified");
+    }
+    let manifest = String::from_utf8(manifest).context("Checksum manifest is not UTF-8")?;
 
-    let binary_name = if cfg!(target_os = "windows") {
-        "port-kill-windows.exe"
-    } else if cfg!(target_os = "macos") {
-        "port-kill-macos"
-    } else {
-        "port-kill-linux"
-    };
+    let asset = asset_name(&file_name(exe));
+    println!("📥 Downloading {}...", asset);
+    let binary = release.fetch(&asset)?;
+    verify_checksum(&binary, &manifest, &asset)?;
+    println!("✅ SHA-256 verified");
+
+    install_binary(exe, &binary)?;
+    if let Some(url) = &release.notes_url {
+        println!("🔗 Release notes: {}", url);
+    }
+    Ok(Some(release.version))
+}
+
+pub fn self_update(config: &UpdateConfig) -> Result<()> {
+    let current_version = env!("CARGO_PKG_VERSION");
+    let current_exe = std::env::current_exe()?;
 
-    Ok(format!(
-        "https://github.com/treadiehq/port-kill/releases/download/{}/{}",
-        tag_name, binary_name
-    ))
+    match update_from(config, &current_exe, current_version)? {
+        Some(version) => {
+            println!("✅ Updated to {}", version);
+            println!(
+                "↩️  The previous binary is kept at {}; run with --rollback to restore it",
+                previous_binary_path(&current_exe).display()
+            );
+            println!("💡 Restart the application to use the new version.");
+        }
+        None => println!(
+            "✅ You're already running the latest version ({})",
+            current_version
+        ),
+    }
+    Ok(())
+}
+
+/// Restore the binary replaced by the last `--self-update`
+pub fn rollback() -> Result<()> {
+    let current_exe = std::env::current_exe()?;
+    rollback_binary(&current_exe)?;
+    println!(
+        "↩️  Restored the previous binary; {} now holds the one it replaced",
+        previous_binary_path(&current_exe).display()
+    );
+    println!("💡 Restart the application to use it.");
+    Ok(())
 }
 
 pub fn print_update_notification(update_info: &UpdateInfo) {
@@ -282,3 +526,118 @@ pub fn print_update_check_result(update_info: &UpdateInfo) {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scratch_dir;
+
+    const MANIFEST: &str = "3370f4b71ddcebc95251bd96211d024bb17042b9e041fb81a027208eb07c8407  port-kill-linux\n\
+                            93d8874c8c86f0fc893dbe15c765ffa0fcd342f798dbf669e08f8cbe095d230c  port-kill-console-linux\n";
+    const PUBLIC_KEY: &str = "RWQBAgMEBQYHCAOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4";
+    const SIGNATURE: &str = "untrusted comment: signature from minisign secret key\n\
+RUQBAgMEBQYHCF7inGyI5Tq4G9oB+O/NRf9cSakDqEN9zswiZllBU+Rv9px9HnECqyB6vZax/pePriHvBHlRGHamf86AyfFjtw4=\n\
+trusted comment: timestamp:1760000000\tfile:SHA256SUMS\n\
+jcg7UiMXpZQSQCVOqY2KH/+y20dy5B9eUw1g6k5LwtMfPjyn9Qcaz6qnQUslo2v01f/+b6GJIxq/dpg9XWhzBw==\n";
+
+    #[test]
+    fn test_checksum_and_signature_verification() {
+        let binary = b"port-kill test binary\n";
+        assert!(verify_checksum(binary, MANIFEST, "port-kill-linux").is_ok());
+        assert!(verify_checksum(b"tampered", MANIFEST, "port-kill-linux").is_err());
+        assert!(verify_checksum(binary, MANIFEST, "port-kill-windows.exe").is_err());
+        assert_eq!(
+            parse_checksum_manifest("ABCD *dist/port-kill-macos\n", "port-kill-macos"),
+            Some("abcd".to_string())
+        );
+
+        assert!(verify_manifest_signature(MANIFEST.as_bytes(), SIGNATURE, PUBLIC_KEY).is_ok());
+        let forged = MANIFEST.replace("3370", "0000");
+        assert!(verify_manifest_signature(forged.as_bytes(), SIGNATURE, PUBLIC_KEY).is_err());
+
+        assert!(compare_versions("0.5.21", "0.5.22"));
+        assert!(compare_versions("0.5.9", "v0.5.10"));
+        assert!(!compare_versions("0.5.21", "0.5.21"));
+        assert!(!compare_versions("0.6.0", "0.5.30"));
+    }
+
+    #[test]
+    fn test_plain_http_source_needs_a_signing_key() {
+        let mut config = UpdateConfig {
+            source: ReleaseSource::parse("http://localhost:8000/releases"),
+            public_key: None,
+        };
+        assert!(config.check_trust().is_err());
+        assert!(update_from(&config, Path::new("/nonexistent/port-kill"), "0.1.0").is_err());
+
+        config.public_key = Some(PUBLIC_KEY.to_string());
+        assert!(config.check_trust().is_ok());
+        for source in ["https://example.com/releases", "./dist", "github"] {
+            let config = UpdateConfig {
+                source: ReleaseSource::parse(source),
+                public_key: None,
+            };
+            assert!(config.check_trust().is_ok(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_update_from_local_source_then_rollback() {
+        let dir = scratch_dir("update", "swap");
+        let releases = dir.join("releases");
+        std::fs::create_dir_all(&releases).unwrap();
+        let exe = dir.join("port-kill-console");
+        std::fs::write(&exe, b"old binary").unwrap();
+
+        let asset = asset_name("port-kill-console");
+        let new_binary = b"new binary";
+        let hash: String = Sha256::digest(new_binary)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        std::fs::write(releases.join(&asset), new_binary).unwrap();
+        std::fs::write(releases.join(VERSION_FILE), "v0.9.0\n").unwrap();
+        let config = UpdateConfig {
+            source: ReleaseSource::parse(releases.to_str().unwrap()),
+            public_key: None,
+        };
+
+        // A manifest that doesn't match the download must leave the binary alone
+        std::fs::write(
+            releases.join(CHECKSUM_MANIFEST),
+            format!("{}  {}\n", "0".repeat(64), asset),
+        )
+        .unwrap();
+        assert!(update_from(&config, &exe, "0.5.21").is_err());
+        assert_eq!(std::fs::read(&exe).unwrap(), b"old binary");
+
+        std::fs::write(
+            releases.join(CHECKSUM_MANIFEST),
+            format!("{}  {}\n", hash, asset),
+        )
+        .unwrap();
+        assert_eq!(
+            update_from(&config, &exe, "0.9.0").unwrap(),
+            None,
+            "already up to date"
+        );
+        assert_eq!(
+            update_from(&config, &exe, "0.5.21").unwrap(),
+            Some("0.9.0".to_string())
+        );
+        assert_eq!(std::fs::read(&exe).unwrap(), new_binary);
+        assert_eq!(
+            std::fs::read(previous_binary_path(&exe)).unwrap(),
+            b"old binary"
+        );
+
+        rollback_binary(&exe).unwrap();
+        assert_eq!(std::fs::read(&exe).unwrap(), b"old binary");
+        assert_eq!(
+            std::fs::read(previous_binary_path(&exe)).unwrap(),
+            new_binary
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}